@@ -0,0 +1,293 @@
+## Every bit of text the game shows, in English. Other languages fall back
+## on these for anything they leave out.
+
+language-name = English
+
+on = On
+off = Off
+back = Back
+keyboard-space = Space
+keyboard-delete = Del
+keyboard-done = OK
+
+## Scoreboard
+
+hud-p1 = P1: { $score }
+hud-p2 = P2: { $score }
+hud-returns = Returns: { $score }
+hud-best = Best: { $score }
+hud-seed = Seed { $seed }
+hud-overtime = OVERTIME
+hud-deuce = DEUCE
+hud-advantage = ADVANTAGE { $player }
+hud-game-point = GAME POINT { $player }
+hud-match-point = MATCH POINT { $player }
+hud-frenzy = FJONG FRENZY
+hud-double-points = FJONG FRENZY: DOUBLE POINTS
+hud-spectators = { $count } watching
+hud-net-stats = Ping { $ping } ms · Delay { $delay } ticks · Loss { $loss }%
+hud-net-poor = POOR CONNECTION
+hint-move = { $player }: { $up }/{ $down } to move
+hint-move-stick = { $player }: left stick to move
+hint-menu = Arrows to choose · Enter to pick · Esc to go back
+hint-menu-pad = D-pad to choose · (A) to pick · (B) to go back
+
+## Emotes
+
+emote-gg = GG
+emote-nice = Nice!
+# The game's font has no emoji, so 😱 is spelled out.
+emote-yikes = Yikes!
+
+## Main menu
+
+main-title = FJONG
+main-play = Play
+main-time-attack = Time attack
+main-breakout = Breakout
+main-practice = Practice
+main-daily = Daily challenge
+main-host-lan = Host LAN game
+main-join-lan = Join LAN game
+main-video = Video
+main-stats = Stats
+main-leaderboard = Leaderboard
+main-achievements = Achievements
+main-profiles = Profiles
+main-quit = Quit
+
+## Match setup
+
+setup-title = MATCH SETUP
+setup-arena = Arena: { $arena }
+setup-court-size = Court: { $size ->
+    [small] Small
+    [wide] Wide
+   *[classic] Classic
+}
+setup-points-to-win = Points to win: { $points }
+setup-best-of = Series: { $games ->
+    [1] Single game
+   *[other] Best of { $games }
+}
+setup-ball-speed = Ball speed: { $speed ->
+    [small] Slow
+    [large] Fast
+   *[normal] Normal
+}
+setup-paddle-size = Paddle size: { $size ->
+    [small] Small
+    [large] Large
+   *[normal] Normal
+}
+setup-ai = AI: { $difficulty ->
+    [easy] Easy
+    [hard] Hard
+    [insane] Insane
+    [learned] Boss
+   *[medium] Medium
+}
+setup-adaptive-ai = Adaptive AI: { $value }
+setup-sticky-paddles = Sticky paddles: { $value }
+setup-win-by-two = Win by two: { $value }
+setup-shrinking-paddles = Shrinking paddles: { $value }
+setup-moving-goals = Moving goals: { $value }
+setup-fog-of-war = Fog of war: { $value }
+setup-mirror-controls = Mirrored controls: { $value }
+setup-p1-handicap = P1 handicap: { $handicap ->
+    [headstart] Head start
+    [bigpaddle] Big paddle
+    [smallpaddle] Small paddle
+    [slowreturns] Slow returns
+   *[none] None
+}
+setup-p2-handicap = P2 handicap: { $handicap ->
+    [headstart] Head start
+    [bigpaddle] Big paddle
+    [smallpaddle] Small paddle
+    [slowreturns] Slow returns
+   *[none] None
+}
+setup-p1-profile = P1: { $profile }
+setup-p2-profile = P2: { $profile }
+setup-start = Start
+
+## Pause menu
+
+kiosk-credits = Credits: { $credits }
+kiosk-insert-coin = Insert coin
+kiosk-press-start = Press start
+pause-title = PAUSED
+pause-resume = Resume
+pause-ai = AI: { $level ->
+    [easy] Easy
+    [hard] Hard
+    [insane] Insane
+    [adaptive] Adaptive
+    [learned] Boss
+   *[medium] Medium
+}
+pause-ai-next = AI from next serve: { $level ->
+    [easy] Easy
+    [hard] Hard
+    [insane] Insane
+    [adaptive] Adaptive
+   *[medium] Medium
+}
+pause-main-menu = Main menu
+
+## Instant replay
+
+instant-replay-title = REPLAY
+instant-replay-skip = Skip
+
+## Series
+
+series-game-won = { $player } takes game { $game }
+series-score = P1 { $p1 } - { $p2 } P2
+series-switch-ends = Players switch ends
+series-won = { $player } wins the series!
+series-next = Next game
+
+## LAN
+
+lan-host-title = Waiting for a player
+lan-host-hint = Others on your network can join from "Join LAN game"
+lan-host-room-code = Room code: { $code }
+lan-lobby-title = LAN games
+lan-lobby-searching = Looking for games on your network…
+lan-lobby-game = { $name } ({ $addr })
+lan-lobby-room-code = Enter a room code
+lan-room-code-title = Join by room code
+lan-room-code-join = Join { $code }
+lan-reconnect-title = Connection lost
+lan-reconnect-countdown = Waiting to reconnect… { $seconds }
+lan-reconnect-give-up = Give up
+
+## Video
+
+video-title = VIDEO
+video-theme = Theme: { $theme ->
+    [neon] Neon
+    [deuteranopia] Deuteranopia
+    [protanopia] Protanopia
+    [tritanopia] Tritanopia
+    [highcontrast] High contrast
+   *[classic] Classic
+}
+video-theme-pack = Theme pack: { $pack }
+theme-pack-none = None
+video-camera = Camera: { $camera ->
+    [follow] Follow
+    [dramatic] Dramatic
+   *[classic] Classic
+}
+video-ball-heat = Ball heat: { $value }
+video-backdrop = Background: { $value }
+video-skin = Skin: { $skin }
+skin-none = None
+video-ui-scale = UI scale: { $percent }%
+video-hud-layout = HUD: { $layout ->
+    [centred] Centred
+   *[corners] Corners
+}
+video-crt = CRT: { $value }
+video-vsync = Vsync: { $value }
+video-fps-cap = FPS cap: { $fps ->
+    [0] Off
+   *[other] { $fps }
+}
+video-low-power = Save power in background: { $value }
+video-reduce-motion = Reduce motion: { $value }
+video-particles = Particles: { $value }
+video-max-brightness = Max brightness: { $percent }%
+video-game-speed = Game speed: { $percent }%
+video-announcer = Announcer: { $value }
+video-language = Language: { $language }
+
+## Stats
+
+stats-title = STATS
+stats-matches-played = Matches played: { $count }
+stats-p1-wins = P1 wins: { $count }
+stats-p2-wins = P2 wins: { $count }
+stats-longest-rally = Longest rally: { $count }
+stats-fastest-ball = Fastest ball: { $speed }
+
+## Leaderboard
+
+leaderboard-title = LEADERBOARD
+leaderboard-loading = Loading...
+leaderboard-empty = No scores yet
+leaderboard-stale = Could not reach the leaderboard, showing the last scores seen
+leaderboard-unavailable = No leaderboard server, start with --leaderboard <url>
+
+## Achievements
+
+achievements-title = ACHIEVEMENTS
+achievements-unlocked = { $unlocked } of { $total } unlocked
+achievement-first-win = First blood
+achievement-first-win-description = Win a match
+achievement-shutout = Clean sheet
+achievement-shutout-description = Win 11-0
+achievement-long-rally = Centurion
+achievement-long-rally-description = Keep a rally going for 100 hits
+achievement-beat-insane = Giant slayer
+achievement-beat-insane-description = Beat the Insane AI
+achievement-double-points = Frenzied
+achievement-double-points-description = Whip a rally up until goals count double
+achievement-practice-streak = Wall
+achievement-practice-streak-description = Return 50 in a row in practice
+
+## Daily challenge
+
+daily-title = DAILY CHALLENGE
+daily-no-modifiers = No modifiers
+daily-best = Best today: won by { $score }
+daily-not-played = Not played yet today
+daily-play = Play
+
+## Profiles
+
+profiles-title = PROFILES
+profiles-new = New profile: { $name }
+profiles-entry = { $name }: { $color ->
+    [red] red
+    [orange] orange
+    [yellow] yellow
+    [green] green
+    [cyan] cyan
+    [blue] blue
+    [purple] purple
+    [pink] pink
+   *[theme] theme colour
+}{ $skin ->
+    [none] {""}
+   *[other] , { $skin }
+}, won { $wins } of { $played }, longest rally { $rally }
+profile-guest = Guest
+profile-ai = AI
+
+## Toasts
+
+toast-settings-saved = Settings saved
+toast-achievement-unlocked = Achievement unlocked: { $name }
+toast-settings-not-saved = Could not save settings
+toast-gamepad-connected = Gamepad connected: P1
+toast-gamepad-disconnected = Gamepad disconnected: P1
+toast-bot-connected = Bot connected: { $peer }
+toast-spectator-joined = Spectator joined: { $peer }
+toast-spectating-ended = The match you were watching is over
+toast-lan-joined = Player joined: { $peer }
+toast-lan-failed = Could not start LAN play
+toast-lan-join-failed = Could not join that game
+toast-lan-joining = Joining…
+toast-lan-lost-guest = Lost the other player, waiting for them to come back
+toast-lan-lost-host = Lost the host, trying to get back in
+toast-lan-reconnected = Reconnected
+toast-lan-guest-forfeited = The other player never came back and forfeits
+toast-lan-forfeited = Could not get back in, match forfeited
+toast-lobby-failed = Could not open a room, only LAN players can join
+toast-lobby-unset = Start fjong with --lobby <address:port> to use room codes
+toast-capture-saved = Saved { $path }
+toast-capture-failed = Could not save the capture
@@ -0,0 +1,291 @@
+## Spelets texter på svenska.
+
+language-name = Svenska
+
+on = På
+off = Av
+back = Tillbaka
+keyboard-space = Mellanslag
+keyboard-delete = Radera
+keyboard-done = OK
+
+## Poängtavla
+
+hud-p1 = P1: { $score }
+hud-p2 = P2: { $score }
+hud-returns = Returer: { $score }
+hud-best = Bäst: { $score }
+hud-seed = Frö { $seed }
+hud-overtime = FÖRLÄNGNING
+hud-deuce = LIKA
+hud-advantage = FÖRDEL { $player }
+hud-game-point = GAMEBOLL { $player }
+hud-match-point = MATCHBOLL { $player }
+hud-frenzy = FJONGFRENESI
+hud-double-points = FJONGFRENESI: DUBBLA POÄNG
+hud-spectators = { $count } tittar
+hud-net-stats = Ping { $ping } ms · Fördröjning { $delay } tick · Förlust { $loss }%
+hud-net-poor = DÅLIG ANSLUTNING
+hint-move = { $player }: { $up }/{ $down } flyttar
+hint-move-stick = { $player }: vänster spak flyttar
+hint-menu = Pilarna väljer · Enter bekräftar · Esc går tillbaka
+hint-menu-pad = Styrkorset väljer · (A) bekräftar · (B) går tillbaka
+
+## Snabbchatt
+
+emote-gg = GG
+emote-nice = Snyggt!
+emote-yikes = Hjälp!
+
+## Huvudmeny
+
+main-title = FJONG
+main-play = Spela
+main-time-attack = Tidsjakt
+main-breakout = Breakout
+main-practice = Träna
+main-daily = Dagens utmaning
+main-host-lan = Starta nätverksspel
+main-join-lan = Gå med i nätverksspel
+main-video = Grafik
+main-stats = Statistik
+main-leaderboard = Topplista
+main-achievements = Prestationer
+main-profiles = Profiler
+main-quit = Avsluta
+
+## Matchinställningar
+
+setup-title = NY MATCH
+setup-arena = Arena: { $arena }
+setup-court-size = Plan: { $size ->
+    [small] Liten
+    [wide] Bred
+   *[classic] Klassisk
+}
+setup-points-to-win = Poäng till vinst: { $points }
+setup-best-of = Serie: { $games ->
+    [1] Enstaka match
+   *[other] Bäst av { $games }
+}
+setup-ball-speed = Bollfart: { $speed ->
+    [small] Långsam
+    [large] Snabb
+   *[normal] Normal
+}
+setup-paddle-size = Racketstorlek: { $size ->
+    [small] Liten
+    [large] Stor
+   *[normal] Normal
+}
+setup-ai = AI: { $difficulty ->
+    [easy] Lätt
+    [hard] Svår
+    [insane] Galen
+    [learned] Boss
+   *[medium] Medel
+}
+setup-adaptive-ai = Anpassad AI: { $value }
+setup-sticky-paddles = Klistriga racketar: { $value }
+setup-win-by-two = Vinn med två: { $value }
+setup-shrinking-paddles = Krympande racketar: { $value }
+setup-moving-goals = Rörliga mål: { $value }
+setup-fog-of-war = Krigets dimma: { $value }
+setup-mirror-controls = Spegelvänd styrning: { $value }
+setup-p1-handicap = Handikapp P1: { $handicap ->
+    [headstart] Försprång
+    [bigpaddle] Stor racket
+    [smallpaddle] Liten racket
+    [slowreturns] Långsamma returer
+   *[none] Inget
+}
+setup-p2-handicap = Handikapp P2: { $handicap ->
+    [headstart] Försprång
+    [bigpaddle] Stor racket
+    [smallpaddle] Liten racket
+    [slowreturns] Långsamma returer
+   *[none] Inget
+}
+setup-p1-profile = P1: { $profile }
+setup-p2-profile = P2: { $profile }
+setup-start = Starta
+
+## Paus
+
+kiosk-credits = Krediter: { $credits }
+kiosk-insert-coin = Sätt i ett mynt
+kiosk-press-start = Tryck på start
+pause-title = PAUS
+pause-resume = Fortsätt
+pause-ai = AI: { $level ->
+    [easy] Lätt
+    [hard] Svår
+    [insane] Galen
+    [adaptive] Anpassad
+    [learned] Boss
+   *[medium] Medel
+}
+pause-ai-next = AI från nästa serv: { $level ->
+    [easy] Lätt
+    [hard] Svår
+    [insane] Galen
+    [adaptive] Anpassad
+   *[medium] Medel
+}
+pause-main-menu = Huvudmeny
+
+## Repris
+
+instant-replay-title = REPRIS
+instant-replay-skip = Hoppa över
+
+## Serie
+
+series-game-won = { $player } tar match { $game }
+series-score = P1 { $p1 } - { $p2 } P2
+series-switch-ends = Spelarna byter sida
+series-won = { $player } vinner serien!
+series-next = Nästa match
+
+## Nätverk
+
+lan-host-title = Väntar på en spelare
+lan-host-hint = Andra på ditt nätverk kan gå med via "Gå med i nätverksspel"
+lan-host-room-code = Rumskod: { $code }
+lan-lobby-title = Spel på nätverket
+lan-lobby-searching = Letar efter spel på ditt nätverk…
+lan-lobby-game = { $name } ({ $addr })
+lan-lobby-room-code = Ange en rumskod
+lan-room-code-title = Gå med via rumskod
+lan-room-code-join = Gå med i { $code }
+lan-reconnect-title = Anslutningen bröts
+lan-reconnect-countdown = Väntar på återanslutning… { $seconds }
+lan-reconnect-give-up = Ge upp
+
+## Grafik
+
+video-title = GRAFIK
+video-theme = Tema: { $theme ->
+    [neon] Neon
+    [deuteranopia] Deuteranopi
+    [protanopia] Protanopi
+    [tritanopia] Tritanopi
+    [highcontrast] Hög kontrast
+   *[classic] Klassiskt
+}
+video-theme-pack = Temapaket: { $pack }
+theme-pack-none = Inget
+video-camera = Kamera: { $camera ->
+    [follow] Följande
+    [dramatic] Dramatisk
+   *[classic] Klassisk
+}
+video-ball-heat = Glödande boll: { $value }
+video-backdrop = Bakgrund: { $value }
+video-skin = Utseende: { $skin }
+skin-none = Inget
+video-ui-scale = Gränssnittets storlek: { $percent }%
+video-hud-layout = Poängtavla: { $layout ->
+    [centred] Centrerad
+   *[corners] Hörnen
+}
+video-crt = CRT: { $value }
+video-vsync = Vsync: { $value }
+video-fps-cap = Bildtaktstak: { $fps ->
+    [0] Av
+   *[other] { $fps }
+}
+video-low-power = Spara ström i bakgrunden: { $value }
+video-reduce-motion = Minska rörelse: { $value }
+video-particles = Partiklar: { $value }
+video-max-brightness = Största ljusstyrka: { $percent }%
+video-game-speed = Spelhastighet: { $percent }%
+video-announcer = Speaker: { $value }
+video-language = Språk: { $language }
+
+## Statistik
+
+stats-title = STATISTIK
+stats-matches-played = Spelade matcher: { $count }
+stats-p1-wins = Vinster P1: { $count }
+stats-p2-wins = Vinster P2: { $count }
+stats-longest-rally = Längsta rally: { $count }
+stats-fastest-ball = Snabbaste boll: { $speed }
+
+## Topplista
+
+leaderboard-title = TOPPLISTA
+leaderboard-loading = Laddar...
+leaderboard-empty = Inga resultat än
+leaderboard-stale = Kunde inte nå topplistan, visar de senaste resultaten
+leaderboard-unavailable = Ingen topplisteserver, starta med --leaderboard <url>
+
+## Prestationer
+
+achievements-title = PRESTATIONER
+achievements-unlocked = { $unlocked } av { $total } upplåsta
+achievement-first-win = Första segern
+achievement-first-win-description = Vinn en match
+achievement-shutout = Nollan
+achievement-shutout-description = Vinn med 11-0
+achievement-long-rally = Hundraklubben
+achievement-long-rally-description = Håll igång en rally i 100 slag
+achievement-beat-insane = Jättedödare
+achievement-beat-insane-description = Besegra AI:n på Galen
+achievement-double-points = Yrsel
+achievement-double-points-description = Driv upp en rally tills mål räknas dubbelt
+achievement-practice-streak = Väggen
+achievement-practice-streak-description = Returnera 50 i rad i träning
+
+## Dagens utmaning
+
+daily-title = DAGENS UTMANING
+daily-no-modifiers = Inga modifierare
+daily-best = Bäst idag: vann med { $score }
+daily-not-played = Inte spelad än idag
+daily-play = Spela
+
+## Profiler
+
+profiles-title = PROFILER
+profiles-new = Ny profil: { $name }
+profiles-entry = { $name }: { $color ->
+    [red] röd
+    [orange] orange
+    [yellow] gul
+    [green] grön
+    [cyan] turkos
+    [blue] blå
+    [purple] lila
+    [pink] rosa
+   *[theme] temats färg
+}{ $skin ->
+    [none] {""}
+   *[other] , { $skin }
+}, vann { $wins } av { $played }, längsta rally { $rally }
+profile-guest = Gäst
+profile-ai = AI
+
+## Notiser
+
+toast-settings-saved = Inställningarna sparade
+toast-achievement-unlocked = Prestation upplåst: { $name }
+toast-settings-not-saved = Kunde inte spara inställningarna
+toast-gamepad-connected = Handkontroll ansluten: P1
+toast-gamepad-disconnected = Handkontroll frånkopplad: P1
+toast-bot-connected = Bot ansluten: { $peer }
+toast-spectator-joined = Åskådare ansluten: { $peer }
+toast-spectating-ended = Matchen du tittade på är slut
+toast-lan-joined = Spelare ansluten: { $peer }
+toast-lan-failed = Kunde inte starta nätverksspel
+toast-lan-join-failed = Kunde inte gå med i spelet
+toast-lan-joining = Ansluter…
+toast-lan-lost-guest = Tappade den andra spelaren, väntar på att de kommer tillbaka
+toast-lan-lost-host = Tappade värden, försöker komma tillbaka
+toast-lan-reconnected = Återansluten
+toast-lan-guest-forfeited = Den andra spelaren kom aldrig tillbaka och förlorar matchen
+toast-lan-forfeited = Kunde inte komma tillbaka, matchen förlorad
+toast-lobby-failed = Kunde inte öppna ett rum, bara spelare på nätverket kan gå med
+toast-lobby-unset = Starta fjong med --lobby <adress:port> för att använda rumskoder
+toast-capture-saved = Sparade { $path }
+toast-capture-failed = Kunde inte spara bilden eller klippet
@@ -0,0 +1,57 @@
+struct BackdropMaterial {
+    color: vec4<f32>;
+    // Size of the court, in world units.
+    size: vec2<f32>;
+    // Where the ball was last hit, from the middle of the court.
+    pulse_at: vec2<f32>;
+    time: f32;
+    pulse: f32;
+    ripple_x: f32;
+    // Seconds since the last goal, below 0 before the first.
+    ripple_age: f32;
+};
+
+[[group(1), binding(0)]]
+var<uniform> material: BackdropMaterial;
+
+struct FragmentInput {
+    [[builtin(front_facing)]] is_front: bool;
+    [[location(0)]] world_position: vec4<f32>;
+    [[location(1)]] world_normal: vec3<f32>;
+    [[location(2)]] uv: vec2<f32>;
+};
+
+let GRID_SPACING: f32 = 40.0;
+let LINE_WIDTH: f32 = 1.5;
+// How far the grid drifts a second, so it never looks quite still.
+let DRIFT_SPEED: f32 = 6.0;
+let RESTING_GLOW: f32 = 0.12;
+// How far from a hit its pulse reaches before it has all but faded.
+let PULSE_REACH: f32 = 120.0;
+let RIPPLE_SPEED: f32 = 700.0;
+let RIPPLE_WIDTH: f32 = 50.0;
+let RIPPLE_SECONDS: f32 = 1.2;
+
+[[stage(fragment)]]
+fn fragment(in: FragmentInput) -> [[location(0)]] vec4<f32> {
+    let position = (in.uv - vec2<f32>(0.5)) * material.size * vec2<f32>(1.0, -1.0);
+
+    // Distance to the nearest line either way, in world units.
+    let drifted = position + vec2<f32>(0.0, material.time * DRIFT_SPEED);
+    let cell = abs(fract(drifted / GRID_SPACING + vec2<f32>(0.5)) - vec2<f32>(0.5)) * GRID_SPACING;
+    let line = 1.0 - smoothStep(0.0, LINE_WIDTH, min(cell.x, cell.y));
+
+    var glow = RESTING_GLOW * (0.8 + 0.2 * sin(material.time * 1.5));
+    let from_hit = length(position - material.pulse_at);
+    glow = glow + material.pulse * exp(-from_hit / PULSE_REACH);
+
+    // A wave rolling out across the court from the goal line.
+    if (material.ripple_age >= 0.0 && material.ripple_age < RIPPLE_SECONDS) {
+        let front = material.ripple_age * RIPPLE_SPEED;
+        let from_goal = abs(position.x - material.ripple_x);
+        let wave = 1.0 - clamp(abs(from_goal - front) / RIPPLE_WIDTH, 0.0, 1.0);
+        glow = glow + wave * (1.0 - material.ripple_age / RIPPLE_SECONDS);
+    }
+
+    return vec4<f32>(material.color.rgb, material.color.a * line * min(glow, 1.0));
+}
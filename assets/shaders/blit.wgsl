@@ -0,0 +1,26 @@
+// Copies the frame the game was drawn into onto the window.
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] uv: vec2<f32>;
+};
+
+[[group(0), binding(0)]]
+var frame: texture_2d<f32>;
+[[group(0), binding(1)]]
+var frame_sampler: sampler;
+
+// One triangle big enough to cover the whole window.
+[[stage(vertex)]]
+fn vertex([[builtin(vertex_index)]] index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+[[stage(fragment)]]
+fn fragment(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return textureSample(frame, frame_sampler, in.uv);
+}
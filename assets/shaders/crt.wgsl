@@ -0,0 +1,39 @@
+struct CrtMaterial {
+    // Number of scanlines from the top of the screen to the bottom.
+    lines: f32;
+};
+
+[[group(1), binding(0)]]
+var<uniform> material: CrtMaterial;
+
+struct FragmentInput {
+    [[builtin(front_facing)]] is_front: bool;
+    [[location(0)]] world_position: vec4<f32>;
+    [[location(1)]] world_normal: vec3<f32>;
+    [[location(2)]] uv: vec2<f32>;
+};
+
+let PI: f32 = 3.14159265;
+let SCANLINE_DARKNESS: f32 = 0.35;
+let BEZEL_RADIUS: f32 = 0.12;
+
+[[stage(fragment)]]
+fn fragment(in: FragmentInput) -> [[location(0)]] vec4<f32> {
+    let centred = in.uv * 2.0 - vec2<f32>(1.0);
+
+    // Bulge the screen a little, so the bezel and scanlines bow outwards.
+    let curved = centred * (1.0 + 0.04 * dot(centred.yx, centred.yx));
+
+    // Black outside a rounded rectangle, like the edge of the tube.
+    let corner = max(abs(curved) - vec2<f32>(1.0 - BEZEL_RADIUS), vec2<f32>(0.0));
+    if (length(corner) > BEZEL_RADIUS) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+
+    let scanline = 0.5 + 0.5 * sin((curved.y * 0.5 + 0.5) * material.lines * 2.0 * PI);
+    let edge = clamp((length(curved) - 0.5) / 1.1, 0.0, 1.0);
+    let vignette = edge * edge * (3.0 - 2.0 * edge);
+    let darkness = 1.0 - (1.0 - SCANLINE_DARKNESS * scanline) * (1.0 - vignette);
+
+    return vec4<f32>(0.0, 0.0, 0.0, darkness);
+}
@@ -0,0 +1,24 @@
+struct GlowMaterial {
+    color: vec4<f32>;
+    // Half the size of the glowing shape, as a fraction of the quad.
+    core: vec2<f32>;
+};
+
+[[group(1), binding(0)]]
+var<uniform> material: GlowMaterial;
+
+struct FragmentInput {
+    [[builtin(front_facing)]] is_front: bool;
+    [[location(0)]] world_position: vec4<f32>;
+    [[location(1)]] world_normal: vec3<f32>;
+    [[location(2)]] uv: vec2<f32>;
+};
+
+[[stage(fragment)]]
+fn fragment(in: FragmentInput) -> [[location(0)]] vec4<f32> {
+    // 0 on the edge of the shape, 1 on the edge of the quad.
+    let outside = max(abs(in.uv - vec2<f32>(0.5)) - material.core, vec2<f32>(0.0));
+    let distance = length(outside / (vec2<f32>(0.5) - material.core));
+    let falloff = pow(1.0 - clamp(distance, 0.0, 1.0), 2.0);
+    return vec4<f32>(material.color.rgb, material.color.a * falloff);
+}
@@ -0,0 +1,134 @@
+use bevy::{math::const_vec2, prelude::*};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use fjong::{
+    collision::{self, SpatialHash},
+    ArenaLayout, Ball, Obstacle, Serve, SimulationPlugin, Velocity, BALL_SIZE, BALL_SPEED,
+    TIME_STEP,
+};
+
+/// How many obstacles to measure with, up to far more than any arena has.
+const COUNTS: [usize; 4] = [10, 100, 1_000, 10_000];
+/// Obstacles are spread over this much of the middle of the court, clear of
+/// the paddles.
+const SPREAD: Vec2 = const_vec2!([600.0, 500.0]);
+const OBSTACLE_SIZE: Vec2 = const_vec2!([4.0, 4.0]);
+
+/// `count` small blocks in a grid over the middle of the court.
+fn obstacles(count: usize) -> Vec<Obstacle> {
+    let columns = (count as f32).sqrt().ceil() as usize;
+    let rows = count.div_ceil(columns);
+    let step = SPREAD / Vec2::new(columns as f32, rows as f32);
+    (0..count)
+        .map(|i| Obstacle {
+            position: -SPREAD / 2.0
+                + step * (Vec2::new((i % columns) as f32, (i / columns) as f32) + 0.5),
+            size: OBSTACLE_SIZE,
+        })
+        .collect()
+}
+
+/// The contact test on its own against every obstacle, as
+/// `check_for_collisions` ran it before there was a broad phase.
+fn contacts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("swept_contact");
+    let ball_size = BALL_SIZE.truncate();
+    let travel = Vec2::new(BALL_SPEED, BALL_SPEED) * TIME_STEP;
+    for count in COUNTS {
+        let obstacles = obstacles(count);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(count),
+            &obstacles,
+            |b, obstacles| {
+                b.iter(|| {
+                    obstacles
+                        .iter()
+                        .filter(|obstacle| {
+                            collision::swept_contact(
+                                black_box(Vec2::ZERO),
+                                ball_size,
+                                travel,
+                                obstacle.position,
+                                obstacle.size,
+                            )
+                            .is_some()
+                        })
+                        .count()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+/// The broad phase picking out what is near the ball, then the contact test
+/// on just those, as `check_for_collisions` runs them each tick.
+fn broad_phase(c: &mut Criterion) {
+    let mut group = c.benchmark_group("broad_phase");
+    let ball_size = BALL_SIZE.truncate();
+    let travel = Vec2::new(BALL_SPEED, BALL_SPEED) * TIME_STEP;
+    for count in COUNTS {
+        let obstacles = obstacles(count);
+        let mut hash = SpatialHash::default();
+        for (i, obstacle) in obstacles.iter().enumerate() {
+            hash.insert(Entity::from_raw(i as u32), obstacle.position, obstacle.size);
+        }
+        group.bench_function(BenchmarkId::from_parameter(count), |b| {
+            b.iter(|| {
+                let ball = black_box(Vec2::ZERO);
+                hash.near(ball - travel / 2.0, ball_size + travel.abs())
+                    .into_iter()
+                    .filter(|entity| {
+                        let obstacle = &obstacles[entity.id() as usize];
+                        collision::swept_contact(
+                            ball,
+                            ball_size,
+                            travel,
+                            obstacle.position,
+                            obstacle.size,
+                        )
+                        .is_some()
+                    })
+                    .count()
+            })
+        });
+    }
+    group.finish();
+}
+
+/// A whole tick of the simulation with the ball in play, most of which goes
+/// on collisions once there are enough obstacles.
+fn ticks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("simulation_tick");
+    for count in COUNTS {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(ArenaLayout {
+                obstacles: obstacles(count),
+                ..default()
+            })
+            .add_plugin(SimulationPlugin::stepped(0));
+        app.update();
+        while app.world.resource::<Serve>().in_progress() {
+            app.update();
+        }
+
+        let mut ball_query = app
+            .world
+            .query_filtered::<(&mut Transform, &mut Velocity), With<Ball>>();
+        group.bench_function(BenchmarkId::from_parameter(count), |b| {
+            b.iter(|| {
+                // Back in the middle every tick, so it never gets as far as
+                // a goal and waits to be served again.
+                let (mut transform, mut velocity) =
+                    ball_query.iter_mut(&mut app.world).next().unwrap();
+                transform.translation = Vec3::new(0.0, 0.0, transform.translation.z);
+                velocity.0 = Vec2::new(BALL_SPEED, BALL_SPEED / 2.0);
+                app.update();
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, contacts, broad_phase, ticks);
+criterion_main!(benches);
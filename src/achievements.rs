@@ -0,0 +1,206 @@
+use std::{
+    collections::BTreeSet,
+    io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use fluent::fluent_args;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ai::{Brain, Difficulty},
+    persist, Frenzy, FrenzyEvent, Locale, MatchOverEvent, MatchRules, P1Paddle, P2Paddle, Player,
+    Scoreboard, StreakEvent, Toast,
+};
+
+/// Where unlocked achievements live, under [`data_path`](crate::data_path).
+pub const ACHIEVEMENTS_PATH: &str = "achievements.ron";
+/// Points the winner needs, with none for the loser, for a shutout.
+const SHUTOUT_POINTS: usize = 11;
+const LONG_RALLY_HITS: usize = 100;
+const PRACTICE_STREAK_RETURNS: usize = 50;
+
+/// Something worth having done, unlocked once and kept for good.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Achievement {
+    /// Win a match.
+    FirstWin,
+    /// Win [`SHUTOUT_POINTS`] to nil.
+    Shutout,
+    /// Keep a rally going for [`LONG_RALLY_HITS`] hits.
+    LongRally,
+    /// Beat the AI on [`Difficulty::Insane`].
+    BeatInsane,
+    /// Whip a rally up into a frenzy where goals count double.
+    DoublePoints,
+    /// Return the ball [`PRACTICE_STREAK_RETURNS`] times in a row in
+    /// practice.
+    PracticeStreak,
+}
+
+impl Achievement {
+    /// In the order the gallery shows them.
+    pub const ALL: [Achievement; 6] = [
+        Achievement::FirstWin,
+        Achievement::Shutout,
+        Achievement::LongRally,
+        Achievement::BeatInsane,
+        Achievement::DoublePoints,
+        Achievement::PracticeStreak,
+    ];
+
+    /// Locale id of the achievement's name, with `-description` added for
+    /// what it takes.
+    pub(crate) fn locale_id(self) -> &'static str {
+        match self {
+            Achievement::FirstWin => "achievement-first-win",
+            Achievement::Shutout => "achievement-shutout",
+            Achievement::LongRally => "achievement-long-rally",
+            Achievement::BeatInsane => "achievement-beat-insane",
+            Achievement::DoublePoints => "achievement-double-points",
+            Achievement::PracticeStreak => "achievement-practice-streak",
+        }
+    }
+}
+
+/// Every achievement unlocked so far, kept in [`ACHIEVEMENTS_PATH`] between
+/// runs.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Achievements {
+    pub unlocked: BTreeSet<Achievement>,
+}
+
+impl Achievements {
+    pub fn load(path: impl AsRef<Path>) -> Achievements {
+        persist::load_or_create(path.as_ref())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        persist::save(self, path.as_ref())
+    }
+}
+
+/// Watches matches for [`Achievement`]s, toasting each as it is unlocked
+/// and keeping it in `path`. Only players count, so nothing a bot does
+/// unlocks anything.
+pub struct AchievementsPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Achievements::load(&self.path))
+            .insert_resource(AchievementsFile(self.path.clone()))
+            .init_resource::<Locale>()
+            .init_resource::<MatchRules>()
+            .add_event::<Toast>()
+            .add_event::<Unlock>()
+            .add_system(check_matches)
+            .add_system(check_rallies)
+            .add_system(unlock.after(check_matches).after(check_rallies));
+    }
+}
+
+struct AchievementsFile(PathBuf);
+
+/// Asks for an achievement to be unlocked, if it isn't already.
+struct Unlock(Achievement);
+
+/// Paddles and whether a bot is driving them.
+type Paddles<'w, 's> = Query<
+    'w,
+    's,
+    (Option<&'static Brain>, Option<&'static P1Paddle>),
+    Or<(With<P1Paddle>, With<P2Paddle>)>,
+>;
+
+fn check_matches(
+    rules: Res<MatchRules>,
+    scoreboard: Res<Scoreboard>,
+    paddle_query: Paddles,
+    mut match_over_events: EventReader<MatchOverEvent>,
+    mut unlocks: EventWriter<Unlock>,
+) {
+    let is_bot = |end: Player| {
+        paddle_query
+            .iter()
+            .any(|(brain, p1)| brain.is_some() && (p1.is_some() == (end == Player::P1)))
+    };
+    for match_over in match_over_events.iter() {
+        let winner = match_over.winner;
+        if is_bot(winner) {
+            continue;
+        }
+        unlocks.send(Unlock(Achievement::FirstWin));
+
+        let (winner_score, loser_score) = match winner {
+            Player::P1 => (scoreboard.p1_score, scoreboard.p2_score),
+            Player::P2 => (scoreboard.p2_score, scoreboard.p1_score),
+        };
+        if winner_score >= SHUTOUT_POINTS && loser_score == 0 {
+            unlocks.send(Unlock(Achievement::Shutout));
+        }
+        if rules.difficulty == Difficulty::Insane && is_bot(winner.other()) {
+            unlocks.send(Unlock(Achievement::BeatInsane));
+        }
+    }
+}
+
+/// Long rallies, frenzies and practice streaks. A rally only counts with a
+/// player in it.
+fn check_rallies(
+    frenzy: Option<Res<Frenzy>>,
+    paddle_query: Paddles,
+    mut frenzy_events: EventReader<FrenzyEvent>,
+    mut streak_events: EventReader<StreakEvent>,
+    mut unlocks: EventWriter<Unlock>,
+) {
+    let with_player = paddle_query.iter().any(|(brain, _)| brain.is_none());
+    if !with_player {
+        return;
+    }
+    if matches!(frenzy, Some(frenzy) if frenzy.rally >= LONG_RALLY_HITS) {
+        unlocks.send(Unlock(Achievement::LongRally));
+    }
+    if frenzy_events
+        .iter()
+        .any(|frenzy| frenzy.level >= Frenzy::DOUBLE_POINTS)
+    {
+        unlocks.send(Unlock(Achievement::DoublePoints));
+    }
+    if streak_events
+        .iter()
+        .any(|streak| streak.returns >= PRACTICE_STREAK_RETURNS)
+    {
+        unlocks.send(Unlock(Achievement::PracticeStreak));
+    }
+}
+
+fn unlock(
+    locale: Res<Locale>,
+    file: Res<AchievementsFile>,
+    mut achievements: ResMut<Achievements>,
+    mut unlocks: EventReader<Unlock>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let mut unlocked_any = false;
+    for Unlock(achievement) in unlocks.iter() {
+        if !achievements.unlocked.insert(*achievement) {
+            continue;
+        }
+        info!("Unlocked {:?}", achievement);
+        let name = locale.text(achievement.locale_id());
+        toasts.send(Toast(locale.text_with(
+            "toast-achievement-unlocked",
+            &fluent_args!["name" => name],
+        )));
+        unlocked_any = true;
+    }
+    if unlocked_any {
+        if let Err(err) = achievements.save(&file.0) {
+            warn!("Could not save {}: {}", file.0.display(), err);
+        }
+    }
+}
@@ -0,0 +1,644 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    collision::MAX_BOUNCE_ANGLE, Arena, Ball, GameConfig, GameRng, GoalEvent, MatchRules, P1Paddle,
+    P2Paddle, Scoreboard, TickRate, Velocity,
+};
+
+/// Fastest a brain is allowed to move its paddle, in units per second.
+pub(crate) const AI_MAX_SPEED: f32 = 800.0;
+
+/// Most bounces off the top and bottom walls [`Predictor::predicted_path`]
+/// follows the ball through.
+const MAX_PATH_BOUNCES: usize = 16;
+
+/// Point difference at which rubber-banding has its full effect.
+const RUBBER_BAND_POINTS: f32 = 4.0;
+/// How far rubber-banding can move a brain towards [`Difficulty::Easy`] or
+/// [`Difficulty::Insane`].
+const RUBBER_BAND_STRENGTH: f32 = 0.6;
+
+/// What a brain gets to know about the ball.
+#[derive(Clone, Copy, Serialize)]
+pub struct BallView {
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+/// What a brain gets to know about the paddle it controls.
+#[derive(Serialize)]
+pub struct PaddleView {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub size: Vec2,
+}
+
+impl BallView {
+    /// Whether the ball is travelling towards the given paddle.
+    pub fn approaching(&self, paddle: &PaddleView) -> bool {
+        (paddle.position.x - self.position.x) * self.velocity.x > 0.0
+    }
+
+    /// Seconds until the ball reaches the paddle's x coordinate.
+    pub fn time_to_reach(&self, paddle: &PaddleView) -> f32 {
+        (paddle.position.x - self.position.x) / self.velocity.x
+    }
+}
+
+/// A bot that can drive a paddle.
+///
+/// `decide` is called once per fixed tick, `tick_rate` times a second, and
+/// returns the vertical velocity the paddle should move at. Implement this
+/// and attach it to a paddle with [`Brain::new`] to add a new opponent. Any
+/// randomness must come from `rng` so matches stay reproducible.
+pub trait PaddleBrain: Send + Sync + 'static {
+    fn decide(
+        &mut self,
+        ball: &BallView,
+        paddle: &PaddleView,
+        arena: &Arena,
+        tick_rate: TickRate,
+        rng: &mut GameRng,
+    ) -> f32;
+
+    /// Where the paddle at the other end is, given before every `decide`
+    /// for brains that aim their returns away from it.
+    fn see_opponent(&mut self, _opponent: &PaddleView) {}
+
+    /// Where the brain last decided to put its paddle, for brains that plan
+    /// that far ahead.
+    fn target_y(&self) -> Option<f32> {
+        None
+    }
+
+    /// The brain's tunable skill, if it has one.
+    fn skill_mut(&mut self) -> Option<&mut Skill> {
+        None
+    }
+}
+
+#[derive(Component)]
+pub struct Brain {
+    brain: Box<dyn PaddleBrain>,
+    wanted: f32,
+}
+
+impl Brain {
+    pub fn new(brain: impl PaddleBrain) -> Brain {
+        Brain {
+            brain: Box::new(brain),
+            wanted: 0.0,
+        }
+    }
+
+    pub fn skill_mut(&mut self) -> Option<&mut Skill> {
+        self.brain.skill_mut()
+    }
+
+    /// The velocity the brain last asked its paddle for.
+    pub fn wanted(&self) -> f32 {
+        self.wanted
+    }
+
+    pub fn target_y(&self) -> Option<f32> {
+        self.brain.target_y()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Insane,
+    /// The hidden boss: P2 played by a trained [`Policy`](crate::Policy),
+    /// only on offer once one is loaded. Anything that needs its skill, like
+    /// a [`Predictor`] standing in for it, gets [`Difficulty::Insane`]'s.
+    Learned,
+}
+
+/// How good a brain is at its job.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+pub struct Skill {
+    /// Seconds between the ball moving and the brain noticing.
+    pub reaction_time: f32,
+    /// Largest error, in either direction, on where the ball is expected to arrive.
+    pub noise: f32,
+    /// Chance of badly misreading the ball each time it heads our way.
+    pub misjudge_chance: f64,
+    pub max_speed: f32,
+    /// How far towards the edge of its paddle, from 0 for always the middle
+    /// to 1 for all the way, the brain takes the ball to angle it away from
+    /// the opponent.
+    #[serde(default)]
+    pub aggression: f32,
+}
+
+impl Skill {
+    /// The skill `t` of the way from this one to `other`.
+    pub fn lerp(&self, other: &Skill, t: f32) -> Skill {
+        let mix = |from: f32, to: f32| from + (to - from) * t;
+        Skill {
+            reaction_time: mix(self.reaction_time, other.reaction_time),
+            noise: mix(self.noise, other.noise),
+            misjudge_chance: self.misjudge_chance
+                + (other.misjudge_chance - self.misjudge_chance) * t as f64,
+            max_speed: mix(self.max_speed, other.max_speed),
+            aggression: mix(self.aggression, other.aggression),
+        }
+    }
+}
+
+impl Difficulty {
+    pub fn skill(self) -> Skill {
+        match self {
+            Difficulty::Easy => Skill {
+                reaction_time: 0.3,
+                noise: 40.0,
+                misjudge_chance: 0.25,
+                max_speed: 450.0,
+                aggression: 0.0,
+            },
+            Difficulty::Medium => Skill {
+                reaction_time: 0.2,
+                noise: 25.0,
+                misjudge_chance: 0.12,
+                max_speed: 600.0,
+                aggression: 0.3,
+            },
+            Difficulty::Hard => Skill {
+                reaction_time: 0.1,
+                noise: 12.0,
+                misjudge_chance: 0.05,
+                max_speed: AI_MAX_SPEED,
+                aggression: 0.6,
+            },
+            Difficulty::Insane | Difficulty::Learned => Skill {
+                reaction_time: 0.0,
+                noise: 0.0,
+                misjudge_chance: 0.0,
+                max_speed: AI_MAX_SPEED,
+                aggression: 0.8,
+            },
+        }
+    }
+}
+
+/// How the AI plays, as picked on the command line or from the pause menu:
+/// one of the difficulties, or adaptive for whichever the match has with
+/// [`MatchRules::adaptive_ai`] on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AiLevel {
+    Easy,
+    Medium,
+    Hard,
+    Insane,
+    Adaptive,
+}
+
+impl AiLevel {
+    pub fn parse(name: &str) -> Option<AiLevel> {
+        match name {
+            "easy" => Some(AiLevel::Easy),
+            "medium" => Some(AiLevel::Medium),
+            "hard" => Some(AiLevel::Hard),
+            "insane" => Some(AiLevel::Insane),
+            "adaptive" => Some(AiLevel::Adaptive),
+            _ => None,
+        }
+    }
+
+    /// The level `rules` have the AI playing at, unless it is the learned
+    /// boss, which isn't one of them.
+    pub fn of(rules: &MatchRules) -> Option<AiLevel> {
+        if rules.adaptive_ai {
+            return Some(AiLevel::Adaptive);
+        }
+        match rules.difficulty {
+            Difficulty::Easy => Some(AiLevel::Easy),
+            Difficulty::Medium => Some(AiLevel::Medium),
+            Difficulty::Hard => Some(AiLevel::Hard),
+            Difficulty::Insane => Some(AiLevel::Insane),
+            Difficulty::Learned => None,
+        }
+    }
+
+    pub fn apply(self, rules: &mut MatchRules) {
+        let difficulty = match self {
+            AiLevel::Easy => Difficulty::Easy,
+            AiLevel::Medium => Difficulty::Medium,
+            AiLevel::Hard => Difficulty::Hard,
+            AiLevel::Insane => Difficulty::Insane,
+            AiLevel::Adaptive => {
+                rules.adaptive_ai = true;
+                return;
+            }
+        };
+        rules.difficulty = difficulty;
+        rules.adaptive_ai = false;
+    }
+}
+
+/// An [`AiLevel`] picked mid-match, waiting for the next serve so the
+/// rally in progress is finished against the AI it started with.
+#[derive(Debug, Default)]
+pub struct PendingAi(pub Option<AiLevel>);
+
+/// Switches to the [`PendingAi`] as a goal is scored, ready for the serve
+/// after it.
+pub fn apply_pending_ai(
+    mut goal_events: EventReader<GoalEvent>,
+    mut pending: ResMut<PendingAi>,
+    mut rules: ResMut<MatchRules>,
+) {
+    if goal_events.iter().count() == 0 {
+        return;
+    }
+    if let Some(level) = pending.0.take() {
+        level.apply(&mut rules);
+    }
+}
+
+/// Moves towards the velocity needed to meet the ball at `target_y` in time.
+fn steer_towards(
+    target_y: f32,
+    ball: &BallView,
+    paddle: &PaddleView,
+    max_speed: f32,
+    tick_rate: TickRate,
+) -> f32 {
+    // Never plan to get there faster than a single tick, or we overshoot.
+    let time_til_collision = ball.time_to_reach(paddle).max(tick_rate.step());
+    let velocity_wanted = (target_y - paddle.position.y) / time_til_collision;
+
+    velocity_wanted.clamp(-max_speed, max_speed)
+}
+
+/// Follows the ball's current height whenever it is heading our way.
+#[derive(Default)]
+pub struct Tracker;
+
+impl PaddleBrain for Tracker {
+    fn decide(
+        &mut self,
+        ball: &BallView,
+        paddle: &PaddleView,
+        _arena: &Arena,
+        tick_rate: TickRate,
+        _rng: &mut GameRng,
+    ) -> f32 {
+        if !ball.approaching(paddle) {
+            return 0.0;
+        }
+
+        steer_towards(ball.position.y, ball, paddle, AI_MAX_SPEED, tick_rate)
+    }
+}
+
+/// Works out where the ball will cross the paddle's line, bounces off the
+/// top and bottom walls included, and heads there.
+///
+/// Below [`Difficulty::Insane`] it reacts late, aims a little off, and now and
+/// then misreads the ball entirely, so it can be beaten. The harder it is, the
+/// further off centre it meets the ball, to angle the return into whichever
+/// corner is further from the opponent.
+pub struct Predictor {
+    pub skill: Skill,
+    seen: VecDeque<BallView>,
+    aim_error: Option<f32>,
+    opponent: Option<Vec2>,
+    aim: Option<f32>,
+    target_y: Option<f32>,
+}
+
+impl Default for Predictor {
+    fn default() -> Self {
+        Predictor::new(Difficulty::Medium)
+    }
+}
+
+impl Predictor {
+    pub fn new(difficulty: Difficulty) -> Predictor {
+        Predictor::with_skill(difficulty.skill())
+    }
+
+    pub fn with_skill(skill: Skill) -> Predictor {
+        Predictor {
+            skill,
+            seen: VecDeque::new(),
+            aim_error: None,
+            opponent: None,
+            aim: None,
+            target_y: None,
+        }
+    }
+
+    /// Feeds in what the ball is doing now and returns what the brain has
+    /// caught up with, if anything yet.
+    fn perceive(&mut self, ball: &BallView, tick_rate: TickRate) -> Option<BallView> {
+        self.seen.push_back(*ball);
+        if self.seen.len() <= tick_rate.ticks(self.skill.reaction_time) as usize {
+            return None;
+        }
+        self.seen.pop_front()
+    }
+
+    /// Picks how far off this approach will be, once per approach so the
+    /// paddle doesn't jitter around.
+    fn roll_aim_error(&self, paddle: &PaddleView, rng: &mut GameRng) -> f32 {
+        if rng.gen_bool(self.skill.misjudge_chance) {
+            let miss = rng.gen_range(paddle.size.y * 0.6..=paddle.size.y);
+            if rng.gen_bool(0.5) {
+                miss
+            } else {
+                -miss
+            }
+        } else if self.skill.noise > 0.0 {
+            rng.gen_range(-self.skill.noise..=self.skill.noise)
+        } else {
+            0.0
+        }
+    }
+
+    /// The offset along the paddle, from -1 to 1 as in
+    /// [`paddle_bounce`](crate::collision::paddle_bounce), that sends a return
+    /// from `intercept_y` straight at whichever corner of the far end is
+    /// further from the `opponent`.
+    pub fn aim_offset(intercept_y: f32, paddle: &PaddleView, opponent: Vec2, arena: &Arena) -> f32 {
+        let corner_y = if opponent.y - arena.bottom > arena.top - opponent.y {
+            arena.bottom
+        } else {
+            arena.top
+        };
+        let across = (opponent.x - paddle.position.x).abs();
+        let angle = (corner_y - intercept_y).atan2(across);
+        (angle / MAX_BOUNCE_ANGLE).clamp(-1.0, 1.0)
+    }
+
+    /// The way [`Predictor::intercept_y`] sees the ball going: from where it
+    /// is, off the top and bottom walls, to where it crosses the paddle's
+    /// line. Just where it is, if it is heading the other way.
+    pub fn predicted_path(ball: &BallView, paddle: &PaddleView, arena: &Arena) -> Vec<Vec2> {
+        let mut path = vec![ball.position];
+        if !ball.approaching(paddle) {
+            return path;
+        }
+
+        let mut position = ball.position;
+        let mut velocity = ball.velocity;
+        for _ in 0..MAX_PATH_BOUNCES {
+            let wall = if velocity.y > 0.0 {
+                arena.top
+            } else {
+                arena.bottom
+            };
+            let to_wall = if velocity.y == 0.0 {
+                f32::INFINITY
+            } else {
+                ((wall - position.y) / velocity.y).max(0.0)
+            };
+            let to_paddle = (paddle.position.x - position.x) / velocity.x;
+            if to_paddle <= to_wall {
+                break;
+            }
+            position += velocity * to_wall;
+            velocity.y = -velocity.y;
+            path.push(position);
+        }
+        path.push(Vec2::new(
+            paddle.position.x,
+            Predictor::intercept_y(ball, paddle, arena),
+        ));
+        path
+    }
+
+    pub fn intercept_y(ball: &BallView, paddle: &PaddleView, arena: &Arena) -> f32 {
+        let time = ball.time_to_reach(paddle);
+        let height = arena.top - arena.bottom;
+        let unfolded = ball.position.y - arena.bottom + ball.velocity.y * time;
+
+        // Mirror the straight-line path back into the arena for every bounce.
+        let folded = unfolded.rem_euclid(2.0 * height);
+        if folded > height {
+            arena.top - (folded - height)
+        } else {
+            arena.bottom + folded
+        }
+    }
+}
+
+impl PaddleBrain for Predictor {
+    fn decide(
+        &mut self,
+        ball: &BallView,
+        paddle: &PaddleView,
+        arena: &Arena,
+        tick_rate: TickRate,
+        rng: &mut GameRng,
+    ) -> f32 {
+        let ball = match self.perceive(ball, tick_rate) {
+            Some(ball) => ball,
+            None => return paddle.velocity.y,
+        };
+
+        if !ball.approaching(paddle) {
+            self.aim_error = None;
+            self.aim = None;
+            self.target_y = None;
+            return 0.0;
+        }
+
+        let aim_error = match self.aim_error {
+            Some(error) => error,
+            None => *self.aim_error.insert(self.roll_aim_error(paddle, rng)),
+        };
+        let intercept_y = Predictor::intercept_y(&ball, paddle, arena);
+        // Picked once per approach too, so the opponent moving about doesn't
+        // have the paddle chasing a different corner every tick.
+        let aim = match (self.aim, self.opponent) {
+            (Some(aim), _) => aim,
+            (None, Some(opponent)) => {
+                let offset = Predictor::aim_offset(intercept_y, paddle, opponent, arena);
+                *self.aim.insert(offset * self.skill.aggression)
+            }
+            (None, None) => 0.0,
+        };
+        // Near a wall, the paddle takes the ball as far off centre as it can.
+        let (bottom_bound, top_bound) = arena.paddle_bounds(paddle.size.y);
+        let aimed_y = (intercept_y - aim * paddle.size.y / 2.0).clamp(bottom_bound, top_bound);
+        let target_y = *self.target_y.insert(aimed_y + aim_error);
+
+        steer_towards(target_y, &ball, paddle, self.skill.max_speed, tick_rate)
+    }
+
+    fn see_opponent(&mut self, opponent: &PaddleView) {
+        self.opponent = Some(opponent.position);
+    }
+
+    fn target_y(&self) -> Option<f32> {
+        self.target_y
+    }
+
+    fn skill_mut(&mut self) -> Option<&mut Skill> {
+        Some(&mut self.skill)
+    }
+}
+
+/// Tracks the ball, but aims at a target that wanders around at random.
+pub struct Chaotic {
+    offset: f32,
+    /// How far from the ball the target may wander.
+    pub spread: f32,
+    /// Chance per tick of picking a new target offset.
+    pub restlessness: f32,
+}
+
+impl Default for Chaotic {
+    fn default() -> Self {
+        Chaotic {
+            offset: 0.0,
+            spread: 80.0,
+            restlessness: 0.05,
+        }
+    }
+}
+
+impl PaddleBrain for Chaotic {
+    fn decide(
+        &mut self,
+        ball: &BallView,
+        paddle: &PaddleView,
+        _arena: &Arena,
+        tick_rate: TickRate,
+        rng: &mut GameRng,
+    ) -> f32 {
+        if rng.gen_bool(self.restlessness as f64) {
+            self.offset = rng.gen_range(-self.spread..=self.spread);
+        }
+
+        if !ball.approaching(paddle) {
+            return 0.0;
+        }
+
+        steer_towards(
+            ball.position.y + self.offset,
+            ball,
+            paddle,
+            AI_MAX_SPEED,
+            tick_rate,
+        )
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn drive_brains(
+    config: Res<GameConfig>,
+    arena: Res<Arena>,
+    tick_rate: Res<TickRate>,
+    mut rng: ResMut<GameRng>,
+    ball_query: Query<(&Velocity, &Transform), With<Ball>>,
+    mut paddle_query: ParamSet<(
+        Query<(&Transform, &Velocity, Option<&P1Paddle>), Or<(With<P1Paddle>, With<P2Paddle>)>>,
+        Query<(&mut Brain, &mut Velocity, &Transform, Option<&P1Paddle>), Without<Ball>>,
+    )>,
+) {
+    let (ball_velocity, ball_transform) = ball_query.single();
+    let ball = BallView {
+        position: ball_transform.translation.truncate(),
+        velocity: ball_velocity.0,
+    };
+
+    let view = |transform: &Transform, velocity: &Velocity| PaddleView {
+        position: transform.translation.truncate(),
+        velocity: velocity.0,
+        size: transform.scale.truncate(),
+    };
+    let paddles: Vec<(bool, PaddleView)> = paddle_query
+        .p0()
+        .iter()
+        .map(|(transform, velocity, p1)| (p1.is_some(), view(transform, velocity)))
+        .collect();
+
+    for (mut brain, mut velocity, transform, p1) in paddle_query.p1().iter_mut() {
+        let paddle = view(transform, &velocity);
+        if let Some((_, opponent)) = paddles.iter().find(|(other, _)| *other != p1.is_some()) {
+            brain.brain.see_opponent(opponent);
+        }
+        let wanted = brain
+            .brain
+            .decide(&ball, &paddle, &arena, *tick_rate, &mut rng);
+        brain.wanted = wanted;
+        velocity.y = config.accelerate(velocity.y, wanted, tick_rate.step());
+    }
+}
+
+/// Gives the AI's brain the skill for [`MatchRules::difficulty`] whenever it
+/// changes, at whichever end it is playing.
+pub fn apply_difficulty(
+    rules: Res<MatchRules>,
+    mut brain_query: Query<(&mut Brain, Option<&mut BaseSkill>)>,
+) {
+    if !rules.is_changed() {
+        return;
+    }
+
+    let skill = rules.difficulty.skill();
+    for (mut brain, base) in brain_query.iter_mut() {
+        if let Some(brain_skill) = brain.skill_mut() {
+            *brain_skill = skill;
+        }
+        // Rubber-banding works from the new skill from now on.
+        if let Some(mut base) = base {
+            base.0 = skill;
+        }
+    }
+}
+
+/// The skill a brain started the match with, before any rubber-banding.
+#[derive(Component)]
+pub struct BaseSkill(pub Skill);
+
+/// With [`MatchRules::adaptive_ai`] on, eases brains off while they are
+/// ahead and sharpens them up while they are behind, to keep games close.
+pub fn rubber_band(
+    mut commands: Commands,
+    rules: Res<MatchRules>,
+    scoreboard: Res<Scoreboard>,
+    mut brain_query: Query<(Entity, &mut Brain, Option<&BaseSkill>, Option<&P1Paddle>)>,
+) {
+    if !rules.adaptive_ai || !(rules.is_changed() || scoreboard.is_changed()) {
+        return;
+    }
+
+    for (entity, mut brain, base, p1) in brain_query.iter_mut() {
+        let skill = match brain.skill_mut() {
+            Some(skill) => skill,
+            None => continue,
+        };
+        let base = match base {
+            Some(base) => base.0,
+            None => {
+                commands.entity(entity).insert(BaseSkill(*skill));
+                *skill
+            }
+        };
+
+        let (own, other) = if p1.is_some() {
+            (scoreboard.p1_score, scoreboard.p2_score)
+        } else {
+            (scoreboard.p2_score, scoreboard.p1_score)
+        };
+        let behind = (other as f32 - own as f32) / RUBBER_BAND_POINTS;
+        let t = behind.clamp(-1.0, 1.0) * RUBBER_BAND_STRENGTH;
+        *skill = if t >= 0.0 {
+            base.lerp(&Difficulty::Insane.skill(), t)
+        } else {
+            base.lerp(&Difficulty::Easy.skill(), -t)
+        };
+    }
+}
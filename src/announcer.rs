@@ -0,0 +1,170 @@
+use std::{collections::HashMap, io::Cursor, path::Path};
+
+use bevy::{
+    audio::{play_queued_audio_system, AudioOutput, Decodable},
+    prelude::*,
+    reflect::TypeUuid,
+};
+use rodio::source::FromIter;
+
+use crate::{
+    settings::AccessibilitySettings, Frenzy, GameMode, GoalEvent, MatchOverEvent, MatchRules,
+    Player, Scoreboard, Series,
+};
+
+/// Folder under `assets/` that the announcer's clips are loaded from.
+pub const VOICE_FOLDER: &str = "sounds/voice";
+
+/// Long enough for the goal stinger to ring out before the announcer speaks.
+const ANNOUNCE_DELAY_SECONDS: f32 = 0.6;
+
+/// Calls out the score after every goal of a versus or time-attack match,
+/// "seven, five, game point", and the winner at the end, when
+/// [`AccessibilitySettings::announcer`] is on.
+///
+/// Announcements are strung together from recorded clips in
+/// [`VOICE_FOLDER`], one word or phrase to a file, named after what is said:
+/// `0.wav` up to `21.wav`, `game_point.wav`, `deuce.wav`, `p1_wins.wav` and
+/// `p2_wins.wav`. The English voice that ships with the game was made with
+/// eSpeak NG, and can be recorded over. Any clip that is missing is left
+/// out.
+pub struct AnnouncerPlugin;
+
+impl Plugin for AnnouncerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Announcement>()
+            .init_resource::<AccessibilitySettings>()
+            .init_resource::<Queued>()
+            .init_resource::<Frenzy>()
+            .init_resource::<Series>()
+            .init_resource::<Audio<Announcement>>()
+            .init_non_send_resource::<AudioOutput<Announcement>>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                play_queued_audio_system::<Announcement>.exclusive_system(),
+            )
+            .add_startup_system(load_voice)
+            .add_system(queue_announcements)
+            .add_system(announce.after(queue_announcements));
+    }
+}
+
+/// Clips one after the other, played as a single sound.
+#[derive(TypeUuid)]
+#[uuid = "c4d81f0e-6a35-4b7e-9f12-83e5a0d6b47c"]
+struct Announcement {
+    clips: Vec<AudioSource>,
+}
+
+impl Decodable for Announcement {
+    type Decoder = FromIter<std::vec::IntoIter<rodio::Decoder<Cursor<AudioSource>>>>;
+    type DecoderItem = i16;
+
+    fn decoder(&self) -> Self::Decoder {
+        let decoders: Vec<_> = self.clips.iter().map(|clip| clip.decoder()).collect();
+        rodio::source::from_iter(decoders)
+    }
+}
+
+/// The announcer's clips, by what they say.
+struct Voice(HashMap<String, Handle<AudioSource>>);
+
+fn load_voice(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let handles = asset_server
+        .load_folder(VOICE_FOLDER)
+        .unwrap_or_else(|err| {
+            // The announcer is quiet without a voice, nothing more.
+            info!("No announcer voice in {}: {:?}", VOICE_FOLDER, err);
+            Vec::new()
+        });
+    let clips = handles
+        .into_iter()
+        .filter_map(|handle| {
+            let path = asset_server.get_handle_path(&handle)?;
+            let word = Path::new(path.path()).file_stem()?.to_str()?.to_string();
+            Some((word, handle.typed()))
+        })
+        .collect();
+    commands.insert_resource(Voice(clips));
+}
+
+/// What to say next, and how long until it is said.
+#[derive(Default)]
+struct Queued {
+    words: Vec<String>,
+    delay: Timer,
+}
+
+/// What the announcer says after a goal, clip by clip: the score, then who
+/// won, or "game point" if the next goal, at whatever the frenzy makes it
+/// worth, would win the match.
+pub fn score_call(
+    rules: &MatchRules,
+    scoreboard: &Scoreboard,
+    frenzy: &Frenzy,
+    winner: Option<Player>,
+) -> Vec<String> {
+    let (p1, p2) = (scoreboard.p1_score, scoreboard.p2_score);
+    let next_goal = rules.points_per_goal * frenzy.points_multiplier();
+    let game_point = rules.plays_to_points()
+        && (rules.is_winning(p1 + next_goal, p2) || rules.is_winning(p2 + next_goal, p1));
+
+    let mut words = vec![p1.to_string(), p2.to_string()];
+    match winner {
+        Some(Player::P1) => words.push("p1_wins".to_string()),
+        Some(Player::P2) => words.push("p2_wins".to_string()),
+        None if game_point => words.push("game_point".to_string()),
+        None if rules.deuce(scoreboard) => words.push("deuce".to_string()),
+        None => {}
+    }
+    words
+}
+
+#[allow(clippy::too_many_arguments)]
+fn queue_announcements(
+    accessibility: Res<AccessibilitySettings>,
+    rules: Res<MatchRules>,
+    series: Res<Series>,
+    scoreboard: Res<Scoreboard>,
+    frenzy: Res<Frenzy>,
+    mut queued: ResMut<Queued>,
+    mut goal_events: EventReader<GoalEvent>,
+    mut match_over_events: EventReader<MatchOverEvent>,
+) {
+    let scored = goal_events.iter().count() > 0;
+    let winner = match_over_events
+        .iter()
+        .last()
+        .map(|event| series.player_on(event.winner));
+    if !scored || !accessibility.announcer || rules.mode == GameMode::Practice {
+        return;
+    }
+
+    // A newer score makes an unsaid one old news.
+    *queued = Queued {
+        words: score_call(&rules, &scoreboard, &frenzy, winner),
+        delay: Timer::from_seconds(ANNOUNCE_DELAY_SECONDS, false),
+    };
+}
+
+fn announce(
+    time: Res<Time>,
+    audio: Res<Audio<Announcement>>,
+    voice: Res<Voice>,
+    sources: Res<Assets<AudioSource>>,
+    mut announcements: ResMut<Assets<Announcement>>,
+    mut queued: ResMut<Queued>,
+) {
+    if queued.words.is_empty() || !queued.delay.tick(time.delta()).finished() {
+        return;
+    }
+
+    let clips: Vec<_> = std::mem::take(&mut queued.words)
+        .iter()
+        .filter_map(|word| sources.get(voice.0.get(word)?))
+        .cloned()
+        .collect();
+    if !clips.is_empty() {
+        audio.play(announcements.add(Announcement { clips }));
+    }
+}
@@ -0,0 +1,207 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{
+    BOTTOM_WALL, GAP_BETWEEN_PADDLE_AND_GOAL, LEFT_WALL, RIGHT_WALL, TOP_WALL, WALL_THICKNESS,
+};
+
+/// Folder under `assets/` that arenas are loaded from.
+pub const ARENAS_FOLDER: &str = "arenas";
+
+/// The court a match is played on: its size, how wide the goals are, what
+/// is in the way and what colour it is.
+///
+/// Arenas are kept as `.arena.ron` files in `assets/arenas/`. Anything left
+/// out of a file is the same as on the classic court.
+#[derive(Clone, Debug, Deserialize, TypeUuid)]
+#[serde(default)]
+#[uuid = "8f3c2a61-7b4e-4d0a-b5c9-1e6f2d8a4c37"]
+pub struct ArenaLayout {
+    pub name: String,
+    /// Width and height inside the walls.
+    pub size: Vec2,
+    pub wall_thickness: f32,
+    /// How far in from its goal each paddle plays.
+    pub paddle_gap: f32,
+    /// Height of the opening at each end, centred on the middle of the
+    /// court, with wall above and below it. The whole end when unset.
+    pub goal_height: Option<f32>,
+    /// Blocks the ball bounces off, like the top and bottom walls.
+    pub obstacles: Vec<Obstacle>,
+    /// Pulls the ball towards a point as it passes, bending its path.
+    pub gravity_well: Option<GravityWell>,
+    /// Colour behind the court, in place of the theme's.
+    pub background: Option<Color>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Obstacle {
+    /// Centre of the block, with the centre of the court at the origin.
+    pub position: Vec2,
+    pub size: Vec2,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct GravityWell {
+    /// Centre of the well, with the centre of the court at the origin.
+    pub position: Vec2,
+    /// How hard the ball is pulled at the very centre, in units per second
+    /// squared. The pull eases off to nothing at `radius`.
+    pub strength: f32,
+    pub radius: f32,
+}
+
+impl Default for ArenaLayout {
+    fn default() -> Self {
+        ArenaLayout {
+            name: "Classic".to_string(),
+            size: Vec2::new(RIGHT_WALL - LEFT_WALL, TOP_WALL - BOTTOM_WALL),
+            wall_thickness: WALL_THICKNESS,
+            paddle_gap: GAP_BETWEEN_PADDLE_AND_GOAL,
+            goal_height: None,
+            obstacles: Vec::new(),
+            gravity_well: None,
+            background: None,
+        }
+    }
+}
+
+/// How big a court to build from an [`ArenaLayout`], picked for each match.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CourtSize {
+    /// Shorter and narrower, with thinner walls and the paddles nearer their
+    /// goals, for quick rallies.
+    Small,
+    /// As the layout is.
+    Classic,
+    /// Longer, with thicker walls and more room behind the paddles.
+    Wide,
+}
+
+impl CourtSize {
+    /// The court `layout` makes at this size. What is in the way keeps its
+    /// size, but moves with the walls so it stays where it was relative to
+    /// them.
+    pub fn apply(self, layout: &ArenaLayout) -> ArenaLayout {
+        let (scale, wall_scale, gap_scale) = match self {
+            CourtSize::Small => (Vec2::splat(0.8), 0.8, 0.75),
+            CourtSize::Classic => return layout.clone(),
+            CourtSize::Wide => (Vec2::new(1.25, 1.0), 1.2, 1.35),
+        };
+        ArenaLayout {
+            size: layout.size * scale,
+            wall_thickness: layout.wall_thickness * wall_scale,
+            paddle_gap: layout.paddle_gap * gap_scale,
+            goal_height: layout.goal_height.map(|height| height * scale.y),
+            obstacles: layout
+                .obstacles
+                .iter()
+                .map(|obstacle| Obstacle {
+                    position: obstacle.position * scale,
+                    size: obstacle.size,
+                })
+                .collect(),
+            gravity_well: layout.gravity_well.as_ref().map(|well| GravityWell {
+                position: well.position * scale,
+                ..well.clone()
+            }),
+            ..layout.clone()
+        }
+    }
+}
+
+/// Loads every arena in [`ARENAS_FOLDER`] and keeps [`Arenas`] up to date
+/// with them.
+pub struct ArenasPlugin;
+
+impl Plugin for ArenasPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ArenaLayout>()
+            .init_asset_loader::<ArenaLoader>()
+            .init_resource::<Arenas>()
+            .add_startup_system(load_arenas)
+            .add_system(list_arenas);
+    }
+}
+
+/// Every arena that has loaded, by name.
+pub struct Arenas(pub Vec<ArenaLayout>);
+
+impl Default for Arenas {
+    fn default() -> Self {
+        // Something to play on even if the folder is missing.
+        Arenas(vec![ArenaLayout::default()])
+    }
+}
+
+impl Arenas {
+    pub fn get(&self, name: &str) -> Option<&ArenaLayout> {
+        self.0.iter().find(|layout| layout.name == name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.0.iter().map(|layout| layout.name.clone()).collect()
+    }
+}
+
+#[derive(Default)]
+struct ArenaLoader;
+
+impl AssetLoader for ArenaLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let layout: ArenaLayout = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(layout));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["arena.ron"]
+    }
+}
+
+/// Keeps the arenas loaded for as long as the game runs.
+struct ArenaHandles(Vec<Handle<ArenaLayout>>);
+
+fn load_arenas(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let handles = asset_server
+        .load_folder(ARENAS_FOLDER)
+        .unwrap_or_else(|err| {
+            warn!("Could not load arenas: {:?}", err);
+            Vec::new()
+        });
+    let handles = handles.into_iter().map(HandleUntyped::typed).collect();
+    commands.insert_resource(ArenaHandles(handles));
+}
+
+fn list_arenas(
+    mut asset_events: EventReader<AssetEvent<ArenaLayout>>,
+    handles: Res<ArenaHandles>,
+    layouts: Res<Assets<ArenaLayout>>,
+    mut arenas: ResMut<Arenas>,
+) {
+    if asset_events.iter().count() == 0 {
+        return;
+    }
+
+    let mut loaded: Vec<_> = handles
+        .0
+        .iter()
+        .filter_map(|handle| layouts.get(handle).cloned())
+        .collect();
+    if loaded.is_empty() {
+        return;
+    }
+    loaded.sort_by(|a, b| a.name.cmp(&b.name));
+    arenas.0 = loaded;
+}
@@ -0,0 +1,265 @@
+use bevy::{
+    ecs::system::{lifetimeless::SRes, SystemParamItem},
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        render_asset::{PrepareAssetError, RenderAsset},
+        render_resource::{
+            std140::{AsStd140, Std140},
+            *,
+        },
+        renderer::RenderDevice,
+    },
+    sprite::{Material2d, Material2dPipeline, Material2dPlugin, MaterialMesh2dBundle},
+};
+
+use crate::{
+    settings::{AccessibilitySettings, Settings},
+    Arena, Ball, GoalEvent, HitEvent, Player, FOREGROUND_COLOR,
+};
+
+/// Behind everything else on the court, gravity wells included.
+const BACKDROP_Z: f32 = -2.0;
+/// How strongly the grid shows against the background, at its brightest.
+const BACKDROP_INTENSITY: f32 = 0.35;
+/// Share of a hit's pulse that fades away each second.
+const PULSE_DECAY: f32 = 3.0;
+/// A smash lights the grid up this much more than a plain hit.
+const SMASH_PULSE: f32 = 1.5;
+
+/// A faint grid behind the court that pulses where the ball is hit and
+/// ripples out from the goal line on every goal, when the backdrop video
+/// setting is on.
+///
+/// It is all drawn by one shader, so reacting to play is only a matter of
+/// moving a few numbers into its uniforms. With reduced motion the grid
+/// stays still.
+pub struct BackdropPlugin;
+
+impl Plugin for BackdropPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(Material2dPlugin::<BackdropMaterial>::default())
+            .init_resource::<Arena>()
+            .init_resource::<AccessibilitySettings>()
+            .init_resource::<Settings>()
+            .add_event::<HitEvent>()
+            .add_event::<GoalEvent>()
+            .add_system(toggle_backdrop)
+            .add_system(animate_backdrop.after(toggle_backdrop));
+    }
+}
+
+#[derive(Clone, PartialEq, TypeUuid)]
+#[uuid = "5b0d7c3e-8f0a-4c41-9a3b-2f6e1d9c7a54"]
+pub struct BackdropMaterial {
+    pub color: Color,
+    /// Size of the court, in world units.
+    pub size: Vec2,
+    /// Where the ball was last hit, from the middle of the court.
+    pub pulse_at: Vec2,
+    /// Seconds the grid has been drifting for.
+    pub time: f32,
+    /// How brightly the last hit still lights the grid, fading to 0.
+    pub pulse: f32,
+    /// The goal line the last goal rippled out from, from the middle of the
+    /// court.
+    pub ripple_x: f32,
+    /// Seconds since the last goal, or below 0 before the first.
+    pub ripple_age: f32,
+}
+
+#[derive(Clone, Default, AsStd140)]
+struct BackdropMaterialUniformData {
+    color: Vec4,
+    size: Vec2,
+    pulse_at: Vec2,
+    time: f32,
+    pulse: f32,
+    ripple_x: f32,
+    ripple_age: f32,
+}
+
+pub struct GpuBackdropMaterial {
+    bind_group: BindGroup,
+}
+
+impl RenderAsset for BackdropMaterial {
+    type ExtractedAsset = BackdropMaterial;
+    type PreparedAsset = GpuBackdropMaterial;
+    type Param = (
+        SRes<RenderDevice>,
+        SRes<Material2dPipeline<BackdropMaterial>>,
+    );
+
+    fn extract_asset(&self) -> Self::ExtractedAsset {
+        self.clone()
+    }
+
+    fn prepare_asset(
+        material: Self::ExtractedAsset,
+        (render_device, pipeline): &mut SystemParamItem<Self::Param>,
+    ) -> Result<Self::PreparedAsset, PrepareAssetError<Self::ExtractedAsset>> {
+        let value = BackdropMaterialUniformData {
+            color: material.color.as_linear_rgba_f32().into(),
+            size: material.size,
+            pulse_at: material.pulse_at,
+            time: material.time,
+            pulse: material.pulse,
+            ripple_x: material.ripple_x,
+            ripple_age: material.ripple_age,
+        };
+        let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("backdrop_material_uniform_buffer"),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            contents: value.as_std140().as_bytes(),
+        });
+        let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+            label: Some("backdrop_material_bind_group"),
+            layout: &pipeline.material2d_layout,
+        });
+
+        Ok(GpuBackdropMaterial { bind_group })
+    }
+}
+
+impl Material2d for BackdropMaterial {
+    fn fragment_shader(asset_server: &AssetServer) -> Option<Handle<Shader>> {
+        Some(asset_server.load("shaders/backdrop.wgsl"))
+    }
+
+    fn bind_group(render_asset: &GpuBackdropMaterial) -> &BindGroup {
+        &render_asset.bind_group
+    }
+
+    fn bind_group_layout(render_device: &RenderDevice) -> BindGroupLayout {
+        render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: BufferSize::new(
+                        BackdropMaterialUniformData::std140_size_static() as u64,
+                    ),
+                },
+                count: None,
+            }],
+            label: Some("backdrop_material_layout"),
+        })
+    }
+}
+
+#[derive(Component)]
+struct Backdrop;
+
+fn toggle_backdrop(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<BackdropMaterial>>,
+    backdrop_query: Query<Entity, With<Backdrop>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let backdrop = backdrop_query.get_single().ok();
+    match (settings.video.backdrop, backdrop) {
+        (true, None) => {
+            commands
+                .spawn_bundle(MaterialMesh2dBundle {
+                    mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE))).into(),
+                    material: materials.add(BackdropMaterial {
+                        color: FOREGROUND_COLOR,
+                        size: Vec2::ONE,
+                        pulse_at: Vec2::ZERO,
+                        time: 0.0,
+                        pulse: 0.0,
+                        ripple_x: 0.0,
+                        ripple_age: -1.0,
+                    }),
+                    transform: Transform::from_xyz(0.0, 0.0, BACKDROP_Z),
+                    ..default()
+                })
+                .insert(Backdrop);
+        }
+        (false, Some(backdrop)) => commands.entity(backdrop).despawn(),
+        _ => {}
+    }
+}
+
+/// Keeps the grid covering the court, and feeds it the hits and goals.
+#[allow(clippy::too_many_arguments)]
+fn animate_backdrop(
+    time: Res<Time>,
+    arena: Res<Arena>,
+    accessibility: Res<AccessibilitySettings>,
+    mut hit_events: EventReader<HitEvent>,
+    mut goal_events: EventReader<GoalEvent>,
+    ball_query: Query<&Transform, With<Ball>>,
+    mut materials: ResMut<Assets<BackdropMaterial>>,
+    mut backdrop_query: Query<(&mut Transform, &Handle<BackdropMaterial>), Without<Ball>>,
+) {
+    let hit = hit_events.iter().last();
+    let goal = goal_events.iter().last();
+    let (mut transform, handle) = match backdrop_query.get_single_mut() {
+        Ok(backdrop) => backdrop,
+        Err(_) => return,
+    };
+    let mut material = match materials.get(handle) {
+        Some(material) => material.clone(),
+        None => return,
+    };
+
+    let centre = Vec2::new(
+        (arena.left + arena.right) / 2.0,
+        (arena.bottom + arena.top) / 2.0,
+    );
+    let size = Vec2::new(arena.width(), arena.height());
+    if transform.scale.truncate() != size || transform.translation.truncate() != centre {
+        transform.translation = centre.extend(BACKDROP_Z);
+        transform.scale = size.extend(1.0);
+    }
+    material.size = size;
+    material.color = accessibility.cap(FOREGROUND_COLOR);
+    material.color.set_a(BACKDROP_INTENSITY);
+
+    if accessibility.reduce_motion {
+        material.pulse = 0.0;
+        material.ripple_age = -1.0;
+    } else {
+        let delta = time.delta_seconds();
+        material.time += delta;
+        material.pulse = (material.pulse - PULSE_DECAY * delta).max(0.0);
+        if material.ripple_age >= 0.0 {
+            material.ripple_age += delta;
+        }
+
+        if let Some(hit) = hit {
+            if let Ok(ball) = ball_query.get_single() {
+                material.pulse_at = ball.translation.truncate() - centre;
+            }
+            material.pulse = if hit.smash { SMASH_PULSE } else { 1.0 };
+        }
+        if let Some(goal) = goal {
+            // A goal for P1 goes in on P2's side, on the right.
+            material.ripple_x = match goal.scorer {
+                Player::P1 => arena.right - centre.x,
+                Player::P2 => arena.left - centre.x,
+            };
+            material.ripple_age = 0.0;
+        }
+    }
+
+    // Only touch the material when it changes, as that re-uploads it.
+    if materials.get(handle) != Some(&material) {
+        if let Some(current) = materials.get_mut(handle) {
+            *current = material;
+        }
+    }
+}
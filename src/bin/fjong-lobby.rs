@@ -0,0 +1,60 @@
+use std::{
+    env,
+    net::{Ipv4Addr, SocketAddr},
+    process,
+};
+
+use fjong::lobby::{Rooms, LOBBY_PORT};
+use tokio::net::TcpListener;
+
+/// A lobby server handing out room codes, so friends can find each other's
+/// games without swapping addresses. Every connection is a task on the one
+/// thread, so a lobby holding rooms open for thousands of hosts costs a
+/// socket each rather than a thread each.
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let addr = parse_args();
+    let listener = match TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("Could not listen on {}: {}", addr, err);
+            process::exit(1);
+        }
+    };
+    println!("Lobby open on {}", addr);
+
+    let rooms = Rooms::default();
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                eprintln!("Could not accept connection: {}", err);
+                continue;
+            }
+        };
+        let rooms = rooms.clone();
+        tokio::spawn(async move {
+            if let Err(err) = rooms.serve(stream).await {
+                eprintln!("Connection dropped: {}", err);
+            }
+        });
+    }
+}
+
+fn parse_args() -> SocketAddr {
+    let mut addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, LOBBY_PORT));
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args.next().unwrap_or_default();
+        match arg.as_str() {
+            "--listen" => addr = value.parse().unwrap_or_else(|_| usage()),
+            _ => usage(),
+        }
+    }
+    addr
+}
+
+fn usage() -> ! {
+    eprintln!("usage: fjong-lobby [--listen <address:port>]");
+    process::exit(2);
+}
@@ -0,0 +1,82 @@
+use std::{env, path::PathBuf, process};
+
+use fjong::Replay;
+
+/// Frames are this size unless told otherwise.
+const DEFAULT_SIZE: (u32, u32) = (1280, 720);
+const DEFAULT_OUT: &str = "frames";
+
+/// Renders a replay written by the game, a frame a tick, either as numbered
+/// PNGs or through `ffmpeg` straight into a video, for trailers and
+/// highlights. Needs no window, and renders the same replay the same way
+/// every time.
+fn main() {
+    let args = Args::parse();
+    let replay = Replay::load(&args.replay).unwrap_or_else(|err| {
+        eprintln!("Could not read {}: {}", args.replay.display(), err);
+        process::exit(1);
+    });
+
+    let (width, height) = args.size;
+    let rendered = match &args.video {
+        Some(video) => replay
+            .export_video(width, height, video)
+            .map(|()| video.display().to_string()),
+        None => replay
+            .export_frames(width, height, &args.out)
+            .map(|frames| format!("{} frames in {}", frames, args.out.display())),
+    };
+    match rendered {
+        Ok(rendered) => println!("Rendered {}", rendered),
+        Err(err) => {
+            eprintln!("Could not render the replay: {}", err);
+            process::exit(1);
+        }
+    }
+}
+
+struct Args {
+    replay: PathBuf,
+    size: (u32, u32),
+    /// Where numbered frames go.
+    out: PathBuf,
+    /// A video for `ffmpeg` to encode instead of writing frames.
+    video: Option<PathBuf>,
+}
+
+impl Args {
+    fn parse() -> Args {
+        let mut args = env::args().skip(1);
+        let mut parsed = Args {
+            replay: args.next().unwrap_or_else(|| usage()).into(),
+            size: DEFAULT_SIZE,
+            out: DEFAULT_OUT.into(),
+            video: None,
+        };
+        while let Some(arg) = args.next() {
+            let value = args.next().unwrap_or_default();
+            match arg.as_str() {
+                "--size" => parsed.size = parse_size(&value).unwrap_or_else(|| usage()),
+                "--out" if !value.is_empty() => parsed.out = value.into(),
+                "--ffmpeg" if !value.is_empty() => parsed.video = Some(value.into()),
+                _ => usage(),
+            }
+        }
+        parsed
+    }
+}
+
+/// A size written as in 1920x1080.
+fn parse_size(value: &str) -> Option<(u32, u32)> {
+    let (width, height) = value.split_once('x')?;
+    let size = (width.parse().ok()?, height.parse().ok()?);
+    (size.0 > 0 && size.1 > 0).then_some(size)
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "usage: fjong-render <replay.json> [--size <width>x<height>] [--out <dir>] \
+         [--ffmpeg <video file>]"
+    );
+    process::exit(2);
+}
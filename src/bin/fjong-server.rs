@@ -0,0 +1,60 @@
+use std::{
+    env,
+    net::{Ipv4Addr, SocketAddr},
+    process,
+};
+
+use bevy::{log::LogPlugin, prelude::*};
+use fjong::{ServerPlugin, SimulationPlugin, SpectatorServerPlugin};
+
+/// Where players connect unless told otherwise.
+const SERVER_PORT: u16 = 41_602;
+
+/// Runs matches for players joining with `fjong --join`, with no window and
+/// nobody playing here, so the server is the only one deciding what happens.
+fn main() {
+    let args = Args::parse();
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(LogPlugin)
+        .add_plugin(SimulationPlugin::default())
+        .add_plugin(ServerPlugin { addr: args.listen });
+    if let Some(addr) = args.spectators {
+        app.add_plugin(SpectatorServerPlugin { addr });
+    }
+    app.run();
+}
+
+struct Args {
+    /// Where to listen for players.
+    listen: SocketAddr,
+    /// Where to listen for people who want to watch.
+    spectators: Option<SocketAddr>,
+}
+
+impl Args {
+    fn parse() -> Args {
+        let mut parsed = Args {
+            listen: SocketAddr::from((Ipv4Addr::UNSPECIFIED, SERVER_PORT)),
+            spectators: None,
+        };
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args.next().unwrap_or_default();
+            match arg.as_str() {
+                "--listen" => parsed.listen = value.parse().unwrap_or_else(|_| usage()),
+                "--spectators" => {
+                    parsed.spectators = Some(value.parse().unwrap_or_else(|_| usage()))
+                }
+                _ => usage(),
+            }
+        }
+        parsed
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("usage: fjong-server [--listen <address:port>] [--spectators <address:port>]");
+    process::exit(2);
+}
@@ -0,0 +1,167 @@
+use std::{
+    env, process,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use fjong::{
+    ai::{Difficulty, Skill},
+    selfplay::{self, Record},
+    MatchRules,
+};
+
+const DEFAULT_MATCHES: usize = 20;
+const DEFAULT_POINTS: usize = 5;
+const DEFAULT_REACTION_TIMES: &[f32] = &[0.0, 0.1, 0.2, 0.3];
+const DEFAULT_NOISE: &[f32] = &[0.0, 12.0, 25.0, 40.0];
+const DEFAULT_MAX_SPEED: &[f32] = &[450.0, 600.0, 800.0];
+
+/// Plays AI against AI, headless and on every core, for every combination of
+/// reaction time, noise and top speed asked for, against one of the
+/// difficulty tiers, and prints how often each won. The rest of each skill is
+/// the tier's own, so only what is being swept differs.
+///
+/// The same arguments always play the same matches.
+fn main() {
+    let args = Args::parse();
+    let against = args.against.skill();
+    let rules = MatchRules {
+        points_to_win: args.points,
+        ..Default::default()
+    };
+
+    let mut grid = Vec::new();
+    for &reaction_time in &args.reaction_times {
+        for &noise in &args.noise {
+            for &max_speed in &args.max_speed {
+                grid.push(Skill {
+                    reaction_time,
+                    noise,
+                    max_speed,
+                    ..against
+                });
+            }
+        }
+    }
+    println!(
+        "{} skills against {:?}, {} matches each to {} points",
+        grid.len(),
+        args.against,
+        args.matches,
+        args.points
+    );
+
+    // Every skill plays the same seeds, so they are compared on the same
+    // matches.
+    let records = Mutex::new(vec![Record::default(); grid.len()]);
+    let next = AtomicUsize::new(0);
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(skill) = grid.get(i) else { break };
+                let record =
+                    selfplay::play_matches(*skill, against, &rules, args.matches, args.seed);
+                records.lock().unwrap()[i] = record;
+            });
+        }
+    });
+
+    println!(
+        "{:>8} {:>6} {:>6} {:>5} {:>6} {:>10} {:>8}",
+        "reaction", "noise", "speed", "wins", "losses", "unfinished", "win rate"
+    );
+    for (skill, record) in grid.iter().zip(records.into_inner().unwrap()) {
+        let win_rate = record
+            .win_rate()
+            .map_or("-".to_string(), |rate| format!("{:.0}%", rate * 100.0));
+        println!(
+            "{:>8.2} {:>6.1} {:>6.0} {:>5} {:>6} {:>10} {:>8}",
+            skill.reaction_time,
+            skill.noise,
+            skill.max_speed,
+            record.wins,
+            record.losses,
+            record.unfinished,
+            win_rate
+        );
+    }
+}
+
+struct Args {
+    against: Difficulty,
+    matches: usize,
+    points: usize,
+    seed: u64,
+    /// In seconds.
+    reaction_times: Vec<f32>,
+    noise: Vec<f32>,
+    max_speed: Vec<f32>,
+}
+
+impl Args {
+    fn parse() -> Args {
+        let mut parsed = Args {
+            against: Difficulty::Medium,
+            matches: DEFAULT_MATCHES,
+            points: DEFAULT_POINTS,
+            seed: 0,
+            reaction_times: DEFAULT_REACTION_TIMES.to_vec(),
+            noise: DEFAULT_NOISE.to_vec(),
+            max_speed: DEFAULT_MAX_SPEED.to_vec(),
+        };
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args.next().unwrap_or_default();
+            match arg.as_str() {
+                "--against" => parsed.against = parse_difficulty(&value).unwrap_or_else(|| usage()),
+                "--matches" => parsed.matches = value.parse().unwrap_or_else(|_| usage()),
+                "--points" => parsed.points = value.parse().unwrap_or_else(|_| usage()),
+                "--seed" => parsed.seed = value.parse().unwrap_or_else(|_| usage()),
+                "--reaction" => {
+                    parsed.reaction_times = parse_list(&value).unwrap_or_else(|| usage())
+                }
+                "--noise" => parsed.noise = parse_list(&value).unwrap_or_else(|| usage()),
+                "--speed" => parsed.max_speed = parse_list(&value).unwrap_or_else(|| usage()),
+                _ => usage(),
+            }
+        }
+        if parsed.matches == 0 || parsed.points == 0 {
+            usage();
+        }
+        parsed
+    }
+}
+
+fn parse_difficulty(value: &str) -> Option<Difficulty> {
+    match value {
+        "easy" => Some(Difficulty::Easy),
+        "medium" => Some(Difficulty::Medium),
+        "hard" => Some(Difficulty::Hard),
+        "insane" => Some(Difficulty::Insane),
+        _ => None,
+    }
+}
+
+/// Values written as in 0,0.1,0.2.
+fn parse_list<T: FromStr>(value: &str) -> Option<Vec<T>> {
+    let list: Vec<T> = value
+        .split(',')
+        .map(|item| item.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    (!list.is_empty()).then_some(list)
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "usage: fjong-tune [--against easy|medium|hard|insane] [--matches <number>] \
+         [--points <number>] [--seed <number>] [--reaction <seconds,...>] [--noise <units,...>] \
+         [--speed <units per second,...>]"
+    );
+    process::exit(2);
+}
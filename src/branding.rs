@@ -0,0 +1,134 @@
+use std::{fs, path::Path};
+
+use bevy::{
+    prelude::*,
+    render::texture::{CompressedImageFormats, ImageType},
+    winit::WinitWindows,
+};
+use serde::{Deserialize, Serialize};
+use winit::window::Icon;
+
+/// Where the game's name and icon are set, relative to the working
+/// directory.
+pub const BRANDING_PATH: &str = "assets/branding.ron";
+
+/// Built into the binary, so the window has an icon without the assets
+/// folder.
+const ICON: &[u8] = include_bytes!("../assets/icon.png");
+
+/// What the game calls itself to the desktop, so a fork can go by its own
+/// name without touching the code.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Branding {
+    /// Shown in the window's title bar and the taskbar.
+    pub title: String,
+    /// PNG to use as the window's icon, relative to the working directory,
+    /// in place of the built-in one.
+    pub icon: Option<String>,
+    /// Tells a Linux taskbar which app the window belongs to, matched
+    /// against `StartupWMClass` in its `.desktop` file.
+    pub app_id: String,
+}
+
+impl Default for Branding {
+    fn default() -> Self {
+        Branding {
+            title: "fjong".into(),
+            icon: None,
+            app_id: "fjong".into(),
+        }
+    }
+}
+
+impl Branding {
+    /// Reads the branding file, or goes by fjong's own name if there is
+    /// none, without writing one: the binary runs from anywhere.
+    pub fn load(path: impl AsRef<Path>) -> Branding {
+        let path = path.as_ref();
+        match fs::read_to_string(path) {
+            Ok(contents) => ron::from_str(&contents).unwrap_or_else(|err| {
+                warn!("Ignoring invalid {}: {}", path.display(), err);
+                Branding::default()
+            }),
+            Err(_) => Branding::default(),
+        }
+    }
+
+    /// The window as it should open, to be inserted before Bevy's
+    /// `DefaultPlugins` so it is never seen under any other title.
+    pub fn window(&self) -> WindowDescriptor {
+        WindowDescriptor {
+            title: self.title.clone(),
+            ..default()
+        }
+    }
+
+    /// Names the app to the desktop. winit only reads this as it opens the
+    /// window, so it has to be called before the app runs.
+    ///
+    /// On X11 this sets the `WM_CLASS` class. Bevy opens the window without
+    /// a Wayland app-id, which is then left to the compositor to guess from
+    /// the binary's name.
+    pub fn name_app(&self) {
+        std::env::set_var("RESOURCE_NAME", &self.app_id);
+    }
+
+    fn icon(&self) -> Option<Icon> {
+        let custom = self.icon.as_ref().and_then(|path| {
+            fs::read(path)
+                .map_err(|err| warn!("Could not read {}: {}", path, err))
+                .ok()
+        });
+        let bytes = custom.as_deref().unwrap_or(ICON);
+        let image = Image::from_buffer(
+            bytes,
+            ImageType::Extension("png"),
+            CompressedImageFormats::NONE,
+            true,
+        )
+        .map_err(|err| warn!("Could not load the window icon: {}", err))
+        .ok()?;
+        let size = image.texture_descriptor.size;
+        Icon::from_rgba(image.data, size.width, size.height)
+            .map_err(|err| warn!("Could not use the window icon: {}", err))
+            .ok()
+    }
+}
+
+/// Keeps the window's title and icon to [`Branding`]. The title is better
+/// set before the window opens too, see [`Branding::window`].
+pub struct BrandingPlugin;
+
+impl Plugin for BrandingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Branding>().add_system(apply_branding);
+    }
+}
+
+fn apply_branding(
+    branding: Res<Branding>,
+    mut windows: ResMut<Windows>,
+    winit_windows: Option<NonSend<WinitWindows>>,
+    mut done: Local<bool>,
+) {
+    if *done && !branding.is_changed() {
+        return;
+    }
+    // The window is only there once the event loop has started.
+    let (window, winit_windows) = match (windows.get_primary_mut(), winit_windows) {
+        (Some(window), Some(winit_windows)) => (window, winit_windows),
+        _ => return,
+    };
+    let winit_window = match winit_windows.get_window(window.id()) {
+        Some(winit_window) => winit_window,
+        None => return,
+    };
+    *done = true;
+
+    if window.title() != branding.title {
+        window.set_title(branding.title.clone());
+    }
+    // Windows and X11 show it; macOS takes the app bundle's icon instead.
+    winit_window.set_window_icon(branding.icon());
+}
@@ -0,0 +1,222 @@
+use bevy::{math::const_vec2, prelude::*};
+use rand::Rng;
+
+use crate::{
+    award_points, collision, Arena, CourtPiece, GameEntity, GameMode, GameRng, GoalEvent, HitEvent,
+    MatchOverEvent, MatchRules, P1Paddle, P2Paddle, Player, Scoreboard, TickRate, Velocity,
+    WallBundle, FOREGROUND_COLOR,
+};
+
+const BRICK_SIZE: Vec2 = const_vec2!([20.0, 50.0]);
+/// Two columns either side of the centre line, leaving room to serve from.
+const BRICK_COLUMNS: [f32; 4] = [-100.0, -60.0, 60.0, 100.0];
+const BRICK_ROWS: [f32; 6] = [-150.0, -90.0, -30.0, 30.0, 90.0, 150.0];
+/// Points for whoever broke a brick.
+const BRICK_POINTS: usize = 1;
+
+/// Chance of a broken brick dropping a power-up.
+const POWER_UP_CHANCE: f64 = 0.25;
+const POWER_UP_SIZE: Vec2 = const_vec2!([16.0, 16.0]);
+const POWER_UP_SPEED: f32 = 200.0;
+/// How much taller a power-up makes the paddle that catches it, and for how
+/// many seconds.
+pub(crate) const GROW_SCALE: f32 = 1.5;
+const GROW_SECONDS: f32 = 10.0;
+
+/// A block in mid-court that breaks when the ball hits it.
+#[derive(Component)]
+pub struct Brick;
+
+/// The ball broke a brick.
+pub struct BrickEvent {
+    pub position: Vec2,
+}
+
+/// Drifts towards the player who broke the brick it fell out of, and makes
+/// their paddle bigger for a while if they catch it.
+#[derive(Component)]
+pub struct PowerUp {
+    player: Player,
+}
+
+/// A paddle that caught a power-up, and how long it stays big.
+#[derive(Component)]
+pub struct Grown {
+    ticks_left: u32,
+}
+
+fn lay_bricks(commands: &mut Commands) {
+    for x in BRICK_COLUMNS {
+        for y in BRICK_ROWS {
+            commands
+                .spawn_bundle(WallBundle::block(Vec2::new(x, y), BRICK_SIZE))
+                .insert(Brick)
+                .insert_bundle((CourtPiece, GameEntity));
+        }
+    }
+}
+
+/// Builds the wall of bricks for a breakout match, and clears it away for
+/// any other. Like the rest of the court, the bricks are put back up when
+/// another arena is picked.
+pub fn arrange_bricks(
+    mut commands: Commands,
+    rules: Res<MatchRules>,
+    arena: Res<Arena>,
+    brick_query: Query<Entity, With<Brick>>,
+) {
+    if !rules.is_changed() && !arena.is_changed() {
+        return;
+    }
+
+    let breakout = rules.mode == GameMode::Breakout;
+    if breakout && brick_query.is_empty() {
+        lay_bricks(&mut commands);
+    }
+    if !breakout {
+        for brick in brick_query.iter() {
+            commands.entity(brick).despawn();
+        }
+    }
+}
+
+type Leftover = Or<(With<Brick>, With<PowerUp>)>;
+
+/// A full wall of bricks for every breakout match, and nothing left over
+/// from the last one.
+pub fn restock_bricks(
+    mut commands: Commands,
+    rules: Res<MatchRules>,
+    leftover_query: Query<Entity, Leftover>,
+    mut grown_query: Query<&mut Grown>,
+) {
+    for entity in leftover_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    // Shrunk back on the next tick.
+    for mut grown in grown_query.iter_mut() {
+        grown.ticks_left = 1;
+    }
+    if rules.mode == GameMode::Breakout {
+        lay_bricks(&mut commands);
+    }
+}
+
+/// Gives a point to whoever last hit the ball for each brick it breaks, and
+/// now and then drops a power-up for them.
+#[allow(clippy::too_many_arguments)]
+pub fn score_bricks(
+    mut commands: Commands,
+    rules: Res<MatchRules>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut rng: ResMut<GameRng>,
+    mut last_hit: Local<Option<Player>>,
+    mut hit_events: EventReader<HitEvent>,
+    mut goal_events: EventReader<GoalEvent>,
+    mut brick_events: EventReader<BrickEvent>,
+    mut match_over_events: EventWriter<MatchOverEvent>,
+) {
+    if let Some(hit) = hit_events.iter().last() {
+        *last_hit = Some(hit.player);
+    }
+    if goal_events.iter().count() > 0 {
+        *last_hit = None;
+    }
+    if rules.mode != GameMode::Breakout {
+        return;
+    }
+
+    for brick in brick_events.iter() {
+        // Nobody to credit before the ball comes off a paddle.
+        let player = match *last_hit {
+            Some(player) => player,
+            None => continue,
+        };
+        award_points(
+            &rules,
+            &mut scoreboard,
+            player,
+            BRICK_POINTS,
+            &mut match_over_events,
+        );
+
+        if rng.gen_bool(POWER_UP_CHANCE) {
+            let direction = match player {
+                Player::P1 => -1.0,
+                Player::P2 => 1.0,
+            };
+            commands
+                .spawn_bundle(SpriteBundle {
+                    transform: Transform {
+                        translation: brick.position.extend(1.0),
+                        scale: POWER_UP_SIZE.extend(1.0),
+                        ..default()
+                    },
+                    sprite: Sprite {
+                        color: FOREGROUND_COLOR,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(Velocity(Vec2::new(direction * POWER_UP_SPEED, 0.0)))
+                .insert(PowerUp { player });
+        }
+    }
+}
+
+/// Grows the paddle that catches a power-up, and lets any that are missed
+/// fall off the court.
+#[allow(clippy::type_complexity)]
+pub fn catch_power_ups(
+    mut commands: Commands,
+    arena: Res<Arena>,
+    tick_rate: Res<TickRate>,
+    power_up_query: Query<(Entity, &Transform, &PowerUp)>,
+    mut paddle_query: Query<
+        (Entity, &Transform, Option<&P1Paddle>, Option<&mut Grown>),
+        (Or<(With<P1Paddle>, With<P2Paddle>)>, Without<PowerUp>),
+    >,
+) {
+    for (power_up, power_up_transform, PowerUp { player }) in power_up_query.iter() {
+        let position = power_up_transform.translation.truncate();
+        if position.x < arena.left || position.x > arena.right {
+            commands.entity(power_up).despawn();
+            continue;
+        }
+
+        for (paddle, transform, p1, grown) in paddle_query.iter_mut() {
+            let owner = if p1.is_some() { Player::P1 } else { Player::P2 };
+            let caught = collision::contact(
+                position,
+                POWER_UP_SIZE,
+                transform.translation.truncate(),
+                transform.scale.truncate(),
+            )
+            .is_some();
+            if owner != *player || !caught {
+                continue;
+            }
+
+            commands.entity(power_up).despawn();
+            match grown {
+                Some(mut grown) => grown.ticks_left = tick_rate.ticks(GROW_SECONDS),
+                None => {
+                    commands.entity(paddle).insert(Grown {
+                        ticks_left: tick_rate.ticks(GROW_SECONDS),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Takes the power-up away from grown paddles once it wears off. The paddles
+/// are sized to match by `resize_paddles`.
+pub fn wear_off_power_ups(mut commands: Commands, mut grown_query: Query<(Entity, &mut Grown)>) {
+    for (paddle, mut grown) in grown_query.iter_mut() {
+        grown.ticks_left = grown.ticks_left.saturating_sub(1);
+        if grown.ticks_left == 0 {
+            commands.entity(paddle).remove::<Grown>();
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+
+use crate::{
+    settings::{AccessibilitySettings, CameraMode, Settings},
+    AppState, Ball, GoalEvent,
+};
+
+/// Share of the ball's distance from the centre that the follow-cam leans.
+const LEAN: f32 = 0.06;
+/// How quickly the camera catches up with where it wants to be, per second.
+const LEAN_RATE: f32 = 4.0;
+/// How far in a goal punches, as a share of the view.
+const PUNCH_ZOOM: f32 = 0.12;
+const PUNCH_SECONDS: f32 = 0.5;
+
+/// The camera the court is drawn with.
+#[derive(Component)]
+pub struct GameCamera;
+
+/// Points the court camera according to [`Settings::video`]'s
+/// [`CameraMode`].
+///
+/// The camera is only for show, so it runs on the frame clock and never
+/// changes what the simulation sees.
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Punch>()
+            .init_resource::<AccessibilitySettings>()
+            .add_startup_system(spawn_camera)
+            .add_system(punch_on_goal)
+            .add_system(move_camera.after(punch_on_goal));
+    }
+}
+
+/// What is left of the last goal's zoom, from 1 down to 0.
+#[derive(Default)]
+struct Punch(f32);
+
+fn spawn_camera(mut commands: Commands) {
+    commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .insert(GameCamera);
+}
+
+fn punch_on_goal(
+    settings: Res<Settings>,
+    accessibility: Res<AccessibilitySettings>,
+    mut goal_events: EventReader<GoalEvent>,
+    mut punch: ResMut<Punch>,
+) {
+    let dramatic = settings.video.camera == CameraMode::Dramatic && !accessibility.reduce_motion;
+    if goal_events.iter().count() > 0 && dramatic {
+        punch.0 = 1.0;
+    }
+}
+
+fn move_camera(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    accessibility: Res<AccessibilitySettings>,
+    state: Res<State<AppState>>,
+    mut punch: ResMut<Punch>,
+    ball_query: Query<&Transform, (With<Ball>, Without<GameCamera>)>,
+    mut camera_query: Query<&mut Transform, With<GameCamera>>,
+) {
+    let mut camera = match camera_query.get_single_mut() {
+        Ok(camera) => camera,
+        Err(_) => return,
+    };
+    let delta = time.delta_seconds();
+
+    // Menus are laid over the whole court, so it is only leaned in a match.
+    let in_match = matches!(
+        state.current(),
+        AppState::Playing
+            | AppState::Paused
+            | AppState::InstantReplay
+            | AppState::Spectating
+            | AppState::Reconnecting
+    );
+    let target = match (settings.video.camera, ball_query.get_single()) {
+        (CameraMode::Classic, _) => Vec2::ZERO,
+        (_, Ok(ball)) if in_match && !accessibility.reduce_motion => {
+            ball.translation.truncate() * LEAN
+        }
+        _ => Vec2::ZERO,
+    };
+    let position = camera.translation.truncate();
+    let eased = position.lerp(target, (LEAN_RATE * delta).min(1.0));
+    camera.translation = eased.extend(camera.translation.z);
+
+    punch.0 = (punch.0 - delta / PUNCH_SECONDS).max(0.0);
+    // Snaps in and eases back out.
+    let zoom = 1.0 - PUNCH_ZOOM * punch.0 * punch.0;
+    camera.scale = Vec3::new(zoom, zoom, 1.0);
+}
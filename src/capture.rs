@@ -0,0 +1,666 @@
+use std::{
+    borrow::Cow,
+    collections::VecDeque,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{mpsc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{ExtractedCamera, RenderTarget},
+        render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext},
+        render_resource::*,
+        renderer::{RenderContext, RenderDevice},
+        texture::BevyDefault,
+        view::{ExtractedWindows, ViewTarget, WindowSystem},
+        RenderApp, RenderStage,
+    },
+    window::WindowId,
+};
+use fluent::fluent_args;
+
+use crate::{job, Locale, Toast};
+
+/// Where screenshots and clips go, under [`data_path`](crate::data_path).
+pub const CAPTURES_DIR: &str = "captures";
+const SCREENSHOT_KEY: KeyCode = KeyCode::F10;
+const CLIP_KEY: KeyCode = KeyCode::F9;
+const CLIP_SECONDS: u32 = 5;
+/// GIFs time frames in hundredths of a second, so this divides 100.
+const CLIP_FPS: u32 = 20;
+/// Clip frames are shrunk to no wider than this, to keep clips small.
+const CLIP_WIDTH: u32 = 640;
+/// Levels of red, green and blue in a clip's palette, which between them
+/// make 252 colours.
+const PALETTE_LEVELS: [u8; 3] = [6, 7, 6];
+const BLIT_NODE: &str = "capture_blit";
+
+/// F10 saves a screenshot as a PNG, and F9 saves the last few seconds as a
+/// GIF, both into `dir`.
+///
+/// The game is drawn into a texture of its own and then onto the window, so
+/// frames can be read back from it. Clip frames are kept shrunk, and
+/// captures are encoded and written on another thread.
+pub struct CapturePlugin {
+    pub dir: PathBuf,
+}
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        let (frame_sender, frame_receiver) = mpsc::channel();
+        let (saved_sender, saved_receiver) = mpsc::channel();
+        app.insert_resource(CaptureDir(self.dir.clone()))
+            .insert_resource(FrameReceiver(Mutex::new(frame_receiver)))
+            .insert_resource(Saves {
+                sender: Mutex::new(saved_sender),
+                receiver: Mutex::new(saved_receiver),
+            })
+            .init_resource::<Clip>()
+            .init_resource::<ClipTimer>()
+            .init_resource::<FrameRequest>()
+            .init_resource::<Locale>()
+            .add_event::<Toast>()
+            .add_system(request_frames)
+            .add_system(receive_frames)
+            .add_system(save_clip.after(receive_frames))
+            .add_system(report_saves);
+
+        let render_app = match app.get_sub_app_mut(RenderApp) {
+            Ok(render_app) => render_app,
+            Err(_) => return,
+        };
+        render_app
+            .insert_resource(FrameSender(Mutex::new(frame_sender)))
+            .init_resource::<BlitPipeline>()
+            .init_resource::<CaptureTarget>()
+            .add_system_to_stage(RenderStage::Extract, extract_frame_request)
+            .add_system_to_stage(
+                RenderStage::Prepare,
+                prepare_capture_target.after(WindowSystem::Prepare),
+            )
+            // Once the views have their targets, which happens while
+            // preparing.
+            .add_system_to_stage(RenderStage::Queue, redirect_view_targets)
+            .add_system_to_stage(RenderStage::Cleanup, read_back_frame);
+        let mut graph = render_app.world.resource_mut::<RenderGraph>();
+        graph.add_node(BLIT_NODE, BlitNode);
+        graph
+            .add_node_edge(bevy::ui::node::UI_PASS_DRIVER, BLIT_NODE)
+            .unwrap();
+    }
+}
+
+struct CaptureDir(PathBuf);
+
+/// Which frames to read back from the GPU this frame, and why.
+#[derive(Clone, Copy, Default)]
+struct FrameRequest {
+    screenshot: bool,
+    clip: bool,
+}
+
+impl FrameRequest {
+    fn any(&self) -> bool {
+        self.screenshot || self.clip
+    }
+}
+
+/// A frame read back from the GPU, RGBA row by row from the top.
+struct Frame {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+    request: FrameRequest,
+}
+
+struct FrameSender(Mutex<mpsc::Sender<Frame>>);
+
+struct FrameReceiver(Mutex<mpsc::Receiver<Frame>>);
+
+/// Captures being written on other threads, and where they ended up.
+struct Saves {
+    sender: Mutex<mpsc::Sender<io::Result<PathBuf>>>,
+    receiver: Mutex<mpsc::Receiver<io::Result<PathBuf>>>,
+}
+
+impl Saves {
+    /// Encodes a capture and writes it into `dir` on another thread.
+    fn spawn(
+        &self,
+        dir: PathBuf,
+        name: &'static str,
+        extension: &'static str,
+        encode: impl FnOnce() -> io::Result<Vec<u8>> + Send + 'static,
+    ) {
+        let sender = self.sender.lock().unwrap().clone();
+        job::in_background(move || {
+            let saved = encode().and_then(|bytes| write_capture(&dir, name, extension, &bytes));
+            let _ = sender.send(saved);
+        });
+    }
+}
+
+struct ClipTimer(Timer);
+
+impl Default for ClipTimer {
+    fn default() -> ClipTimer {
+        ClipTimer(Timer::from_seconds(1.0 / CLIP_FPS as f32, true))
+    }
+}
+
+/// A frame of a [`Clip`], shrunk and with its colours picked from the few a
+/// GIF can have.
+#[derive(Clone)]
+pub struct ClipFrame {
+    pub width: u32,
+    pub height: u32,
+    indices: Vec<u8>,
+}
+
+impl ClipFrame {
+    /// Shrinks an RGBA frame to fit in a clip, averaging the pixels that go
+    /// into each of the frame's.
+    pub fn new(width: u32, height: u32, rgba: &[u8]) -> ClipFrame {
+        let scale = width.div_ceil(CLIP_WIDTH).max(1);
+        let (clip_width, clip_height) = (width / scale, height / scale);
+        let mut indices = Vec::with_capacity((clip_width * clip_height) as usize);
+        for y in 0..clip_height {
+            for x in 0..clip_width {
+                let mut sum = [0u32; 3];
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let pixel = (((y * scale + dy) * width + x * scale + dx) * 4) as usize;
+                        for (channel, value) in sum.iter_mut().zip(&rgba[pixel..pixel + 3]) {
+                            *channel += *value as u32;
+                        }
+                    }
+                }
+                let average = sum.map(|channel| (channel / (scale * scale)) as u8);
+                indices.push(palette_index(average));
+            }
+        }
+        ClipFrame {
+            width: clip_width,
+            height: clip_height,
+            indices,
+        }
+    }
+
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+}
+
+/// The last few seconds, ready to be saved as a GIF.
+#[derive(Clone, Default)]
+pub struct Clip {
+    frames: VecDeque<ClipFrame>,
+}
+
+impl Clip {
+    /// How many frames a clip keeps, dropping the oldest for newer ones.
+    pub const FRAMES: usize = (CLIP_SECONDS * CLIP_FPS) as usize;
+
+    /// Adds the newest frame. The window changing size starts the clip over,
+    /// as every frame of a GIF is the same size.
+    pub fn push(&mut self, frame: ClipFrame) {
+        if matches!(self.frames.back(), Some(last) if last.size() != frame.size()) {
+            self.frames.clear();
+        }
+        self.frames.push_back(frame);
+        while self.frames.len() > Clip::FRAMES {
+            self.frames.pop_front();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The clip as a looping GIF, or nothing if there is no clip yet.
+    pub fn gif(&self) -> Option<io::Result<Vec<u8>>> {
+        let (width, height) = self.frames.front()?.size();
+        Some(self.encode(width as u16, height as u16))
+    }
+
+    fn encode(&self, width: u16, height: u16) -> io::Result<Vec<u8>> {
+        let palette: Vec<u8> = palette().concat();
+        let mut encoder =
+            gif::Encoder::new(Vec::new(), width, height, &palette).map_err(io::Error::other)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(io::Error::other)?;
+        for frame in &self.frames {
+            let frame = gif::Frame {
+                width,
+                height,
+                // In hundredths of a second.
+                delay: (100 / CLIP_FPS) as u16,
+                buffer: Cow::Borrowed(&frame.indices),
+                ..default()
+            };
+            encoder.write_frame(&frame).map_err(io::Error::other)?;
+        }
+        encoder.into_inner()
+    }
+}
+
+fn request_frames(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut timer: ResMut<ClipTimer>,
+    mut request: ResMut<FrameRequest>,
+) {
+    timer.0.tick(time.delta());
+    *request = FrameRequest {
+        screenshot: keyboard_input.just_pressed(SCREENSHOT_KEY),
+        clip: timer.0.just_finished(),
+    };
+}
+
+fn receive_frames(
+    dir: Res<CaptureDir>,
+    saves: Res<Saves>,
+    receiver: Res<FrameReceiver>,
+    mut clip: ResMut<Clip>,
+) {
+    for frame in receiver.0.lock().unwrap().try_iter() {
+        if frame.request.clip {
+            clip.push(ClipFrame::new(frame.width, frame.height, &frame.rgba));
+        }
+        if frame.request.screenshot {
+            let Frame {
+                width,
+                height,
+                rgba,
+                ..
+            } = frame;
+            saves.spawn(dir.0.clone(), "screenshot", "png", move || {
+                png(width, height, &rgba)
+            });
+        }
+    }
+}
+
+fn save_clip(
+    keyboard_input: Res<Input<KeyCode>>,
+    dir: Res<CaptureDir>,
+    saves: Res<Saves>,
+    clip: Res<Clip>,
+) {
+    if !keyboard_input.just_pressed(CLIP_KEY) || clip.is_empty() {
+        return;
+    }
+    let clip = clip.clone();
+    saves.spawn(dir.0.clone(), "clip", "gif", move || {
+        clip.gif().unwrap_or_else(|| Ok(Vec::new()))
+    });
+}
+
+fn report_saves(locale: Res<Locale>, saves: Res<Saves>, mut toasts: EventWriter<Toast>) {
+    for saved in saves.receiver.lock().unwrap().try_iter() {
+        match saved {
+            Ok(path) => {
+                info!("Saved {}", path.display());
+                let path = path.display().to_string();
+                toasts.send(Toast(
+                    locale.text_with("toast-capture-saved", &fluent_args!["path" => path]),
+                ));
+            }
+            Err(err) => {
+                warn!("Could not save the capture: {}", err);
+                toasts.send(Toast(locale.text("toast-capture-failed")));
+            }
+        }
+    }
+}
+
+/// An RGBA image as a PNG, 8 bits a channel.
+pub(crate) fn png(width: u32, height: u32, rgba: &[u8]) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, width, height);
+    encoder.set_color(png::ColorType::RGBA);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(rgba))
+        .map_err(io::Error::other)?;
+    Ok(bytes)
+}
+
+/// The colours clips are written with: evenly spread levels of red, green
+/// and blue, with black at index 0.
+fn palette() -> Vec<[u8; 3]> {
+    let [reds, greens, blues] = PALETTE_LEVELS;
+    let level = |value: u8, levels: u8| (value as u32 * 255 / (levels as u32 - 1)) as u8;
+    let mut palette = Vec::with_capacity(256);
+    for r in 0..reds {
+        for g in 0..greens {
+            for b in 0..blues {
+                palette.push([level(r, reds), level(g, greens), level(b, blues)]);
+            }
+        }
+    }
+    palette.resize(256, [0, 0, 0]);
+    palette
+}
+
+/// Index of the colour in a clip's palette nearest to `rgb`.
+fn palette_index(rgb: [u8; 3]) -> u8 {
+    let [reds, greens, blues] = PALETTE_LEVELS;
+    let level = |value: u8, levels: u8| (value as u32 * (levels as u32 - 1) + 127) / 255;
+    let (r, g, b) = (
+        level(rgb[0], reds),
+        level(rgb[1], greens),
+        level(rgb[2], blues),
+    );
+    (r * (greens * blues) as u32 + g * blues as u32 + b) as u8
+}
+
+fn write_capture(dir: &Path, name: &str, extension: &str, bytes: &[u8]) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    // Captures taken in the same second don't overwrite each other.
+    let mut path = dir.join(format!("{}-{}.{}", name, now, extension));
+    let mut number = 1;
+    while path.exists() {
+        number += 1;
+        path = dir.join(format!("{}-{}-{}.{}", name, now, number, extension));
+    }
+    fs::write(&path, bytes)?;
+    Ok(path)
+}
+
+/// Copies the texture the game is drawn into onto the window.
+struct BlitPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    pipeline: RenderPipeline,
+}
+
+impl FromWorld for BlitPipeline {
+    fn from_world(world: &mut World) -> BlitPipeline {
+        let render_device = world.resource::<RenderDevice>();
+        let layout = render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("capture_blit_layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+        let shader = render_device.create_shader_module(&ShaderModuleDescriptor {
+            label: Some("capture_blit_shader"),
+            source: ShaderSource::Wgsl(include_str!("../assets/shaders/blit.wgsl").into()),
+        });
+        let pipeline_layout = render_device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("capture_blit_pipeline_layout"),
+            bind_group_layouts: &[&*layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = render_device.create_render_pipeline(&RawRenderPipelineDescriptor {
+            label: Some("capture_blit_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: RawVertexState {
+                module: &shader,
+                entry_point: "vertex",
+                buffers: &[],
+            },
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            fragment: Some(RawFragmentState {
+                module: &shader,
+                entry_point: "fragment",
+                targets: &[ColorTargetState {
+                    format: TextureFormat::bevy_default(),
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                }],
+            }),
+            multiview: None,
+        });
+        BlitPipeline {
+            layout,
+            sampler,
+            pipeline,
+        }
+    }
+}
+
+/// The texture the game is drawn into instead of the window, and a buffer
+/// to read it back into.
+#[derive(Default)]
+struct CaptureTarget(Option<Target>);
+
+struct Target {
+    width: u32,
+    height: u32,
+    texture: Texture,
+    view: TextureView,
+    bind_group: BindGroup,
+    buffer: Buffer,
+    /// Rows are padded out in the buffer, as copies out of textures need.
+    padded_row: u32,
+}
+
+fn extract_frame_request(mut commands: Commands, request: Res<FrameRequest>) {
+    commands.insert_resource(*request);
+}
+
+/// Keeps the target the same size as the window.
+fn prepare_capture_target(
+    windows: Res<ExtractedWindows>,
+    render_device: Res<RenderDevice>,
+    pipeline: Res<BlitPipeline>,
+    mut target: ResMut<CaptureTarget>,
+) {
+    let (width, height) = match windows.get(&WindowId::primary()) {
+        Some(window) => (window.physical_width, window.physical_height),
+        None => return,
+    };
+    if width == 0 || height == 0 {
+        // Minimised.
+        target.0 = None;
+        return;
+    }
+    if matches!(&target.0, Some(target) if (target.width, target.height) == (width, height)) {
+        return;
+    }
+
+    let texture = render_device.create_texture(&TextureDescriptor {
+        label: Some("capture_texture"),
+        size: Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::bevy_default(),
+        usage: TextureUsages::RENDER_ATTACHMENT
+            | TextureUsages::TEXTURE_BINDING
+            | TextureUsages::COPY_SRC,
+    });
+    let view = texture.create_view(&TextureViewDescriptor::default());
+    let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+        label: Some("capture_blit_bind_group"),
+        layout: &pipeline.layout,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(&view),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::Sampler(&pipeline.sampler),
+            },
+        ],
+    });
+    let padded_row = RenderDevice::align_copy_bytes_per_row(width as usize * 4) as u32;
+    let buffer = render_device.create_buffer(&BufferDescriptor {
+        label: Some("capture_buffer"),
+        size: (padded_row * height) as u64,
+        usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    target.0 = Some(Target {
+        width,
+        height,
+        texture,
+        view,
+        bind_group,
+        buffer,
+        padded_row,
+    });
+}
+
+/// Points the cameras drawing to the window at the target instead.
+fn redirect_view_targets(
+    target: Res<CaptureTarget>,
+    mut views: Query<(&ExtractedCamera, &mut ViewTarget)>,
+) {
+    let target = match &target.0 {
+        Some(target) => target,
+        None => return,
+    };
+    let window = RenderTarget::Window(WindowId::primary());
+    for (camera, mut view) in views.iter_mut() {
+        if camera.target == window {
+            view.view = target.view.clone();
+        }
+    }
+}
+
+/// Draws the target onto the window once everything else is drawn, copying
+/// it into the buffer too when a frame is wanted.
+struct BlitNode;
+
+impl Node for BlitNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let target = match &world.resource::<CaptureTarget>().0 {
+            Some(target) => target,
+            None => return Ok(()),
+        };
+        let windows = world.resource::<ExtractedWindows>();
+        let swap_chain_texture = match windows
+            .get(&WindowId::primary())
+            .and_then(|window| window.swap_chain_texture.as_ref())
+        {
+            Some(texture) => texture,
+            None => return Ok(()),
+        };
+        let pipeline = world.resource::<BlitPipeline>();
+
+        {
+            let mut pass =
+                render_context
+                    .command_encoder
+                    .begin_render_pass(&RenderPassDescriptor {
+                        label: Some("capture_blit_pass"),
+                        color_attachments: &[RenderPassColorAttachment {
+                            view: swap_chain_texture,
+                            resolve_target: None,
+                            ops: Operations {
+                                load: LoadOp::Load,
+                                store: true,
+                            },
+                        }],
+                        depth_stencil_attachment: None,
+                    });
+            pass.set_pipeline(&pipeline.pipeline);
+            pass.set_bind_group(0, &target.bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+
+        if world.resource::<FrameRequest>().any() {
+            render_context.command_encoder.copy_texture_to_buffer(
+                target.texture.as_image_copy(),
+                ImageCopyBuffer {
+                    buffer: &target.buffer,
+                    layout: ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: std::num::NonZeroU32::new(target.padded_row),
+                        rows_per_image: None,
+                    },
+                },
+                Extent3d {
+                    width: target.width,
+                    height: target.height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Sends the frame copied into the buffer back to the game, once the GPU is
+/// done with it.
+fn read_back_frame(
+    render_device: Res<RenderDevice>,
+    request: Res<FrameRequest>,
+    target: Res<CaptureTarget>,
+    sender: Res<FrameSender>,
+) {
+    let target = match &target.0 {
+        Some(target) if request.any() => target,
+        _ => return,
+    };
+    let slice = target.buffer.slice(..);
+    render_device.map_buffer(&slice, MapMode::Read);
+    let row = target.width as usize * 4;
+    let mut rgba = Vec::with_capacity(row * target.height as usize);
+    for padded in slice
+        .get_mapped_range()
+        .chunks(target.padded_row as usize)
+        .take(target.height as usize)
+    {
+        rgba.extend_from_slice(&padded[..row]);
+    }
+    target.buffer.unmap();
+
+    let bgra = TextureFormat::bevy_default() == TextureFormat::Bgra8UnormSrgb;
+    for pixel in rgba.chunks_exact_mut(4) {
+        if bgra {
+            pixel.swap(0, 2);
+        }
+        // Whatever was blended into the alpha, the window shows it opaque.
+        pixel[3] = u8::MAX;
+    }
+    let _ = sender.0.lock().unwrap().send(Frame {
+        width: target.width,
+        height: target.height,
+        rgba,
+        request: *request,
+    });
+}
@@ -0,0 +1,140 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::{
+    Ball, GameConfig, HitEvent, MatchRules, P1Paddle, P2Paddle, Player, Scoreboard, Spin, TickRate,
+    Velocity,
+};
+
+/// Longest the ball can be held before it is let go anyway, in seconds.
+const SHOT_CLOCK_SECONDS: f32 = 1.5;
+/// Steepest angle the ball can be sent off at, when the paddle is moving at
+/// full speed.
+const MAX_RELEASE_ANGLE: f32 = PI / 4.0;
+/// Room left between the paddle and a held ball, so they don't collide.
+const HOLD_GAP: f32 = 1.0;
+
+/// Whether the player is holding the catch button. Only matters with
+/// [`MatchRules::sticky_paddles`] on.
+#[derive(Component, Default)]
+pub struct Grip(pub bool);
+
+pub struct Caught {
+    pub player: Player,
+    /// Height of the ball above the middle of the paddle.
+    offset: f32,
+    ticks_left: u32,
+}
+
+/// The ball stuck to a paddle, if it is.
+#[derive(Default)]
+pub struct Catch(pub Option<Caught>);
+
+impl Catch {
+    pub fn holding(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+type P1Query<'w, 's> =
+    Query<'w, 's, (&'static Transform, &'static Velocity, &'static Grip), With<P1Paddle>>;
+type P2Query<'w, 's> =
+    Query<'w, 's, (&'static Transform, &'static Velocity, &'static Grip), With<P2Paddle>>;
+
+/// Keeps ball queries apart from the paddle queries.
+type LooseBall = (With<Ball>, Without<Grip>);
+
+fn paddle<'a>(
+    player: Player,
+    p1_query: &'a P1Query,
+    p2_query: &'a P2Query,
+) -> Option<(&'a Transform, &'a Velocity, &'a Grip)> {
+    match player {
+        Player::P1 => p1_query.get_single().ok(),
+        Player::P2 => p2_query.get_single().ok(),
+    }
+}
+
+/// Grabs the ball when it hits a paddle whose player is holding on.
+pub fn catch_ball(
+    rules: Res<MatchRules>,
+    tick_rate: Res<TickRate>,
+    mut catch: ResMut<Catch>,
+    mut hit_events: EventReader<HitEvent>,
+    mut ball_query: Query<(&Transform, &mut Velocity, &mut Spin), LooseBall>,
+    p1_query: P1Query,
+    p2_query: P2Query,
+) {
+    if !rules.sticky_paddles {
+        return;
+    }
+
+    for hit in hit_events.iter() {
+        let (paddle_transform, _, grip) = match paddle(hit.player, &p1_query, &p2_query) {
+            Some(paddle) => paddle,
+            None => continue,
+        };
+        if !grip.0 || catch.holding() {
+            continue;
+        }
+
+        let (ball_transform, mut ball_velocity, mut spin) = ball_query.single_mut();
+        ball_velocity.0 = Vec2::ZERO;
+        spin.0 = 0.0;
+        catch.0 = Some(Caught {
+            player: hit.player,
+            offset: ball_transform.translation.y - paddle_transform.translation.y,
+            ticks_left: tick_rate.ticks(SHOT_CLOCK_SECONDS),
+        });
+    }
+}
+
+/// Carries a caught ball along with its paddle, and sends it off when the
+/// player lets go or the shot clock runs out, angled the way the paddle is
+/// moving and as fast as the paddle would have returned it.
+pub fn hold_ball(
+    config: Res<GameConfig>,
+    rules: Res<MatchRules>,
+    scoreboard: Res<Scoreboard>,
+    mut catch: ResMut<Catch>,
+    mut ball_query: Query<(&mut Transform, &mut Velocity), LooseBall>,
+    p1_query: P1Query,
+    p2_query: P2Query,
+) {
+    let caught = match catch.0.as_mut() {
+        Some(caught) => caught,
+        None => return,
+    };
+    let player = caught.player;
+    let (paddle_transform, paddle_velocity, grip) = match paddle(player, &p1_query, &p2_query) {
+        Some(paddle) => paddle,
+        None => {
+            catch.0 = None;
+            return;
+        }
+    };
+    let (mut ball_transform, mut ball_velocity) = ball_query.single_mut();
+
+    // Towards the opponent.
+    let direction = match player {
+        Player::P1 => 1.0,
+        Player::P2 => -1.0,
+    };
+    let reach = (paddle_transform.scale.x + ball_transform.scale.x) / 2.0 + HOLD_GAP;
+    ball_transform.translation.x = paddle_transform.translation.x + direction * reach;
+    ball_transform.translation.y = paddle_transform.translation.y + caught.offset;
+
+    caught.ticks_left = caught.ticks_left.saturating_sub(1);
+    if grip.0 && caught.ticks_left > 0 {
+        return;
+    }
+
+    let aim = (paddle_velocity.y / config.paddle_speed).clamp(-1.0, 1.0);
+    let angle = aim * MAX_RELEASE_ANGLE;
+    let speed = config.ball_speed
+        * config.rally_speed_multiplier(scoreboard.fjongs)
+        * rules.handicap(player).return_speed;
+    ball_velocity.0 = Vec2::new(direction * angle.cos(), angle.sin()) * speed;
+    catch.0 = None;
+}
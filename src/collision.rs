@@ -0,0 +1,180 @@
+use std::{collections::HashMap, f32::consts::FRAC_PI_4};
+
+use bevy::prelude::*;
+
+/// Overlaps closer to square than this are treated as hitting a corner.
+const CORNER_TOLERANCE: f32 = 2.0;
+/// Side of a [`SpatialHash`] cell. About half a paddle, so most things
+/// only cover a few.
+pub const CELL_SIZE: f32 = 64.0;
+/// Steepest a paddle sends the ball back at, either way from straight
+/// across.
+pub const MAX_BOUNCE_ANGLE: f32 = FRAC_PI_4;
+
+/// How the ball is touching a box.
+pub struct Contact {
+    /// Direction the ball is pushed out of the box on each axis: -1, 0 or 1.
+    /// Both axes are set when it caught a corner.
+    pub normal: Vec2,
+    /// Moves the ball to just outside the box.
+    pub push_out: Vec2,
+}
+
+impl Contact {
+    /// Flips whichever parts of `velocity` are heading into the box.
+    pub fn reflect(&self, mut velocity: Vec2) -> Vec2 {
+        if velocity.x * self.normal.x < 0.0 {
+            velocity.x = -velocity.x;
+        }
+        if velocity.y * self.normal.y < 0.0 {
+            velocity.y = -velocity.y;
+        }
+        velocity
+    }
+}
+
+/// Finds how the ball overlaps a box, both given as centre and size.
+///
+/// The ball is sent back out along whichever axis it has sunk into least,
+/// which is the side it most likely came in through. When that is too close
+/// to call it hit the corner, and goes back out diagonally.
+pub fn contact(ball: Vec2, ball_size: Vec2, other: Vec2, other_size: Vec2) -> Option<Contact> {
+    let offset = ball - other;
+    let overlap = (ball_size + other_size) / 2.0 - offset.abs();
+    if overlap.x <= 0.0 || overlap.y <= 0.0 {
+        return None;
+    }
+
+    let side = Vec2::new(offset.x.signum(), offset.y.signum());
+    let normal = if (overlap.x - overlap.y).abs() < CORNER_TOLERANCE {
+        side
+    } else if overlap.x < overlap.y {
+        Vec2::new(side.x, 0.0)
+    } else {
+        Vec2::new(0.0, side.y)
+    };
+
+    Some(Contact {
+        normal,
+        push_out: normal * overlap,
+    })
+}
+
+/// Like [`contact`], but goes by where the ball was before its last move of
+/// `travel`, so a ball fast enough to sink past the middle of a box in one
+/// tick still goes back out the side it came in through, rather than out
+/// the far side.
+///
+/// A ball that was already in line with the box on both axes, say because
+/// a paddle moved onto it, goes back out as [`contact`] would send it.
+pub fn swept_contact(
+    ball: Vec2,
+    ball_size: Vec2,
+    travel: Vec2,
+    other: Vec2,
+    other_size: Vec2,
+) -> Option<Contact> {
+    let resting = contact(ball, ball_size, other, other_size)?;
+    let reach = (ball_size + other_size) / 2.0;
+    let before = ball - travel - other;
+    let gap = before.abs() - reach;
+    // How far through the move the ball reached the box on an axis, if it
+    // was clear of it on that axis beforehand.
+    let entry = |gap: f32, step: f32| (gap >= 0.0 && step != 0.0).then(|| gap / step.abs());
+    let side = Vec2::new(before.x.signum(), before.y.signum());
+    let normal = match (entry(gap.x, travel.x), entry(gap.y, travel.y)) {
+        (None, None) => return Some(resting),
+        (Some(_), None) => Vec2::new(side.x, 0.0),
+        (None, Some(_)) => Vec2::new(0.0, side.y),
+        // Whichever side it reached last is the one it came in through.
+        (Some(x), Some(y)) if x > y => Vec2::new(side.x, 0.0),
+        (Some(x), Some(y)) if y > x => Vec2::new(0.0, side.y),
+        (Some(_), Some(_)) => side,
+    };
+
+    let offset = ball - other;
+    let mut push_out = Vec2::ZERO;
+    if normal.x != 0.0 {
+        push_out.x = normal.x * reach.x - offset.x;
+    }
+    if normal.y != 0.0 {
+        push_out.y = normal.y * reach.y - offset.y;
+    }
+    Some(Contact { normal, push_out })
+}
+
+/// Velocity the ball comes off a paddle's face with, at `speed` and heading
+/// `towards` the side it is pushed out on, -1 for left and 1 for right.
+///
+/// `offset` is where on the face it was hit, from -1 at the bottom edge to 1
+/// at the top: the middle sends it straight back and the edges send it off
+/// at [`MAX_BOUNCE_ANGLE`], up off the top half and down off the bottom.
+/// Either paddle returns the ball the same way, mirrored.
+pub fn paddle_bounce(speed: f32, offset: f32, towards: f32) -> Vec2 {
+    let angle = offset.clamp(-1.0, 1.0) * MAX_BOUNCE_ANGLE;
+    Vec2::new(towards.signum() * angle.cos(), angle.sin()) * speed
+}
+
+/// A broad phase for collisions: a grid over the court, noting which boxes
+/// cover each of its cells, so only those near the ball need an exact
+/// [`contact`] test rather than every box on the court.
+#[derive(Default)]
+pub struct SpatialHash {
+    cells: HashMap<IVec2, Vec<Entity>>,
+    /// The corner cells of what each entity covers, to take it out again.
+    covering: HashMap<Entity, (IVec2, IVec2)>,
+}
+
+impl SpatialHash {
+    /// Puts `entity` in every cell its box covers, moving it if it was
+    /// already in.
+    pub fn insert(&mut self, entity: Entity, centre: Vec2, size: Vec2) {
+        let covered = cells(centre, size);
+        if self.covering.get(&entity) == Some(&covered) {
+            return;
+        }
+        self.remove(entity);
+        for cell in cells_between(covered) {
+            self.cells.entry(cell).or_default().push(entity);
+        }
+        self.covering.insert(entity, covered);
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        let covered = match self.covering.remove(&entity) {
+            Some(covered) => covered,
+            None => return,
+        };
+        for cell in cells_between(covered) {
+            if let Some(entities) = self.cells.get_mut(&cell) {
+                entities.retain(|other| *other != entity);
+                if entities.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    /// Everything sharing a cell with the box, each once and in order, so
+    /// they are always tested against the ball in the same order.
+    pub fn near(&self, centre: Vec2, size: Vec2) -> Vec<Entity> {
+        let mut near: Vec<Entity> = cells_between(cells(centre, size))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        near.sort();
+        near.dedup();
+        near
+    }
+}
+
+/// The corner cells of those a box covers.
+fn cells(centre: Vec2, size: Vec2) -> (IVec2, IVec2) {
+    let cell = |corner: Vec2| (corner / CELL_SIZE).floor().as_ivec2();
+    (cell(centre - size / 2.0), cell(centre + size / 2.0))
+}
+
+fn cells_between((min, max): (IVec2, IVec2)) -> impl Iterator<Item = IVec2> {
+    (min.x..=max.x).flat_map(move |x| (min.y..=max.y).map(move |y| IVec2::new(x, y)))
+}
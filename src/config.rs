@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{BALL_SPEED, BALL_SPEED_X, BALL_SPEED_Y, PADDLE_SIZE, PADDLE_SPEED, SERVE_COOLDOWN};
+
+/// Speed the ball picks up off a paddle for each hit in the rally so far.
+const RALLY_SPEED_UP: f32 = 4.0;
+
+/// Tunables for how the game feels.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+#[cfg_attr(feature = "debug", derive(bevy_inspector_egui::Inspectable))]
+pub struct GameConfig {
+    /// Top speed of a player's paddle.
+    pub paddle_speed: f32,
+    /// How quickly a paddle gets up to speed, in units per second squared.
+    pub paddle_acceleration: f32,
+    /// How quickly a paddle stops or turns around.
+    pub paddle_deceleration: f32,
+    /// Share of the paddle's vertical velocity handed to the ball on a hit.
+    pub spin_transfer: f32,
+    /// Ball spin, in radians per second, for each unit of paddle speed across
+    /// the ball on a hit.
+    pub spin_per_paddle_speed: f32,
+    /// How hard spin bends the ball's path.
+    pub magnus: f32,
+    /// Share of the ball's spin lost each second.
+    pub spin_decay: f32,
+    pub paddle_size: Vec2,
+    /// Speed of the ball coming off a paddle, before the rally speed-up.
+    pub ball_speed: f32,
+    /// Ball velocity on the serve, with x pointing towards the receiver.
+    pub serve_speed: Vec2,
+    /// Seconds the ball spends gliding back to the centre between points.
+    pub serve_cooldown: f32,
+    /// Share of the paddle, at each end, that smashes the ball.
+    pub smash_edge: f32,
+    /// Speed-up applied to a smashed ball.
+    pub smash_multiplier: f32,
+    /// Rally lengths that whip up a fjong frenzy, each wilder than the last:
+    /// the ball changes colour, then speeds up, then the next goal counts
+    /// double.
+    pub frenzy_rallies: Vec<usize>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            paddle_speed: PADDLE_SPEED,
+            paddle_acceleration: 4000.0,
+            paddle_deceleration: 6000.0,
+            spin_transfer: 0.3,
+            spin_per_paddle_speed: 0.02,
+            magnus: 0.06,
+            spin_decay: 0.8,
+            paddle_size: PADDLE_SIZE.truncate(),
+            ball_speed: BALL_SPEED,
+            serve_speed: Vec2::new(BALL_SPEED_X, BALL_SPEED_Y),
+            serve_cooldown: SERVE_COOLDOWN,
+            smash_edge: 0.15,
+            smash_multiplier: 1.5,
+            frenzy_rallies: vec![10, 20, 30],
+        }
+    }
+}
+
+impl GameConfig {
+    /// Moves a paddle's velocity one tick of `step` seconds closer to
+    /// `wanted`.
+    pub fn accelerate(&self, current: f32, wanted: f32, step: f32) -> f32 {
+        let speeding_up = wanted * current >= 0.0 && wanted.abs() > current.abs();
+        let rate = if speeding_up {
+            self.paddle_acceleration
+        } else {
+            self.paddle_deceleration
+        };
+        let max_change = rate * step;
+
+        current + (wanted - current).clamp(-max_change, max_change)
+    }
+
+    /// How much faster than [`GameConfig::ball_speed`] the ball comes off a
+    /// paddle `fjongs` hits into a rally.
+    pub fn rally_speed_multiplier(&self, fjongs: usize) -> f32 {
+        1.0 + fjongs as f32 * RALLY_SPEED_UP / self.ball_speed
+    }
+}
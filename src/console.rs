@@ -0,0 +1,316 @@
+use std::collections::BTreeMap;
+
+use bevy::{input::InputSystem, prelude::*, window::ReceivedCharacter};
+
+use crate::{ai::Difficulty, fonts::UiFont, AppState, GameConfig, MatchRules, Policy, Scoreboard};
+
+const TOGGLE_KEY: KeyCode = KeyCode::Grave;
+const CONSOLE_FONT_SIZE: f32 = 14.0;
+const CONSOLE_PADDING: Val = Val::Px(8.0);
+const CONSOLE_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.8);
+const PROMPT_COLOR: Color = Color::rgb(0.6, 1.0, 0.6);
+/// Lines of output kept on screen.
+const SCROLLBACK: usize = 12;
+
+/// What a console command does with its arguments. Returns a line to show
+/// the player, or what went wrong.
+pub type CommandFn = fn(&mut World, &[&str]) -> Result<String, String>;
+
+pub struct ConsoleCommand {
+    /// Shown by `help`, e.g. `score <p1|p2> <points>`.
+    pub usage: &'static str,
+    pub run: CommandFn,
+}
+
+/// Every command the console knows, by name.
+#[derive(Default)]
+pub struct ConsoleCommands(BTreeMap<&'static str, ConsoleCommand>);
+
+/// Lets plugins add their own console commands.
+pub trait ConsoleCommandsExt {
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        run: CommandFn,
+    ) -> &mut Self;
+}
+
+impl ConsoleCommandsExt for App {
+    fn add_console_command(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        run: CommandFn,
+    ) -> &mut Self {
+        self.init_resource::<ConsoleCommands>();
+        self.world
+            .resource_mut::<ConsoleCommands>()
+            .0
+            .insert(name, ConsoleCommand { usage, run });
+        self
+    }
+}
+
+/// A developer console, opened with the key under Escape, for poking at the
+/// game while it runs: `set ball_speed 600`, `score p1 10`,
+/// `ai difficulty hard`, `reset`, and `help` for the rest.
+///
+/// Other plugins add commands with
+/// [`add_console_command`](ConsoleCommandsExt::add_console_command).
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .init_resource::<ConsoleCommands>()
+            .add_console_command("set", "set <setting> <value>", set_config)
+            .add_console_command("score", "score <p1|p2> <points>", set_score)
+            .add_console_command("ai", "ai difficulty <easy|medium|hard|insane>", set_ai)
+            .add_console_command("reset", "reset", reset_match)
+            // Ahead of everything else, so typing doesn't also steer paddles
+            // and menus.
+            .add_system_to_stage(CoreStage::PreUpdate, type_into_console.after(InputSystem))
+            .add_system(run_commands.exclusive_system())
+            .add_system(show_console);
+    }
+}
+
+#[derive(Default)]
+struct Console {
+    open: bool,
+    input: String,
+    /// Lines entered but not run yet.
+    pending: Vec<String>,
+    log: Vec<String>,
+}
+
+impl Console {
+    fn print(&mut self, line: impl Into<String>) {
+        self.log.push(line.into());
+        let overflow = self.log.len().saturating_sub(SCROLLBACK);
+        self.log.drain(..overflow);
+    }
+}
+
+fn type_into_console(
+    mut console: ResMut<Console>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+) {
+    // Read either way, so nothing typed while closed turns up on opening.
+    let typed: String = characters.iter().map(|character| character.char).collect();
+    if keyboard_input.just_pressed(TOGGLE_KEY) {
+        console.open = !console.open;
+    }
+    if !console.open {
+        return;
+    }
+
+    // The toggle key types a character of its own.
+    let typed = typed
+        .chars()
+        .filter(|c| !c.is_control() && *c != '`' && *c != '~');
+    console.input.extend(typed);
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.input);
+        if !line.trim().is_empty() {
+            console.pending.push(line);
+        }
+    }
+
+    // The console has the keyboard to itself while it is open.
+    let keys: Vec<KeyCode> = keyboard_input
+        .get_pressed()
+        .chain(keyboard_input.get_just_pressed())
+        .chain(keyboard_input.get_just_released())
+        .copied()
+        .collect();
+    for key in keys {
+        keyboard_input.reset(key);
+    }
+}
+
+fn run_commands(world: &mut World) {
+    let lines = std::mem::take(&mut world.resource_mut::<Console>().pending);
+    for line in lines {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let output = world.resource_scope(|world, commands: Mut<ConsoleCommands>| match words[0] {
+            "help" => Ok(commands
+                .0
+                .values()
+                .map(|command| command.usage)
+                .chain(["help"])
+                .collect::<Vec<_>>()
+                .join(", ")),
+            name => match commands.0.get(name) {
+                Some(command) => (command.run)(world, &words[1..])
+                    .map_err(|err| format!("{}\nusage: {}", err, command.usage)),
+                None => Err(format!("Unknown command {}, try help", name)),
+            },
+        });
+
+        let mut console = world.resource_mut::<Console>();
+        console.print(format!("> {}", line));
+        for output_line in output.unwrap_or_else(|err| err).lines() {
+            console.print(output_line);
+        }
+    }
+}
+
+#[derive(Component)]
+struct ConsoleScreen;
+
+#[derive(Component)]
+struct ConsoleText;
+
+fn show_console(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    console: Res<Console>,
+    screen_query: Query<Entity, With<ConsoleScreen>>,
+    mut text_query: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+    if !console.open {
+        for screen in screen_query.iter() {
+            commands.entity(screen).despawn_recursive();
+        }
+        return;
+    }
+
+    let style = |color| TextStyle {
+        font: font.0.clone(),
+        font_size: CONSOLE_FONT_SIZE,
+        color,
+    };
+    let mut log: String = console
+        .log
+        .iter()
+        .map(|line| format!("{}\n", line))
+        .collect();
+    log.push_str("> ");
+    let text = Text {
+        sections: vec![
+            TextSection {
+                value: log,
+                style: style(Color::WHITE),
+            },
+            TextSection {
+                value: format!("{}_", console.input),
+                style: style(PROMPT_COLOR),
+            },
+        ],
+        ..default()
+    };
+
+    if let Ok(mut shown) = text_query.get_single_mut() {
+        *shown = text;
+        return;
+    }
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(0.0),
+                    left: Val::Px(0.0),
+                    ..default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                padding: Rect::all(CONSOLE_PADDING),
+                ..default()
+            },
+            color: CONSOLE_COLOR.into(),
+            ..default()
+        })
+        .insert(ConsoleScreen)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle { text, ..default() })
+                .insert(ConsoleText);
+        });
+}
+
+/// Tunables `set` can change.
+fn config_field<'a>(config: &'a mut GameConfig, name: &str) -> Option<&'a mut f32> {
+    Some(match name {
+        "paddle_speed" => &mut config.paddle_speed,
+        "paddle_acceleration" => &mut config.paddle_acceleration,
+        "paddle_deceleration" => &mut config.paddle_deceleration,
+        "paddle_width" => &mut config.paddle_size.x,
+        "paddle_height" => &mut config.paddle_size.y,
+        "spin_transfer" => &mut config.spin_transfer,
+        "spin_per_paddle_speed" => &mut config.spin_per_paddle_speed,
+        "magnus" => &mut config.magnus,
+        "spin_decay" => &mut config.spin_decay,
+        "ball_speed" => &mut config.ball_speed,
+        "serve_speed_x" => &mut config.serve_speed.x,
+        "serve_speed_y" => &mut config.serve_speed.y,
+        "smash_edge" => &mut config.smash_edge,
+        "smash_multiplier" => &mut config.smash_multiplier,
+        _ => return None,
+    })
+}
+
+fn set_config(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let (name, value) = match args {
+        [name, value] => (*name, *value),
+        _ => return Err("Expected a setting and a value".to_string()),
+    };
+    let value: f32 = value
+        .parse()
+        .map_err(|_| format!("{} is not a number", value))?;
+    let mut config = world.resource_mut::<GameConfig>();
+    let field = config_field(&mut config, name).ok_or(format!("No setting called {}", name))?;
+    let old = std::mem::replace(field, value);
+    Ok(format!("{}: {} -> {}", name, old, value))
+}
+
+fn set_score(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let (player, points) = match args {
+        [player, points] => (*player, *points),
+        _ => return Err("Expected a player and a score".to_string()),
+    };
+    let points: usize = points
+        .parse()
+        .map_err(|_| format!("{} is not a score", points))?;
+    let mut scoreboard = world.resource_mut::<Scoreboard>();
+    match player {
+        "p1" => scoreboard.p1_score = points,
+        "p2" => scoreboard.p2_score = points,
+        _ => return Err(format!("No player called {}", player)),
+    }
+    Ok(format!("{} now has {}", player, points))
+}
+
+fn set_ai(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let level = match args {
+        ["difficulty", level] => *level,
+        _ => return Err("Expected difficulty and a level".to_string()),
+    };
+    let difficulty = match level {
+        "easy" => Difficulty::Easy,
+        "medium" => Difficulty::Medium,
+        "hard" => Difficulty::Hard,
+        "insane" => Difficulty::Insane,
+        "learned" if world.contains_resource::<Policy>() => Difficulty::Learned,
+        _ => return Err(format!("No difficulty called {}", level)),
+    };
+    world.resource_mut::<MatchRules>().difficulty = difficulty;
+    Ok(format!("AI is now {:?}", difficulty))
+}
+
+fn reset_match(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let mut state = world.resource_mut::<State<AppState>>();
+    if *state.current() != AppState::Playing {
+        return Err("Not in a match".to_string());
+    }
+    state.restart().map_err(|err| format!("{:?}", err))?;
+    Ok("Match restarted".to_string())
+}
@@ -0,0 +1,175 @@
+use bevy::{
+    ecs::system::{lifetimeless::SRes, SystemParamItem},
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        render_asset::{PrepareAssetError, RenderAsset},
+        render_resource::{
+            std140::{AsStd140, Std140},
+            *,
+        },
+        renderer::RenderDevice,
+    },
+    sprite::{Material2d, Material2dPipeline, Material2dPlugin, MaterialMesh2dBundle},
+    transform::TransformSystem,
+};
+
+use crate::{camera::GameCamera, settings::Settings};
+
+const PIXELS_PER_SCANLINE: f32 = 3.0;
+/// In front of everything else the 2D camera draws.
+const CRT_Z: f32 = 900.0;
+
+/// Scanlines, a vignette and a curved bezel drawn over the court when the
+/// CRT video setting is on.
+pub struct CrtPlugin;
+
+impl Plugin for CrtPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(Material2dPlugin::<CrtMaterial>::default())
+            .add_system(toggle_crt)
+            // Once the camera has moved for the frame.
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                fit_crt_to_window.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+#[derive(Clone, TypeUuid)]
+#[uuid = "a05f14e5-297a-475c-a430-7abbdd5e022f"]
+pub struct CrtMaterial {
+    pub lines: f32,
+}
+
+#[derive(Clone, Default, AsStd140)]
+struct CrtMaterialUniformData {
+    lines: f32,
+}
+
+pub struct GpuCrtMaterial {
+    bind_group: BindGroup,
+}
+
+impl RenderAsset for CrtMaterial {
+    type ExtractedAsset = CrtMaterial;
+    type PreparedAsset = GpuCrtMaterial;
+    type Param = (SRes<RenderDevice>, SRes<Material2dPipeline<CrtMaterial>>);
+
+    fn extract_asset(&self) -> Self::ExtractedAsset {
+        self.clone()
+    }
+
+    fn prepare_asset(
+        material: Self::ExtractedAsset,
+        (render_device, pipeline): &mut SystemParamItem<Self::Param>,
+    ) -> Result<Self::PreparedAsset, PrepareAssetError<Self::ExtractedAsset>> {
+        let value = CrtMaterialUniformData {
+            lines: material.lines,
+        };
+        let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("crt_material_uniform_buffer"),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            contents: value.as_std140().as_bytes(),
+        });
+        let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+            label: Some("crt_material_bind_group"),
+            layout: &pipeline.material2d_layout,
+        });
+
+        Ok(GpuCrtMaterial { bind_group })
+    }
+}
+
+impl Material2d for CrtMaterial {
+    fn fragment_shader(asset_server: &AssetServer) -> Option<Handle<Shader>> {
+        Some(asset_server.load("shaders/crt.wgsl"))
+    }
+
+    fn bind_group(render_asset: &GpuCrtMaterial) -> &BindGroup {
+        &render_asset.bind_group
+    }
+
+    fn bind_group_layout(render_device: &RenderDevice) -> BindGroupLayout {
+        render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: BufferSize::new(
+                        CrtMaterialUniformData::std140_size_static() as u64
+                    ),
+                },
+                count: None,
+            }],
+            label: Some("crt_material_layout"),
+        })
+    }
+}
+
+#[derive(Component)]
+struct CrtOverlay;
+
+fn toggle_crt(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<CrtMaterial>>,
+    overlay_query: Query<Entity, With<CrtOverlay>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let overlay = overlay_query.get_single().ok();
+    match (settings.video.crt, overlay) {
+        (true, None) => {
+            commands
+                .spawn_bundle(MaterialMesh2dBundle {
+                    mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE))).into(),
+                    material: materials.add(CrtMaterial { lines: 0.0 }),
+                    transform: Transform::from_xyz(0.0, 0.0, CRT_Z),
+                    ..default()
+                })
+                .insert(CrtOverlay);
+        }
+        (false, Some(overlay)) => commands.entity(overlay).despawn(),
+        _ => {}
+    }
+}
+
+/// Keeps the overlay covering the whole window wherever the camera points,
+/// with scanlines a fixed number of pixels apart.
+fn fit_crt_to_window(
+    windows: Res<Windows>,
+    mut materials: ResMut<Assets<CrtMaterial>>,
+    camera_query: Query<&Transform, (With<GameCamera>, Without<CrtOverlay>)>,
+    mut overlay_query: Query<(&mut Transform, &Handle<CrtMaterial>), With<CrtOverlay>>,
+) {
+    let (window, camera, (mut transform, material)) = match (
+        windows.get_primary(),
+        camera_query.get_single(),
+        overlay_query.get_single_mut(),
+    ) {
+        (Some(window), Ok(camera), Ok(overlay)) => (window, camera, overlay),
+        _ => return,
+    };
+
+    let position = camera.translation.truncate();
+    transform.translation = position.extend(CRT_Z);
+    let size = Vec2::new(window.width(), window.height());
+    let scale = size * camera.scale.truncate();
+    if transform.scale.truncate() == scale {
+        return;
+    }
+    transform.scale = scale.extend(1.0);
+    if let Some(material) = materials.get_mut(material) {
+        material.lines = size.y / PIXELS_PER_SCANLINE;
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+// Works in the browser too, where std's clock panics.
+use instant::SystemTime;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ai::Difficulty, persist, AppState, GameRng, MatchOverEvent, MatchRules, Player, Scoreboard,
+    Series,
+};
+
+/// Where the best daily challenge scores live, under
+/// [`data_path`](crate::data_path).
+pub const DAILY_PATH: &str = "daily.ron";
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+/// Mixed into the day so daily seeds aren't just small numbers that a
+/// player might pick for a match of their own.
+const DAILY_SALT: u64 = 0x9e37_79b9_7f4a_7c15;
+/// Daily challenges are never easy.
+const DAILY_DIFFICULTIES: [Difficulty; 3] =
+    [Difficulty::Medium, Difficulty::Hard, Difficulty::Insane];
+/// How likely each modifier is to be on for the day.
+const MODIFIER_CHANCE: f64 = 0.3;
+
+/// The same match for everyone on a given day: the seed, the AI's difficulty
+/// and the modifiers all come from the date. Days are counted in UTC, so
+/// everyone gets the same one wherever they are.
+#[derive(Clone, Debug)]
+pub struct DailyChallenge {
+    /// Days since the Unix epoch.
+    pub day: u64,
+    pub seed: u64,
+    pub rules: MatchRules,
+}
+
+impl DailyChallenge {
+    pub fn today() -> DailyChallenge {
+        let since_epoch = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs());
+        DailyChallenge::for_day(since_epoch / SECONDS_PER_DAY)
+    }
+
+    pub fn for_day(day: u64) -> DailyChallenge {
+        let seed = day.wrapping_add(1).wrapping_mul(DAILY_SALT);
+        // Drawn from a generator of its own, so picking the rules doesn't
+        // use up any of what the match itself draws.
+        let mut rng = GameRng::new(seed);
+        let difficulty = DAILY_DIFFICULTIES[rng.gen_range(0..DAILY_DIFFICULTIES.len())];
+        let mut modifier = || rng.gen_bool(MODIFIER_CHANCE);
+        let rules = MatchRules {
+            difficulty,
+            sticky_paddles: modifier(),
+            win_by_two: modifier(),
+            shrinking_paddles: modifier(),
+            moving_goals: modifier(),
+            fog_of_war: modifier(),
+            mirror_controls: modifier(),
+            ..default()
+        };
+        DailyChallenge { day, seed, rules }
+    }
+
+    /// The day as year, month and day of the month.
+    pub fn date(&self) -> (i64, u32, u32) {
+        civil_from_days(self.day as i64)
+    }
+
+    /// The day written the ISO way, as in 2024-03-01.
+    pub fn date_string(&self) -> String {
+        let (year, month, day) = self.date();
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+
+    /// How much P1 won by, or nothing for a loss.
+    pub fn score(p1_score: usize, p2_score: usize) -> u32 {
+        p1_score.saturating_sub(p2_score) as u32
+    }
+}
+
+/// Howard Hinnant's `civil_from_days`, turning days since 1970-01-01 into a
+/// date on the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // Counted from March, so the leap day comes last.
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Best daily challenge scores played here, by day, kept in [`DAILY_PATH`]
+/// between runs.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyBests {
+    pub best: BTreeMap<u64, u32>,
+}
+
+impl DailyBests {
+    pub fn load(path: impl AsRef<Path>) -> DailyBests {
+        persist::load_or_create(path.as_ref())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        persist::save(self, path.as_ref())
+    }
+}
+
+/// Sent when a daily challenge beats the best played here that day.
+pub struct DailyScoreEvent {
+    pub day: u64,
+    pub seed: u64,
+    pub score: u32,
+}
+
+/// The daily challenge being played, if it is one.
+pub struct DailyRun {
+    pub challenge: DailyChallenge,
+    /// Seed and rules to go back to once the challenge is over.
+    pub previous_seed: u64,
+    pub previous_rules: MatchRules,
+}
+
+/// Sets up daily challenges, keeps the best score of each day in `path`, and
+/// sends a [`DailyScoreEvent`] for every new best, which the
+/// [`LeaderboardPlugin`](crate::LeaderboardPlugin) passes on when there is
+/// one.
+pub struct DailyPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DailyBests::load(&self.path))
+            .insert_resource(DailyFile(self.path.clone()))
+            .init_resource::<Series>()
+            .init_resource::<MatchRules>()
+            .add_event::<DailyScoreEvent>()
+            .add_system(record_daily_scores)
+            .add_system_set(SystemSet::on_enter(AppState::MainMenu).with_system(end_daily));
+    }
+}
+
+struct DailyFile(PathBuf);
+
+/// Starts `challenge`, to be played from [`AppState::Playing`] on.
+pub fn start_daily(
+    commands: &mut Commands,
+    rng: &mut GameRng,
+    rules: &mut MatchRules,
+    challenge: DailyChallenge,
+) {
+    let previous_seed = rng.seed();
+    *rng = GameRng::new(challenge.seed);
+    let previous_rules = std::mem::replace(rules, challenge.rules.clone());
+    commands.insert_resource(DailyRun {
+        challenge,
+        previous_seed,
+        previous_rules,
+    });
+}
+
+fn record_daily_scores(
+    run: Option<Res<DailyRun>>,
+    file: Res<DailyFile>,
+    series: Res<Series>,
+    scoreboard: Res<Scoreboard>,
+    mut bests: ResMut<DailyBests>,
+    mut match_over_events: EventReader<MatchOverEvent>,
+    mut daily_scores: EventWriter<DailyScoreEvent>,
+) {
+    let run = match run {
+        Some(run) => run,
+        None => return,
+    };
+    for _ in match_over_events.iter() {
+        let (p1_score, p2_score) = match series.player_on(Player::P1) {
+            Player::P1 => (scoreboard.p1_score, scoreboard.p2_score),
+            Player::P2 => (scoreboard.p2_score, scoreboard.p1_score),
+        };
+        let score = DailyChallenge::score(p1_score, p2_score);
+        let day = run.challenge.day;
+        info!("Daily challenge {} played for {}", day, score);
+        if matches!(bests.best.get(&day), Some(best) if score <= *best) {
+            continue;
+        }
+        bests.best.insert(day, score);
+        if let Err(err) = bests.save(&file.0) {
+            warn!("Could not save {}: {}", file.0.display(), err);
+        }
+        daily_scores.send(DailyScoreEvent {
+            day,
+            seed: run.challenge.seed,
+            score,
+        });
+    }
+}
+
+/// Puts the seed and rules back as they were before the challenge.
+fn end_daily(
+    mut commands: Commands,
+    run: Option<Res<DailyRun>>,
+    mut rng: ResMut<GameRng>,
+    mut rules: ResMut<MatchRules>,
+) {
+    if let Some(run) = run {
+        *rng = GameRng::new(run.previous_seed);
+        *rules = run.previous_rules.clone();
+        commands.remove_resource::<DailyRun>();
+    }
+}
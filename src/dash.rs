@@ -0,0 +1,141 @@
+use bevy::{math::const_vec2, prelude::*};
+
+use crate::{ai::Brain, TickRate, FOREGROUND_COLOR};
+
+/// How much faster a dashing paddle goes.
+const DASH_BOOST: f32 = 2.0;
+/// How long a dash lasts, in seconds.
+const DASH_SECONDS: f32 = 0.2;
+/// And how long it takes to charge again from when it starts.
+const COOLDOWN_SECONDS: f32 = 2.0;
+
+const METER_SIZE: Vec2 = const_vec2!([20.0, 4.0]);
+/// Room between the bottom of a paddle and its meter.
+const METER_GAP: f32 = 8.0;
+/// A meter still filling up is dimmer than a full one.
+const CHARGING_ALPHA: f32 = 0.35;
+
+/// A short burst of speed for a paddle, tapped with the dash key or the
+/// right trigger, that takes a while to charge back up.
+#[derive(Component, Default)]
+pub struct Dash {
+    /// The player asked for a dash since the last tick.
+    wanted: bool,
+    ticks_left: u32,
+    cooldown_left: u32,
+    /// Ticks the last cooldown started from.
+    cooldown_ticks: u32,
+}
+
+impl Dash {
+    /// Dashes on the next tick, if one is charged by then.
+    pub fn request(&mut self) {
+        self.wanted = true;
+    }
+
+    /// What to multiply the paddle's top speed by this tick.
+    pub fn boost(&self) -> f32 {
+        if self.ticks_left > 0 {
+            DASH_BOOST
+        } else {
+            1.0
+        }
+    }
+
+    /// How far the next dash is from being ready, from 0 just used to 1
+    /// ready to go.
+    pub fn charge(&self) -> f32 {
+        if self.cooldown_left == 0 {
+            return 1.0;
+        }
+        1.0 - self.cooldown_left as f32 / self.cooldown_ticks as f32
+    }
+}
+
+/// Counts down dashes and their cooldowns, and sets off any that were asked
+/// for and are ready. Asking while still charging does nothing.
+pub fn run_dashes(tick_rate: Res<TickRate>, mut dash_query: Query<&mut Dash>) {
+    for mut dash in dash_query.iter_mut() {
+        if !dash.wanted && dash.cooldown_left == 0 {
+            continue;
+        }
+        dash.ticks_left = dash.ticks_left.saturating_sub(1);
+        dash.cooldown_left = dash.cooldown_left.saturating_sub(1);
+        if std::mem::take(&mut dash.wanted) && dash.cooldown_left == 0 {
+            dash.ticks_left = tick_rate.ticks(DASH_SECONDS);
+            dash.cooldown_ticks = tick_rate.ticks(COOLDOWN_SECONDS);
+            dash.cooldown_left = dash.cooldown_ticks;
+        }
+    }
+}
+
+/// A bar under each player's paddle that fills up as its [`Dash`] charges.
+/// Bots never dash, so their paddles go without.
+///
+/// Meters are only for show and never change what the simulation sees.
+pub struct DashMeterPlugin;
+
+impl Plugin for DashMeterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(add_meters)
+            .add_system_to_stage(CoreStage::PostUpdate, fit_meters);
+    }
+}
+
+/// Shows how charged `paddle`'s dash is.
+#[derive(Component)]
+struct DashMeter {
+    paddle: Entity,
+}
+
+fn add_meters(mut commands: Commands, paddle_query: Query<Entity, Added<Dash>>) {
+    for paddle in paddle_query.iter() {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: FOREGROUND_COLOR,
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(DashMeter { paddle });
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn fit_meters(
+    mut commands: Commands,
+    paddle_query: Query<
+        (&Transform, &Sprite, &Visibility, &Dash, Option<&Brain>),
+        Without<DashMeter>,
+    >,
+    mut meter_query: Query<(
+        Entity,
+        &DashMeter,
+        &mut Transform,
+        &mut Sprite,
+        &mut Visibility,
+    )>,
+) {
+    for (entity, meter, mut transform, mut sprite, mut visibility) in meter_query.iter_mut() {
+        let (paddle_transform, paddle_sprite, paddle_visibility, dash, brain) =
+            match paddle_query.get(meter.paddle) {
+                Ok(paddle) => paddle,
+                Err(_) => {
+                    commands.entity(entity).despawn();
+                    continue;
+                }
+            };
+
+        visibility.is_visible = paddle_visibility.is_visible && brain.is_none();
+        let charge = dash.charge();
+        let below = paddle_transform.scale.y / 2.0 + METER_GAP;
+        transform.translation = paddle_transform.translation - Vec3::Y * below;
+        transform.scale = Vec3::new(METER_SIZE.x * charge, METER_SIZE.y, 1.0);
+        // In the paddle's own colour, whatever the theme.
+        sprite.color = paddle_sprite.color;
+        if charge < 1.0 {
+            sprite.color.set_a(CHARGING_ALPHA);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use bevy_inspector_egui::{plugin::InspectorWindows, Inspectable, InspectorPlugin};
+
+use crate::{
+    ai::{BallView, Brain, Difficulty, PaddleView, Predictor, Skill},
+    Arena, Ball, GameConfig, Velocity,
+};
+
+const TOGGLE_KEY: KeyCode = KeyCode::F12;
+/// Above everything else on the court.
+const OVERLAY_Z: f32 = 5.0;
+const LINE_THICKNESS: f32 = 2.0;
+const TARGET_SIZE: f32 = 10.0;
+/// Seconds of travel the desired-velocity bar is drawn as.
+const VELOCITY_SECONDS: f32 = 0.25;
+const PATH_COLOR: Color = Color::rgba(0.2, 0.9, 1.0, 0.6);
+const TARGET_COLOR: Color = Color::rgba(1.0, 0.3, 0.3, 0.9);
+const VELOCITY_COLOR: Color = Color::rgba(1.0, 0.9, 0.2, 0.8);
+
+/// In-game inspector for tuning the game while it runs, toggled with F12.
+///
+/// While it is open, every brain's view of the match is drawn over the
+/// court too: the path it predicts for the ball, bounces and all, where it
+/// means to put its paddle, and the velocity it is asking the paddle for.
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(InspectorPlugin::<GameConfig>::new_insert_manually())
+            .add_plugin(InspectorPlugin::<AiTuning>::new())
+            .add_startup_system(hide_inspectors)
+            .add_system(toggle_inspectors)
+            .add_system(apply_ai_tuning)
+            .add_system(draw_ai_overlay);
+    }
+}
+
+/// Skill handed to every brain that has one, once it is edited.
+#[derive(Inspectable)]
+struct AiTuning {
+    skill: Skill,
+}
+
+impl Default for AiTuning {
+    fn default() -> Self {
+        AiTuning {
+            skill: Difficulty::Medium.skill(),
+        }
+    }
+}
+
+fn set_inspectors_visible(windows: &mut InspectorWindows, visible: bool) {
+    windows.window_data_mut::<GameConfig>().visible = visible;
+    windows.window_data_mut::<AiTuning>().visible = visible;
+}
+
+fn hide_inspectors(mut windows: ResMut<InspectorWindows>) {
+    set_inspectors_visible(&mut windows, false);
+}
+
+fn toggle_inspectors(keyboard_input: Res<Input<KeyCode>>, mut windows: ResMut<InspectorWindows>) {
+    if keyboard_input.just_pressed(TOGGLE_KEY) {
+        let visible = windows.window_data::<GameConfig>().visible;
+        set_inspectors_visible(&mut windows, !visible);
+    }
+}
+
+fn apply_ai_tuning(tuning: Res<AiTuning>, mut brain_query: Query<&mut Brain>) {
+    // Brains keep the difficulty's skill until the tuning is edited.
+    if !tuning.is_changed() || tuning.is_added() {
+        return;
+    }
+    for mut brain in brain_query.iter_mut() {
+        if let Some(skill) = brain.skill_mut() {
+            *skill = tuning.skill;
+        }
+    }
+}
+
+/// Part of the AI overlay, all of which is drawn afresh every frame.
+#[derive(Component)]
+struct AiOverlay;
+
+fn draw_ai_overlay(
+    mut commands: Commands,
+    windows: Res<InspectorWindows>,
+    arena: Res<Arena>,
+    overlay_query: Query<Entity, With<AiOverlay>>,
+    ball_query: Query<(&Transform, &Velocity), With<Ball>>,
+    brain_query: Query<(&Brain, &Transform), Without<Ball>>,
+) {
+    for entity in overlay_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    if !windows.window_data::<GameConfig>().visible {
+        return;
+    }
+    let (ball_transform, ball_velocity) = match ball_query.get_single() {
+        Ok(ball) => ball,
+        Err(_) => return,
+    };
+    let ball = BallView {
+        position: ball_transform.translation.truncate(),
+        velocity: ball_velocity.0,
+    };
+
+    let mut draw = |from: Vec2, to: Vec2, thickness: f32, color: Color| {
+        let line = to - from;
+        commands
+            .spawn_bundle(SpriteBundle {
+                transform: Transform {
+                    translation: ((from + to) / 2.0).extend(OVERLAY_Z),
+                    rotation: Quat::from_rotation_z(line.y.atan2(line.x)),
+                    scale: Vec3::new(line.length().max(thickness), thickness, 1.0),
+                },
+                sprite: Sprite { color, ..default() },
+                ..default()
+            })
+            .insert(AiOverlay);
+    };
+    for (brain, transform) in brain_query.iter() {
+        let paddle = PaddleView {
+            position: transform.translation.truncate(),
+            velocity: Vec2::new(0.0, brain.wanted()),
+            size: transform.scale.truncate(),
+        };
+        for leg in Predictor::predicted_path(&ball, &paddle, &arena).windows(2) {
+            draw(leg[0], leg[1], LINE_THICKNESS, PATH_COLOR);
+        }
+        if let Some(target_y) = brain.target_y() {
+            let target = Vec2::new(paddle.position.x, target_y);
+            draw(target, target, TARGET_SIZE, TARGET_COLOR);
+        }
+        let heading = paddle.position + paddle.velocity * VELOCITY_SECONDS;
+        draw(
+            paddle.position,
+            heading,
+            LINE_THICKNESS * 2.0,
+            VELOCITY_COLOR,
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{fonts::UiFont, Locale, P1Paddle, P2Paddle, Player, FOREGROUND_COLOR};
+
+const BUBBLE_FONT_SIZE: f32 = 14.0;
+const BUBBLE_SECONDS: f32 = 1.5;
+/// How long a bubble takes to fade out at the end of its time on screen.
+const FADE_SECONDS: f32 = 0.5;
+/// Room between the top of a paddle and its bubble.
+const BUBBLE_GAP: f32 = 16.0;
+/// How far a bubble drifts up over its time on screen.
+const BUBBLE_RISE: f32 = 40.0;
+/// Above the ball and everything else on the court.
+const BUBBLE_Z: f32 = 5.0;
+
+/// Something quick to say to the other player mid-match, without stopping.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Emote {
+    Gg,
+    Nice,
+    Yikes,
+}
+
+impl Emote {
+    /// In the order of the number keys and D-pad directions that send them.
+    pub const ALL: [Emote; 3] = [Emote::Gg, Emote::Nice, Emote::Yikes];
+
+    fn locale_id(self) -> &'static str {
+        match self {
+            Emote::Gg => "emote-gg",
+            Emote::Nice => "emote-nice",
+            Emote::Yikes => "emote-yikes",
+        }
+    }
+}
+
+/// Sent when someone emotes, whether they are playing here or elsewhere.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EmoteEvent {
+    /// The end whose paddle the emote goes over.
+    pub end: Player,
+    pub emote: Emote,
+}
+
+/// Emotes a player on another machine has sent for the paddle this is on,
+/// left by their brain for [`hear_remote_emotes`] to pass on.
+#[derive(Component, Clone, Default)]
+pub(crate) struct RemoteEmotes(Arc<Mutex<Vec<Emote>>>);
+
+impl RemoteEmotes {
+    pub(crate) fn push(&self, emote: Emote) {
+        self.0.lock().unwrap().push(emote);
+    }
+}
+
+/// Sends an [`EmoteEvent`] for everything players elsewhere have emoted.
+pub(crate) fn hear_remote_emotes(
+    query: Query<(&RemoteEmotes, Option<&P1Paddle>)>,
+    mut emotes: EventWriter<EmoteEvent>,
+) {
+    for (remote, p1) in query.iter() {
+        let end = if p1.is_some() { Player::P1 } else { Player::P2 };
+        for emote in remote.0.lock().unwrap().drain(..) {
+            emotes.send(EmoteEvent { end, emote });
+        }
+    }
+}
+
+/// Shows each [`EmoteEvent`] as a bubble that floats up from the paddle it
+/// was sent for and fades away.
+///
+/// Bubbles are only for show and never change what the simulation sees.
+pub struct EmotePlugin;
+
+impl Plugin for EmotePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>()
+            .add_event::<EmoteEvent>()
+            .add_system_to_stage(CoreStage::PostUpdate, show_emotes)
+            .add_system_to_stage(CoreStage::PostUpdate, float_bubbles.after(show_emotes));
+    }
+}
+
+#[derive(Component)]
+struct EmoteBubble {
+    paddle: Entity,
+    remaining: f32,
+}
+
+fn show_emotes(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    locale: Res<Locale>,
+    mut emotes: EventReader<EmoteEvent>,
+    p1_query: Query<Entity, With<P1Paddle>>,
+    p2_query: Query<Entity, With<P2Paddle>>,
+) {
+    for event in emotes.iter() {
+        let paddle = match event.end {
+            Player::P1 => p1_query.get_single(),
+            Player::P2 => p2_query.get_single(),
+        };
+        let paddle = match paddle {
+            Ok(paddle) => paddle,
+            Err(_) => continue,
+        };
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::with_section(
+                    locale.text(event.emote.locale_id()),
+                    TextStyle {
+                        font: font.0.clone(),
+                        font_size: BUBBLE_FONT_SIZE,
+                        color: FOREGROUND_COLOR,
+                    },
+                    TextAlignment {
+                        vertical: VerticalAlign::Bottom,
+                        horizontal: HorizontalAlign::Center,
+                    },
+                ),
+                ..default()
+            })
+            .insert(EmoteBubble {
+                paddle,
+                remaining: BUBBLE_SECONDS,
+            });
+    }
+}
+
+/// Keeps each bubble over its paddle as it rises and fades, so a player can
+/// emote on the move.
+fn float_bubbles(
+    mut commands: Commands,
+    time: Res<Time>,
+    paddle_query: Query<&Transform, Without<EmoteBubble>>,
+    mut bubble_query: Query<(Entity, &mut EmoteBubble, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut bubble, mut transform, mut text) in bubble_query.iter_mut() {
+        bubble.remaining -= time.delta_seconds();
+        let paddle = match paddle_query.get(bubble.paddle) {
+            Ok(paddle) if bubble.remaining > 0.0 => paddle,
+            _ => {
+                commands.entity(entity).despawn();
+                continue;
+            }
+        };
+
+        let risen = 1.0 - bubble.remaining / BUBBLE_SECONDS;
+        let above = paddle.scale.y / 2.0 + BUBBLE_GAP + risen * BUBBLE_RISE;
+        transform.translation = paddle.translation.truncate().extend(BUBBLE_Z) + Vec3::Y * above;
+        let alpha = (bubble.remaining / FADE_SECONDS).min(1.0);
+        text.sections[0].style.color.set_a(alpha);
+    }
+}
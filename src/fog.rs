@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+use crate::{Arena, Ball, MatchRules, Serve};
+
+/// Out to this share of the court's width either side of the centre line,
+/// the ball can't be seen at all. That makes the middle third of the court.
+const HIDDEN: f32 = 1.0 / 6.0;
+/// From here out towards the paddles the ball is in full view again.
+const CLEAR: f32 = 1.0 / 3.0;
+/// How quickly the ball fades, per second, so it never pops in or out.
+const FADE_RATE: f32 = 12.0;
+
+/// Hides the ball in the middle of the court under
+/// [`MatchRules::fog_of_war`], so players have to read where it is going
+/// before it disappears.
+///
+/// Fog is only for show, like the camera, and never changes what the
+/// simulation sees.
+pub struct FogPlugin;
+
+impl Plugin for FogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MatchRules>()
+            .init_resource::<Arena>()
+            .init_resource::<Serve>()
+            .add_system_to_stage(CoreStage::PostUpdate, fade_ball);
+    }
+}
+
+/// How much of the ball shows through the fog at `x`, from 0 in the middle
+/// third of the court to 1 near the paddles.
+fn visibility(arena: &Arena, x: f32) -> f32 {
+    let from_centre = (x - (arena.left + arena.right) / 2.0).abs() / arena.width();
+    let t = ((from_centre - HIDDEN) / (CLEAR - HIDDEN)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn fade_ball(
+    time: Res<Time>,
+    rules: Res<MatchRules>,
+    arena: Res<Arena>,
+    serve: Res<Serve>,
+    mut ball_query: Query<(&Transform, &mut Sprite), With<Ball>>,
+) {
+    // The serve fades the ball in by itself.
+    if serve.in_progress() {
+        return;
+    }
+
+    for (transform, mut sprite) in ball_query.iter_mut() {
+        let target = if rules.fog_of_war {
+            visibility(&arena, transform.translation.x)
+        } else {
+            1.0
+        };
+        let alpha = sprite.color.a();
+        if alpha == target {
+            continue;
+        }
+        let step = (FADE_RATE * time.delta_seconds()).min(1.0);
+        let faded = alpha + (target - alpha) * step;
+        // Close enough is all the way, so the sprite settles.
+        let faded = if (target - faded).abs() < 0.01 {
+            target
+        } else {
+            faded
+        };
+        sprite.color.set_a(faded);
+    }
+}
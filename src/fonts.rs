@@ -0,0 +1,67 @@
+use bevy::{asset::LoadState, prelude::*};
+
+const FONT_PATH: &str = "fonts/PressStart2P-Regular.ttf";
+/// Built into the binary, so there is always something to draw text with.
+const FALLBACK_FONT: &[u8] = include_bytes!("../assets/fonts/FiraMono-Medium.ttf");
+
+/// The font all of the game's text is drawn in.
+pub struct UiFont(pub Handle<Font>);
+
+impl FromWorld for UiFont {
+    fn from_world(world: &mut World) -> Self {
+        UiFont(world.resource::<AssetServer>().load(FONT_PATH))
+    }
+}
+
+/// Loads [`UiFont`], and swaps in a font built into the binary if it can't be
+/// loaded, so the scoreboard and menus still show up without the assets
+/// folder. Text already drawn in it follows it when it is changed.
+pub struct FontPlugin;
+
+impl Plugin for FontPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UiFont>()
+            .add_system(fall_back_on_missing_font)
+            .add_system(follow_ui_font);
+    }
+}
+
+fn fall_back_on_missing_font(
+    font: Res<UiFont>,
+    asset_server: Res<AssetServer>,
+    mut fonts: ResMut<Assets<Font>>,
+    mut fell_back: Local<bool>,
+) {
+    if *fell_back || asset_server.get_load_state(&font.0) != LoadState::Failed {
+        return;
+    }
+    *fell_back = true;
+
+    warn!("Could not load {}, using the built-in font", FONT_PATH);
+    match Font::try_from_bytes(FALLBACK_FONT.to_vec()) {
+        // Text already pointing at the missing font picks this up as is.
+        Ok(fallback) => fonts.set_untracked(&font.0, fallback),
+        Err(err) => error!("Built-in font is broken: {}", err),
+    }
+}
+
+fn follow_ui_font(
+    font: Res<UiFont>,
+    mut previous: Local<Option<Handle<Font>>>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !font.is_changed() {
+        return;
+    }
+    let previous = match previous.replace(font.0.clone()) {
+        Some(previous) if previous != font.0 => previous,
+        _ => return,
+    };
+    for mut text in text_query.iter_mut() {
+        for section in text.sections.iter_mut() {
+            if section.style.font == previous {
+                section.style.font = font.0.clone();
+            }
+        }
+    }
+}
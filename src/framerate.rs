@@ -0,0 +1,75 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use bevy::{prelude::*, window::PresentMode};
+
+use crate::settings::Settings;
+
+/// Frame rate to drop to while the game is in the background.
+#[cfg(not(target_arch = "wasm32"))]
+const BACKGROUND_FPS: u32 = 10;
+
+/// Applies [`Settings::video`]'s vsync and frame rate cap, and throttles the
+/// game while its window is unfocused or minimized, so it doesn't keep a
+/// laptop's fans going behind the player's back.
+///
+/// A match pauses itself when the window loses focus, so slowing down never
+/// costs the player a point.
+pub struct FrameLimiterPlugin;
+
+impl Plugin for FrameLimiterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(apply_vsync);
+        // Last, so the wait covers everything else the frame did. The browser
+        // paces frames itself, and the game can't sleep there.
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_system_to_stage(CoreStage::Last, limit_frame_rate);
+    }
+}
+
+fn apply_vsync(settings: Option<Res<Settings>>, mut windows: ResMut<Windows>) {
+    let settings = match settings {
+        Some(settings) if settings.is_changed() => settings,
+        _ => return,
+    };
+    let present_mode = if settings.video.vsync {
+        PresentMode::Fifo
+    } else {
+        PresentMode::Immediate
+    };
+    if let Some(window) = windows.get_primary_mut() {
+        if window.present_mode() != present_mode {
+            window.set_present_mode(present_mode);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn limit_frame_rate(
+    settings: Option<Res<Settings>>,
+    windows: Res<Windows>,
+    mut last_frame: Local<Option<Instant>>,
+) {
+    let video = settings
+        .map(|settings| settings.video.clone())
+        .unwrap_or_default();
+    let away = windows
+        .get_primary()
+        .is_some_and(|window| !window.is_focused() || window.physical_width() == 0);
+    let fps = if away && video.low_power_in_background {
+        Some(BACKGROUND_FPS)
+    } else {
+        video.fps_cap
+    };
+
+    if let (Some(fps), Some(last_frame)) = (fps.filter(|fps| *fps > 0), *last_frame) {
+        let frame = Duration::from_secs_f64(1.0 / fps as f64);
+        if let Some(left) = frame.checked_sub(last_frame.elapsed()) {
+            thread::sleep(left);
+        }
+    }
+    *last_frame = Some(Instant::now());
+}
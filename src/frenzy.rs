@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+
+use crate::{Ball, GameConfig, GoalEvent, HitEvent, Velocity};
+
+/// The ball turns this colour as a frenzy builds.
+const FRENZY_COLOR: Color = Color::rgb(1.0, 0.3, 0.1);
+/// Speed-up given to the ball when a frenzy reaches [`Frenzy::SPEED_BURST`].
+const SPEED_BURST: f32 = 1.25;
+
+/// How wild the current rally has got. Each of
+/// [`GameConfig::frenzy_rallies`] the rally gets past takes the frenzy up a
+/// level, and a goal calms it right back down.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Frenzy {
+    /// Paddle hits since the last goal.
+    pub rally: usize,
+    pub level: usize,
+}
+
+impl Frenzy {
+    /// The ball shifts colour from the first level on.
+    pub const COLOR_SHIFT: usize = 1;
+    /// The ball speeds up as the frenzy reaches the second level.
+    pub const SPEED_BURST: usize = 2;
+    /// From the third level on, the next goal counts double.
+    pub const DOUBLE_POINTS: usize = 3;
+
+    /// What the next goal is worth, as a multiple of a normal goal.
+    pub fn points_multiplier(&self) -> usize {
+        if self.level >= Frenzy::DOUBLE_POINTS {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// `color`, shifted further towards [`FRENZY_COLOR`] the higher the
+    /// frenzy.
+    pub fn tint(&self, color: Color) -> Color {
+        if self.level < Frenzy::COLOR_SHIFT {
+            return color;
+        }
+        let t = (self.level as f32 / Frenzy::DOUBLE_POINTS as f32).min(1.0);
+        let [r, g, b, a] = color.as_rgba_f32();
+        let [fr, fg, fb, _] = FRENZY_COLOR.as_rgba_f32();
+        Color::rgba(r + (fr - r) * t, g + (fg - g) * t, b + (fb - b) * t, a)
+    }
+}
+
+/// A rally got past one of [`GameConfig::frenzy_rallies`].
+pub struct FrenzyEvent {
+    pub level: usize,
+}
+
+/// Counts the rally and whips up a frenzy when it gets long enough. Runs
+/// after the goal is scored, so a goal that counts double still does before
+/// the frenzy is over.
+pub fn whip_up_frenzy(
+    config: Res<GameConfig>,
+    mut frenzy: ResMut<Frenzy>,
+    mut hit_events: EventReader<HitEvent>,
+    mut goal_events: EventReader<GoalEvent>,
+    mut frenzy_events: EventWriter<FrenzyEvent>,
+    mut ball_query: Query<&mut Velocity, With<Ball>>,
+) {
+    if goal_events.iter().count() > 0 {
+        hit_events.iter().for_each(drop);
+        // Only touch the frenzy if there was one, to keep change detection
+        // quiet.
+        if *frenzy != Frenzy::default() {
+            *frenzy = Frenzy::default();
+        }
+        return;
+    }
+
+    for _ in hit_events.iter() {
+        frenzy.rally += 1;
+        let level = config
+            .frenzy_rallies
+            .iter()
+            .filter(|&&rally| frenzy.rally >= rally)
+            .count();
+        if level <= frenzy.level {
+            continue;
+        }
+
+        let burst = frenzy.level < Frenzy::SPEED_BURST && level >= Frenzy::SPEED_BURST;
+        frenzy.level = level;
+        frenzy_events.send(FrenzyEvent { level });
+        if burst {
+            for mut velocity in ball_query.iter_mut() {
+                velocity.0 *= SPEED_BURST;
+            }
+        }
+    }
+}
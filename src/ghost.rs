@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+
+use crate::{GameMode, GoalEvent, MatchRules, P1Paddle};
+
+/// How much of the live paddle's colour shows through the ghost.
+const GHOST_ALPHA: f32 = 0.25;
+/// Just behind the live paddle, so it stays on top where they overlap.
+const GHOST_Z: f32 = -0.1;
+
+/// Where P1's paddle was on each tick of the practice streak so far, and of
+/// the streak before it, which the ghost paddle plays back.
+///
+/// The previous streak outlives the match, so the first streak of a new
+/// practice session races the last one of the session before.
+#[derive(Default)]
+pub struct GhostRecording {
+    pub current: Vec<f32>,
+    pub previous: Vec<f32>,
+}
+
+impl GhostRecording {
+    /// Where the ghost is this far into the current streak, until the
+    /// streak outlasts the one it is replaying.
+    pub fn ghost_y(&self) -> Option<f32> {
+        self.previous.get(self.current.len()).copied()
+    }
+}
+
+/// Records P1's paddle every tick of practice, and keeps the streak just
+/// ended for the ghost when P1 misses.
+pub fn record_ghost(
+    rules: Res<MatchRules>,
+    mut recording: ResMut<GhostRecording>,
+    mut goal_events: EventReader<GoalEvent>,
+    paddle_query: Query<&Transform, With<P1Paddle>>,
+) {
+    if rules.mode != GameMode::Practice {
+        return;
+    }
+
+    if goal_events.iter().count() > 0 {
+        recording.previous = std::mem::take(&mut recording.current);
+        return;
+    }
+    for transform in paddle_query.iter() {
+        recording.current.push(transform.translation.y);
+    }
+}
+
+/// A see-through copy of P1's paddle replaying their previous practice
+/// streak next to the live one, like the ghost car in a racing game.
+///
+/// The ghost is only for show and never changes what the simulation sees.
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MatchRules>()
+            .init_resource::<GhostRecording>()
+            .add_system_to_stage(CoreStage::PostUpdate, follow_recording);
+    }
+}
+
+#[derive(Component)]
+struct Ghost;
+
+#[allow(clippy::type_complexity)]
+fn follow_recording(
+    mut commands: Commands,
+    rules: Res<MatchRules>,
+    recording: Res<GhostRecording>,
+    paddle_query: Query<(&Transform, &Sprite), (With<P1Paddle>, Without<Ghost>)>,
+    mut ghost_query: Query<(&mut Transform, &mut Sprite, &mut Visibility), With<Ghost>>,
+) {
+    let (paddle_transform, paddle_sprite) = match paddle_query.get_single() {
+        Ok(paddle) => paddle,
+        Err(_) => return,
+    };
+    let (mut transform, mut sprite, mut visibility) = match ghost_query.get_single_mut() {
+        Ok(ghost) => ghost,
+        Err(_) => {
+            commands.spawn_bundle(SpriteBundle::default()).insert(Ghost);
+            return;
+        }
+    };
+
+    let ghost_y = recording
+        .ghost_y()
+        .filter(|_| rules.mode == GameMode::Practice);
+    visibility.is_visible = ghost_y.is_some();
+    if let Some(y) = ghost_y {
+        transform.translation = paddle_transform.translation;
+        transform.translation.y = y;
+        transform.translation.z += GHOST_Z;
+        transform.scale = paddle_transform.scale;
+        // In the paddle's own colour, whatever the theme.
+        sprite.color = paddle_sprite.color;
+        sprite.color.set_a(GHOST_ALPHA);
+    }
+}
@@ -0,0 +1,117 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{Arena, ArenaLayout, MatchRules, P1Goal, P2Goal, Player, SimTick, TickRate};
+
+/// Height of each goal under [`MatchRules::moving_goals`].
+const MOVING_GOAL_HEIGHT: f32 = 160.0;
+/// Seconds for a moving goal to sweep to one end of its wall, the other, and
+/// back to the middle.
+const SWEEP_SECONDS: f32 = 6.0;
+
+/// Wall above or below a goal, filling in the rest of that end of the court.
+#[derive(Component)]
+pub(crate) struct GoalPost {
+    pub(crate) end: Player,
+    pub(crate) above: bool,
+}
+
+/// The opening in one end of the court. Balls that reach it score, and
+/// balls that reach the [`GoalPost`]s either side of it bounce back.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct GoalMouth {
+    pub(crate) y: f32,
+    pub(crate) height: f32,
+    /// Height of the whole end, goal and posts together.
+    end_height: f32,
+}
+
+impl GoalMouth {
+    /// The goal the layout asks for, centred on its end.
+    pub(crate) fn fixed(layout: &ArenaLayout, arena: &Arena) -> GoalMouth {
+        let end_height = arena.height() + arena.wall_thickness;
+        GoalMouth {
+            y: 0.0,
+            height: layout
+                .goal_height
+                .map_or(end_height, |height| height.min(end_height)),
+            end_height,
+        }
+    }
+
+    /// Where `end`'s goal is on `tick`. Moving goals sweep up and down
+    /// their whole end, mirrored so that one is high when the other is low.
+    fn at(
+        rules: &MatchRules,
+        layout: &ArenaLayout,
+        arena: &Arena,
+        tick: u64,
+        tick_rate: TickRate,
+        end: Player,
+    ) -> GoalMouth {
+        let mut mouth = GoalMouth::fixed(layout, arena);
+        if !rules.moving_goals {
+            return mouth;
+        }
+
+        mouth.height = mouth.height.min(MOVING_GOAL_HEIGHT);
+        let travel = (mouth.end_height - mouth.height) / 2.0;
+        let sweep_ticks = u64::from(tick_rate.ticks(SWEEP_SECONDS));
+        let phase = (tick % sweep_ticks) as f32 / sweep_ticks as f32;
+        mouth.y = travel * (phase * TAU).sin();
+        if end == Player::P2 {
+            mouth.y = -mouth.y;
+        }
+        mouth
+    }
+
+    /// Centre and height of the post above or below the goal. Posts are
+    /// flat when the goal reaches that end of the wall.
+    pub(crate) fn post(&self, above: bool) -> (f32, f32) {
+        let half_end = self.end_height / 2.0;
+        let (from, to) = if above {
+            (self.y + self.height / 2.0, half_end)
+        } else {
+            (-half_end, self.y - self.height / 2.0)
+        };
+        ((from + to) / 2.0, (to - from).max(0.0))
+    }
+}
+
+type IsGoal = Or<(With<P1Goal>, With<P2Goal>)>;
+
+/// Slides the goals along their ends, carrying the posts either side with
+/// them, and puts them back in the middle when the rule is turned off.
+#[allow(clippy::type_complexity)]
+pub fn sweep_goals(
+    rules: Res<MatchRules>,
+    layout: Res<ArenaLayout>,
+    arena: Res<Arena>,
+    tick: Res<SimTick>,
+    tick_rate: Res<TickRate>,
+    mut ends: ParamSet<(
+        Query<(&mut Transform, Option<&P1Goal>), IsGoal>,
+        Query<(&mut Transform, &GoalPost)>,
+    )>,
+) {
+    let mouth = |end| GoalMouth::at(&rules, &layout, &arena, tick.0, *tick_rate, end);
+
+    for (mut transform, p1) in ends.p0().iter_mut() {
+        let end = if p1.is_some() { Player::P1 } else { Player::P2 };
+        let mouth = mouth(end);
+        place(&mut transform, mouth.y, mouth.height);
+    }
+    for (mut transform, post) in ends.p1().iter_mut() {
+        let (y, height) = mouth(post.end).post(post.above);
+        place(&mut transform, y, height);
+    }
+}
+
+/// Only touches pieces that actually moved, to keep change detection quiet.
+fn place(transform: &mut Mut<Transform>, y: f32, height: f32) {
+    if transform.translation.y != y || transform.scale.y != height {
+        transform.translation.y = y;
+        transform.scale.y = height;
+    }
+}
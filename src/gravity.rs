@@ -0,0 +1,34 @@
+use bevy::prelude::*;
+
+use crate::{ArenaLayout, Ball, Catch, Serve, TickRate, Velocity};
+
+/// Pulls the ball towards the arena's [`GravityWell`](crate::GravityWell),
+/// hardest at its centre and not at all from `radius` out, so shots that
+/// pass close by curve round it.
+pub fn pull_ball(
+    layout: Res<ArenaLayout>,
+    tick_rate: Res<TickRate>,
+    serve: Res<Serve>,
+    catch: Res<Catch>,
+    mut ball_query: Query<(&Transform, &mut Velocity), With<Ball>>,
+) {
+    let well = match &layout.gravity_well {
+        Some(well) => well,
+        None => return,
+    };
+    // The ball glides back to the centre between points, and sits still in
+    // a paddle that caught it, whatever the well says.
+    if serve.in_progress() || catch.holding() {
+        return;
+    }
+
+    for (transform, mut velocity) in ball_query.iter_mut() {
+        let towards = well.position - transform.translation.truncate();
+        let distance = towards.length();
+        if distance >= well.radius {
+            continue;
+        }
+        let pull = well.strength * (1.0 - distance / well.radius);
+        velocity.0 += towards.normalize_or_zero() * pull * tick_rate.step();
+    }
+}
@@ -0,0 +1,221 @@
+use bevy::{
+    ecs::event::{Events, ManualEventReader},
+    prelude::*,
+};
+
+use crate::{
+    ai::{BallView, PaddleView},
+    input::push_paddle,
+    Arena, Ball, GameConfig, GoalEvent, HitEvent, MatchOverEvent, MatchRules, P1Paddle, Player,
+    SimulationLabel, SimulationPlugin, SimulationStage, TickRate, Velocity,
+};
+
+/// Reward for winning a point; losing one costs as much.
+const POINT_REWARD: f32 = 1.0;
+/// Reward for getting a paddle on the ball, so agents learn to return it
+/// before they learn to win points.
+const RETURN_REWARD: f32 = 0.1;
+
+/// The game as a reinforcement-learning environment, in the style of Gym.
+///
+/// The agent plays P1 against the usual AI on a headless simulation. Each
+/// [`step`](FjongEnv::step) plays one tick and reports how it went; an
+/// episode is one match.
+///
+/// ```no_run
+/// # use fjong::FjongEnv;
+/// let mut env = FjongEnv::new(7);
+/// let mut observation = env.reset();
+/// loop {
+///     let action = (observation.ball_position.y - observation.paddle_y).signum();
+///     let step = env.step(action);
+///     observation = step.observation;
+///     if step.done {
+///         break;
+///     }
+/// }
+/// ```
+pub struct FjongEnv {
+    app: App,
+    seed: u64,
+    rules: MatchRules,
+    goals: ManualEventReader<GoalEvent>,
+    hits: ManualEventReader<HitEvent>,
+    match_over: ManualEventReader<MatchOverEvent>,
+}
+
+/// What happened on one [`FjongEnv::step`].
+pub struct Step {
+    pub observation: Observation,
+    pub reward: f32,
+    /// The match is over, and the environment needs a
+    /// [`reset`](FjongEnv::reset).
+    pub done: bool,
+}
+
+/// The agent's paddle speed for the next tick, from -1 (full speed down) to 1
+/// (full speed up).
+struct AgentAction(f32);
+
+impl FjongEnv {
+    /// An environment whose first episode plays out from `seed`.
+    pub fn new(seed: u64) -> FjongEnv {
+        FjongEnv::with_rules(seed, MatchRules::default())
+    }
+
+    /// An environment playing matches by `rules`, such as shorter ones or
+    /// against a different level of AI.
+    pub fn with_rules(seed: u64, rules: MatchRules) -> FjongEnv {
+        let mut env = FjongEnv {
+            app: App::new(),
+            seed,
+            rules,
+            goals: default(),
+            hits: default(),
+            match_over: default(),
+        };
+        env.reset();
+        env
+    }
+
+    /// Starts a new match, with the next seed along, and returns the first
+    /// observation.
+    pub fn reset(&mut self) -> Observation {
+        self.app = App::new();
+        self.app
+            .add_plugins(MinimalPlugins)
+            .add_plugin(SimulationPlugin::stepped(self.seed))
+            .insert_resource(self.rules.clone())
+            .insert_resource(AgentAction(0.0))
+            .add_system_to_stage(
+                SimulationStage,
+                drive_agent
+                    .label(SimulationLabel::Movement)
+                    .before(SimulationLabel::Collisions),
+            );
+        self.seed = self.seed.wrapping_add(1);
+        self.goals = default();
+        self.hits = default();
+        self.match_over = default();
+
+        self.app.update();
+        self.observe()
+    }
+
+    /// Plays one tick with the paddle pushed at `action`, from -1 to 1.
+    pub fn step(&mut self, action: f32) -> Step {
+        self.app.world.resource_mut::<AgentAction>().0 = action.clamp(-1.0, 1.0);
+        self.app.update();
+
+        let world = &self.app.world;
+        let mut reward = 0.0;
+        for goal in self.goals.iter(world.resource::<Events<GoalEvent>>()) {
+            reward += match goal.scorer {
+                Player::P1 => POINT_REWARD,
+                Player::P2 => -POINT_REWARD,
+            };
+        }
+        for hit in self.hits.iter(world.resource::<Events<HitEvent>>()) {
+            if hit.player == Player::P1 {
+                reward += RETURN_REWARD;
+            }
+        }
+        let done = self
+            .match_over
+            .iter(world.resource::<Events<MatchOverEvent>>())
+            .count()
+            > 0;
+
+        Step {
+            observation: self.observe(),
+            reward,
+            done,
+        }
+    }
+
+    /// What the agent can see right now.
+    pub fn observe(&mut self) -> Observation {
+        let world = &mut self.app.world;
+        let ball = world
+            .query_filtered::<(&Transform, &Velocity), With<Ball>>()
+            .iter(world)
+            .map(|(transform, velocity)| BallView {
+                position: transform.translation.truncate(),
+                velocity: velocity.0,
+            })
+            .next()
+            .unwrap();
+        let paddle = world
+            .query_filtered::<(&Transform, &Velocity), With<P1Paddle>>()
+            .iter(world)
+            .map(|(transform, velocity)| PaddleView {
+                position: transform.translation.truncate(),
+                velocity: velocity.0,
+                size: transform.scale.truncate(),
+            })
+            .next()
+            .unwrap();
+
+        Observation::new(&ball, &paddle, world.resource::<Arena>())
+    }
+}
+
+fn drive_agent(
+    action: Res<AgentAction>,
+    config: Res<GameConfig>,
+    tick_rate: Res<TickRate>,
+    mut query: Query<&mut Velocity, With<P1Paddle>>,
+) {
+    for mut velocity in query.iter_mut() {
+        push_paddle(action.0, &config, &tick_rate, &mut velocity);
+    }
+}
+
+/// The court as a paddle sees it, scaled so the arena spans -1 to 1 both
+/// ways and turned around so the paddle is always on the left.
+///
+/// Positions are in arena half-widths and half-heights, and velocities in
+/// the same per second. The same observation works for either side, so an
+/// agent trained as P1 can play as P2.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Observation {
+    pub ball_position: Vec2,
+    pub ball_velocity: Vec2,
+    pub paddle_y: f32,
+    pub paddle_velocity: f32,
+}
+
+impl Observation {
+    /// Length of [`Observation::to_array`].
+    pub const SIZE: usize = 6;
+
+    pub fn new(ball: &BallView, paddle: &PaddleView, arena: &Arena) -> Observation {
+        let centre = Vec2::new(
+            (arena.left + arena.right) / 2.0,
+            (arena.bottom + arena.top) / 2.0,
+        );
+        let mut scale = 2.0 / Vec2::new(arena.width(), arena.height());
+        if paddle.position.x > centre.x {
+            scale.x = -scale.x;
+        }
+
+        Observation {
+            ball_position: (ball.position - centre) * scale,
+            ball_velocity: ball.velocity * scale,
+            paddle_y: (paddle.position.y - centre.y) * scale.y,
+            paddle_velocity: paddle.velocity.y * scale.y,
+        }
+    }
+
+    /// The observation as a flat list of numbers, for feeding to a network.
+    pub fn to_array(&self) -> [f32; Observation::SIZE] {
+        [
+            self.ball_position.x,
+            self.ball_position.y,
+            self.ball_velocity.x,
+            self.ball_velocity.y,
+            self.paddle_y,
+            self.paddle_velocity,
+        ]
+    }
+}
@@ -0,0 +1,677 @@
+use std::{cmp::Ordering, f32::consts::TAU};
+
+use bevy::prelude::*;
+use fluent::fluent_args;
+
+use crate::{
+    ai::Brain,
+    fonts::UiFont,
+    input::{key_label, MyGamepad},
+    match_in_play,
+    settings::{AccessibilitySettings, HudLayout, Settings},
+    widget::{Menu, UNSELECTED_COLOR},
+    AppState, Frenzy, GameEntity, GameMode, GameRng, KeyBindings, Locale, MatchClock, MatchRules,
+    NetStats, P1Paddle, P2Paddle, Player, PracticeScore, Scoreboard, Series, Serve, SpectatorCount,
+    TickRate, FOREGROUND_COLOR,
+};
+
+const SCOREBOARD_FONT_SIZE: f32 = 32.0;
+const SCOREBOARD_TEXT_PADDING: f32 = 15.0;
+/// How far apart the scores are in the centred layout.
+const CENTRED_SCORE_GAP: f32 = 30.0;
+const SEED_FONT_SIZE: f32 = 12.0;
+const BANNER_FONT_SIZE: f32 = 20.0;
+/// Scores a goal from winning the game, or the series, are tinted this.
+const POINT_COLOR: Color = Color::rgb(1.0, 0.8, 0.2);
+/// Beats per second of the scores' pulse at deuce.
+const DEUCE_PULSE_RATE: f32 = 1.5;
+/// How much bigger the scores get at the top of each beat.
+const DEUCE_PULSE_SIZE: f32 = 0.15;
+/// The connection readout sits just above the spectator count.
+const NET_STATS_BOTTOM: f32 = 35.0;
+/// The UI scale setting is kept between these.
+const MIN_UI_SCALE: f32 = 0.75;
+const MAX_UI_SCALE: f32 = 2.0;
+const NET_WARNING_COLOR: Color = Color::rgb(1.0, 0.4, 0.3);
+/// Control hints sit above the connection warning.
+const HINTS_BOTTOM: f32 = 35.0;
+
+/// The UI camera, the score readout, the time-attack clock, deuce or frenzy
+/// call, a banner for game and match point, the match seed, how many are
+/// watching, hints at the controls while serving and in menus and, when the
+/// match is on another machine, how the connection to it is doing. Laid out
+/// as the [`HudLayout`] setting says, at the UI scale.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AccessibilitySettings>()
+            .init_resource::<Settings>()
+            .init_resource::<Locale>()
+            .init_resource::<Series>()
+            .init_resource::<Frenzy>()
+            .init_resource::<SpectatorCount>()
+            .init_resource::<NetStats>()
+            .init_resource::<KeyBindings>()
+            .add_startup_system(setup)
+            .add_system(respawn_hud)
+            .add_system(layout_hud)
+            .add_system(update_scoreboard)
+            .add_system(pulse_scores)
+            .add_system(update_status)
+            .add_system(call_points)
+            .add_system(update_seed)
+            .add_system(update_spectators)
+            .add_system(update_net_stats)
+            .add_system(show_hints);
+    }
+}
+
+#[derive(Component)]
+struct P1GoalText;
+
+#[derive(Component)]
+struct P2GoalText;
+
+type ScoreText = Or<(With<P1GoalText>, With<P2GoalText>)>;
+
+/// The clock in time attack, deuce or advantage in a versus match, and any
+/// fjong frenzy.
+#[derive(Component)]
+struct StatusText;
+
+/// Game point or match point, for as long as it is.
+#[derive(Component)]
+struct PointBanner;
+
+#[derive(Component)]
+struct SeedText;
+
+#[derive(Component)]
+struct SpectatorText;
+
+#[derive(Component)]
+struct NetStatsText;
+
+#[derive(Component)]
+struct NetWarningText;
+
+#[derive(Component)]
+struct HintText;
+
+fn setup(mut commands: Commands, font: Res<UiFont>) {
+    commands.spawn_bundle(UiCameraBundle::default());
+    spawn_hud(&mut commands, &font);
+}
+
+/// Puts the HUD back after [`tear_down_match`](crate::tear_down_match) has
+/// cleared it away with the rest of the last match.
+fn respawn_hud(mut commands: Commands, font: Res<UiFont>, hud_query: Query<(), With<HudElement>>) {
+    if hud_query.is_empty() {
+        spawn_hud(&mut commands, &font);
+    }
+}
+
+fn spawn_hud(commands: &mut Commands, font: &UiFont) {
+    let text = |font_size, color| {
+        let style = TextStyle {
+            font: font.0.clone(),
+            font_size,
+            color,
+        };
+        Text::with_section("", style, default())
+    };
+    commands
+        .spawn_bundle(TextBundle {
+            text: text(SCOREBOARD_FONT_SIZE, FOREGROUND_COLOR),
+            ..default()
+        })
+        .insert_bundle((HudElement::P1Score, P1GoalText, GameEntity));
+    commands
+        .spawn_bundle(TextBundle {
+            text: text(SCOREBOARD_FONT_SIZE, FOREGROUND_COLOR),
+            ..default()
+        })
+        .insert_bundle((HudElement::P2Score, P2GoalText, GameEntity));
+
+    // Small and out of the way, but there to note down after a good match.
+    commands
+        .spawn_bundle(TextBundle {
+            text: text(SEED_FONT_SIZE, UNSELECTED_COLOR),
+            ..default()
+        })
+        .insert_bundle((HudElement::Seed, SeedText, GameEntity));
+    commands
+        .spawn_bundle(TextBundle {
+            text: text(SEED_FONT_SIZE, UNSELECTED_COLOR),
+            ..default()
+        })
+        .insert_bundle((HudElement::Spectators, SpectatorText, GameEntity));
+    commands
+        .spawn_bundle(TextBundle {
+            text: text(SEED_FONT_SIZE, UNSELECTED_COLOR),
+            ..default()
+        })
+        .insert_bundle((HudElement::NetStats, NetStatsText, GameEntity));
+
+    // The clock and calls, centred across the top.
+    commands
+        .spawn_bundle(NodeBundle {
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert_bundle((HudElement::Status, GameEntity))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: text(SCOREBOARD_FONT_SIZE, FOREGROUND_COLOR),
+                    ..default()
+                })
+                .insert(StatusText);
+        });
+    commands
+        .spawn_bundle(NodeBundle {
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert_bundle((HudElement::PointBanner, GameEntity))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: text(BANNER_FONT_SIZE, POINT_COLOR),
+                    ..default()
+                })
+                .insert(PointBanner);
+        });
+
+    // Across the bottom, where it can't be missed but keeps off the court's
+    // middle.
+    commands
+        .spawn_bundle(NodeBundle {
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert_bundle((HudElement::NetWarning, GameEntity))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: text(SEED_FONT_SIZE, NET_WARNING_COLOR),
+                    ..default()
+                })
+                .insert(NetWarningText);
+        });
+    commands
+        .spawn_bundle(NodeBundle {
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert_bundle((HudElement::Hints, GameEntity))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: text(SEED_FONT_SIZE, UNSELECTED_COLOR),
+                    ..default()
+                })
+                .insert(HintText);
+        });
+}
+
+/// Where a part of the HUD goes, laid out by [`hud_style`].
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+enum HudElement {
+    P1Score,
+    P2Score,
+    /// A row the full width of the screen, centring the status text.
+    Status,
+    /// A row the full width of the screen, under the status text.
+    PointBanner,
+    Seed,
+    Spectators,
+    NetStats,
+    /// A row the full width of the screen, centring the warning.
+    NetWarning,
+    /// A row the full width of the screen, centring the control hints.
+    Hints,
+}
+
+/// Places `element` for the layout, with its spacing grown or shrunk to
+/// the UI scale.
+fn hud_style(element: HudElement, layout: HudLayout, scale: f32) -> Style {
+    let padding = Val::Px(SCOREBOARD_TEXT_PADDING * scale);
+    let absolute = |position| Style {
+        position_type: PositionType::Absolute,
+        position,
+        ..default()
+    };
+    let row = |position| Style {
+        size: Size::new(Val::Percent(100.0), Val::Auto),
+        justify_content: JustifyContent::Center,
+        ..absolute(position)
+    };
+    match (element, layout) {
+        (HudElement::P1Score, HudLayout::Corners) => absolute(Rect {
+            top: padding,
+            left: padding,
+            ..default()
+        }),
+        (HudElement::P2Score, HudLayout::Corners) => Style {
+            align_self: AlignSelf::FlexEnd,
+            ..absolute(Rect {
+                top: padding,
+                right: padding,
+                ..default()
+            })
+        },
+        // Either side of the centre line, a gap apart.
+        (HudElement::P1Score, HudLayout::Centred) => Style {
+            margin: Rect {
+                right: Val::Px(CENTRED_SCORE_GAP * scale),
+                ..default()
+            },
+            ..absolute(Rect {
+                top: padding,
+                right: Val::Percent(50.0),
+                ..default()
+            })
+        },
+        (HudElement::P2Score, HudLayout::Centred) => Style {
+            margin: Rect {
+                left: Val::Px(CENTRED_SCORE_GAP * scale),
+                ..default()
+            },
+            ..absolute(Rect {
+                top: padding,
+                left: Val::Percent(50.0),
+                ..default()
+            })
+        },
+        (HudElement::Status, _) => row(Rect {
+            top: Val::Px(status_top(layout) * scale),
+            ..default()
+        }),
+        (HudElement::PointBanner, _) => row(Rect {
+            top: Val::Px(
+                (status_top(layout) + SCOREBOARD_FONT_SIZE + SCOREBOARD_TEXT_PADDING) * scale,
+            ),
+            ..default()
+        }),
+        (HudElement::Seed, _) => absolute(Rect {
+            bottom: padding,
+            left: padding,
+            ..default()
+        }),
+        (HudElement::Spectators, _) => absolute(Rect {
+            bottom: padding,
+            right: padding,
+            ..default()
+        }),
+        (HudElement::NetStats, _) => absolute(Rect {
+            bottom: Val::Px(NET_STATS_BOTTOM * scale),
+            right: padding,
+            ..default()
+        }),
+        (HudElement::NetWarning, _) => row(Rect {
+            bottom: padding,
+            ..default()
+        }),
+        (HudElement::Hints, _) => row(Rect {
+            bottom: Val::Px(HINTS_BOTTOM * scale),
+            ..default()
+        }),
+    }
+}
+
+/// How far down the status text is, before the UI scale.
+fn status_top(layout: HudLayout) -> f32 {
+    match layout {
+        HudLayout::Corners => SCOREBOARD_TEXT_PADDING,
+        // Below the scores, which have the middle of the top to themselves.
+        HudLayout::Centred => 2.0 * SCOREBOARD_TEXT_PADDING + SCOREBOARD_FONT_SIZE,
+    }
+}
+
+type SmallText = Or<(
+    With<SeedText>,
+    With<SpectatorText>,
+    With<NetStatsText>,
+    With<NetWarningText>,
+    With<HintText>,
+)>;
+
+/// Lays the HUD out again, and resizes its text, when the layout or UI scale
+/// is changed. The scores are sized by [`pulse_scores`].
+#[allow(clippy::type_complexity)]
+fn layout_hud(
+    settings: Res<Settings>,
+    mut element_query: Query<(&HudElement, &mut Style)>,
+    mut text_query: ParamSet<(
+        Query<&mut Text, With<StatusText>>,
+        Query<&mut Text, SmallText>,
+        Query<&mut Text, With<PointBanner>>,
+    )>,
+    new_elements: Query<(), Added<HudElement>>,
+) {
+    if !settings.is_changed() && new_elements.is_empty() {
+        return;
+    }
+    let (layout, scale) = (settings.video.hud_layout, ui_scale(&settings));
+    for (element, mut style) in element_query.iter_mut() {
+        let laid_out = hud_style(*element, layout, scale);
+        // Only touch the style when it changes, as that lays the UI out
+        // again.
+        if *style != laid_out {
+            *style = laid_out;
+        }
+    }
+    for mut text in text_query.p0().iter_mut() {
+        set_font_size(&mut text, SCOREBOARD_FONT_SIZE * scale);
+    }
+    for mut text in text_query.p1().iter_mut() {
+        set_font_size(&mut text, SEED_FONT_SIZE * scale);
+    }
+    for mut text in text_query.p2().iter_mut() {
+        set_font_size(&mut text, BANNER_FONT_SIZE * scale);
+    }
+}
+
+fn ui_scale(settings: &Settings) -> f32 {
+    settings.video.ui_scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE)
+}
+
+fn set_font_size(text: &mut Mut<Text>, font_size: f32) {
+    if text.sections[0].style.font_size != font_size {
+        text.sections[0].style.font_size = font_size;
+    }
+}
+
+/// Scores are labelled with whoever is playing at that end.
+fn score_label(series: &Series, end: Player) -> &'static str {
+    match series.player_on(end) {
+        Player::P1 => "hud-p1",
+        Player::P2 => "hud-p2",
+    }
+}
+
+/// Writes out both scores, or the returns and best in practice. Only when
+/// one of them changes, as it lays the text out again.
+fn update_scoreboard(
+    locale: Res<Locale>,
+    rules: Res<MatchRules>,
+    series: Res<Series>,
+    scoreboard: Res<Scoreboard>,
+    practice_score: Res<PracticeScore>,
+    mut query: Query<(&mut Text, Option<&P1GoalText>), ScoreText>,
+) {
+    let changed = locale.is_changed()
+        || rules.is_changed()
+        || series.is_changed()
+        || scoreboard.is_changed()
+        || practice_score.is_changed();
+    if !changed {
+        return;
+    }
+
+    for (mut text, p1) in query.iter_mut() {
+        let end = if p1.is_some() { Player::P1 } else { Player::P2 };
+        let (id, score) = match (rules.mode, end) {
+            (GameMode::Practice, Player::P1) => ("hud-returns", practice_score.returns),
+            (GameMode::Practice, Player::P2) => ("hud-best", practice_score.best),
+            (_, Player::P1) => (score_label(&series, end), scoreboard.p1_score),
+            (_, Player::P2) => (score_label(&series, end), scoreboard.p2_score),
+        };
+        text.sections[0].value = locale.text_with(id, &fluent_args!["score" => score]);
+    }
+}
+
+/// Scores throb while the match is at deuce, unless
+/// [`AccessibilitySettings::reduce_motion`] is on.
+fn pulse_scores(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    accessibility: Res<AccessibilitySettings>,
+    rules: Res<MatchRules>,
+    scoreboard: Res<Scoreboard>,
+    mut query: Query<&mut Text, ScoreText>,
+) {
+    let pulse = if rules.deuce(&scoreboard) && !accessibility.reduce_motion {
+        let beat = (time.seconds_since_startup() as f32 * DEUCE_PULSE_RATE * TAU).sin();
+        1.0 + DEUCE_PULSE_SIZE * (0.5 + 0.5 * beat)
+    } else {
+        1.0
+    };
+    let font_size = SCOREBOARD_FONT_SIZE * ui_scale(&settings) * pulse;
+    for mut text in query.iter_mut() {
+        // Left alone when steady, so the text isn't laid out again every
+        // frame.
+        set_font_size(&mut text, font_size);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_status(
+    locale: Res<Locale>,
+    rules: Res<MatchRules>,
+    series: Res<Series>,
+    clock: Res<MatchClock>,
+    tick_rate: Res<TickRate>,
+    scoreboard: Res<Scoreboard>,
+    frenzy: Res<Frenzy>,
+    mut query: Query<&mut Text, With<StatusText>>,
+) {
+    let mut text = query.single_mut();
+    let advantage = |end| {
+        let player = format!("{:?}", series.player_on(end));
+        locale.text_with("hud-advantage", &fluent_args!["player" => player])
+    };
+    text.sections[0].value = match rules.mode {
+        GameMode::TimeAttack if clock.overtime => locale.text("hud-overtime"),
+        GameMode::TimeAttack => {
+            // Rounded up, so the clock reads 0:00 only once time is up.
+            let seconds = clock.seconds_left(*tick_rate).ceil() as u32;
+            format!("{}:{:02}", seconds / 60, seconds % 60)
+        }
+        _ if rules.deuce(&scoreboard) => match scoreboard.p1_score.cmp(&scoreboard.p2_score) {
+            Ordering::Greater => advantage(Player::P1),
+            Ordering::Less => advantage(Player::P2),
+            Ordering::Equal => locale.text("hud-deuce"),
+        },
+        _ if frenzy.level >= Frenzy::DOUBLE_POINTS => locale.text("hud-double-points"),
+        _ if frenzy.level >= Frenzy::COLOR_SHIFT => locale.text("hud-frenzy"),
+        _ => String::new(),
+    };
+}
+
+/// Calls game point or match point for whoever is a goal from it, and tints
+/// their score.
+#[allow(clippy::type_complexity)]
+fn call_points(
+    locale: Res<Locale>,
+    accessibility: Res<AccessibilitySettings>,
+    rules: Res<MatchRules>,
+    series: Res<Series>,
+    scoreboard: Res<Scoreboard>,
+    mut text_query: ParamSet<(
+        Query<&mut Text, With<PointBanner>>,
+        Query<&mut Text, With<P1GoalText>>,
+        Query<&mut Text, With<P2GoalText>>,
+    )>,
+) {
+    let call = |end| {
+        let id = if series.match_point(&rules, &scoreboard, end) {
+            "hud-match-point"
+        } else if rules.game_point_for(&scoreboard, end) {
+            "hud-game-point"
+        } else {
+            return None;
+        };
+        let player = format!("{:?}", series.player_on(end));
+        Some(locale.text_with(id, &fluent_args!["player" => player]))
+    };
+    let calls = [call(Player::P1), call(Player::P2)];
+    let banner = calls
+        .iter()
+        .flatten()
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("  ");
+    for mut text in text_query.p0().iter_mut() {
+        // Only touch the text when it changes, so it isn't laid out again
+        // every frame.
+        if text.sections[0].value != banner {
+            text.sections[0].value = banner.clone();
+        }
+    }
+
+    let color = |call: &Option<String>| {
+        accessibility.cap(if call.is_some() {
+            POINT_COLOR
+        } else {
+            FOREGROUND_COLOR
+        })
+    };
+    let colors = [color(&calls[0]), color(&calls[1])];
+    for mut text in text_query.p1().iter_mut() {
+        set_color(&mut text, colors[0]);
+    }
+    for mut text in text_query.p2().iter_mut() {
+        set_color(&mut text, colors[1]);
+    }
+}
+
+fn set_color(text: &mut Mut<Text>, color: Color) {
+    if text.sections[0].style.color != color {
+        text.sections[0].style.color = color;
+    }
+}
+
+fn update_seed(
+    locale: Res<Locale>,
+    rng: Res<GameRng>,
+    mut query: Query<&mut Text, With<SeedText>>,
+) {
+    if !locale.is_changed() && !rng.is_changed() {
+        return;
+    }
+    // Too big a number for Fluent to show exactly.
+    let seed = rng.seed().to_string();
+    for mut text in query.iter_mut() {
+        text.sections[0].value =
+            locale.text_with("hud-seed", &fluent_args!["seed" => seed.clone()]);
+    }
+}
+
+fn update_spectators(
+    locale: Res<Locale>,
+    count: Res<SpectatorCount>,
+    mut query: Query<&mut Text, With<SpectatorText>>,
+) {
+    if !locale.is_changed() && !count.is_changed() {
+        return;
+    }
+    for mut text in query.iter_mut() {
+        text.sections[0].value = if count.0 > 0 {
+            locale.text_with("hud-spectators", &fluent_args!["count" => count.0])
+        } else {
+            String::new()
+        };
+    }
+}
+
+fn update_net_stats(
+    locale: Res<Locale>,
+    stats: Res<NetStats>,
+    mut stats_query: Query<&mut Text, (With<NetStatsText>, Without<NetWarningText>)>,
+    mut warning_query: Query<&mut Text, With<NetWarningText>>,
+) {
+    if !locale.is_changed() && !stats.is_changed() {
+        return;
+    }
+    // Nothing to show when the match is being played here.
+    let watching = *stats != NetStats::default();
+    for mut text in stats_query.iter_mut() {
+        text.sections[0].value = if watching {
+            let ping = stats
+                .ping_ms
+                .map_or_else(|| "-".to_string(), |ping| format!("{:.0}", ping));
+            let args = fluent_args![
+                "ping" => ping,
+                "delay" => format!("{:.1}", stats.delay_ticks),
+                "loss" => format!("{:.0}", stats.loss * 100.0)
+            ];
+            locale.text_with("hud-net-stats", &args)
+        } else {
+            String::new()
+        };
+    }
+    for mut text in warning_query.iter_mut() {
+        text.sections[0].value = if watching && stats.poor() {
+            locale.text("hud-net-poor")
+        } else {
+            String::new()
+        };
+    }
+}
+
+type HumanPaddle = (Or<(With<P1Paddle>, With<P2Paddle>)>, Without<Brain>);
+
+/// Tells whoever is about to play how to move while the ball waits to be
+/// served, and how to get around a menu while one is up, with the keys they
+/// have bound or, once P1 has a gamepad, its stick and buttons.
+#[allow(clippy::too_many_arguments)]
+fn show_hints(
+    state: Option<Res<State<AppState>>>,
+    locale: Res<Locale>,
+    serve: Res<Serve>,
+    series: Res<Series>,
+    bindings: Res<KeyBindings>,
+    gamepad: Option<Res<MyGamepad>>,
+    paddle_query: Query<Option<&P1Paddle>, HumanPaddle>,
+    menu_query: Query<(), With<Menu>>,
+    mut query: Query<&mut Text, With<HintText>>,
+) {
+    let hints = if serve.in_progress() && match_in_play(state) {
+        let mut players: Vec<Player> = paddle_query
+            .iter()
+            .map(|p1| {
+                let end = if p1.is_some() { Player::P1 } else { Player::P2 };
+                series.player_on(end)
+            })
+            .collect();
+        players.sort_by_key(|player| *player == Player::P2);
+        let hints: Vec<String> = players
+            .into_iter()
+            .map(|player| {
+                let name = match player {
+                    Player::P1 => "P1",
+                    Player::P2 => "P2",
+                };
+                // The gamepad is always P1's.
+                if player == Player::P1 && gamepad.is_some() {
+                    return locale.text_with("hint-move-stick", &fluent_args!["player" => name]);
+                }
+                let keys = bindings.keys(player);
+                let args = fluent_args![
+                    "player" => name,
+                    "up" => key_label(keys.up),
+                    "down" => key_label(keys.down)
+                ];
+                locale.text_with("hint-move", &args)
+            })
+            .collect();
+        hints.join("    ")
+    } else if !menu_query.is_empty() {
+        match gamepad {
+            Some(_) => locale.text("hint-menu-pad"),
+            None => locale.text("hint-menu"),
+        }
+    } else {
+        String::new()
+    };
+    for mut text in query.iter_mut() {
+        // Only touch the text when it changes, so it isn't laid out again
+        // every frame.
+        if text.sections[0].value != hints {
+            text.sections[0].value = hints.clone();
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+
+use crate::{
+    fonts::UiFont, match_in_play, settings::AccessibilitySettings, AppState, Arena, GoalEvent,
+    HitEvent, P1Paddle, P2Paddle, Player, Serve, FOREGROUND_COLOR,
+};
+
+const ARROW_FONT_SIZE: f32 = 24.0;
+/// How far up from the bottom wall the serve arrow sits.
+const ARROW_INSET: f32 = 40.0;
+/// Size of the marker over the paddle that last touched the ball.
+const MARKER_SIZE: f32 = 8.0;
+/// Room between the top of a paddle and its marker.
+const MARKER_GAP: f32 = 12.0;
+/// Over the court, under emote bubbles.
+const INDICATOR_Z: f32 = 4.0;
+
+/// An arrow at the foot of the centre line pointing the way the next serve
+/// goes, for as long as the serve is coming, and a small marker over the
+/// paddle that last touched the ball, which goes once a goal is scored.
+pub struct IndicatorsPlugin;
+
+impl Plugin for IndicatorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Possession>()
+            .init_resource::<Arena>()
+            .init_resource::<AccessibilitySettings>()
+            .add_event::<HitEvent>()
+            .add_event::<GoalEvent>()
+            .add_startup_system(spawn_indicators)
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(forget_possession))
+            .add_system(track_possession)
+            .add_system(show_serve_arrow)
+            .add_system(show_possession_marker.after(track_possession));
+    }
+}
+
+/// The end whose paddle last touched the ball, until the next goal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Possession(pub Option<Player>);
+
+#[derive(Component)]
+struct ServeArrow;
+
+#[derive(Component)]
+struct PossessionMarker;
+
+fn spawn_indicators(mut commands: Commands, font: Res<UiFont>) {
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::with_section(
+                "",
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: ARROW_FONT_SIZE,
+                    color: FOREGROUND_COLOR,
+                },
+                TextAlignment {
+                    vertical: VerticalAlign::Center,
+                    horizontal: HorizontalAlign::Center,
+                },
+            ),
+            ..default()
+        })
+        .insert(ServeArrow);
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: FOREGROUND_COLOR,
+                custom_size: Some(Vec2::splat(MARKER_SIZE)),
+                ..default()
+            },
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(PossessionMarker);
+}
+
+fn forget_possession(mut possession: ResMut<Possession>) {
+    possession.0 = None;
+}
+
+fn track_possession(
+    mut possession: ResMut<Possession>,
+    mut hit_events: EventReader<HitEvent>,
+    mut goal_events: EventReader<GoalEvent>,
+) {
+    let hit = hit_events.iter().last().map(|hit| hit.player);
+    if goal_events.iter().count() > 0 {
+        possession.0 = None;
+    } else if hit.is_some() && possession.0 != hit {
+        possession.0 = hit;
+    }
+}
+
+fn show_serve_arrow(
+    state: Option<Res<State<AppState>>>,
+    serve: Res<Serve>,
+    arena: Res<Arena>,
+    accessibility: Res<AccessibilitySettings>,
+    mut query: Query<(&mut Text, &mut Transform), With<ServeArrow>>,
+) {
+    // Not over the menus, where the ball waits to be served too.
+    let serving = serve.in_progress() && match_in_play(state);
+    let arrow = match (serving, serve.towards()) {
+        (false, _) => "",
+        (true, Player::P1) => "<<",
+        (true, Player::P2) => ">>",
+    };
+    let x = (arena.left + arena.right) / 2.0;
+    let translation = Vec3::new(x, arena.bottom + ARROW_INSET, INDICATOR_Z);
+    let color = accessibility.cap(FOREGROUND_COLOR);
+    for (mut text, mut transform) in query.iter_mut() {
+        // Only touch the text when it changes, so it isn't laid out again
+        // every frame.
+        if text.sections[0].value != arrow || text.sections[0].style.color != color {
+            text.sections[0].value = arrow.to_string();
+            text.sections[0].style.color = color;
+        }
+        transform.translation = translation;
+    }
+}
+
+fn show_possession_marker(
+    possession: Res<Possession>,
+    accessibility: Res<AccessibilitySettings>,
+    p1_query: Query<&Transform, (With<P1Paddle>, Without<PossessionMarker>)>,
+    p2_query: Query<&Transform, (With<P2Paddle>, Without<PossessionMarker>)>,
+    mut marker_query: Query<(&mut Transform, &mut Visibility, &mut Sprite), With<PossessionMarker>>,
+) {
+    let paddle = match possession.0 {
+        Some(Player::P1) => p1_query.iter().next(),
+        Some(Player::P2) => p2_query.iter().next(),
+        None => None,
+    };
+    let color = accessibility.cap(FOREGROUND_COLOR);
+    for (mut transform, mut visibility, mut sprite) in marker_query.iter_mut() {
+        if visibility.is_visible != paddle.is_some() {
+            visibility.is_visible = paddle.is_some();
+        }
+        if sprite.color != color {
+            sprite.color = color;
+        }
+        if let Some(paddle) = paddle {
+            let above = paddle.scale.y / 2.0 + MARKER_GAP;
+            transform.translation =
+                paddle.translation.truncate().extend(INDICATOR_Z) + Vec3::Y * above;
+        }
+    }
+}
@@ -0,0 +1,458 @@
+use bevy::{
+    input::gamepad::{GamepadEvent, GamepadEventType},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ai::Brain,
+    dash::Dash,
+    emote::{Emote, EmoteEvent},
+    settings::{Settings, StickMode},
+    toast::Toast,
+    AppState, Arena, GameConfig, Grip, Locale, MatchRules, P1Paddle, P2Paddle, Player, Series,
+    SimulationLabel, SimulationStage, TickRate, Velocity,
+};
+
+/// Keyboard and gamepad control of whichever paddles have no [`Brain`].
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Settings>()
+            .init_resource::<MatchRules>()
+            .init_resource::<Series>()
+            .init_resource::<Locale>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<InputState>()
+            .add_event::<Toast>()
+            .add_event::<LostGamepad>()
+            .add_system(gamepad_connections)
+            .add_event::<EmoteEvent>()
+            .add_system(request_dashes)
+            .add_system(request_emotes)
+            .add_system_set_to_stage(
+                SimulationStage,
+                SystemSet::new()
+                    .with_system(
+                        sample_input
+                            .label(SimulationLabel::Input)
+                            .before(SimulationLabel::Movement),
+                    )
+                    .with_system(
+                        move_paddles
+                            .label(SimulationLabel::Movement)
+                            .after(SimulationLabel::Input)
+                            .before(SimulationLabel::Collisions),
+                    ),
+            );
+    }
+}
+
+/// What each player is asking of their paddle this tick, sampled from every
+/// device at the start of the tick so all of movement sees the same thing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputState {
+    pub p1: PaddleInput,
+    pub p2: PaddleInput,
+}
+
+impl InputState {
+    pub fn player(&self, player: Player) -> &PaddleInput {
+        match player {
+            Player::P1 => &self.p1,
+            Player::P2 => &self.p2,
+        }
+    }
+}
+
+/// One player's controls at the start of a tick. Dashes and emotes are taps,
+/// picked up every frame instead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PaddleInput {
+    /// From -1 for full down to 1 for full up, already mirrored if the rules
+    /// say so.
+    pub direction: f32,
+    /// The stick puts the paddle straight at `direction` of the way to the
+    /// wall, see [`StickMode::Absolute`], rather than pushing it.
+    pub absolute: bool,
+    pub grip: bool,
+}
+
+/// Keys that move and grip one paddle. Saved with each key by name, any of
+/// letters, digits, arrows, modifiers, the number pad and punctuation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "PaddleKeyNames", try_from = "PaddleKeyNames")]
+pub struct PaddleKeys {
+    pub up: KeyCode,
+    pub down: KeyCode,
+    pub grip: KeyCode,
+    pub dash: KeyCode,
+    /// One for each of [`Emote::ALL`], in order.
+    pub emotes: [KeyCode; 3],
+}
+
+/// Keyboard controls for both paddles.
+#[derive(Clone)]
+pub struct KeyBindings {
+    pub p1: PaddleKeys,
+    pub p2: PaddleKeys,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            p1: PaddleKeys {
+                up: KeyCode::W,
+                down: KeyCode::S,
+                grip: KeyCode::Space,
+                dash: KeyCode::LShift,
+                emotes: [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3],
+            },
+            p2: PaddleKeys {
+                up: KeyCode::O,
+                down: KeyCode::L,
+                grip: KeyCode::K,
+                dash: KeyCode::J,
+                emotes: [KeyCode::Key8, KeyCode::Key9, KeyCode::Key0],
+            },
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn keys(&self, player: Player) -> &PaddleKeys {
+        match player {
+            Player::P1 => &self.p1,
+            Player::P2 => &self.p2,
+        }
+    }
+}
+
+impl PaddleKeys {
+    /// -1 for down, 1 for up, or 0 for neither or both.
+    pub(crate) fn direction(&self, keyboard_input: &Input<KeyCode>) -> f32 {
+        let mut direction = 0.0;
+        if keyboard_input.pressed(self.down) {
+            direction -= 1.0;
+        }
+        if keyboard_input.pressed(self.up) {
+            direction += 1.0;
+        }
+        direction
+    }
+
+    /// Whichever emote was just asked for, if any.
+    pub(crate) fn emote(&self, keyboard_input: &Input<KeyCode>) -> Option<Emote> {
+        Emote::ALL
+            .into_iter()
+            .zip(self.emotes)
+            .find_map(|(emote, key)| keyboard_input.just_pressed(key).then_some(emote))
+    }
+}
+
+/// Keys a paddle can be bound to in a saved [`PaddleKeys`].
+#[rustfmt::skip]
+const BINDABLE_KEYS: [KeyCode; 68] = [
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G, KeyCode::H,
+    KeyCode::I, KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N, KeyCode::O, KeyCode::P,
+    KeyCode::Q, KeyCode::R, KeyCode::S, KeyCode::T, KeyCode::U, KeyCode::V, KeyCode::W, KeyCode::X,
+    KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5,
+    KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+    KeyCode::Space, KeyCode::Tab, KeyCode::Return, KeyCode::LShift, KeyCode::RShift,
+    KeyCode::LControl, KeyCode::RControl, KeyCode::LAlt, KeyCode::RAlt,
+    KeyCode::Numpad0, KeyCode::Numpad1, KeyCode::Numpad2, KeyCode::Numpad3, KeyCode::Numpad4,
+    KeyCode::Numpad5, KeyCode::Numpad6, KeyCode::Numpad7, KeyCode::Numpad8, KeyCode::Numpad9,
+    KeyCode::Comma, KeyCode::Period, KeyCode::Semicolon, KeyCode::Slash, KeyCode::Apostrophe,
+    KeyCode::LBracket, KeyCode::RBracket, KeyCode::Minus, KeyCode::Equals,
+];
+
+/// The name a key is saved under, as it is written in the code.
+fn key_name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+/// How a key is shown to players: as it is saved, but with digits on their
+/// own.
+pub(crate) fn key_label(key: KeyCode) -> String {
+    let name = key_name(key);
+    match name.strip_prefix("Key") {
+        Some(digit) => digit.to_string(),
+        None => name,
+    }
+}
+
+fn parse_key(name: &str) -> Result<KeyCode, String> {
+    BINDABLE_KEYS
+        .into_iter()
+        .find(|key| key_name(*key) == name)
+        .ok_or_else(|| format!("{} can't be bound to a paddle", name))
+}
+
+/// [`PaddleKeys`] as saved. Bevy's keys can't be saved as they are.
+#[derive(Serialize, Deserialize)]
+struct PaddleKeyNames {
+    up: String,
+    down: String,
+    grip: String,
+    dash: String,
+    emotes: [String; 3],
+}
+
+impl From<PaddleKeys> for PaddleKeyNames {
+    fn from(keys: PaddleKeys) -> Self {
+        PaddleKeyNames {
+            up: key_name(keys.up),
+            down: key_name(keys.down),
+            grip: key_name(keys.grip),
+            dash: key_name(keys.dash),
+            emotes: keys.emotes.map(key_name),
+        }
+    }
+}
+
+impl TryFrom<PaddleKeyNames> for PaddleKeys {
+    type Error = String;
+
+    fn try_from(names: PaddleKeyNames) -> Result<Self, String> {
+        let [gg, nice, yikes] = &names.emotes;
+        Ok(PaddleKeys {
+            up: parse_key(&names.up)?,
+            down: parse_key(&names.down)?,
+            grip: parse_key(&names.grip)?,
+            dash: parse_key(&names.dash)?,
+            emotes: [parse_key(gg)?, parse_key(nice)?, parse_key(yikes)?],
+        })
+    }
+}
+
+/// D-pad directions that send each of [`Emote::ALL`], in order. Down is left
+/// alone, being too easy to hit by accident while steering with the stick.
+const EMOTE_BUTTONS: [GamepadButtonType; 3] = [
+    GamepadButtonType::DPadUp,
+    GamepadButtonType::DPadLeft,
+    GamepadButtonType::DPadRight,
+];
+
+/// Turns up into down and down into up under
+/// [`MatchRules::mirror_controls`], whatever the player is steering with.
+fn mirror(rules: &MatchRules, direction: f32) -> f32 {
+    if rules.mirror_controls {
+        -direction
+    } else {
+        direction
+    }
+}
+
+/// Simple resource to store the ID of the connected gamepad.
+/// We need to know which gamepad to use for player input.
+pub(crate) struct MyGamepad(pub(crate) Gamepad);
+
+/// Sent when the gamepad P1 is playing with goes away.
+pub(crate) struct LostGamepad;
+
+fn gamepad_connections(
+    mut commands: Commands,
+    my_gamepad: Option<Res<MyGamepad>>,
+    locale: Res<Locale>,
+    mut gamepad_evr: EventReader<GamepadEvent>,
+    mut toasts: EventWriter<Toast>,
+    mut lost_gamepads: EventWriter<LostGamepad>,
+) {
+    for GamepadEvent(id, kind) in gamepad_evr.iter() {
+        match kind {
+            GamepadEventType::Connected => {
+                // if we don't have any gamepad yet, use this one
+                if my_gamepad.is_none() {
+                    info!("Gamepad {:?} connected, using it for P1", id);
+                    toasts.send(Toast(locale.text("toast-gamepad-connected")));
+                    commands.insert_resource(MyGamepad(*id));
+                } else {
+                    info!("Gamepad {:?} connected", id);
+                }
+            }
+            GamepadEventType::Disconnected => {
+                // if it's the one we previously associated with the player,
+                // disassociate it:
+                if let Some(MyGamepad(old_id)) = my_gamepad.as_deref() {
+                    if old_id == id {
+                        warn!("Lost P1's gamepad {:?}", id);
+                        toasts.send(Toast(locale.text("toast-gamepad-disconnected")));
+                        commands.remove_resource::<MyGamepad>();
+                        lost_gamepads.send(LostGamepad);
+                        continue;
+                    }
+                }
+                info!("Gamepad {:?} disconnected", id);
+            }
+            // other events are irrelevant
+            _ => {}
+        }
+    }
+}
+
+/// Dashes are tapped, so they are picked up every frame rather than on
+/// simulation ticks, which could miss a press that comes and goes between
+/// two of them.
+fn request_dashes(
+    series: Res<Series>,
+    bindings: Res<KeyBindings>,
+    keyboard_input: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    my_gamepad: Option<Res<MyGamepad>>,
+    mut dash_query: Query<(&mut Dash, Option<&P1Paddle>), PlayerPaddle>,
+) {
+    let mut p1_dash = keyboard_input.just_pressed(bindings.p1.dash);
+    if let Some(gp) = &my_gamepad {
+        p1_dash |= buttons.just_pressed(GamepadButton(gp.0, GamepadButtonType::RightTrigger2));
+    }
+    let p2_dash = keyboard_input.just_pressed(bindings.p2.dash);
+
+    for (mut dash, p1) in dash_query.iter_mut() {
+        let end = if p1.is_some() { Player::P1 } else { Player::P2 };
+        let wanted = match series.player_on(end) {
+            Player::P1 => p1_dash,
+            Player::P2 => p2_dash,
+        };
+        if wanted {
+            dash.request();
+        }
+    }
+}
+
+/// Sends an [`EmoteEvent`] for whoever plays at each end asking for one.
+/// Only during a match: a LAN guest's emotes go to the host, see
+/// [`LanPlugin`](crate::LanPlugin).
+#[allow(clippy::too_many_arguments)]
+fn request_emotes(
+    state: Option<Res<State<AppState>>>,
+    series: Res<Series>,
+    bindings: Res<KeyBindings>,
+    keyboard_input: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    my_gamepad: Option<Res<MyGamepad>>,
+    paddle_query: Query<Option<&P1Paddle>, PlayerPaddle>,
+    mut emotes: EventWriter<EmoteEvent>,
+) {
+    if !crate::match_in_play(state) {
+        return;
+    }
+    for p1 in paddle_query.iter() {
+        let end = if p1.is_some() { Player::P1 } else { Player::P2 };
+        let player = series.player_on(end);
+        let mut emote = bindings.keys(player).emote(&keyboard_input);
+        if let Some(gp) = my_gamepad.as_ref().filter(|_| player == Player::P1) {
+            emote = emote.or_else(|| {
+                Emote::ALL
+                    .into_iter()
+                    .zip(EMOTE_BUTTONS)
+                    .find_map(|(emote, button)| {
+                        buttons
+                            .just_pressed(GamepadButton(gp.0, button))
+                            .then_some(emote)
+                    })
+            });
+        }
+        if let Some(emote) = emote {
+            emotes.send(EmoteEvent { end, emote });
+        }
+    }
+}
+
+/// Eases the paddle's velocity towards `direction` at full speed. The walls
+/// are left to [`clamp_paddles`](crate::clamp_paddles) once it has moved.
+pub(crate) fn push_paddle(
+    direction: f32,
+    config: &GameConfig,
+    tick_rate: &TickRate,
+    velocity: &mut Velocity,
+) {
+    velocity.y = config.accelerate(
+        velocity.y,
+        direction * config.paddle_speed,
+        tick_rate.step(),
+    );
+}
+
+/// Paddles that no bot is driving.
+type PlayerPaddle = (Or<(With<P1Paddle>, With<P2Paddle>)>, Without<Brain>);
+
+type Controlled<'a> = (
+    &'a Transform,
+    &'a mut Velocity,
+    &'a mut Grip,
+    &'a Dash,
+    Option<&'a P1Paddle>,
+);
+
+/// Reads every player's keys, and P1's gamepad, into [`InputState`]. A
+/// gamepad takes over from P1's keys while it is plugged in.
+#[allow(clippy::too_many_arguments)]
+fn sample_input(
+    rules: Res<MatchRules>,
+    settings: Res<Settings>,
+    bindings: Res<KeyBindings>,
+    keyboard_input: Res<Input<KeyCode>>,
+    axes: Res<Axis<GamepadAxis>>,
+    buttons: Res<Input<GamepadButton>>,
+    my_gamepad: Option<Res<MyGamepad>>,
+    mut input: ResMut<InputState>,
+) {
+    let keys = |keys: &PaddleKeys| PaddleInput {
+        direction: mirror(&rules, keys.direction(&keyboard_input)),
+        absolute: false,
+        grip: keyboard_input.pressed(keys.grip),
+    };
+    let mut sampled = InputState {
+        p1: keys(&bindings.p1),
+        p2: keys(&bindings.p2),
+    };
+
+    if let Some(gp) = my_gamepad {
+        let stick = axes.get(GamepadAxis(gp.0, GamepadAxisType::LeftStickY));
+        sampled.p1.direction =
+            stick.map_or(0.0, |y| mirror(&rules, settings.controls.shape_axis(y)));
+        sampled.p1.absolute =
+            stick.is_some() && settings.controls.stick_mode == StickMode::Absolute;
+        sampled.p1.grip |= buttons.pressed(GamepadButton(gp.0, GamepadButtonType::South));
+    }
+
+    if *input != sampled {
+        *input = sampled;
+    }
+}
+
+/// Moves each paddle with the [`InputState`] of whoever is playing at its
+/// end, which is only the other player's after a [`Series`] switches ends.
+fn move_paddles(
+    config: Res<GameConfig>,
+    series: Res<Series>,
+    arena: Res<Arena>,
+    tick_rate: Res<TickRate>,
+    input: Res<InputState>,
+    mut query: Query<Controlled, PlayerPaddle>,
+) {
+    // Paddles a bot is driving are left alone.
+    for (paddle_transform, mut paddle_velocity, mut grip, dash, p1) in query.iter_mut() {
+        let end = if p1.is_some() { Player::P1 } else { Player::P2 };
+        let controls = input.player(series.player_on(end));
+
+        grip.0 = controls.grip;
+        if controls.absolute {
+            // Whatever speed gets the paddle to where the stick points by the
+            // next tick, so it moves, and hits, like any other paddle.
+            let (bottom_bound, top_bound) = arena.paddle_bounds(paddle_transform.scale.y);
+            let target = (controls.direction * 250.0).clamp(bottom_bound, top_bound);
+            paddle_velocity.y = (target - paddle_transform.translation.y) / tick_rate.step();
+        } else {
+            push_paddle(
+                controls.direction * dash.boost(),
+                &config,
+                &tick_rate,
+                &mut paddle_velocity,
+            );
+        }
+    }
+}
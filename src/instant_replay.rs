@@ -0,0 +1,163 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    spectate::{self, IsWatched, Watched},
+    AppState, Ball, MatchOverEvent, P1Paddle, P2Paddle, Scoreboard, SimTick, Snapshot, TickRate,
+};
+
+/// How much of the end of a match the instant replay shows, in seconds.
+const REPLAY_SECONDS: f32 = 3.0;
+/// How fast the instant replay plays, against the match itself.
+const SLOW_MOTION: f32 = 0.4;
+
+/// Once a goal wins a match, plays its last few seconds again in slow
+/// motion before the results, unless it is skipped. Needs the
+/// [`SimulationPlugin`](crate::SimulationPlugin).
+pub struct InstantReplayPlugin;
+
+impl Plugin for InstantReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LastMoments>()
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(forget_moments))
+            // Once the tick is over, as replays record it.
+            .add_system_to_stage(CoreStage::PostUpdate, record_moments)
+            .add_system_set(
+                SystemSet::on_enter(AppState::InstantReplay).with_system(start_instant_replay),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::InstantReplay).with_system(play_instant_replay),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::InstantReplay).with_system(end_instant_replay),
+            );
+    }
+}
+
+/// The last few seconds of the match, a [`Snapshot`] a tick. Nothing more is
+/// added once the match is over, so the goal that won it stays last.
+#[derive(Default)]
+pub struct LastMoments {
+    snapshots: VecDeque<Snapshot>,
+    over: bool,
+}
+
+impl LastMoments {
+    pub fn snapshots(&self) -> &VecDeque<Snapshot> {
+        &self.snapshots
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.over
+    }
+}
+
+/// Where to go once the instant replay is over or skipped.
+pub(crate) struct AfterReplay(pub(crate) AppState);
+
+/// The instant replay being shown, and how far through it is in ticks.
+struct Playback {
+    snapshots: Vec<Snapshot>,
+    position: f32,
+}
+
+fn forget_moments(mut moments: ResMut<LastMoments>) {
+    *moments = LastMoments::default();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn record_moments(
+    tick: Res<SimTick>,
+    tick_rate: Res<TickRate>,
+    scoreboard: Res<Scoreboard>,
+    mut moments: ResMut<LastMoments>,
+    ball_query: Query<&Transform, With<Ball>>,
+    p1_query: Query<&Transform, With<P1Paddle>>,
+    p2_query: Query<&Transform, With<P2Paddle>>,
+    mut match_over_events: EventReader<MatchOverEvent>,
+) {
+    if moments.over || !tick.is_changed() || tick.is_added() {
+        return;
+    }
+    moments.snapshots.push_back(Snapshot::take(
+        &tick,
+        &scoreboard,
+        ball_query.get_single().ok(),
+        p1_query.iter().next(),
+        p2_query.iter().next(),
+    ));
+    while moments.snapshots.len() > tick_rate.ticks(REPLAY_SECONDS) as usize {
+        moments.snapshots.pop_front();
+    }
+    if match_over_events.iter().count() > 0 {
+        moments.over = true;
+    }
+}
+
+fn start_instant_replay(
+    mut commands: Commands,
+    moments: Res<LastMoments>,
+    after: Res<AfterReplay>,
+    mut state: ResMut<State<AppState>>,
+) {
+    if moments.snapshots.len() < 2 {
+        let _ = state.set(after.0);
+        return;
+    }
+    commands.insert_resource(Playback {
+        snapshots: moments.snapshots.iter().copied().collect(),
+        position: 0.0,
+    });
+}
+
+fn play_instant_replay(
+    time: Res<Time>,
+    tick_rate: Res<TickRate>,
+    after: Res<AfterReplay>,
+    playback: Option<ResMut<Playback>>,
+    mut state: ResMut<State<AppState>>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut query: Query<Watched, IsWatched>,
+) {
+    let mut playback = match playback {
+        Some(playback) => playback,
+        None => return,
+    };
+    playback.position += time.delta_seconds() * SLOW_MOTION / tick_rate.step();
+    let index = playback.position as usize;
+    let (from, to) = match playback.snapshots.get(index..index + 2) {
+        Some([from, to]) => (*from, *to),
+        _ => {
+            let _ = state.set(after.0);
+            return;
+        }
+    };
+    spectate::show_between(&from, &to, playback.position.fract(), &mut query);
+    // The score as it was then, so the winning point goes up again.
+    if scoreboard.p1_score != from.p1_score || scoreboard.p2_score != from.p2_score {
+        scoreboard.p1_score = from.p1_score;
+        scoreboard.p2_score = from.p2_score;
+    }
+}
+
+/// Leaves everything as the match ended, however far the replay got.
+fn end_instant_replay(
+    mut commands: Commands,
+    playback: Option<Res<Playback>>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut query: Query<Watched, IsWatched>,
+) {
+    commands.remove_resource::<AfterReplay>();
+    commands.remove_resource::<Playback>();
+    let last = match playback
+        .as_ref()
+        .and_then(|playback| playback.snapshots.last())
+    {
+        Some(last) => *last,
+        None => return,
+    };
+    spectate::show_between(&last, &last, 1.0, &mut query);
+    scoreboard.p1_score = last.p1_score;
+    scoreboard.p2_score = last.p2_score;
+}
@@ -0,0 +1,43 @@
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::{match_in_play, AppState, Interpolated, TickProgress};
+
+/// Further than this in one tick and the entity was put somewhere rather
+/// than moved there, like the ball after a goal, so it is drawn there at once.
+const MAX_GLIDE: f32 = 100.0;
+
+/// Draws the ball and paddles part of the way between where the last tick
+/// found them and where it left them, by how far real time has got towards
+/// the next tick, so they move smoothly however much faster than the
+/// simulation the screen refreshes.
+///
+/// Only the drawn `GlobalTransform` is changed, a tick behind at most, and
+/// only during a match: everything else, like replays and spectating, moves
+/// things outside the simulation and is drawn as it is.
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TickProgress>().add_system_to_stage(
+            CoreStage::PostUpdate,
+            interpolate.after(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+fn interpolate(
+    state: Option<Res<State<AppState>>>,
+    progress: Res<TickProgress>,
+    mut query: Query<(&Transform, &Interpolated, &mut GlobalTransform)>,
+) {
+    let in_play = match_in_play(state);
+    let progress = progress.0.clamp(0.0, 1.0);
+    for (transform, interpolated, mut global) in query.iter_mut() {
+        let current = transform.translation;
+        global.translation = if in_play && interpolated.previous.distance(current) <= MAX_GLIDE {
+            interpolated.previous.lerp(current, progress)
+        } else {
+            current
+        };
+    }
+}
@@ -0,0 +1,40 @@
+use std::{
+    io,
+    sync::{mpsc, Mutex},
+};
+
+/// Work going on on another thread, to be picked up once it is done.
+pub(crate) struct Job<T>(Mutex<mpsc::Receiver<io::Result<T>>>);
+
+impl<T: Send + 'static> Job<T> {
+    pub(crate) fn spawn(work: impl FnOnce() -> io::Result<T> + Send + 'static) -> Job<T> {
+        let (sender, receiver) = mpsc::channel();
+        in_background(move || {
+            // Nobody is left waiting if the game quit meanwhile.
+            let _ = sender.send(work());
+        });
+        Job(Mutex::new(receiver))
+    }
+
+    /// What the job came to, if it is done.
+    pub(crate) fn poll(&self) -> Option<io::Result<T>> {
+        match self.0.lock().unwrap().try_recv() {
+            Ok(result) => Some(result),
+            Err(mpsc::TryRecvError::Empty) => None,
+            Err(mpsc::TryRecvError::Disconnected) => Some(Err(io::Error::other("job died"))),
+        }
+    }
+}
+
+/// Does `work` on another thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn in_background(work: impl FnOnce() + Send + 'static) {
+    std::thread::spawn(work);
+}
+
+/// Does `work` there and then, as the browser gives the game no other
+/// threads.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn in_background(work: impl FnOnce() + Send + 'static) {
+    work();
+}
@@ -0,0 +1,285 @@
+use bevy::{
+    ecs::event::Events,
+    prelude::*,
+    window::{WindowCloseRequested, WindowMode},
+};
+use fluent::fluent_args;
+
+use crate::{
+    ai::{Brain, PendingAi, Predictor},
+    toast::Toast,
+    widget, AppState, GameMode, Locale, MatchRules, P1Paddle, Series,
+};
+
+/// How long the attract demo plays before the menu is shown again.
+const DEMO_SECONDS: f32 = 60.0;
+/// How long any screen but the main menu or a match waits for somebody to
+/// press something before going back to the main menu.
+const IDLE_RESET_SECONDS: f32 = 60.0;
+/// Points the attract demo plays to, far more than fit in it, so it never
+/// ends as a match and never counts towards anything.
+const DEMO_POINTS: usize = 99;
+
+/// Runs fjong on an arcade cabinet: fullscreen with no mouse cursor, a coin
+/// to pay for each match and a start button to begin one, an attract demo
+/// of the AI playing itself whenever nobody is at the controls, and no way
+/// to quit short of pulling the plug.
+///
+/// Every visit to the main menu puts the rules back as the cabinet started
+/// with, so each player begins from the same match as the last one did, and
+/// anything left waiting on a screen goes back there by itself.
+///
+/// Needs the [`FjongPlugin`](crate::FjongPlugin).
+#[derive(Clone)]
+pub struct KioskPlugin {
+    /// Key the coin mechanism presses, 5 as in most cabinets. A gamepad's
+    /// select button drops a coin too.
+    pub coin: KeyCode,
+    /// Key the start button presses, 1 as in most cabinets. So does a
+    /// gamepad's start button.
+    pub start: KeyCode,
+    /// Matches are free, no coin needed.
+    pub free_play: bool,
+    /// Seconds of nobody touching the main menu before the demo starts.
+    pub attract_after: f32,
+}
+
+impl Default for KioskPlugin {
+    fn default() -> Self {
+        KioskPlugin {
+            coin: KeyCode::Key5,
+            start: KeyCode::Key1,
+            free_play: false,
+            attract_after: 30.0,
+        }
+    }
+}
+
+impl Plugin for KioskPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MatchRules>()
+            .init_resource::<PendingAi>()
+            .init_resource::<Series>()
+            .init_resource::<Locale>()
+            .init_resource::<HouseRules>()
+            .add_event::<Toast>()
+            .insert_resource(Kiosk {
+                coin: self.coin,
+                start: self.start,
+                free_play: self.free_play,
+                attract_after: self.attract_after,
+                credits: 0,
+            })
+            .add_startup_system(remember_house_rules)
+            .add_system_to_stage(CoreStage::PreUpdate, ignore_close_requests)
+            .add_system(keep_fullscreen)
+            .add_system(take_coins.before(widget::navigate_menus))
+            .add_system(run_attract.before(widget::navigate_menus).after(take_coins))
+            .add_system_set(SystemSet::on_enter(AppState::MainMenu).with_system(reset_rules))
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(charge_for_match));
+    }
+}
+
+/// The cabinet, see [`KioskPlugin`].
+pub struct Kiosk {
+    pub coin: KeyCode,
+    pub start: KeyCode,
+    pub free_play: bool,
+    pub attract_after: f32,
+    /// Coins dropped and not yet played.
+    pub credits: u32,
+}
+
+/// There while the attract demo is playing.
+pub struct Attract;
+
+/// The rules the cabinet started with.
+#[derive(Default)]
+struct HouseRules(MatchRules);
+
+fn remember_house_rules(rules: Res<MatchRules>, mut house_rules: ResMut<HouseRules>) {
+    house_rules.0 = rules.clone();
+}
+
+/// Closing the window is the quickest way out, so it is turned down.
+fn ignore_close_requests(close_requests: Option<ResMut<Events<WindowCloseRequested>>>) {
+    if let Some(mut close_requests) = close_requests {
+        close_requests.clear();
+    }
+}
+
+fn keep_fullscreen(mut windows: Option<ResMut<Windows>>) {
+    let window = match windows
+        .as_mut()
+        .and_then(|windows| windows.get_primary_mut())
+    {
+        Some(window) => window,
+        None => return,
+    };
+    if window.mode() != WindowMode::BorderlessFullscreen {
+        window.set_mode(WindowMode::BorderlessFullscreen);
+    }
+    if window.cursor_visible() {
+        window.set_cursor_visibility(false);
+    }
+}
+
+/// Whether `key`, or `button` on any gamepad, went down this frame.
+fn just_pressed(
+    keyboard_input: &Input<KeyCode>,
+    gamepads: &Gamepads,
+    buttons: &Input<GamepadButton>,
+    key: KeyCode,
+    button: GamepadButtonType,
+) -> bool {
+    keyboard_input.just_pressed(key)
+        || gamepads
+            .iter()
+            .any(|gamepad| buttons.just_pressed(GamepadButton(*gamepad, button)))
+}
+
+/// Counts coins in, and starts a match from the main menu when the start
+/// button is pressed, so long as there is a credit to pay for it.
+#[allow(clippy::too_many_arguments)]
+fn take_coins(
+    mut kiosk: ResMut<Kiosk>,
+    house_rules: Res<HouseRules>,
+    attract: Option<Res<Attract>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    locale: Res<Locale>,
+    mut rules: ResMut<MatchRules>,
+    mut state: ResMut<State<AppState>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let pressed = |key, button| just_pressed(&keyboard_input, &gamepads, &buttons, key, button);
+    if pressed(kiosk.coin, GamepadButtonType::Select) {
+        kiosk.credits += 1;
+        toasts.send(Toast(locale.text_with(
+            "kiosk-credits",
+            &fluent_args!["credits" => kiosk.credits],
+        )));
+    }
+
+    // A press during the demo only stops it.
+    let on_menu = *state.current() == AppState::MainMenu && attract.is_none();
+    if on_menu && pressed(kiosk.start, GamepadButtonType::Start) {
+        if kiosk.free_play || kiosk.credits > 0 {
+            *rules = MatchRules {
+                mode: GameMode::Versus,
+                ..house_rules.0.clone()
+            };
+            let _ = state.set(AppState::Playing);
+        } else {
+            toasts.send(Toast(locale.text("kiosk-insert-coin")));
+        }
+    }
+}
+
+/// Takes a credit for every match, games after the first of a series aside,
+/// and sends whoever hasn't paid back to the main menu.
+fn charge_for_match(
+    mut kiosk: ResMut<Kiosk>,
+    attract: Option<Res<Attract>>,
+    series: Res<Series>,
+    locale: Res<Locale>,
+    mut state: ResMut<State<AppState>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if kiosk.free_play || attract.is_some() || series.games_played() > 0 {
+        return;
+    }
+    if kiosk.credits > 0 {
+        kiosk.credits -= 1;
+    } else {
+        toasts.send(Toast(locale.text("kiosk-insert-coin")));
+        let _ = state.set(AppState::MainMenu);
+    }
+}
+
+/// Puts the rules back as the cabinet started with for the next player,
+/// whatever the last one picked.
+fn reset_rules(
+    house_rules: Res<HouseRules>,
+    mut rules: ResMut<MatchRules>,
+    mut pending: ResMut<PendingAi>,
+) {
+    *rules = house_rules.0.clone();
+    pending.0 = None;
+}
+
+/// Starts the demo once the main menu has been left alone long enough, and
+/// stops it again at the first press or once it has played for a while. Any
+/// other screen left alone goes back to the main menu.
+#[allow(clippy::too_many_arguments)]
+fn run_attract(
+    mut commands: Commands,
+    time: Res<Time>,
+    kiosk: Res<Kiosk>,
+    house_rules: Res<HouseRules>,
+    attract: Option<Res<Attract>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    locale: Res<Locale>,
+    mut rules: ResMut<MatchRules>,
+    mut state: ResMut<State<AppState>>,
+    mut toasts: EventWriter<Toast>,
+    p1_query: Query<Entity, With<P1Paddle>>,
+    mut idle: Local<f32>,
+    mut last_state: Local<Option<AppState>>,
+) {
+    let current = *state.current();
+    let touched = keyboard_input.get_just_pressed().next().is_some()
+        || buttons.get_just_pressed().next().is_some();
+    if touched || *last_state != Some(current) {
+        *idle = 0.0;
+        *last_state = Some(current);
+    } else {
+        *idle += time.delta_seconds();
+    }
+
+    if attract.is_some() {
+        // Paused counts as stopped too: whoever paused it is at the controls.
+        if touched || *idle > DEMO_SECONDS || current != AppState::Playing {
+            for p1 in p1_query.iter() {
+                commands.entity(p1).remove::<Brain>();
+            }
+            commands.remove_resource::<Attract>();
+            if current != AppState::MainMenu {
+                let _ = state.replace(AppState::MainMenu);
+            }
+        }
+        return;
+    }
+
+    match current {
+        AppState::MainMenu if *idle > kiosk.attract_after => {
+            *rules = MatchRules {
+                mode: GameMode::Versus,
+                points_to_win: DEMO_POINTS,
+                best_of: 1,
+                ..house_rules.0.clone()
+            };
+            for p1 in p1_query.iter() {
+                commands
+                    .entity(p1)
+                    .insert(Brain::new(Predictor::new(rules.difficulty)));
+            }
+            commands.insert_resource(Attract);
+            let _ = state.set(AppState::Playing);
+            let prompt = if kiosk.free_play {
+                "kiosk-press-start"
+            } else {
+                "kiosk-insert-coin"
+            };
+            toasts.send(Toast(locale.text(prompt)));
+        }
+        // A match ends by itself, however long nobody touches it for.
+        AppState::MainMenu | AppState::Playing => {}
+        _ if *idle > IDLE_RESET_SECONDS => {
+            let _ = state.replace(AppState::MainMenu);
+        }
+        _ => {}
+    }
+}
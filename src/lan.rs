@@ -0,0 +1,1247 @@
+use std::{
+    env, fmt,
+    io::{self, Read, Write},
+    net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream, UdpSocket},
+    time::Duration,
+};
+
+use bevy::{input::InputSystem, prelude::*, window::ReceivedCharacter};
+use fluent::fluent_args;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ai::{Brain, Predictor},
+    emote::Emote,
+    input::KeyBindings,
+    job::Job,
+    lobby::{self, CODE_LENGTH},
+    remote::{Echo, RemoteBrain},
+    spectate::{self, Spectators, Watching},
+    transport::{self, Transport},
+    webrtc::{Signalling, WebRtcRoom},
+    AppState, GameConfig, GameMode, Locale, MatchRules, P2Paddle, Player, Toast,
+};
+
+/// Hosts announce their games on this UDP port, and the lobby listens on it.
+pub const DISCOVERY_PORT: u16 = 41_600;
+/// How often a host announces its game.
+const BEACON_SECONDS: f32 = 1.0;
+/// Games not heard from in this long have gone, and drop out of the lobby.
+const GAME_TIMEOUT_SECONDS: f64 = 3.5;
+/// How long to wait for a host to answer when joining its game.
+const JOIN_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long to wait for the host to turn up when joining a game through a
+/// room on the signalling server, which takes a while to connect.
+const ROOM_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often a guest pings the host, to time the round trip.
+const PING_SECONDS: f32 = 0.5;
+/// How long a dropped guest has to get back into the match before it is
+/// forfeited.
+const RECONNECT_SECONDS: f32 = 30.0;
+/// How often a guest tries to get back to a host it lost, each try waiting
+/// as long as joining does.
+const RETRY_SECONDS: f32 = 1.0;
+/// Longer greetings than this are not from fjong.
+const MAX_GREETING: usize = 256;
+
+/// Versus over the local network, with no addresses to type in.
+///
+/// The host announces its game over UDP broadcast until someone joins from
+/// their lobby. The host's game runs the match, playing the guest's paddle
+/// from the moves they send, and the guest watches it through the same
+/// [`Snapshot`](crate::Snapshot)s spectators get.
+///
+/// If the line between them drops, the match is paused on both ends while
+/// the guest gets back in with the session the host gave them on joining,
+/// and forfeited if that takes longer than [`RECONNECT_SECONDS`]. The host
+/// keeps the score and serve, so the next snapshot is all the guest needs to
+/// pick up where they left off.
+pub struct LanPlugin;
+
+impl Plugin for LanPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Toast>()
+            .init_resource::<Locale>()
+            .init_resource::<KeyBindings>()
+            .add_system_set(SystemSet::on_enter(AppState::LanHost).with_system(open_game))
+            .add_system_set(
+                SystemSet::on_update(AppState::LanHost)
+                    .with_system(announce_game)
+                    .with_system(open_room)
+                    .with_system(accept_guest),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::LanHost).with_system(close_game))
+            .add_system_set(
+                SystemSet::on_enter(AppState::MainMenu)
+                    .with_system(send_guest_home)
+                    .with_system(stop_joining),
+            )
+            .add_system(finish_joining)
+            .add_system_set(SystemSet::on_enter(AppState::LanLobby).with_system(open_lobby))
+            .add_system_set(SystemSet::on_update(AppState::LanLobby).with_system(discover_games))
+            .add_system_set(SystemSet::on_exit(AppState::LanLobby).with_system(close_lobby))
+            .init_resource::<RoomCode>()
+            .add_system_set(SystemSet::on_enter(AppState::RoomCode).with_system(clear_room_code))
+            // Ahead of the menus, so typing a code doesn't also move through
+            // them.
+            .add_system_to_stage(CoreStage::PreUpdate, type_room_code.after(InputSystem))
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(lose_guest))
+            .add_system_set(SystemSet::on_update(AppState::Spectating).with_system(send_moves))
+            .add_system_set(SystemSet::on_exit(AppState::Spectating).with_system(leave_game))
+            .add_system_set(
+                SystemSet::on_update(AppState::Reconnecting)
+                    .with_system(welcome_back_guest)
+                    .with_system(rejoin_host)
+                    .with_system(give_up_reconnecting),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Reconnecting).with_system(stop_reconnecting),
+            );
+    }
+}
+
+/// What a host broadcasts about its game.
+#[derive(Serialize, Deserialize)]
+struct Beacon {
+    /// Only games of the same version can play each other.
+    fjong: String,
+    name: String,
+    /// Where the game takes its guest.
+    port: u16,
+}
+
+/// The first thing a guest says to a host, naming the session they were in
+/// if they are coming back after losing the line.
+#[derive(Serialize, Deserialize)]
+struct Hello {
+    session: Option<u64>,
+}
+
+/// The host's answer, with the session to name when coming back.
+#[derive(Serialize, Deserialize)]
+struct Welcome {
+    session: u64,
+}
+
+/// Reads one line of JSON, a byte at a time so nothing sent after it is
+/// lost.
+fn read_message<T: for<'de> Deserialize<'de>>(stream: &mut TcpStream) -> io::Result<T> {
+    let mut line = Vec::new();
+    let mut byte = [0];
+    while line.len() < MAX_GREETING {
+        stream.read_exact(&mut byte)?;
+        if byte[0] == b'\n' {
+            return Ok(serde_json::from_slice(&line)?);
+        }
+        line.push(byte[0]);
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "greeting too long",
+    ))
+}
+
+fn write_message(stream: &mut TcpStream, message: &impl Serialize) -> io::Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    stream.write_all(&line)
+}
+
+/// Waits for a guest who just connected to say hello, giving the session
+/// they name.
+pub(crate) fn hear_hello(stream: &mut TcpStream) -> io::Result<Option<u64>> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(JOIN_TIMEOUT))?;
+    let hello: Hello = read_message(stream)?;
+    Ok(hello.session)
+}
+
+/// Lets a guest in, giving them `session` to name if they have to come back.
+pub(crate) fn welcome(stream: &mut TcpStream, session: u64) -> io::Result<()> {
+    write_message(stream, &Welcome { session })
+}
+
+pub(crate) fn not_in_session() -> io::Error {
+    io::Error::new(io::ErrorKind::PermissionDenied, "not in this session")
+}
+
+/// One side of a greeting, heard a frame at a time so that someone who
+/// never answers holds nothing up.
+struct Handshake {
+    line: Box<dyn Transport>,
+    heard: Vec<u8>,
+    timer: Timer,
+}
+
+impl Handshake {
+    fn new(line: Box<dyn Transport>) -> io::Result<Handshake> {
+        line.set_up()?;
+        Ok(Handshake {
+            line,
+            heard: Vec::new(),
+            timer: Timer::new(JOIN_TIMEOUT, false),
+        })
+    }
+
+    fn say(&mut self, message: &impl Serialize) -> io::Result<()> {
+        let mut line = serde_json::to_vec(message)?;
+        line.push(b'\n');
+        self.line.write_all(&line)
+    }
+
+    /// The line of JSON heard from the other end, once all of it is in. Read
+    /// a byte at a time so nothing sent after it is lost.
+    fn hear<T: for<'de> Deserialize<'de>>(&mut self, delta: Duration) -> io::Result<Option<T>> {
+        if self.timer.tick(delta).finished() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "no greeting"));
+        }
+        let mut byte = [0];
+        loop {
+            match self.line.read(&mut byte) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(_) if byte[0] == b'\n' => return Ok(Some(serde_json::from_slice(&self.heard)?)),
+                Ok(_) if self.heard.len() >= MAX_GREETING => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "greeting too long",
+                    ))
+                }
+                Ok(_) => self.heard.push(byte[0]),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(None),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+/// Someone who just connected to the host, being greeted a frame at a time.
+struct Greeting {
+    peer: String,
+    /// The session they have to name to be let in, if coming back.
+    expected: Option<u64>,
+    /// What they are given to name if they have to come back.
+    session: u64,
+    handshake: Handshake,
+}
+
+impl Greeting {
+    /// Greets whoever is on the other end of `line`, letting them in if they
+    /// name `expected`, and giving them a new session if that is none.
+    fn new(line: Box<dyn Transport>, peer: String, expected: Option<u64>) -> io::Result<Greeting> {
+        Ok(Greeting {
+            peer,
+            expected,
+            session: expected.unwrap_or_else(rand::random),
+            handshake: Handshake::new(line)?,
+        })
+    }
+
+    /// The host's side of the greeting, saying if they have been let in yet.
+    fn greet(&mut self, delta: Duration) -> io::Result<bool> {
+        let hello: Hello = match self.handshake.hear(delta)? {
+            Some(hello) => hello,
+            None => return Ok(false),
+        };
+        if hello.session != self.expected {
+            return Err(not_in_session());
+        }
+        let session = self.session;
+        self.handshake.say(&Welcome { session })?;
+        Ok(true)
+    }
+}
+
+/// Starts greeting whoever has connected to `listener` or turned up in
+/// `room`, to let in those who name `expected`. The room is closed if the
+/// signalling server goes away.
+fn greet_arrivals(
+    listener: Option<&TcpListener>,
+    room: &mut Option<WebRtcRoom>,
+    expected: Option<u64>,
+    greetings: &mut Vec<Greeting>,
+) {
+    let mut arrivals = Vec::new();
+    if let Some(listener) = listener {
+        match listener.accept() {
+            Ok((stream, peer)) => arrivals.push((stream.into(), peer.to_string())),
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+            Err(err) => warn!("Could not accept LAN guest: {}", err),
+        }
+    }
+    if let Some(open) = room {
+        match open.arrivals() {
+            Ok(lines) => arrivals.extend(lines),
+            Err(err) => {
+                warn!("Closed room {}: {}", open.code(), err);
+                *room = None;
+            }
+        }
+    }
+    for (line, peer) in arrivals {
+        match Greeting::new(line, peer.clone(), expected) {
+            Ok(greeting) => greetings.push(greeting),
+            Err(err) => warn!("Could not greet {}: {}", peer, err),
+        }
+    }
+}
+
+/// Takes the first of `greetings` to be let in, turning away those who
+/// can't be.
+fn take_greeted(greetings: &mut Vec<Greeting>, delta: Duration) -> Option<Greeting> {
+    let mut index = 0;
+    while index < greetings.len() {
+        match greetings[index].greet(delta) {
+            Ok(false) => index += 1,
+            Ok(true) => return Some(greetings.remove(index)),
+            Err(err) => {
+                warn!("Turned away {}: {}", greetings[index].peer, err);
+                greetings.remove(index);
+            }
+        }
+    }
+    None
+}
+
+/// The guest's side: connects to the host at `addr`, naming `session` if
+/// coming back, and gives the stream and the session to come back to.
+fn connect(
+    addr: SocketAddr,
+    session: Option<u64>,
+    timeout: Duration,
+) -> io::Result<(TcpStream, u64)> {
+    let mut stream = TcpStream::connect_timeout(&addr, timeout)?;
+    stream.set_nodelay(true)?;
+    stream.set_read_timeout(Some(timeout))?;
+    write_message(&mut stream, &Hello { session })?;
+    let welcome: Welcome = read_message(&mut stream)?;
+    Ok((stream, welcome.session))
+}
+
+/// Plays P1's keys in the match at `addr`, hosted by a LAN game or
+/// `fjong-server`, instead of starting at the main menu.
+pub struct JoinPlugin {
+    pub addr: SocketAddr,
+}
+
+impl Plugin for JoinPlugin {
+    fn build(&self, app: &mut App) {
+        let (stream, token) = match connect(self.addr, None, JOIN_TIMEOUT) {
+            Ok(joined) => joined,
+            Err(err) => {
+                error!("Could not join the match at {}: {}", self.addr, err);
+                return;
+            }
+        };
+        let (moves, watching) = match play_over(stream.into()) {
+            Ok(joined) => joined,
+            Err(err) => {
+                error!("Could not join the match at {}: {}", self.addr, err);
+                return;
+            }
+        };
+        info!("Joined the match at {}", self.addr);
+
+        app.insert_resource(moves)
+            .insert_resource(watching)
+            .insert_resource(GuestSession::new(Host::Tcp(self.addr), token))
+            .add_system(start_playing);
+    }
+}
+
+/// Skips the main menu and goes straight to the match.
+fn start_playing(mut started: Local<bool>, mut state: ResMut<State<AppState>>) {
+    if !*started {
+        *started = true;
+        let _ = state.set(AppState::Spectating);
+    }
+}
+
+/// Lets friends anywhere join games hosted from here by room code, through
+/// the lobby server at `addr`. See [`Rooms`](crate::lobby::Rooms).
+pub struct LobbyPlugin {
+    pub addr: SocketAddr,
+}
+
+impl Plugin for LobbyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LobbyServer(self.addr));
+    }
+}
+
+pub(crate) struct LobbyServer(SocketAddr);
+
+/// A game hosted from here, waiting for someone to join.
+pub(crate) struct HostedGame {
+    /// Where guests on the network connect, unless the game can't listen
+    /// there, as in the browser.
+    lan: Option<LanListener>,
+    timer: Timer,
+    /// The code it can be joined by, and the line to the lobby server that
+    /// keeps the room open.
+    room: Option<(TcpStream, String)>,
+    /// The lobby server being asked for a room, on another thread.
+    opening_room: Option<Job<(TcpStream, String)>>,
+    /// The room on the signalling server it can be joined through instead,
+    /// with [`WebRtcPlugin`](crate::WebRtcPlugin).
+    web_room: Option<WebRtcRoom>,
+    /// Whoever has connected and not yet been let in.
+    greetings: Vec<Greeting>,
+}
+
+/// Where guests on the network connect, and how they hear about it.
+struct LanListener {
+    listener: TcpListener,
+    beacon: UdpSocket,
+    port: u16,
+}
+
+impl LanListener {
+    fn open() -> io::Result<LanListener> {
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        listener.set_nonblocking(true)?;
+        let beacon = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        beacon.set_broadcast(true)?;
+        Ok(LanListener {
+            port: listener.local_addr()?.port(),
+            listener,
+            beacon,
+        })
+    }
+}
+
+/// What this machine goes by in other people's lobbies.
+pub(crate) fn host_name() -> String {
+    env::var("HOSTNAME")
+        .or_else(|_| env::var("COMPUTERNAME"))
+        .unwrap_or_else(|_| "fjong".to_string())
+}
+
+impl HostedGame {
+    fn new(lan: Option<LanListener>, web_room: Option<WebRtcRoom>) -> HostedGame {
+        // Announced straight away, then every so often.
+        let mut timer = Timer::from_seconds(BEACON_SECONDS, true);
+        timer.set_elapsed(Duration::from_secs_f32(BEACON_SECONDS));
+        HostedGame {
+            lan,
+            timer,
+            room: None,
+            opening_room: None,
+            web_room,
+            greetings: Vec::new(),
+        }
+    }
+
+    pub(crate) fn room_code(&self) -> Option<&str> {
+        match &self.web_room {
+            Some(room) => Some(room.code()),
+            None => self.room.as_ref().map(|(_, code)| code.as_str()),
+        }
+    }
+}
+
+/// Hosts a game on the network, and in a room people anywhere can join it
+/// through: on the signalling server if there is one, or else the lobby
+/// server.
+fn open_game(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    lobby: Option<Res<LobbyServer>>,
+    signalling: Option<Res<Signalling>>,
+    mut state: ResMut<State<AppState>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let lan = match LanListener::open() {
+        Ok(lan) => {
+            info!("Hosting a LAN game on port {}", lan.port);
+            Some(lan)
+        }
+        Err(err) => {
+            warn!("Could not host a LAN game: {}", err);
+            None
+        }
+    };
+    let web_room = signalling.as_deref().map(|Signalling(url)| {
+        let room = WebRtcRoom::open(url, &lobby::random_code());
+        info!("Opened room {} at {}", room.code(), url);
+        room
+    });
+    if lan.is_none() && web_room.is_none() {
+        toasts.send(Toast(locale.text("toast-lan-failed")));
+        let _ = state.set(AppState::MainMenu);
+        return;
+    }
+
+    let mut game = HostedGame::new(lan, web_room);
+    if let (Some(&LobbyServer(addr)), Some(lan), None) =
+        (lobby.as_deref(), &game.lan, &game.web_room)
+    {
+        let port = lan.port;
+        game.opening_room = Some(Job::spawn(move || {
+            lobby::create_room(addr, &host_name(), port)
+        }));
+    }
+    commands.insert_resource(game);
+}
+
+/// Shows the room code once the lobby server has given one.
+fn open_room(
+    locale: Res<Locale>,
+    lobby: Option<Res<LobbyServer>>,
+    game: Option<ResMut<HostedGame>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let (mut game, LobbyServer(addr)) = match (game, lobby.as_deref()) {
+        (Some(game), Some(lobby)) => (game, lobby),
+        _ => return,
+    };
+    let opened = match game.opening_room.as_ref().and_then(Job::poll) {
+        Some(opened) => opened,
+        None => return,
+    };
+    game.opening_room = None;
+    match opened {
+        Ok((stream, code)) => {
+            info!("Opened room {} at {}", code, addr);
+            game.room = Some((stream, code));
+        }
+        Err(err) => {
+            warn!("Could not open a room at {}: {}", addr, err);
+            toasts.send(Toast(locale.text("toast-lobby-failed")));
+        }
+    }
+}
+
+fn announce_game(time: Res<Time>, game: Option<ResMut<HostedGame>>) {
+    let mut game = match game {
+        Some(game) => game,
+        None => return,
+    };
+    if !game.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let lan = match &game.lan {
+        Some(lan) => lan,
+        None => return,
+    };
+
+    let beacon = Beacon {
+        fjong: env!("CARGO_PKG_VERSION").to_string(),
+        name: host_name(),
+        port: lan.port,
+    };
+    // Plain strings and numbers always serialize.
+    let message = serde_json::to_vec(&beacon).unwrap();
+    if let Err(err) = lan
+        .beacon
+        .send_to(&message, (Ipv4Addr::BROADCAST, DISCOVERY_PORT))
+    {
+        warn!("Could not announce LAN game: {}", err);
+    }
+}
+
+/// Marks P2's paddle while a guest on another machine is playing it.
+#[derive(Component)]
+struct LanGuest;
+
+/// The match a guest is playing in from another machine, and how they get
+/// back into it.
+struct HostSession {
+    listener: Option<TcpListener>,
+    room: Option<WebRtcRoom>,
+    token: u64,
+    /// Whoever has connected while the guest was away, and not yet been
+    /// let back in.
+    greetings: Vec<Greeting>,
+}
+
+/// Counts down the time left to get a dropped LAN match going again.
+pub(crate) struct Reconnect {
+    pub(crate) timer: Timer,
+}
+
+impl Reconnect {
+    pub(crate) fn new() -> Reconnect {
+        Reconnect {
+            timer: Timer::from_seconds(RECONNECT_SECONDS, false),
+        }
+    }
+
+    pub(crate) fn seconds_left(&self) -> f32 {
+        (self.timer.duration() - self.timer.elapsed()).as_secs_f32()
+    }
+}
+
+/// Hands `paddle` to whoever is on the other end of `line`, and sends them
+/// snapshots. Gives back what they ping, which is there for as long as
+/// they are.
+pub(crate) fn seat_player(
+    commands: &mut Commands,
+    spectators: &mut Spectators,
+    paddle: Entity,
+    line: Box<dyn Transport>,
+    peer: String,
+) -> io::Result<Echo> {
+    let echo = Echo::default();
+    let snapshots = line.try_clone_line()?;
+    let brain = RemoteBrain::listening(line, echo.clone())?;
+    spectators.add_player(snapshots, peer, echo.clone());
+    commands
+        .entity(paddle)
+        .insert(brain.emotes())
+        .insert(Brain::new(brain));
+    Ok(echo)
+}
+
+/// Greets whoever connects, and lets the first to say hello play P2.
+#[allow(clippy::too_many_arguments)]
+fn accept_guest(
+    mut commands: Commands,
+    time: Res<Time>,
+    game: Option<ResMut<HostedGame>>,
+    locale: Res<Locale>,
+    mut rules: ResMut<MatchRules>,
+    mut spectators: ResMut<Spectators>,
+    mut state: ResMut<State<AppState>>,
+    mut toasts: EventWriter<Toast>,
+    p2_query: Query<Entity, With<P2Paddle>>,
+) {
+    let mut game = match game {
+        Some(game) => game,
+        None => return,
+    };
+    let game = &mut *game;
+    let listener = game.lan.as_ref().map(|lan| &lan.listener);
+    greet_arrivals(listener, &mut game.web_room, None, &mut game.greetings);
+    let guest = match p2_query.iter().next() {
+        Some(entity) => entity,
+        None => return,
+    };
+    let greeting = match take_greeted(&mut game.greetings, time.delta()) {
+        Some(greeting) => greeting,
+        None => return,
+    };
+    let peer = greeting.peer.clone();
+    let listener = game
+        .lan
+        .as_ref()
+        .map(|lan| lan.listener.try_clone())
+        .transpose();
+    let seated = listener.and_then(|listener| {
+        let line = greeting.handshake.line;
+        seat_player(&mut commands, &mut spectators, guest, line, peer.clone())?;
+        Ok(listener)
+    });
+    match seated {
+        Ok(listener) => {
+            commands.entity(guest).insert(LanGuest);
+            commands.insert_resource(HostSession {
+                listener,
+                room: game.web_room.clone(),
+                token: greeting.session,
+                greetings: Vec::new(),
+            });
+        }
+        Err(err) => {
+            warn!("Could not set up LAN guest {}: {}", peer, err);
+            return;
+        }
+    }
+
+    info!("LAN guest joined from {}", peer);
+    let message = locale.text_with("toast-lan-joined", &fluent_args!["peer" => peer]);
+    toasts.send(Toast(message));
+    rules.mode = GameMode::Versus;
+    let _ = state.set(AppState::Playing);
+}
+
+fn close_game(mut commands: Commands) {
+    commands.remove_resource::<HostedGame>();
+}
+
+/// Hangs up on the guest once the match is over, and gives their paddle back
+/// to the AI.
+fn send_guest_home(
+    mut commands: Commands,
+    rules: Res<MatchRules>,
+    mut spectators: ResMut<Spectators>,
+    guest_query: Query<Entity, With<LanGuest>>,
+) {
+    for entity in guest_query.iter() {
+        commands
+            .entity(entity)
+            .remove::<LanGuest>()
+            .insert(Brain::new(Predictor::new(rules.difficulty)));
+        spectators.hang_up_players();
+    }
+    commands.remove_resource::<HostSession>();
+}
+
+/// Pauses the match when the guest's line drops, to wait for them to get
+/// back in.
+fn lose_guest(
+    mut commands: Commands,
+    session: Option<Res<HostSession>>,
+    spectators: Res<Spectators>,
+    locale: Res<Locale>,
+    mut state: ResMut<State<AppState>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    if session.is_none() || spectators.has_players() {
+        return;
+    }
+    info!("Lost the LAN guest, waiting for them to come back");
+    toasts.send(Toast(locale.text("toast-lan-lost-guest")));
+    commands.insert_resource(Reconnect::new());
+    let _ = state.push(AppState::Reconnecting);
+}
+
+/// Lets the guest back in if they name the session, and carries on with the
+/// match.
+#[allow(clippy::too_many_arguments)]
+fn welcome_back_guest(
+    mut commands: Commands,
+    time: Res<Time>,
+    session: Option<ResMut<HostSession>>,
+    locale: Res<Locale>,
+    mut spectators: ResMut<Spectators>,
+    mut state: ResMut<State<AppState>>,
+    mut toasts: EventWriter<Toast>,
+    guest_query: Query<Entity, With<LanGuest>>,
+) {
+    let (mut session, guest) = match (session, guest_query.get_single()) {
+        (Some(session), Ok(guest)) => (session, guest),
+        _ => return,
+    };
+    let session = &mut *session;
+    let token = Some(session.token);
+    greet_arrivals(
+        session.listener.as_ref(),
+        &mut session.room,
+        token,
+        &mut session.greetings,
+    );
+    let greeting = match take_greeted(&mut session.greetings, time.delta()) {
+        Some(greeting) => greeting,
+        None => return,
+    };
+    let peer = greeting.peer.clone();
+    let line = greeting.handshake.line;
+    if let Err(err) = seat_player(&mut commands, &mut spectators, guest, line, peer.clone()) {
+        warn!("Turned away {} while waiting for the guest: {}", peer, err);
+        return;
+    }
+    session.greetings.clear();
+
+    info!("LAN guest came back from {}", peer);
+    toasts.send(Toast(locale.text("toast-lan-reconnected")));
+    let _ = state.pop();
+}
+
+/// Forfeits the match once the line has been down too long, on either end.
+fn give_up_reconnecting(
+    time: Res<Time>,
+    locale: Res<Locale>,
+    host: Option<Res<HostSession>>,
+    reconnect: Option<ResMut<Reconnect>>,
+    mut state: ResMut<State<AppState>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let mut reconnect = match reconnect {
+        Some(reconnect) => reconnect,
+        None => return,
+    };
+    if !reconnect.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    info!("Gave up on the LAN match");
+    // Whoever dropped is the one to forfeit, and only the host can't have.
+    let message = if host.is_some() {
+        "toast-lan-guest-forfeited"
+    } else {
+        "toast-lan-forfeited"
+    };
+    toasts.send(Toast(locale.text(message)));
+    let _ = state.replace(AppState::MainMenu);
+}
+
+fn stop_reconnecting(mut commands: Commands) {
+    commands.remove_resource::<Reconnect>();
+}
+
+/// A game heard about on the network.
+pub(crate) struct LanGame {
+    pub(crate) name: String,
+    pub(crate) addr: SocketAddr,
+    /// Seconds since startup it was last heard from.
+    last_heard: f64,
+}
+
+/// Games on the network that can be joined, listened for while the lobby is
+/// open.
+pub(crate) struct Lobby {
+    /// Where hosts are heard from, unless the game can't listen there, as in
+    /// the browser, which only joins by room code.
+    socket: Option<UdpSocket>,
+    pub(crate) games: Vec<LanGame>,
+}
+
+fn open_lobby(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    lobby: Option<Res<LobbyServer>>,
+    signalling: Option<Res<Signalling>>,
+    mut state: ResMut<State<AppState>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, DISCOVERY_PORT))
+        .and_then(|socket| socket.set_nonblocking(true).map(|_| socket));
+    let socket = match socket {
+        Ok(socket) => Some(socket),
+        Err(err) => {
+            warn!("Could not listen for LAN games: {}", err);
+            None
+        }
+    };
+    if socket.is_none() && lobby.is_none() && signalling.is_none() {
+        toasts.send(Toast(locale.text("toast-lan-failed")));
+        let _ = state.set(AppState::MainMenu);
+        return;
+    }
+    commands.insert_resource(Lobby {
+        socket,
+        games: Vec::new(),
+    });
+}
+
+/// Adds games as their hosts announce them, and drops those that have gone
+/// quiet.
+fn discover_games(time: Res<Time>, lobby: Option<ResMut<Lobby>>) {
+    let mut lobby = match lobby {
+        Some(lobby) => lobby,
+        None => return,
+    };
+    let lobby = &mut *lobby;
+    let socket = match &lobby.socket {
+        Some(socket) => socket,
+        None => return,
+    };
+    let now = time.seconds_since_startup();
+    let mut buffer = [0; 512];
+    let mut heard = Vec::new();
+    loop {
+        match socket.recv_from(&mut buffer) {
+            Ok((read, from)) => match serde_json::from_slice::<Beacon>(&buffer[..read]) {
+                Ok(beacon) if beacon.fjong == env!("CARGO_PKG_VERSION") => {
+                    heard.push((beacon.name, SocketAddr::new(from.ip(), beacon.port)))
+                }
+                Ok(_) => {}
+                Err(err) => warn!("Ignoring bad LAN beacon from {}: {}", from, err),
+            },
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+            Err(err) => {
+                warn!("Stopped listening for LAN games: {}", err);
+                break;
+            }
+        }
+    }
+
+    for (name, addr) in heard {
+        match lobby.games.iter_mut().find(|game| game.addr == addr) {
+            Some(game) => game.last_heard = now,
+            None => lobby.games.push(LanGame {
+                name,
+                addr,
+                last_heard: now,
+            }),
+        }
+    }
+    lobby
+        .games
+        .retain(|game| now - game.last_heard < GAME_TIMEOUT_SECONDS);
+}
+
+fn close_lobby(mut commands: Commands) {
+    commands.remove_resource::<Lobby>();
+}
+
+/// The room code typed in so far.
+#[derive(Default)]
+pub(crate) struct RoomCode(pub(crate) String);
+
+fn clear_room_code(mut code: ResMut<RoomCode>) {
+    code.0.clear();
+}
+
+/// Types letters into the room code, keeping them from the menus. Backspace
+/// on an empty code is left to go back as usual.
+fn type_room_code(
+    state: Res<State<AppState>>,
+    mut code: ResMut<RoomCode>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+) {
+    let typed: Vec<char> = characters.iter().map(|character| character.char).collect();
+    if *state.current() != AppState::RoomCode {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Back) && !code.0.is_empty() {
+        code.0.pop();
+        keyboard_input.reset(KeyCode::Back);
+    }
+    for letter in typed.into_iter().filter(char::is_ascii_alphabetic) {
+        if code.0.len() < CODE_LENGTH {
+            code.0.push(letter.to_ascii_uppercase());
+        }
+    }
+
+    let keys: Vec<KeyCode> = keyboard_input
+        .get_pressed()
+        .chain(keyboard_input.get_just_pressed())
+        .chain(keyboard_input.get_just_released())
+        .copied()
+        .filter(|key| !matches!(key, KeyCode::Return | KeyCode::Escape | KeyCode::Back))
+        .filter(|key| !matches!(key, KeyCode::Up | KeyCode::Down))
+        .collect();
+    for key in keys {
+        keyboard_input.reset(key);
+    }
+}
+
+/// Starts joining the game in room `code`, asking `lobby` where it is
+/// first.
+pub(crate) fn join_room(commands: &mut Commands, lobby: &LobbyServer, code: &str) {
+    let &LobbyServer(lobby) = lobby;
+    let code = code.to_string();
+    commands.insert_resource(Joining(Dialing::Tcp(Job::spawn(move || {
+        let addr = lobby::find_room(lobby, &code)?;
+        let (stream, session) = connect(addr, None, JOIN_TIMEOUT)?;
+        Ok(Joined {
+            host: Host::Tcp(addr),
+            line: stream.into(),
+            session,
+        })
+    }))));
+}
+
+/// Starts joining the game in room `code` on the signalling server, hosted
+/// in the browser or anywhere else with [`WebRtcPlugin`](crate::WebRtcPlugin).
+pub(crate) fn join_web_room(commands: &mut Commands, signalling: &Signalling, code: &str) {
+    let Signalling(url) = signalling;
+    let host = Host::Room {
+        url: url.clone(),
+        code: code.to_string(),
+    };
+    commands.insert_resource(Joining(host.dial(None)));
+}
+
+/// The guest's line back to the host, for their moves.
+struct Moves {
+    line: Box<dyn Transport>,
+    outgoing: Vec<u8>,
+    /// Last velocity sent, so a held key is only sent once.
+    velocity: f32,
+    ping_timer: Timer,
+}
+
+impl Moves {
+    fn new(line: Box<dyn Transport>) -> Moves {
+        Moves {
+            line,
+            outgoing: Vec::new(),
+            velocity: 0.0,
+            ping_timer: Timer::from_seconds(PING_SECONDS, true),
+        }
+    }
+}
+
+/// Watches the match the host sends down `line`, and sends moves back up
+/// it.
+fn play_over(line: Box<dyn Transport>) -> io::Result<(Moves, Watching)> {
+    Ok((Moves::new(line.try_clone_line()?), Watching::new(line)?))
+}
+
+/// Where a game being joined is hosted.
+#[derive(Clone)]
+enum Host {
+    Tcp(SocketAddr),
+    /// A room on the signalling server.
+    Room {
+        url: String,
+        code: String,
+    },
+}
+
+impl fmt::Display for Host {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Host::Tcp(addr) => write!(f, "{}", addr),
+            Host::Room { url, code } => write!(f, "room {} at {}", code, url),
+        }
+    }
+}
+
+impl Host {
+    /// Starts getting into the game, naming `session` if coming back.
+    fn dial(&self, session: Option<u64>) -> Dialing {
+        match self {
+            &Host::Tcp(addr) => Dialing::Tcp(Job::spawn(move || {
+                let (stream, session) = connect(addr, session, JOIN_TIMEOUT)?;
+                Ok(Joined {
+                    host: Host::Tcp(addr),
+                    line: stream.into(),
+                    session,
+                })
+            })),
+            Host::Room { url, code } => Dialing::Room(Box::new(RoomDialing {
+                host: self.clone(),
+                room: WebRtcRoom::open(url, code),
+                session,
+                handshake: None,
+                timer: Timer::new(ROOM_TIMEOUT, false),
+            })),
+        }
+    }
+}
+
+/// A host being got into without holding up the game: over TCP on another
+/// thread, or through a room a frame at a time.
+enum Dialing {
+    Tcp(Job<Joined>),
+    Room(Box<RoomDialing>),
+}
+
+/// A game got into: its host, the line to it and the session to name to
+/// come back.
+struct Joined {
+    host: Host,
+    line: Box<dyn Transport>,
+    session: u64,
+}
+
+struct RoomDialing {
+    host: Host,
+    room: WebRtcRoom,
+    session: Option<u64>,
+    /// Saying hello to whoever turned up in the room first.
+    handshake: Option<Handshake>,
+    /// Time left for the host to turn up.
+    timer: Timer,
+}
+
+impl Dialing {
+    fn poll(&mut self, delta: Duration) -> Option<io::Result<Joined>> {
+        match self {
+            Dialing::Tcp(job) => job.poll(),
+            Dialing::Room(dialing) => match dialing.poll(delta) {
+                Ok(Some(welcome)) => {
+                    // Only the line is left to take once welcomed.
+                    let line = dialing.handshake.take().unwrap().line;
+                    Some(Ok(Joined {
+                        host: dialing.host.clone(),
+                        line,
+                        session: welcome.session,
+                    }))
+                }
+                Ok(None) => None,
+                Err(err) => Some(Err(err)),
+            },
+        }
+    }
+}
+
+impl RoomDialing {
+    /// Says hello to the first to turn up in the room, and hears whether
+    /// they let us in.
+    fn poll(&mut self, delta: Duration) -> io::Result<Option<Welcome>> {
+        if let Some(handshake) = &mut self.handshake {
+            return handshake.hear(delta);
+        }
+        if self.timer.tick(delta).finished() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "nobody in the room",
+            ));
+        }
+        if let Some((line, _)) = self.room.arrivals()?.into_iter().next() {
+            let mut handshake = Handshake::new(line)?;
+            handshake.say(&Hello {
+                session: self.session,
+            })?;
+            self.handshake = Some(handshake);
+        }
+        Ok(None)
+    }
+}
+
+/// The host a guest is playing with, and the session to name to get back
+/// into the match if the line drops.
+pub(crate) struct GuestSession {
+    host: Host,
+    token: u64,
+    retry_timer: Timer,
+    /// The try at getting back in going on, giving the line back and the
+    /// session to name next time.
+    rejoining: Option<Dialing>,
+}
+
+impl GuestSession {
+    fn new(host: Host, token: u64) -> GuestSession {
+        GuestSession {
+            host,
+            token,
+            retry_timer: Timer::from_seconds(RETRY_SECONDS, true),
+            rejoining: None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Move {
+    velocity: f32,
+    /// Milliseconds since the guest started, echoed back in the snapshots.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ping: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    emote: Option<Emote>,
+}
+
+/// A game being joined, with the host answering without holding up the
+/// menus meanwhile.
+pub(crate) struct Joining(Dialing);
+
+/// Starts joining the game at `addr` as its guest, to watch the match from
+/// here and play P2's paddle once the host lets us in.
+pub(crate) fn join(commands: &mut Commands, addr: SocketAddr) {
+    commands.insert_resource(Joining(Host::Tcp(addr).dial(None)));
+}
+
+/// Goes into the match once the game being joined has let us in, or says
+/// it couldn't be joined.
+fn finish_joining(
+    mut commands: Commands,
+    time: Res<Time>,
+    joining: Option<ResMut<Joining>>,
+    locale: Res<Locale>,
+    mut state: ResMut<State<AppState>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let joined = match joining.and_then(|mut joining| joining.0.poll(time.delta())) {
+        Some(joined) => joined,
+        None => return,
+    };
+    commands.remove_resource::<Joining>();
+    let joined = joined.and_then(|joined| {
+        let (moves, watching) = play_over(joined.line)?;
+        Ok((joined.host, moves, watching, joined.session))
+    });
+    match joined {
+        Ok((host, moves, watching, token)) => {
+            info!("Joined the LAN game at {}", host);
+            commands.insert_resource(watching);
+            commands.insert_resource(moves);
+            commands.insert_resource(GuestSession::new(host, token));
+            let _ = state.set(AppState::Spectating);
+        }
+        Err(err) => {
+            warn!("Could not join the game: {}", err);
+            toasts.send(Toast(locale.text("toast-lan-join-failed")));
+        }
+    }
+}
+
+/// Forgets any game still being joined on going back to the main menu.
+fn stop_joining(mut commands: Commands) {
+    commands.remove_resource::<Joining>();
+}
+
+/// Sends the host the guest's moves, from P1's keys on this machine, and a
+/// ping every so often.
+fn send_moves(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    config: Res<GameConfig>,
+    moves: Option<ResMut<Moves>>,
+) {
+    let mut moves = match moves {
+        Some(moves) => moves,
+        None => return,
+    };
+    let velocity = key_bindings.keys(Player::P1).direction(&keyboard_input) * config.paddle_speed;
+    let ping = moves
+        .ping_timer
+        .tick(time.delta())
+        .just_finished()
+        .then(|| spectate::now_ms(&time));
+    let emote = key_bindings.keys(Player::P1).emote(&keyboard_input);
+    if velocity == moves.velocity && ping.is_none() && emote.is_none() {
+        return;
+    }
+    moves.velocity = velocity;
+
+    let moves = &mut *moves;
+    let sent = Move {
+        velocity,
+        ping,
+        emote,
+    };
+    // Plain numbers and field names always serialize.
+    serde_json::to_writer(&mut moves.outgoing, &sent).unwrap();
+    moves.outgoing.push(b'\n');
+    // Losing the host is noticed on the snapshots coming back.
+    if let Err(err) = transport::flush(&mut moves.outgoing, &mut *moves.line) {
+        debug!("Could not send move: {}", err);
+    }
+}
+
+/// Tries now and then to get back into the match after losing the host,
+/// starting over from the next snapshot once back in.
+fn rejoin_host(
+    mut commands: Commands,
+    time: Res<Time>,
+    locale: Res<Locale>,
+    session: Option<ResMut<GuestSession>>,
+    mut state: ResMut<State<AppState>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let mut session = match session {
+        Some(session) => session,
+        None => return,
+    };
+    let session = &mut *session;
+    let rejoined = session
+        .rejoining
+        .as_mut()
+        .and_then(|rejoining| rejoining.poll(time.delta()));
+    if let Some(rejoined) = rejoined {
+        session.rejoining = None;
+        let rejoined = rejoined.and_then(|joined| {
+            let (moves, watching) = play_over(joined.line)?;
+            Ok((moves, watching, joined.session))
+        });
+        match rejoined {
+            Ok((moves, watching, token)) => {
+                info!("Got back to the LAN game at {}", session.host);
+                session.token = token;
+                commands.insert_resource(moves);
+                commands.insert_resource(watching);
+                toasts.send(Toast(locale.text("toast-lan-reconnected")));
+                let _ = state.pop();
+                return;
+            }
+            Err(err) => debug!("Could not get back to {}: {}", session.host, err),
+        }
+    }
+
+    // One try at a time, however long each takes.
+    let retry = session.retry_timer.tick(time.delta()).just_finished();
+    if retry && session.rejoining.is_none() {
+        session.rejoining = Some(session.host.dial(Some(session.token)));
+    }
+}
+
+fn leave_game(mut commands: Commands) {
+    commands.remove_resource::<Moves>();
+    commands.remove_resource::<GuestSession>();
+}
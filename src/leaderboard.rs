@@ -0,0 +1,333 @@
+use std::{io, path::Path, str::FromStr, time::Duration};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    daily::DailyScoreEvent, data_path, job::Job, lan, persist, settings::Settings, AppState,
+    GameRng, StreakEvent,
+};
+
+/// Where scores waiting to go up and the last top scores fetched live,
+/// under [`data_path`].
+pub const LEADERBOARD_PATH: &str = "leaderboard.ron";
+/// How many of the best scores the leaderboard shows.
+pub const TOP_COUNT: usize = 100;
+/// How long to wait for the leaderboard server to answer.
+#[cfg(not(target_arch = "wasm32"))]
+const HTTP_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often to try sending scores the server couldn't be reached for.
+const RETRY_SECONDS: f32 = 30.0;
+
+/// A practice streak or daily challenge, as the leaderboard server has it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    /// Returns in the streak, or what the daily challenge was won by.
+    pub score: u32,
+    pub seed: u64,
+    /// Only the same version of the game plays the same way.
+    pub fjong: String,
+    /// The day of the [`DailyChallenge`](crate::DailyChallenge) played, in
+    /// days since the Unix epoch, or none for a practice streak.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily: Option<u64>,
+}
+
+/// Scores not yet sent, and the top scores as last fetched, kept in
+/// [`LEADERBOARD_PATH`] between runs so neither is lost offline.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LeaderboardCache {
+    pub queued: Vec<LeaderboardEntry>,
+    pub top: Vec<LeaderboardEntry>,
+    /// Best streak played here, the only ones worth sending.
+    pub best: u32,
+}
+
+impl LeaderboardCache {
+    pub fn load(path: impl AsRef<Path>) -> LeaderboardCache {
+        persist::load_or_create(path.as_ref())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        persist::save(self, path.as_ref())
+    }
+}
+
+/// A leaderboard server at an `http://` or `https://` URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Endpoint {
+    url: String,
+}
+
+impl FromStr for Endpoint {
+    type Err = String;
+
+    fn from_str(url: &str) -> Result<Endpoint, String> {
+        let rest = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))
+            .ok_or_else(|| format!("{} is not an http:// or https:// URL", url))?;
+        let authority = rest.split('/').next().unwrap_or_default();
+        let host = match authority.rsplit_once(':') {
+            Some((host, port)) => {
+                port.parse::<u16>()
+                    .map_err(|_| format!("bad port in {}", url))?;
+                host
+            }
+            None => authority,
+        };
+        if host.is_empty() {
+            return Err(format!("no host in {}", url));
+        }
+        Ok(Endpoint {
+            url: url.to_string(),
+        })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Endpoint {
+    /// A request to the server, over TLS for an `https://` URL.
+    fn request(&self, method: &str) -> ureq::Request {
+        ureq::AgentBuilder::new()
+            .timeout(HTTP_TIMEOUT)
+            .user_agent(concat!("fjong/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .request(method, &self.url)
+    }
+
+    /// Adds `entry` to the leaderboard.
+    pub fn submit(&self, entry: &LeaderboardEntry) -> io::Result<()> {
+        self.request("POST")
+            .set("Content-Type", "application/json")
+            .send_bytes(&serde_json::to_vec(entry)?)
+            .map_err(io::Error::other)?;
+        Ok(())
+    }
+
+    /// The best [`TOP_COUNT`] scores on the leaderboard, best first.
+    pub fn top(&self) -> io::Result<Vec<LeaderboardEntry>> {
+        let response = self.request("GET").call().map_err(io::Error::other)?;
+        let mut top: Vec<LeaderboardEntry> = serde_json::from_reader(response.into_reader())?;
+        top.truncate(TOP_COUNT);
+        Ok(top)
+    }
+}
+
+/// Games in the browser can't reach a leaderboard server yet.
+#[cfg(target_arch = "wasm32")]
+impl Endpoint {
+    pub fn submit(&self, _entry: &LeaderboardEntry) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    pub fn top(&self) -> io::Result<Vec<LeaderboardEntry>> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+/// Sends practice streaks and daily challenges to a leaderboard server at
+/// `url`, and fetches its top scores for the leaderboard screen.
+///
+/// Only scores that beat the best played here go up. Each is a `POST` of a
+/// [`LeaderboardEntry`] as JSON, such as
+///
+/// ```json
+/// {"name":"kitchen","score":42,"seed":1234,"fjong":"0.1.0"}
+/// ```
+///
+/// with a `"daily"` day number added for daily challenges.
+///
+/// and a `GET` of the same URL answers with a JSON list of them, best first.
+/// Scores the server can't be reached for are kept in [`LEADERBOARD_PATH`]
+/// and tried again every so often, even after a restart. Everything happens
+/// off the main thread, so a slow server never holds up the game.
+pub struct LeaderboardPlugin {
+    pub url: String,
+}
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        let endpoint = match self.url.parse() {
+            Ok(endpoint) => endpoint,
+            Err(err) => {
+                error!("Not using the leaderboard: {}", err);
+                return;
+            }
+        };
+        info!("Sending scores to the leaderboard at {}", self.url);
+
+        // Anything left over from last time goes straight away.
+        let mut retry_timer = Timer::from_seconds(RETRY_SECONDS, true);
+        retry_timer.set_elapsed(Duration::from_secs_f32(RETRY_SECONDS));
+        app.init_resource::<Settings>()
+            .init_resource::<TopScores>()
+            .add_event::<StreakEvent>()
+            .add_event::<DailyScoreEvent>()
+            .insert_resource(LeaderboardCache::load(data_path(LEADERBOARD_PATH)))
+            .insert_resource(LeaderboardServer {
+                endpoint,
+                retry_timer,
+                sending: None,
+                fetching: None,
+            })
+            .add_system(queue_streaks)
+            .add_system(queue_daily_scores)
+            .add_system(send_queued.after(queue_streaks).after(queue_daily_scores))
+            .add_system_set(
+                SystemSet::on_enter(AppState::Leaderboard).with_system(fetch_top_scores),
+            )
+            .add_system(receive_top_scores);
+    }
+}
+
+struct LeaderboardServer {
+    endpoint: Endpoint,
+    retry_timer: Timer,
+    /// How many of the queued scores went up, counting from the first.
+    sending: Option<Job<usize>>,
+    fetching: Option<Job<Vec<LeaderboardEntry>>>,
+}
+
+/// The leaderboard as the leaderboard screen shows it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) enum TopScores {
+    /// There is no leaderboard server to ask, see [`LeaderboardPlugin`].
+    #[default]
+    Unavailable,
+    Loading,
+    Loaded(Vec<LeaderboardEntry>),
+    /// The server couldn't be reached, so these are as last fetched.
+    Stale(Vec<LeaderboardEntry>),
+}
+
+fn save_cache(cache: &LeaderboardCache) {
+    let path = data_path(LEADERBOARD_PATH);
+    if let Err(err) = cache.save(&path) {
+        warn!("Could not save {}: {}", path.display(), err);
+    }
+}
+
+/// Queues any streak better than the best played here.
+fn queue_streaks(
+    settings: Res<Settings>,
+    rng: Res<GameRng>,
+    mut server: ResMut<LeaderboardServer>,
+    mut cache: ResMut<LeaderboardCache>,
+    mut streak_events: EventReader<StreakEvent>,
+) {
+    for streak in streak_events.iter() {
+        let score = streak.returns as u32;
+        if score <= cache.best {
+            continue;
+        }
+        cache.best = score;
+        cache.queued.push(LeaderboardEntry {
+            name: settings.name.clone().unwrap_or_else(lan::host_name),
+            score,
+            seed: rng.seed(),
+            fjong: env!("CARGO_PKG_VERSION").to_string(),
+            daily: None,
+        });
+        save_cache(&cache);
+        send_now(&mut server);
+    }
+}
+
+/// Queues every new best daily challenge, which the
+/// [`DailyPlugin`](crate::DailyPlugin) has already checked.
+fn queue_daily_scores(
+    settings: Res<Settings>,
+    mut server: ResMut<LeaderboardServer>,
+    mut cache: ResMut<LeaderboardCache>,
+    mut daily_scores: EventReader<DailyScoreEvent>,
+) {
+    for daily in daily_scores.iter() {
+        cache.queued.push(LeaderboardEntry {
+            name: settings.name.clone().unwrap_or_else(lan::host_name),
+            score: daily.score,
+            seed: daily.seed,
+            fjong: env!("CARGO_PKG_VERSION").to_string(),
+            daily: Some(daily.day),
+        });
+        save_cache(&cache);
+        send_now(&mut server);
+    }
+}
+
+/// Sends what is queued right away rather than at the next retry.
+fn send_now(server: &mut LeaderboardServer) {
+    let duration = server.retry_timer.duration();
+    server.retry_timer.set_elapsed(duration);
+}
+
+/// Sends queued scores in the order they were played, keeping any the
+/// server couldn't be reached for until the next try.
+fn send_queued(
+    time: Res<Time>,
+    mut server: ResMut<LeaderboardServer>,
+    mut cache: ResMut<LeaderboardCache>,
+) {
+    let server = &mut *server;
+    if let Some(job) = &server.sending {
+        let sent = match job.poll() {
+            Some(sent) => sent,
+            None => return,
+        };
+        server.sending = None;
+        if let Ok(sent) = sent {
+            let sent = sent.min(cache.queued.len());
+            cache.queued.drain(..sent);
+            save_cache(&cache);
+        }
+    }
+
+    server.retry_timer.tick(time.delta());
+    if cache.queued.is_empty() || !server.retry_timer.finished() {
+        return;
+    }
+    server.retry_timer.reset();
+    let endpoint = server.endpoint.clone();
+    let queued = cache.queued.clone();
+    server.sending = Some(Job::spawn(move || {
+        for (sent, entry) in queued.iter().enumerate() {
+            if let Err(err) = endpoint.submit(entry) {
+                warn!("Could not send scores to the leaderboard: {}", err);
+                return Ok(sent);
+            }
+        }
+        info!("Sent {} scores to the leaderboard", queued.len());
+        Ok(queued.len())
+    }));
+}
+
+fn fetch_top_scores(mut server: ResMut<LeaderboardServer>, mut top_scores: ResMut<TopScores>) {
+    let endpoint = server.endpoint.clone();
+    server.fetching = Some(Job::spawn(move || endpoint.top()));
+    *top_scores = TopScores::Loading;
+}
+
+fn receive_top_scores(
+    mut server: ResMut<LeaderboardServer>,
+    mut cache: ResMut<LeaderboardCache>,
+    mut top_scores: ResMut<TopScores>,
+) {
+    let fetched = match server.fetching.as_ref().and_then(Job::poll) {
+        Some(fetched) => fetched,
+        None => return,
+    };
+    server.fetching = None;
+    match fetched {
+        Ok(top) => {
+            cache.top = top.clone();
+            save_cache(&cache);
+            *top_scores = TopScores::Loaded(top);
+        }
+        Err(err) => {
+            warn!("Could not fetch the leaderboard: {}", err);
+            *top_scores = TopScores::Stale(cache.top.clone());
+        }
+    }
+}
@@ -0,0 +1,1748 @@
+mod achievements;
+pub mod ai;
+mod announcer;
+mod arenas;
+mod backdrop;
+mod branding;
+mod breakout;
+mod camera;
+mod capture;
+mod catch;
+pub mod collision;
+mod config;
+mod console;
+mod crt;
+mod daily;
+mod dash;
+#[cfg(feature = "debug")]
+mod debug;
+mod emote;
+mod fog;
+mod fonts;
+mod framerate;
+mod frenzy;
+mod ghost;
+mod goals;
+mod gravity;
+mod gym;
+mod hud;
+mod indicators;
+mod input;
+mod instant_replay;
+mod interpolate;
+mod job;
+mod kiosk;
+mod lan;
+mod leaderboard;
+pub mod lobby;
+mod locale;
+mod menu;
+mod overlay;
+mod particles;
+mod persist;
+mod policy;
+mod practice;
+mod profiles;
+#[cfg(feature = "rapier")]
+mod rapier;
+mod remote;
+mod replay;
+mod results;
+mod rng;
+#[cfg(feature = "scripting")]
+mod scripting;
+pub mod selfplay;
+mod series;
+mod server;
+pub mod settings;
+mod shrink;
+mod skins;
+mod sound;
+mod spectate;
+mod spin;
+pub mod stats;
+mod storage;
+mod theme;
+mod theme_packs;
+mod time_attack;
+mod toast;
+mod transport;
+#[cfg(feature = "dev")]
+mod tuning;
+mod tween;
+mod webrtc;
+mod widget;
+
+pub use achievements::{Achievement, Achievements, AchievementsPlugin, ACHIEVEMENTS_PATH};
+pub use announcer::{score_call, AnnouncerPlugin};
+pub use arenas::{ArenaLayout, Arenas, ArenasPlugin, CourtSize, GravityWell, Obstacle};
+pub use backdrop::BackdropPlugin;
+pub use branding::{Branding, BrandingPlugin, BRANDING_PATH};
+pub use breakout::{Brick, BrickEvent, Grown, PowerUp};
+pub use camera::{CameraPlugin, GameCamera};
+pub use capture::{CapturePlugin, Clip, ClipFrame, CAPTURES_DIR};
+pub use catch::{Catch, Grip};
+pub use config::GameConfig;
+pub use console::{CommandFn, ConsoleCommand, ConsoleCommands, ConsoleCommandsExt, ConsolePlugin};
+pub use crt::CrtPlugin;
+pub use daily::{DailyBests, DailyChallenge, DailyPlugin, DailyRun, DailyScoreEvent, DAILY_PATH};
+pub use dash::{Dash, DashMeterPlugin};
+#[cfg(feature = "debug")]
+pub use debug::DebugPlugin;
+pub use emote::{Emote, EmoteEvent, EmotePlugin};
+pub use fog::FogPlugin;
+pub use fonts::{FontPlugin, UiFont};
+pub use framerate::FrameLimiterPlugin;
+pub use frenzy::{Frenzy, FrenzyEvent};
+pub use ghost::{GhostPlugin, GhostRecording};
+pub use gym::{FjongEnv, Observation, Step};
+pub use hud::HudPlugin;
+pub use indicators::{IndicatorsPlugin, Possession};
+pub use input::{ControlsPlugin, InputState, KeyBindings, PaddleInput, PaddleKeys};
+pub use instant_replay::{InstantReplayPlugin, LastMoments};
+pub use interpolate::InterpolationPlugin;
+pub use kiosk::{Attract, Kiosk, KioskPlugin};
+pub use lan::{JoinPlugin, LanPlugin, LobbyPlugin, DISCOVERY_PORT};
+pub use leaderboard::{
+    Endpoint, LeaderboardCache, LeaderboardEntry, LeaderboardPlugin, LEADERBOARD_PATH, TOP_COUNT,
+};
+pub use locale::{Locale, LocalePlugin};
+pub use menu::MenuPlugin;
+pub use overlay::OverlayPlugin;
+pub use particles::ParticlesPlugin;
+pub use policy::{Layer, Policy, PolicyBrain, PolicyPlugin};
+pub use practice::{PracticeScore, StreakEvent};
+pub use profiles::{PaddleColor, Profile, ProfileStats, Profiles, ProfilesPlugin, PROFILES_PATH};
+#[cfg(feature = "rapier")]
+pub use rapier::RapierPlugin;
+pub use remote::{Echo, RemoteBotPlugin, RemoteBrain};
+pub use replay::{Replay, ReplayPlugin, ReplayRenderer, REPLAYS_DIR};
+pub use results::{MatchResult, PointResult, ResultsPlugin, RESULTS_DIR};
+pub use rng::GameRng;
+#[cfg(feature = "scripting")]
+pub use scripting::ScriptingPlugin;
+pub use series::Series;
+pub use server::ServerPlugin;
+pub use settings::{AccessibilitySettings, Language, Settings};
+pub use shrink::Shrink;
+pub use skins::{Skin, Skins, SkinsPlugin, SKINS_FOLDER};
+pub use sound::SoundPlugin;
+pub use spectate::{
+    NetStats, PaddleSnapshot, Snapshot, SpectatePlugin, SpectatorCount, SpectatorServerPlugin,
+    WatchPlugin,
+};
+pub use spin::Spin;
+pub use stats::StatsPlugin;
+pub use storage::{config_path, data_path, migrate_legacy_files, migrate_saves};
+pub use theme::ThemePlugin;
+pub use theme_packs::{
+    PackColors, PackSkin, PackSounds, ThemePack, ThemePacks, ThemePacksPlugin, THEME_PACKS_PATH,
+};
+pub use time_attack::{MatchClock, TIME_ATTACK_SECONDS};
+pub use toast::{Toast, ToastPlugin};
+pub use transport::Transport;
+#[cfg(feature = "dev")]
+pub use tuning::TuningPlugin;
+pub use tween::TweenPlugin;
+pub use webrtc::{WebRtcPlugin, WebRtcRoom};
+pub use widget::{OnScreenKey, OnScreenKeyboard, WidgetPlugin};
+
+use bevy::{
+    ecs::{schedule::ShouldRun, system::CommandQueue},
+    hierarchy::despawn_with_children_recursive,
+    math::{const_vec2, const_vec3},
+    prelude::*,
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use ai::{AiLevel, Brain, Difficulty, Predictor};
+use goals::{GoalMouth, GoalPost};
+use instant_replay::AfterReplay;
+
+/// Length of a tick at the default [`TickRate`].
+pub const TIME_STEP: f32 = 1.0 / 60.0;
+
+pub const PADDLE_SIZE: Vec3 = const_vec3!([20.0, 120.0, 0.0]);
+/// How far in from its goal each paddle plays on the classic court.
+pub const GAP_BETWEEN_PADDLE_AND_GOAL: f32 = 60.0;
+pub const PADDLE_SPEED: f32 = 500.0;
+
+// We set the z-value of the ball to 1 so it renders on top in the case of overlapping sprites.
+pub const BALL_STARTING_POSITION: Vec3 = const_vec3!([0.0, 0.0, 1.0]);
+pub const BALL_SIZE: Vec3 = const_vec3!([30.0, 30.0, 0.0]);
+pub const BALL_SPEED: f32 = 400.0;
+pub const BALL_SPEED_X: f32 = 400.0;
+pub const BALL_SPEED_Y: f32 = 50.0;
+/// Seconds the ball waits at the centre after a goal before it is served again.
+pub const SERVE_COOLDOWN: f32 = 0.7;
+
+/// Thickness of the walls and goals on the classic court.
+pub const WALL_THICKNESS: f32 = 10.0;
+// x coordinates
+pub const LEFT_WALL: f32 = -450.;
+pub const RIGHT_WALL: f32 = 450.;
+// y coordinates
+pub const BOTTOM_WALL: f32 = -300.;
+pub const TOP_WALL: f32 = 300.;
+
+const BACKGROUND_COLOR: Color = Color::BLACK;
+const FOREGROUND_COLOR: Color = Color::WHITE;
+
+/// The whole game: simulation, player controls, scoreboard and menus.
+///
+/// Needs `DefaultPlugins` (or at least rendering, text and input) to be added first.
+/// To embed fjong in another app, set it up with the builder methods:
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use fjong::{FjongPlugin, MatchRules};
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugin(
+///         FjongPlugin::default()
+///             .arena_size(Vec2::new(600.0, 400.0))
+///             .rules(MatchRules {
+///                 points_to_win: 5,
+///                 ..default()
+///             }),
+///     )
+///     .run();
+/// ```
+#[derive(Default)]
+pub struct FjongPlugin {
+    layout: ArenaLayout,
+    rules: MatchRules,
+    bindings: KeyBindings,
+    seed: Option<u64>,
+    tick_rate: TickRate,
+    ai: Option<AiLevel>,
+}
+
+impl FjongPlugin {
+    /// Width and height of the court, inside the walls.
+    pub fn arena_size(mut self, size: Vec2) -> FjongPlugin {
+        self.layout.size = size;
+        self
+    }
+
+    pub fn rules(mut self, rules: MatchRules) -> FjongPlugin {
+        self.rules = rules;
+        self
+    }
+
+    pub fn bindings(mut self, bindings: KeyBindings) -> FjongPlugin {
+        self.bindings = bindings;
+        self
+    }
+
+    /// Seed for [`GameRng`], so a match can be played again. Without one the
+    /// seed comes from [`Settings::seed`], or is picked at random.
+    pub fn seed(mut self, seed: u64) -> FjongPlugin {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// How many times a second the simulation ticks, see [`TickRate`].
+    pub fn tick_rate(mut self, hz: u32) -> FjongPlugin {
+        self.tick_rate = TickRate(hz);
+        self
+    }
+
+    /// How the AI plays, over the rules and over [`Settings::ai`], for this
+    /// run only.
+    pub fn ai(mut self, level: AiLevel) -> FjongPlugin {
+        self.ai = Some(level);
+        self
+    }
+}
+
+impl Plugin for FjongPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = Settings::load(config_path(settings::SETTINGS_PATH));
+        let seed = self.seed.or(settings.seed).unwrap_or_else(rand::random);
+        info!("Match seed: {}", seed);
+        let mut rules = self.rules.clone();
+        if let Some(level) = self.ai.or(settings.ai) {
+            level.apply(&mut rules);
+        }
+
+        app.add_state(AppState::MainMenu)
+            .insert_resource(self.layout.clone())
+            .insert_resource(rules)
+            .insert_resource(self.bindings.clone())
+            .insert_resource(self.tick_rate)
+            .insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(settings.accessibility.clone())
+            .insert_resource(Locale::new(settings.language))
+            .insert_resource(settings)
+            .add_plugin(BrandingPlugin)
+            .add_plugin(FontPlugin)
+            .add_plugin(LocalePlugin)
+            .add_plugin(ArenasPlugin)
+            .add_plugin(SimulationPlugin { seed, ..default() })
+            .add_plugin(ControlsPlugin)
+            .add_plugin(CameraPlugin)
+            .add_plugin(InterpolationPlugin)
+            .add_plugin(HudPlugin)
+            .add_plugin(WidgetPlugin)
+            .add_plugin(MenuPlugin)
+            .add_plugin(StatsPlugin)
+            .add_plugin(AchievementsPlugin {
+                path: data_path(ACHIEVEMENTS_PATH),
+            })
+            .add_plugin(ProfilesPlugin {
+                path: data_path(PROFILES_PATH),
+            })
+            .add_plugin(DailyPlugin {
+                path: data_path(DAILY_PATH),
+            })
+            .add_plugin(ResultsPlugin {
+                dir: data_path(RESULTS_DIR),
+            })
+            .add_plugin(ReplayPlugin {
+                dir: data_path(REPLAYS_DIR),
+            })
+            .add_plugin(InstantReplayPlugin)
+            .add_plugin(CapturePlugin {
+                dir: data_path(CAPTURES_DIR),
+            })
+            .add_plugin(SoundPlugin)
+            .add_plugin(AnnouncerPlugin)
+            .add_plugin(ThemePacksPlugin {
+                dir: THEME_PACKS_PATH.into(),
+            })
+            .add_plugin(SkinsPlugin)
+            .add_plugin(ThemePlugin)
+            .add_plugin(BackdropPlugin)
+            .add_plugin(FogPlugin)
+            .add_plugin(DashMeterPlugin)
+            .add_plugin(EmotePlugin)
+            .add_plugin(IndicatorsPlugin)
+            .add_plugin(GhostPlugin)
+            .add_plugin(SpectatePlugin)
+            .add_plugin(LanPlugin)
+            .add_plugin(CrtPlugin)
+            .add_plugin(TweenPlugin)
+            .add_plugin(ParticlesPlugin)
+            .add_plugin(ToastPlugin)
+            .add_plugin(ConsolePlugin)
+            .add_plugin(OverlayPlugin)
+            .add_plugin(FrameLimiterPlugin)
+            .add_system_set(
+                SystemSet::on_enter(AppState::Playing)
+                    .with_system(start_match)
+                    .with_system(time_attack::reset_clock)
+                    .with_system(breakout::restock_bricks),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::SeriesBreak)
+                    .with_system(series::next_game.exclusive_system()),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::MainMenu)
+                    .with_system(series::end_series.exclusive_system())
+                    .with_system(tear_down_match.exclusive_system()),
+            )
+            .add_system(end_match);
+
+        #[cfg(feature = "debug")]
+        app.add_plugin(DebugPlugin);
+        #[cfg(feature = "dev")]
+        app.add_plugin(TuningPlugin);
+        #[cfg(feature = "scripting")]
+        app.add_plugin(ScriptingPlugin);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AppState {
+    MainMenu,
+    Video,
+    Stats,
+    /// The best practice streaks on the leaderboard, see
+    /// [`LeaderboardPlugin`].
+    Leaderboard,
+    /// Every achievement and whether it is unlocked yet, see
+    /// [`AchievementsPlugin`].
+    Achievements,
+    /// Making profiles and picking their paddle colours, see
+    /// [`ProfilesPlugin`].
+    Profiles,
+    /// Picking the rules before a versus match.
+    Setup,
+    /// Today's [`DailyChallenge`], before playing it.
+    Daily,
+    Playing,
+    /// Pushed on top of [`AppState::Playing`], which stays underneath.
+    Paused,
+    /// The end of a match won by a goal, again in slow motion, see
+    /// [`InstantReplayPlugin`].
+    InstantReplay,
+    /// Between the games of a [`Series`].
+    SeriesBreak,
+    /// Watching another game's match, see [`WatchPlugin`], or playing in one
+    /// hosted on the LAN.
+    Spectating,
+    /// Waiting for someone on the LAN to join, see [`LanPlugin`].
+    LanHost,
+    /// Looking for games on the LAN to join.
+    LanLobby,
+    /// Typing in the code of a room to join, see [`LobbyPlugin`].
+    RoomCode,
+    /// Pushed on top of a LAN match while the line between host and guest
+    /// is down.
+    Reconnecting,
+}
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, StageLabel)]
+pub struct SimulationStage;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+pub enum SimulationLabel {
+    /// Every device is sampled into [`InputState`] before anything steers.
+    Input,
+    /// Anything steering the paddles runs before this.
+    Movement,
+    /// Everything is moved by its [`Velocity`].
+    Velocity,
+    Collisions,
+}
+
+/// How often [`SimulationStage`] runs. Either way it only runs while a match
+/// is being played, or always if there is no `State<AppState>`.
+pub enum Stepping {
+    /// Once every [`TickRate::step`] seconds of real time.
+    Fixed,
+    /// Exactly once per `App::update`, for driving the game by hand in tests
+    /// and tools.
+    EveryUpdate,
+}
+
+/// Court, ball, AI and scoring without any rendering or input, so it can
+/// run headless on `MinimalPlugins`.
+///
+/// The simulation never looks at `Time`: it only advances in whole ticks and
+/// draws randomness from [`GameRng`], so a given seed and set of inputs always
+/// produces the same match.
+pub struct SimulationPlugin {
+    pub stepping: Stepping,
+    pub seed: u64,
+}
+
+impl Default for SimulationPlugin {
+    fn default() -> Self {
+        SimulationPlugin {
+            stepping: Stepping::Fixed,
+            seed: rand::random(),
+        }
+    }
+}
+
+impl SimulationPlugin {
+    pub fn stepped(seed: u64) -> SimulationPlugin {
+        SimulationPlugin {
+            stepping: Stepping::EveryUpdate,
+            seed,
+        }
+    }
+}
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        let stage = match self.stepping {
+            Stepping::Fixed => SystemStage::parallel().with_run_criteria(fixed_tick),
+            Stepping::EveryUpdate => SystemStage::parallel().with_run_criteria(every_update),
+        };
+
+        app.init_resource::<Arena>()
+            .init_resource::<ArenaLayout>()
+            .init_resource::<GameConfig>()
+            .init_resource::<MatchRules>()
+            .init_resource::<ai::PendingAi>()
+            .init_resource::<PracticeScore>()
+            .init_resource::<Catch>()
+            .init_resource::<SimTick>()
+            .init_resource::<TickRate>()
+            .init_resource::<Serve>()
+            .init_resource::<TickProgress>()
+            .init_resource::<MatchClock>()
+            .init_resource::<Series>()
+            .init_resource::<Frenzy>()
+            .init_resource::<GhostRecording>()
+            .init_resource::<collision::SpatialHash>()
+            .insert_resource(GameRng::new(self.seed))
+            .insert_resource(Scoreboard {
+                p1_score: 0,
+                p2_score: 0,
+                fjongs: 0,
+            })
+            .add_event::<CollisionEvent>()
+            .add_event::<GoalEvent>()
+            .add_event::<WallBounceEvent>()
+            .add_event::<HitEvent>()
+            .add_event::<MatchOverEvent>()
+            .add_event::<BrickEvent>()
+            .add_event::<FrenzyEvent>()
+            .add_event::<StreakEvent>()
+            .add_startup_system(setup)
+            .add_system(build_court)
+            .add_stage_after(CoreStage::Update, SimulationStage, stage)
+            .add_system_to_stage(
+                SimulationStage,
+                remember_translations.exclusive_system().at_start(),
+            )
+            .add_system_set_to_stage(
+                SimulationStage,
+                SystemSet::new()
+                    .with_system(advance_tick.before(SimulationLabel::Movement))
+                    .with_system(ai::apply_difficulty.before(ai::rubber_band))
+                    .with_system(ai::rubber_band.before(SimulationLabel::Movement))
+                    .with_system(
+                        check_for_collisions
+                            .with_run_criteria(hand_rolled_physics)
+                            .label(SimulationLabel::Collisions),
+                    )
+                    .with_system(
+                        index_colliders
+                            .with_run_criteria(hand_rolled_physics)
+                            .after(clamp_paddles)
+                            .after(resize_paddles)
+                            .after(goals::sweep_goals)
+                            .after(practice::arrange_court)
+                            .after(catch::hold_ball)
+                            .before(SimulationLabel::Collisions),
+                    )
+                    .with_system(serve_ball.before(SimulationLabel::Collisions))
+                    .with_system(score_goals.after(SimulationLabel::Collisions))
+                    .with_system(time_attack::run_clock.after(score_goals))
+                    .with_system(frenzy::whip_up_frenzy.after(score_goals))
+                    .with_system(reset_ball.after(SimulationLabel::Collisions))
+                    .with_system(ai::apply_pending_ai.after(SimulationLabel::Collisions))
+                    .with_system(practice::arrange_court.before(SimulationLabel::Collisions))
+                    .with_system(practice::count_returns.after(SimulationLabel::Collisions))
+                    .with_system(ghost::record_ghost.after(SimulationLabel::Collisions))
+                    .with_system(breakout::arrange_bricks.before(SimulationLabel::Collisions))
+                    .with_system(breakout::score_bricks.after(SimulationLabel::Collisions))
+                    .with_system(breakout::catch_power_ups.after(apply_velocity))
+                    .with_system(breakout::wear_off_power_ups.before(SimulationLabel::Movement))
+                    .with_system(dash::run_dashes.before(SimulationLabel::Movement))
+                    .with_system(shrink::shrink_on_return.after(SimulationLabel::Collisions))
+                    .with_system(
+                        goals::sweep_goals
+                            .after(advance_tick)
+                            .before(SimulationLabel::Collisions),
+                    )
+                    .with_system(
+                        resize_paddles
+                            .after(SimulationLabel::Movement)
+                            .before(SimulationLabel::Collisions),
+                    )
+                    .with_system(catch::catch_ball.after(SimulationLabel::Collisions))
+                    .with_system(
+                        catch::hold_ball
+                            .after(clamp_paddles)
+                            .before(SimulationLabel::Collisions),
+                    )
+                    .with_system(
+                        ai::drive_brains
+                            .label(SimulationLabel::Movement)
+                            .before(SimulationLabel::Collisions),
+                    )
+                    .with_system(
+                        spin::curve_ball
+                            .after(SimulationLabel::Movement)
+                            .before(apply_velocity),
+                    )
+                    .with_system(
+                        gravity::pull_ball
+                            .after(SimulationLabel::Movement)
+                            .before(apply_velocity),
+                    )
+                    .with_system(
+                        apply_velocity
+                            .label(SimulationLabel::Velocity)
+                            .after(SimulationLabel::Movement)
+                            .before(SimulationLabel::Collisions),
+                    )
+                    .with_system(
+                        clamp_paddles
+                            .after(apply_velocity)
+                            .before(SimulationLabel::Collisions),
+                    ),
+            );
+        #[cfg(feature = "rapier")]
+        app.add_plugin(RapierPlugin);
+    }
+}
+
+pub(crate) fn match_in_play(state: Option<Res<State<AppState>>>) -> bool {
+    state.is_none_or(|state| *state.current() == AppState::Playing)
+}
+
+/// Runs the simulation once for every [`TickRate::step`] of real time that passes
+/// during a match. Time spent in menus is not made up for afterwards.
+///
+/// [`AccessibilitySettings::game_speed`] changes how much real time a tick
+/// takes rather than what happens in one, so slowing the game down slows the
+/// ball, the paddles, the AI and the clocks alike, and a seed plays out the
+/// same at any speed.
+fn fixed_tick(
+    time: Res<Time>,
+    tick_rate: Res<TickRate>,
+    state: Option<Res<State<AppState>>>,
+    accessibility: Option<Res<AccessibilitySettings>>,
+    mut progress: ResMut<TickProgress>,
+    mut accumulator: Local<f64>,
+    mut looping: Local<bool>,
+) -> ShouldRun {
+    if !*looping {
+        if !match_in_play(state) {
+            return ShouldRun::No;
+        }
+        let speed = accessibility.map_or(1.0, |accessibility| {
+            accessibility
+                .game_speed
+                .clamp(settings::MIN_GAME_SPEED, settings::MAX_GAME_SPEED)
+        });
+        *accumulator += time.delta_seconds_f64() * speed as f64;
+    }
+
+    let step = tick_rate.step() as f64;
+    if *accumulator >= step {
+        *accumulator -= step;
+        *looping = true;
+        ShouldRun::YesAndCheckAgain
+    } else {
+        *looping = false;
+        progress.0 = (*accumulator / step) as f32;
+        ShouldRun::No
+    }
+}
+
+/// Whether the game's own collisions run, rather than Rapier's with the
+/// `rapier` feature.
+fn hand_rolled_physics() -> ShouldRun {
+    if cfg!(feature = "rapier") {
+        ShouldRun::No
+    } else {
+        ShouldRun::Yes
+    }
+}
+
+fn every_update(state: Option<Res<State<AppState>>>) -> ShouldRun {
+    if match_in_play(state) {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+#[derive(Component)]
+pub struct P1Paddle;
+
+#[derive(Component)]
+pub struct P2Paddle;
+
+#[derive(Component)]
+pub struct Ball;
+
+#[derive(Component)]
+pub struct P1Goal;
+
+#[derive(Component)]
+pub struct P2Goal;
+
+#[derive(Component, Deref, DerefMut)]
+pub struct Velocity(pub Vec2);
+
+/// Where the entity was before the current tick moved it, so it can be drawn
+/// part of the way between the two, see [`InterpolationPlugin`].
+#[derive(Component, Default)]
+pub struct Interpolated {
+    pub previous: Vec3,
+}
+
+#[derive(Component)]
+pub struct Collider;
+
+#[derive(Default)]
+pub struct CollisionEvent;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Player {
+    P1,
+    P2,
+}
+
+impl Player {
+    pub fn other(self) -> Player {
+        match self {
+            Player::P1 => Player::P2,
+            Player::P2 => Player::P1,
+        }
+    }
+}
+
+pub struct GoalEvent {
+    pub scorer: Player,
+}
+
+/// The ball bounced off the top or bottom wall.
+#[derive(Default)]
+pub struct WallBounceEvent;
+
+/// The ball came off the face of a player's paddle.
+pub struct HitEvent {
+    pub player: Player,
+    /// Where along the paddle the ball struck, from -1 at the bottom edge to
+    /// 1 at the top.
+    pub offset: f32,
+    /// The ball came off the very edge of the paddle and was sent back faster.
+    pub smash: bool,
+}
+
+pub struct MatchOverEvent {
+    pub winner: Player,
+}
+
+#[derive(Component)]
+pub struct Wall;
+
+/// Part of the court built from the [`ArenaLayout`], torn down when another
+/// layout is picked.
+#[derive(Component)]
+pub(crate) struct CourtPiece;
+
+/// Belongs to the match: the court, the ball, the paddles and the HUD. All
+/// of it is cleared away and put up fresh on the way back to the main menu,
+/// so nothing from one match is left over in the next.
+#[derive(Component)]
+pub struct GameEntity;
+
+/// A screen of the menus, cleared away along with the match if the state it
+/// belongs to was left without taking it down.
+#[derive(Component)]
+pub struct MenuEntity;
+
+#[derive(Bundle)]
+struct WallBundle {
+    #[bundle]
+    sprite_bundle: SpriteBundle,
+    wall: Wall,
+    collider: Collider,
+}
+
+/// Bounds of the playing field, and how the court around them is built.
+/// Anything missing from a saved one, like a replay's, is as on the classic
+/// court.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Arena {
+    pub left: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub top: f32,
+    pub wall_thickness: f32,
+    /// How far in from its goal each paddle plays.
+    pub paddle_gap: f32,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Arena {
+            left: LEFT_WALL,
+            right: RIGHT_WALL,
+            bottom: BOTTOM_WALL,
+            top: TOP_WALL,
+            wall_thickness: WALL_THICKNESS,
+            paddle_gap: GAP_BETWEEN_PADDLE_AND_GOAL,
+        }
+    }
+}
+
+impl Arena {
+    /// An arena of the given width and height, centred on the origin, with
+    /// the classic court's walls and paddle gap.
+    pub fn from_size(size: Vec2) -> Arena {
+        Arena {
+            left: -size.x / 2.0,
+            right: size.x / 2.0,
+            bottom: -size.y / 2.0,
+            top: size.y / 2.0,
+            ..default()
+        }
+    }
+
+    /// The arena `layout` asks for.
+    pub fn from_layout(layout: &ArenaLayout) -> Arena {
+        Arena {
+            wall_thickness: layout.wall_thickness,
+            paddle_gap: layout.paddle_gap,
+            ..Arena::from_size(layout.size)
+        }
+    }
+
+    /// Where `end`'s paddle plays, across the court.
+    pub fn paddle_x(&self, end: Player) -> f32 {
+        match end {
+            Player::P1 => self.left + self.paddle_gap,
+            Player::P2 => self.right - self.paddle_gap,
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> f32 {
+        self.top - self.bottom
+    }
+
+    /// Lowest and highest a paddle of the given height can be centred
+    /// without poking into the walls.
+    pub fn paddle_bounds(&self, paddle_height: f32) -> (f32, f32) {
+        (
+            self.bottom + paddle_height / 2.0,
+            self.top - paddle_height / 2.0,
+        )
+    }
+}
+
+enum WallLocation {
+    Bottom,
+    Top,
+    /// Across P2's end of the court, in practice.
+    Return,
+}
+
+impl WallLocation {
+    fn position(&self, arena: &Arena) -> Vec2 {
+        match self {
+            WallLocation::Bottom => Vec2::new(0.0, arena.bottom),
+            WallLocation::Top => Vec2::new(0.0, arena.top),
+            WallLocation::Return => Vec2::new(arena.paddle_x(Player::P2), 0.0),
+        }
+    }
+
+    fn size(&self, arena: &Arena) -> Vec2 {
+        let arena_width = arena.width();
+        let arena_height = arena.height();
+        let thickness = arena.wall_thickness;
+
+        match self {
+            WallLocation::Bottom => Vec2::new(arena_width + thickness, thickness),
+            WallLocation::Top => Vec2::new(arena_width + thickness, thickness),
+            WallLocation::Return => Vec2::new(thickness, arena_height),
+        }
+    }
+}
+
+impl WallBundle {
+    fn new(location: WallLocation, arena: &Arena) -> WallBundle {
+        WallBundle::block(location.position(arena), location.size(arena))
+    }
+
+    /// A wall of any size, anywhere.
+    fn block(position: Vec2, size: Vec2) -> WallBundle {
+        WallBundle {
+            sprite_bundle: SpriteBundle {
+                transform: Transform {
+                    translation: position.extend(0.0),
+                    scale: size.extend(1.0),
+                    ..default()
+                },
+                sprite: Sprite {
+                    color: FOREGROUND_COLOR,
+                    ..default()
+                },
+                ..default()
+            },
+            wall: Wall,
+            collider: Collider,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Scoreboard {
+    pub p1_score: usize,
+    pub p2_score: usize,
+    pub fjongs: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum GameMode {
+    /// P1 against P2, first to [`MatchRules::points_to_win`].
+    Versus,
+    /// P1 alone against a wall, counting returns until they miss.
+    Practice,
+    /// P1 against P2 for [`TIME_ATTACK_SECONDS`], most points wins. A tie
+    /// goes to sudden death.
+    TimeAttack,
+    /// Versus with a wall of [`Brick`]s in mid-court, each worth a point to
+    /// whoever breaks it.
+    Breakout,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MatchRules {
+    pub mode: GameMode,
+    pub points_to_win: usize,
+    /// Points a goal is worth.
+    pub points_per_goal: usize,
+    /// Let the AI ease off when ahead and try harder when behind.
+    pub adaptive_ai: bool,
+    /// Let players catch the ball by holding a button, and aim the release.
+    pub sticky_paddles: bool,
+    /// How good the AI playing P2 is.
+    pub difficulty: Difficulty,
+    /// At deuce, play on until someone is two points clear.
+    pub win_by_two: bool,
+    /// Paddles [`Shrink`] with every return, back to full size after a goal.
+    pub shrinking_paddles: bool,
+    /// Goals are a narrower opening that slides up and down each end, with
+    /// wall either side of it.
+    pub moving_goals: bool,
+    /// The ball can't be seen in the middle third of the court.
+    pub fog_of_war: bool,
+    /// Up on the controls moves the paddle down, and down moves it up, for
+    /// both players.
+    pub mirror_controls: bool,
+    /// Games in a [`Series`], most of which wins it. A single game when 1.
+    pub best_of: usize,
+    pub p1_handicap: Handicap,
+    pub p2_handicap: Handicap,
+}
+
+/// Evens out a match between players of different strength, either by
+/// helping the weaker one along or by holding the stronger one back.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct Handicap {
+    /// Points the player starts the match on.
+    pub head_start: usize,
+    /// Multiplier on the height of the player's paddle.
+    pub paddle_scale: f32,
+    /// Multiplier on how fast the player sends the ball back.
+    pub return_speed: f32,
+}
+
+impl Default for Handicap {
+    fn default() -> Self {
+        Handicap {
+            head_start: 0,
+            paddle_scale: 1.0,
+            return_speed: 1.0,
+        }
+    }
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        MatchRules {
+            mode: GameMode::Versus,
+            points_to_win: 11,
+            points_per_goal: 1,
+            adaptive_ai: false,
+            sticky_paddles: false,
+            difficulty: Difficulty::Medium,
+            win_by_two: false,
+            shrinking_paddles: false,
+            moving_goals: false,
+            fog_of_war: false,
+            mirror_controls: false,
+            best_of: 1,
+            p1_handicap: Handicap::default(),
+            p2_handicap: Handicap::default(),
+        }
+    }
+}
+
+impl MatchRules {
+    pub fn handicap(&self, player: Player) -> &Handicap {
+        match player {
+            Player::P1 => &self.p1_handicap,
+            Player::P2 => &self.p2_handicap,
+        }
+    }
+
+    /// Whether a player on `score` has won against one on `other`.
+    pub fn is_winning(&self, score: usize, other: usize) -> bool {
+        score >= self.points_to_win && (!self.win_by_two || score >= other + 2)
+    }
+
+    /// The match is won by reaching [`MatchRules::points_to_win`], rather
+    /// than on the clock or not at all.
+    pub fn plays_to_points(&self) -> bool {
+        matches!(self.mode, GameMode::Versus | GameMode::Breakout)
+    }
+
+    /// Both players are within a point of winning a match that has to be won
+    /// by two.
+    pub fn deuce(&self, scoreboard: &Scoreboard) -> bool {
+        let trailing = scoreboard.p1_score.min(scoreboard.p2_score);
+        self.plays_to_points() && self.win_by_two && trailing + 1 >= self.points_to_win
+    }
+
+    /// The next goal could win the match.
+    pub fn game_point(&self, scoreboard: &Scoreboard) -> bool {
+        self.game_point_for(scoreboard, Player::P1) || self.game_point_for(scoreboard, Player::P2)
+    }
+
+    /// The next goal for whoever is at `end` would win them the match.
+    pub fn game_point_for(&self, scoreboard: &Scoreboard, end: Player) -> bool {
+        let (score, other) = match end {
+            Player::P1 => (scoreboard.p1_score, scoreboard.p2_score),
+            Player::P2 => (scoreboard.p2_score, scoreboard.p1_score),
+        };
+        self.plays_to_points() && self.is_winning(score + self.points_per_goal, other)
+    }
+}
+
+/// Number of simulation ticks played so far.
+#[derive(Default)]
+pub struct SimTick(pub u64);
+
+/// How many times a second the simulation ticks, 60 unless one is inserted
+/// before [`SimulationPlugin`] is added. Speeds, accelerations and spin are
+/// all per second, so a faster rate moves things in smaller steps rather
+/// than faster, and waits, like [`GameConfig::serve_cooldown`], are kept in
+/// seconds and counted out in [`TickRate::ticks`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TickRate(pub u32);
+
+impl TickRate {
+    /// Seconds of game time in one tick.
+    pub fn step(&self) -> f32 {
+        1.0 / self.0 as f32
+    }
+
+    /// The nearest whole number of ticks to `seconds`.
+    pub fn ticks(&self, seconds: f32) -> u32 {
+        (seconds / self.step()).round() as u32
+    }
+}
+
+impl Default for TickRate {
+    fn default() -> Self {
+        TickRate((1.0 / TIME_STEP).round() as u32)
+    }
+}
+
+/// How far real time has got towards the next tick, from 0 just after one to
+/// nearly 1 just before the next. Stays at 1 when the simulation is
+/// [`Stepping::EveryUpdate`], which has nothing in between.
+pub struct TickProgress(pub f32);
+
+impl Default for TickProgress {
+    fn default() -> Self {
+        TickProgress(1.0)
+    }
+}
+
+/// Between points the ball glides back to the centre for a few ticks, then
+/// gets served towards `direction`.
+pub struct Serve {
+    ticks_left: u32,
+    total_ticks: u32,
+    direction: f32,
+    /// Where the ball was when the last point ended.
+    from: Vec3,
+}
+
+/// The opening serve, counted out at whatever [`TickRate`] was inserted.
+impl FromWorld for Serve {
+    fn from_world(world: &mut World) -> Self {
+        let tick_rate = world
+            .get_resource::<TickRate>()
+            .copied()
+            .unwrap_or_default();
+        let cooldown = world
+            .get_resource::<GameConfig>()
+            .map_or(SERVE_COOLDOWN, |config| config.serve_cooldown);
+        Serve::new(tick_rate.ticks(cooldown))
+    }
+}
+
+impl Serve {
+    /// Serves from the centre towards P1 after `ticks`.
+    fn new(ticks: u32) -> Self {
+        Serve {
+            ticks_left: ticks,
+            total_ticks: ticks,
+            direction: -1.0,
+            from: BALL_STARTING_POSITION,
+        }
+    }
+
+    pub fn in_progress(&self) -> bool {
+        self.ticks_left > 0
+    }
+
+    /// The end the ball is served towards.
+    pub fn towards(&self) -> Player {
+        if self.direction < 0.0 {
+            Player::P1
+        } else {
+            Player::P2
+        }
+    }
+}
+
+fn setup(mut commands: Commands, config: Res<GameConfig>, arena: Res<Arena>) {
+    spawn_match(&mut commands, &config, &arena);
+}
+
+/// Clears away everything left of the last match, and any menu screen left
+/// up, and puts up a fresh court, ball and paddles for the next one. The
+/// paddles keep their brains, so the AI is still playing whoever it was.
+pub fn tear_down_match(world: &mut World) {
+    // Nothing has happened yet to what `setup` put up.
+    if world.resource::<SimTick>().0 == 0 {
+        return;
+    }
+
+    let mut brains: Vec<(bool, Option<Brain>)> = paddle_ends(world)
+        .into_iter()
+        .map(|(entity, is_p1)| (is_p1, world.entity_mut(entity).remove::<Brain>()))
+        .collect();
+    let leftovers: Vec<Entity> = world
+        .query_filtered::<Entity, Or<(With<GameEntity>, With<MenuEntity>)>>()
+        .iter(world)
+        .collect();
+    for entity in leftovers {
+        despawn_with_children_recursive(world, entity);
+    }
+
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, world);
+    spawn_match(&mut commands, world.resource(), world.resource());
+    queue.apply(world);
+
+    for (entity, is_p1) in paddle_ends(world) {
+        let brain = match brains.iter_mut().find(|(was_p1, _)| *was_p1 == is_p1) {
+            Some((_, brain)) => brain.take(),
+            None => continue,
+        };
+        let mut paddle = world.entity_mut(entity);
+        match brain {
+            Some(brain) => {
+                paddle.insert(brain);
+            }
+            None => {
+                paddle.remove::<Brain>();
+            }
+        }
+    }
+    // The court goes back up as if its layout had just been picked.
+    world.resource_mut::<ArenaLayout>().set_changed();
+}
+
+/// Every paddle, and whether it is P1's.
+fn paddle_ends(world: &mut World) -> Vec<(Entity, bool)> {
+    world
+        .query_filtered::<(Entity, Option<&P1Paddle>), AnyPaddle>()
+        .iter(world)
+        .map(|(entity, p1_paddle)| (entity, p1_paddle.is_some()))
+        .collect()
+}
+
+fn spawn_match(commands: &mut Commands, config: &GameConfig, arena: &Arena) {
+    let p1_paddle_x = arena.paddle_x(Player::P1);
+    let p2_paddle_x = arena.paddle_x(Player::P2);
+
+    // P1 paddle
+    commands
+        .spawn()
+        .insert(P1Paddle)
+        .insert_bundle(SpriteBundle {
+            transform: Transform {
+                translation: Vec3::new(p1_paddle_x, 0.0, 0.0),
+                scale: config.paddle_size.extend(0.0),
+                ..default()
+            },
+            sprite: Sprite {
+                color: FOREGROUND_COLOR,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Velocity(const_vec2!([0.0, 0.0])))
+        .insert(Grip::default())
+        .insert(Dash::default())
+        .insert(Shrink::default())
+        .insert(Interpolated::default())
+        .insert(Collider)
+        .insert(GameEntity);
+    //
+    // P2 paddle
+    commands
+        .spawn()
+        .insert(P2Paddle)
+        .insert_bundle(SpriteBundle {
+            transform: Transform {
+                translation: Vec3::new(p2_paddle_x, 0.0, 0.0),
+                scale: config.paddle_size.extend(0.0),
+                ..default()
+            },
+            sprite: Sprite {
+                color: FOREGROUND_COLOR,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Velocity(const_vec2!([0.0, 0.0])))
+        .insert(Grip::default())
+        .insert(Dash::default())
+        .insert(Shrink::default())
+        .insert(Brain::new(Predictor::new(Difficulty::Medium)))
+        .insert(Interpolated::default())
+        .insert(Collider)
+        .insert(GameEntity);
+
+    // Ball
+    commands
+        .spawn()
+        .insert(Ball)
+        .insert_bundle(SpriteBundle {
+            transform: Transform {
+                scale: BALL_SIZE,
+                translation: BALL_STARTING_POSITION,
+                ..default()
+            },
+            sprite: Sprite {
+                color: FOREGROUND_COLOR,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Velocity(Vec2::ZERO))
+        .insert(Spin::default())
+        .insert(Interpolated::default())
+        .insert(GameEntity);
+}
+
+/// Puts up the walls, goals and obstacles of the current [`ArenaLayout`], and
+/// moves the paddles to their ends of it, whenever a layout is picked.
+fn build_court(
+    mut commands: Commands,
+    layout: Res<ArenaLayout>,
+    mut arena: ResMut<Arena>,
+    court_query: Query<Entity, With<CourtPiece>>,
+    mut paddle_query: Query<(&mut Transform, Option<&P1Paddle>), AnyPaddle>,
+) {
+    if !layout.is_changed() {
+        return;
+    }
+
+    *arena = Arena::from_layout(&layout);
+    for entity in court_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    commands
+        .spawn_bundle(WallBundle::new(WallLocation::Bottom, &arena))
+        .insert_bundle((CourtPiece, GameEntity));
+    commands
+        .spawn_bundle(WallBundle::new(WallLocation::Top, &arena))
+        .insert_bundle((CourtPiece, GameEntity));
+
+    // Whatever part of each end isn't goal is wall. Moving goals take the
+    // posts along with them as they go.
+    let mouth = GoalMouth::fixed(&layout, &arena);
+    for (x, end) in [(arena.left, Player::P1), (arena.right, Player::P2)] {
+        let mut goal = commands.spawn();
+        match end {
+            Player::P1 => goal.insert(P1Goal),
+            Player::P2 => goal.insert(P2Goal),
+        };
+        goal.insert_bundle(SpriteBundle {
+            transform: Transform {
+                translation: Vec3::new(x, mouth.y, 0.0),
+                scale: Vec3::new(arena.wall_thickness, mouth.height, 1.0),
+                ..default()
+            },
+            sprite: Sprite {
+                color: BACKGROUND_COLOR,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(Collider)
+        .insert_bundle((CourtPiece, GameEntity));
+
+        for above in [false, true] {
+            let (y, height) = mouth.post(above);
+            commands
+                .spawn_bundle(WallBundle::block(
+                    Vec2::new(x, y),
+                    Vec2::new(arena.wall_thickness, height),
+                ))
+                .insert(GoalPost { end, above })
+                .insert_bundle((CourtPiece, GameEntity));
+        }
+    }
+
+    for obstacle in &layout.obstacles {
+        commands
+            .spawn_bundle(WallBundle::block(obstacle.position, obstacle.size))
+            .insert_bundle((CourtPiece, GameEntity));
+    }
+
+    for (mut transform, p1) in paddle_query.iter_mut() {
+        let end = if p1.is_some() { Player::P1 } else { Player::P2 };
+        transform.translation.x = arena.paddle_x(end);
+        let (bottom_bound, top_bound) = arena.paddle_bounds(transform.scale.y);
+        transform.translation.y = transform.translation.y.clamp(bottom_bound, top_bound);
+    }
+}
+
+fn advance_tick(mut tick: ResMut<SimTick>) {
+    tick.0 += 1;
+}
+
+/// First thing each tick, before anything has moved.
+fn remember_translations(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        interpolated.previous = transform.translation;
+    }
+}
+
+fn apply_velocity(
+    tick_rate: Res<TickRate>,
+    mut query: Query<(&mut Transform, &Velocity, Option<&Ball>)>,
+) {
+    let step = tick_rate.step();
+    for (mut transform, velocity, ball) in query.iter_mut() {
+        // Rapier moves the ball itself.
+        if cfg!(feature = "rapier") && ball.is_some() {
+            continue;
+        }
+        transform.translation.x += velocity.x * step;
+        transform.translation.y += velocity.y * step;
+    }
+}
+
+/// Stops every paddle at the walls once it has moved, whether a player or a
+/// bot is driving it, and takes away whatever speed was carrying it further,
+/// so a paddle pinned there doesn't put spin on the ball.
+fn clamp_paddles(arena: Res<Arena>, mut query: Query<(&mut Transform, &mut Velocity), AnyPaddle>) {
+    for (mut transform, mut velocity) in query.iter_mut() {
+        let (bottom_bound, top_bound) = arena.paddle_bounds(transform.scale.y);
+        let y = transform.translation.y;
+        if y < bottom_bound || y > top_bound {
+            transform.translation.y = y.clamp(bottom_bound, top_bound);
+            velocity.y = 0.0;
+        }
+    }
+}
+
+fn reset_ball(
+    config: Res<GameConfig>,
+    tick_rate: Res<TickRate>,
+    mut serve: ResMut<Serve>,
+    mut goal_events: EventReader<GoalEvent>,
+    mut ball_query: Query<(&mut Velocity, &mut Spin, &Transform), With<Ball>>,
+) {
+    // Only the latest goal matters if several landed in one tick.
+    if let Some(goal) = goal_events.iter().last() {
+        let (mut ball_velocity, mut spin, ball_transform) = ball_query.single_mut();
+
+        serve.from = ball_transform.translation;
+        // Serve towards whoever just conceded.
+        serve.direction = match goal.scorer {
+            Player::P1 => 1.0,
+            Player::P2 => -1.0,
+        };
+        serve.ticks_left = tick_rate.ticks(config.serve_cooldown);
+        serve.total_ticks = serve.ticks_left;
+        ball_velocity.0 = Vec2::ZERO;
+        spin.0 = 0.0;
+    }
+}
+
+fn serve_ball(
+    config: Res<GameConfig>,
+    mut serve: ResMut<Serve>,
+    mut rng: ResMut<GameRng>,
+    mut ball_query: Query<(&mut Velocity, &mut Transform, &mut Sprite), With<Ball>>,
+) {
+    if !serve.in_progress() {
+        return;
+    }
+
+    let (mut ball_velocity, mut ball_transform, mut sprite) = ball_query.single_mut();
+    serve.ticks_left -= 1;
+
+    // Ease back to the centre, fading in on the way.
+    let t = 1.0 - serve.ticks_left as f32 / serve.total_ticks as f32;
+    let eased = t * t * (3.0 - 2.0 * t);
+    ball_transform.translation = serve.from.lerp(BALL_STARTING_POSITION, eased);
+    sprite.color.set_a(t);
+
+    if !serve.in_progress() {
+        // Up or down, at anywhere from half to the full serve angle.
+        let lift = rng.gen_range(0.5..=1.0) * if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+        ball_velocity.0 = config.serve_speed * Vec2::new(serve.direction, lift);
+    }
+}
+
+fn score_goals(
+    rules: Res<MatchRules>,
+    frenzy: Res<Frenzy>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut goal_events: EventReader<GoalEvent>,
+    mut match_over_events: EventWriter<MatchOverEvent>,
+) {
+    // Practice keeps its own count, see `practice::count_returns`.
+    if rules.mode == GameMode::Practice {
+        return;
+    }
+
+    for goal in goal_events.iter() {
+        if scoreboard.fjongs >= 5 {
+            scoreboard.fjongs = 2;
+        }
+        let points = rules.points_per_goal * frenzy.points_multiplier();
+        award_points(
+            &rules,
+            &mut scoreboard,
+            goal.scorer,
+            points,
+            &mut match_over_events,
+        );
+    }
+}
+
+/// Adds `points` to `player`'s score, and ends the match if that wins it.
+fn award_points(
+    rules: &MatchRules,
+    scoreboard: &mut Scoreboard,
+    player: Player,
+    points: usize,
+    match_over_events: &mut EventWriter<MatchOverEvent>,
+) {
+    let Scoreboard {
+        p1_score, p2_score, ..
+    } = scoreboard;
+    let (score, other) = match player {
+        Player::P1 => (p1_score, *p2_score),
+        Player::P2 => (p2_score, *p1_score),
+    };
+    let before = *score;
+    *score += points;
+
+    // Only the points that take a player over the line end the match. Time
+    // attack ends on the clock instead, see `time_attack::run_clock`.
+    let crossed = !rules.is_winning(before, other) && rules.is_winning(*score, other);
+    if crossed && rules.plays_to_points() {
+        match_over_events.send(MatchOverEvent { winner: player });
+    }
+}
+
+/// Fresh scores, less any head starts, and a new opening serve whenever a
+/// match starts.
+#[allow(clippy::too_many_arguments)]
+fn start_match(
+    config: Res<GameConfig>,
+    tick_rate: Res<TickRate>,
+    rules: Res<MatchRules>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut practice_score: ResMut<PracticeScore>,
+    mut frenzy: ResMut<Frenzy>,
+    mut ghost_recording: ResMut<GhostRecording>,
+    mut catch: ResMut<Catch>,
+    mut serve: ResMut<Serve>,
+    mut rng: ResMut<GameRng>,
+    mut ball_query: Query<(&mut Velocity, &mut Spin, &Transform), With<Ball>>,
+) {
+    let (mut ball_velocity, mut spin, ball_transform) = ball_query.single_mut();
+
+    // Every match plays out from the seed, however long was spent in menus.
+    *rng = GameRng::new(rng.seed());
+    *scoreboard = Scoreboard {
+        p1_score: rules.p1_handicap.head_start,
+        p2_score: rules.p2_handicap.head_start,
+        ..default()
+    };
+    practice_score.returns = 0;
+    *frenzy = Frenzy::default();
+    // Keep the last streak of the previous match for the ghost to replay.
+    ghost_recording.current.clear();
+    catch.0 = None;
+    *serve = Serve {
+        from: ball_transform.translation,
+        ..Serve::new(tick_rate.ticks(config.serve_cooldown))
+    };
+    ball_velocity.0 = Vec2::ZERO;
+    spin.0 = 0.0;
+}
+
+type AnyPaddle = Or<(With<P1Paddle>, With<P2Paddle>)>;
+
+/// Sizes the paddles from [`GameConfig::paddle_size`], each player's
+/// [`Handicap::paddle_scale`], any power-up they've caught and how far they
+/// have [`Shrink`]ed this rally, and keeps them inside the court at that
+/// size. Collisions go by the paddles' scale, so they follow along.
+#[allow(clippy::type_complexity)]
+fn resize_paddles(
+    config: Res<GameConfig>,
+    rules: Res<MatchRules>,
+    arena: Res<Arena>,
+    mut paddle_query: Query<
+        (
+            &mut Transform,
+            Option<&P1Paddle>,
+            Option<&Grown>,
+            Option<&Shrink>,
+        ),
+        AnyPaddle,
+    >,
+) {
+    for (mut transform, p1, grown, shrink) in paddle_query.iter_mut() {
+        let player = if p1.is_some() { Player::P1 } else { Player::P2 };
+        let mut height = config.paddle_size.y * rules.handicap(player).paddle_scale;
+        if grown.is_some() {
+            height *= breakout::GROW_SCALE;
+        }
+        if let Some(shrink) = shrink {
+            height *= shrink.0;
+        }
+        let size = Vec2::new(config.paddle_size.x, height);
+        if transform.scale.truncate() == size {
+            continue;
+        }
+        let (bottom_bound, top_bound) = arena.paddle_bounds(size.y);
+        transform.scale = size.extend(0.0);
+        transform.translation.y = transform.translation.y.clamp(bottom_bound, top_bound);
+    }
+}
+
+fn end_match(
+    mut commands: Commands,
+    rules: Res<MatchRules>,
+    mut series: ResMut<Series>,
+    mut state: ResMut<State<AppState>>,
+    mut match_over_events: EventReader<MatchOverEvent>,
+    mut goal_events: EventReader<GoalEvent>,
+) {
+    let scored = goal_events.iter().count() > 0;
+    if let Some(match_over) = match_over_events.iter().last() {
+        let next = if rules.best_of > 1 {
+            series.record(match_over.winner);
+            AppState::SeriesBreak
+        } else {
+            AppState::MainMenu
+        };
+        // Nothing to do if something else already changed the state.
+        if scored {
+            // Show the goal that won it again first.
+            commands.insert_resource(AfterReplay(next));
+            let _ = state.set(AppState::InstantReplay);
+        } else {
+            let _ = state.set(next);
+        }
+    }
+}
+
+type MovedCollider = (
+    With<Collider>,
+    Without<Ball>,
+    Or<(Changed<Transform>, Added<Collider>)>,
+);
+
+/// Keeps the [`SpatialHash`](collision::SpatialHash) of colliders up to date
+/// with where they are and how big, for [`check_for_collisions`] to look up
+/// what is near the ball in. Anything despawned while the simulation wasn't
+/// ticking is left in, and skipped when it is looked up.
+fn index_colliders(
+    mut colliders: ResMut<collision::SpatialHash>,
+    moved_query: Query<(Entity, &Transform), MovedCollider>,
+    removed: RemovedComponents<Collider>,
+) {
+    for entity in removed.iter() {
+        colliders.remove(entity);
+    }
+    for (entity, transform) in moved_query.iter() {
+        colliders.insert(
+            entity,
+            transform.translation.truncate(),
+            transform.scale.truncate(),
+        );
+    }
+}
+
+/// Sends the ball back off the face of `player`'s paddle, heading `towards`
+/// the side it came off, -1 for left and 1 for right. It is angled by where
+/// on the face it struck, and faster the longer the rally, off the edges and
+/// for a player with a faster [`Handicap::return_speed`].
+pub(crate) fn return_ball(
+    config: &GameConfig,
+    rules: &MatchRules,
+    scoreboard: &mut Scoreboard,
+    player: Player,
+    towards: f32,
+    paddle: &Transform,
+    ball: &Transform,
+) -> (Vec2, HitEvent) {
+    scoreboard.fjongs += 1;
+    let relative_y = ball.translation.y - paddle.translation.y;
+    let offset = (relative_y / (paddle.scale.y / 2.0)).clamp(-1.0, 1.0);
+    let speed = config.ball_speed * config.rally_speed_multiplier(scoreboard.fjongs);
+    let mut velocity = collision::paddle_bounce(speed, offset, towards);
+
+    let smash = offset.abs() >= 1.0 - config.smash_edge;
+    if smash {
+        velocity *= config.smash_multiplier;
+    }
+    velocity *= rules.handicap(player).return_speed;
+    (
+        velocity,
+        HitEvent {
+            player,
+            offset,
+            smash,
+        },
+    )
+}
+
+/// A moving paddle drags the ball along with it, and sets it spinning.
+pub(crate) fn drag_ball(
+    config: &GameConfig,
+    paddle_velocity: Vec2,
+    ball_velocity: &mut Vec2,
+    spin: &mut Spin,
+) {
+    ball_velocity.y += paddle_velocity.y * config.spin_transfer;
+    *spin = Spin::from_hit(config, paddle_velocity, *ball_velocity);
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn check_for_collisions(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    rules: Res<MatchRules>,
+    tick_rate: Res<TickRate>,
+    mut scoreboard: ResMut<Scoreboard>,
+    serve: Res<Serve>,
+    catch: Res<Catch>,
+    colliders: Res<collision::SpatialHash>,
+    mut ball_query: Query<(&mut Velocity, &mut Transform, &mut Spin), With<Ball>>,
+    collider_query: Query<
+        (
+            Entity,
+            &Transform,
+            Option<&Velocity>,
+            Option<&P1Goal>,
+            Option<&P2Goal>,
+            Option<&P1Paddle>,
+            Option<&P2Paddle>,
+            Option<&Brick>,
+        ),
+        (With<Collider>, Without<Ball>),
+    >,
+    mut collision_events: EventWriter<CollisionEvent>,
+    mut goal_events: EventWriter<GoalEvent>,
+    mut wall_bounce_events: EventWriter<WallBounceEvent>,
+    mut hit_events: EventWriter<HitEvent>,
+    mut brick_events: EventWriter<BrickEvent>,
+) {
+    // The ball is out of play until it has been served, and while it is
+    // held.
+    if serve.in_progress() || catch.holding() {
+        return;
+    }
+
+    let (mut ball_velocity, mut ball_transform, mut spin) = ball_query.single_mut();
+    let ball_size = ball_transform.scale.truncate();
+    // How far the ball moved this tick, to tell which side of a collider it
+    // came in through.
+    let travel = ball_velocity.0 * tick_rate.step();
+    // Only what is near where the ball went this tick can be touching it.
+    let near = colliders.near(
+        ball_transform.translation.truncate() - travel / 2.0,
+        ball_size + travel.abs(),
+    );
+
+    // wall collision
+    for (
+        entity,
+        transform,
+        maybe_velocity,
+        maybe_p1_goal,
+        maybe_p2_goal,
+        maybe_p1_paddle,
+        maybe_p2_paddle,
+        maybe_brick,
+    ) in near
+        .into_iter()
+        .filter_map(|entity| collider_query.get(entity).ok())
+    {
+        let contact = collision::swept_contact(
+            ball_transform.translation.truncate(),
+            ball_size,
+            travel,
+            transform.translation.truncate(),
+            transform.scale.truncate(),
+        );
+
+        if let Some(contact) = contact {
+            collision_events.send_default();
+
+            if maybe_p1_goal.is_some() {
+                goal_events.send(GoalEvent { scorer: Player::P2 });
+                continue;
+            }
+
+            if maybe_p2_goal.is_some() {
+                goal_events.send(GoalEvent { scorer: Player::P1 });
+                continue;
+            }
+
+            // Get clear of the collider first, so the ball can't be caught
+            // inside it and bounce again next tick.
+            ball_transform.translation += contact.push_out.extend(0.0);
+            ball_velocity.0 = contact.reflect(ball_velocity.0);
+
+            if maybe_p1_paddle.is_none() && maybe_p2_paddle.is_none() {
+                wall_bounce_events.send_default();
+            }
+
+            // Bricks bounce the ball once and break.
+            if maybe_brick.is_some() {
+                commands.entity(entity).despawn();
+                brick_events.send(BrickEvent {
+                    position: transform.translation.truncate(),
+                });
+                continue;
+            }
+
+            // Only hits on the face of a paddle get angled returns; clipping
+            // its top or bottom just bounces off.
+            let face = match (maybe_p1_paddle, maybe_p2_paddle) {
+                (Some(_), _) if contact.normal.x > 0.0 => Some(Player::P1),
+                (_, Some(_)) if contact.normal.x < 0.0 => Some(Player::P2),
+                _ => None,
+            };
+            if let Some(player) = face {
+                let (velocity, hit) = return_ball(
+                    &config,
+                    &rules,
+                    &mut scoreboard,
+                    player,
+                    contact.normal.x,
+                    transform,
+                    &ball_transform,
+                );
+                ball_velocity.0 = velocity;
+                hit_events.send(hit);
+            }
+
+            if maybe_p1_paddle.is_some() || maybe_p2_paddle.is_some() {
+                if let Some(paddle_velocity) = maybe_velocity {
+                    drag_ball(&config, paddle_velocity.0, &mut ball_velocity.0, &mut spin);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use std::{
+    io::{self, Write},
+    net::{SocketAddr, TcpStream},
+    time::Duration,
+};
+// The lobby server itself only runs on the desktop.
+#[cfg(not(target_arch = "wasm32"))]
+use std::{
+    collections::BTreeMap,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+/// Where `fjong-lobby` listens unless told otherwise.
+pub const LOBBY_PORT: u16 = 41_601;
+/// Letters room codes are made of, leaving out I and O to keep them from
+/// being read as numbers.
+const CODE_LETTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+pub const CODE_LENGTH: usize = 4;
+/// How long to wait for the lobby to answer.
+const LOBBY_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LobbyRequest {
+    /// Opens a room for the game listening on `port` of the asking machine.
+    Create {
+        /// Only games of the same version can play each other.
+        fjong: String,
+        name: String,
+        port: u16,
+    },
+    List,
+    Join {
+        fjong: String,
+        code: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LobbyReply {
+    Created { code: String },
+    Rooms { rooms: Vec<RoomInfo> },
+    Joined { addr: SocketAddr },
+    Error { message: String },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoomInfo {
+    pub code: String,
+    pub name: String,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct Room {
+    name: String,
+    fjong: String,
+    addr: SocketAddr,
+}
+
+/// Every open room, by code, shared between the connections of a lobby
+/// server such as `fjong-lobby`, for playing over the internet by room code.
+///
+/// A host creates a room and gets a four-letter code back, which a friend
+/// joins by to learn where the host's game is. The match itself then runs
+/// between the two games just as on the LAN, so the host's game must be
+/// reachable from the guest's. Rooms last as long as their host stays
+/// connected to the lobby.
+///
+/// Each request and reply is a line of JSON:
+///
+/// ```json
+/// {"create":{"fjong":"0.1.0","name":"kitchen","port":51234}}
+/// {"created":{"code":"QXRT"}}
+/// "list"
+/// {"rooms":{"rooms":[{"code":"QXRT","name":"kitchen"}]}}
+/// {"join":{"fjong":"0.1.0","code":"QXRT"}}
+/// {"joined":{"addr":"203.0.113.7:51234"}}
+/// ```
+///
+/// and anything that goes wrong is answered with `{"error":{"message":...}}`.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone, Default)]
+pub struct Rooms(Arc<Mutex<BTreeMap<String, Room>>>);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Rooms {
+    /// Opens a room under a code nobody else is using.
+    fn create(&self, name: String, fjong: String, addr: SocketAddr) -> String {
+        let mut rooms = self.0.lock().unwrap();
+        let code = loop {
+            let code = random_code();
+            if !rooms.contains_key(&code) {
+                break code;
+            }
+        };
+        rooms.insert(code.clone(), Room { name, fjong, addr });
+        code
+    }
+
+    fn list(&self) -> Vec<RoomInfo> {
+        let rooms = self.0.lock().unwrap();
+        rooms
+            .iter()
+            .map(|(code, room)| RoomInfo {
+                code: code.clone(),
+                name: room.name.clone(),
+            })
+            .collect()
+    }
+
+    fn join(&self, code: &str, fjong: &str) -> Result<SocketAddr, String> {
+        let rooms = self.0.lock().unwrap();
+        match rooms.get(&code.to_ascii_uppercase()) {
+            Some(room) if room.fjong == fjong => Ok(room.addr),
+            Some(room) => Err(format!("that room is playing fjong {}", room.fjong)),
+            None => Err(format!("no room {}", code)),
+        }
+    }
+
+    fn close(&self, code: &str) {
+        self.0.lock().unwrap().remove(code);
+    }
+
+    /// Answers whoever is on the other end of `stream` until they hang up,
+    /// closing any rooms they opened. Meant to be spawned as a task of its
+    /// own for each connection.
+    pub async fn serve(&self, stream: tokio::net::TcpStream) -> io::Result<()> {
+        let peer = stream.peer_addr()?;
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let mut opened = Vec::new();
+        let served = async {
+            while let Some(line) = lines.next_line().await? {
+                let reply = match serde_json::from_str(&line) {
+                    Ok(request) => self.answer(request, peer.ip(), &mut opened),
+                    Err(err) => LobbyReply::Error {
+                        message: err.to_string(),
+                    },
+                };
+                writer.write_all(&json_line(&reply)?).await?;
+            }
+            Ok(())
+        }
+        .await;
+        for code in opened {
+            self.close(&code);
+        }
+        served
+    }
+
+    fn answer(&self, request: LobbyRequest, ip: IpAddr, opened: &mut Vec<String>) -> LobbyReply {
+        match request {
+            LobbyRequest::Create { fjong, name, port } => {
+                let code = self.create(name, fjong, SocketAddr::new(ip, port));
+                opened.push(code.clone());
+                LobbyReply::Created { code }
+            }
+            LobbyRequest::List => LobbyReply::Rooms { rooms: self.list() },
+            LobbyRequest::Join { fjong, code } => match self.join(&code, &fjong) {
+                Ok(addr) => LobbyReply::Joined { addr },
+                Err(message) => LobbyReply::Error { message },
+            },
+        }
+    }
+}
+
+fn json_line(message: &impl Serialize) -> io::Result<Vec<u8>> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+/// Sends the lobby `request` and waits for its answer, turning errors from
+/// the lobby into `io::Error`s.
+fn ask(stream: &mut TcpStream, request: &LobbyRequest) -> io::Result<LobbyReply> {
+    stream.set_read_timeout(Some(LOBBY_TIMEOUT))?;
+    stream.write_all(&json_line(request)?)?;
+    // A byte at a time, so the stream can be kept without losing anything.
+    let mut line = Vec::new();
+    let mut byte = [0];
+    loop {
+        io::Read::read_exact(stream, &mut byte)?;
+        if byte[0] == b'\n' {
+            break;
+        }
+        line.push(byte[0]);
+    }
+    match serde_json::from_slice(&line)? {
+        LobbyReply::Error { message } => Err(io::Error::other(message)),
+        reply => Ok(reply),
+    }
+}
+
+fn unexpected(reply: LobbyReply) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("unexpected reply {:?}", reply),
+    )
+}
+
+fn connect(lobby: SocketAddr) -> io::Result<TcpStream> {
+    TcpStream::connect_timeout(&lobby, LOBBY_TIMEOUT)
+}
+
+/// A code for a room, which may be taken already.
+pub(crate) fn random_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..CODE_LENGTH)
+        .map(|_| CODE_LETTERS[rng.gen_range(0..CODE_LETTERS.len())] as char)
+        .collect()
+}
+
+/// Opens a room at `lobby` for a game listening on `port`, giving back its
+/// code and the connection that keeps it open.
+pub fn create_room(lobby: SocketAddr, name: &str, port: u16) -> io::Result<(TcpStream, String)> {
+    let mut stream = connect(lobby)?;
+    let request = LobbyRequest::Create {
+        fjong: env!("CARGO_PKG_VERSION").to_string(),
+        name: name.to_string(),
+        port,
+    };
+    match ask(&mut stream, &request)? {
+        LobbyReply::Created { code } => Ok((stream, code)),
+        reply => Err(unexpected(reply)),
+    }
+}
+
+/// Every room open at `lobby`.
+pub fn list_rooms(lobby: SocketAddr) -> io::Result<Vec<RoomInfo>> {
+    match ask(&mut connect(lobby)?, &LobbyRequest::List)? {
+        LobbyReply::Rooms { rooms } => Ok(rooms),
+        reply => Err(unexpected(reply)),
+    }
+}
+
+/// Where the game in room `code` at `lobby` can be joined.
+pub fn find_room(lobby: SocketAddr, code: &str) -> io::Result<SocketAddr> {
+    let request = LobbyRequest::Join {
+        fjong: env!("CARGO_PKG_VERSION").to_string(),
+        code: code.to_string(),
+    };
+    match ask(&mut connect(lobby)?, &request)? {
+        LobbyReply::Joined { addr } => Ok(addr),
+        reply => Err(unexpected(reply)),
+    }
+}
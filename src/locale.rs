@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use fluent::{concurrent::FluentBundle, FluentArgs, FluentResource};
+use unic_langid::LanguageIdentifier;
+
+use crate::settings::{Language, Settings};
+
+/// Fallback for anything a translation leaves out.
+const ENGLISH: &str = include_str!("../assets/locales/en.ftl");
+
+impl Language {
+    fn id(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Swedish => "sv",
+        }
+    }
+
+    /// Translations are built in, so a language can be picked before the
+    /// assets have loaded.
+    fn source(self) -> &'static str {
+        match self {
+            Language::English => ENGLISH,
+            Language::Swedish => include_str!("../assets/locales/sv.ftl"),
+        }
+    }
+}
+
+/// All of the text shown to the player, in the language picked in
+/// [`Settings::language`], written as [Fluent](https://projectfluent.org)
+/// in `assets/locales/`.
+pub struct Locale {
+    language: Language,
+    bundle: FluentBundle<FluentResource>,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::new(Language::default())
+    }
+}
+
+impl Locale {
+    pub fn new(language: Language) -> Locale {
+        let id: LanguageIdentifier = language.id().parse().unwrap_or_default();
+        let mut bundle = FluentBundle::new_concurrent(vec![id]);
+        // Bidi isolation marks would show up as boxes in the game's font.
+        bundle.set_use_isolating(false);
+        for source in [ENGLISH, language.source()] {
+            let resource =
+                FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, errors)| {
+                    warn!("Errors in the {:?} translation: {:?}", language, errors);
+                    resource
+                });
+            bundle.add_resource_overriding(resource);
+        }
+        Locale { language, bundle }
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// The message called `id`.
+    pub fn text(&self, id: &str) -> String {
+        self.format(id, None)
+    }
+
+    /// The message called `id`, filled in from `args`.
+    pub fn text_with(&self, id: &str, args: &FluentArgs) -> String {
+        self.format(id, Some(args))
+    }
+
+    fn format(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        let pattern = match self
+            .bundle
+            .get_message(id)
+            .and_then(|message| message.value())
+        {
+            Some(pattern) => pattern,
+            None => {
+                warn!("No text for {}", id);
+                return id.to_string();
+            }
+        };
+        let mut errors = Vec::new();
+        let text = self.bundle.format_pattern(pattern, args, &mut errors);
+        if !errors.is_empty() {
+            warn!("Errors in the text for {}: {:?}", id, errors);
+        }
+        text.into_owned()
+    }
+}
+
+/// Keeps [`Locale`] in the language the player picked, switching as soon as
+/// the setting changes.
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Locale>().add_system(switch_language);
+    }
+}
+
+fn switch_language(settings: Option<Res<Settings>>, mut locale: ResMut<Locale>) {
+    if let Some(settings) = settings {
+        if settings.is_changed() && settings.language != locale.language {
+            *locale = Locale::new(settings.language);
+        }
+    }
+}
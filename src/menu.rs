@@ -0,0 +1,2278 @@
+use std::net::SocketAddr;
+
+use bevy::{
+    app::AppExit,
+    input::InputSystem,
+    prelude::*,
+    window::{ReceivedCharacter, WindowFocused},
+};
+use fluent::fluent_args;
+
+use crate::{
+    achievements::{Achievement, Achievements},
+    ai::{AiLevel, Difficulty, PendingAi},
+    daily::{self, DailyBests, DailyChallenge},
+    fonts::UiFont,
+    input::LostGamepad,
+    instant_replay::AfterReplay,
+    lan::{self, HostedGame, Lobby, LobbyServer, Reconnect, RoomCode},
+    leaderboard::{TopScores, TOP_COUNT},
+    lobby::CODE_LENGTH,
+    profiles::{PaddleColor, Profiles, MAX_NAME_LENGTH},
+    settings::{
+        AccessibilitySettings, CameraMode, HudLayout, Language, Settings, Theme, SETTINGS_PATH,
+    },
+    stats::Stats,
+    storage::config_path,
+    toast::Toast,
+    webrtc::Signalling,
+    widget::{
+        self, Menu, MenuAction, MenuButton, MenuEvent, OnScreenKey, OnScreenKeyboard,
+        UNSELECTED_COLOR,
+    },
+    AppState, ArenaLayout, Arenas, CourtSize, GameConfig, GameMode, GameRng, Handicap, Kiosk,
+    Locale, MatchRules, MenuEntity, Player, Policy, Series, Skins, ThemePacks, FOREGROUND_COLOR,
+};
+
+const TITLE_FONT_SIZE: f32 = 64.0;
+const MENU_FONT_SIZE: f32 = 32.0;
+const STATS_FONT_SIZE: f32 = 20.0;
+/// Small enough for all of [`TOP_COUNT`] scores to fit at once.
+const LEADERBOARD_FONT_SIZE: f32 = 8.0;
+const LEADERBOARD_COLUMNS: usize = 4;
+/// Longer names are cut short to keep the columns apart.
+const LEADERBOARD_NAME_LENGTH: usize = 10;
+const MENU_ITEM_SPACING: Val = Val::Px(12.0);
+
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.85);
+
+const POINTS_TO_WIN: [usize; 6] = [3, 5, 7, 11, 15, 21];
+const BEST_OF: [usize; 4] = [1, 3, 5, 7];
+const MAX_BRIGHTNESS: [f32; 4] = [0.4, 0.6, 0.8, 1.0];
+const GAME_SPEEDS: [f32; 5] = [0.5, 0.75, 1.0, 1.25, 1.5];
+const UI_SCALES: [f32; 6] = [0.75, 1.0, 1.25, 1.5, 1.75, 2.0];
+const HUD_LAYOUTS: [HudLayout; 2] = [HudLayout::Corners, HudLayout::Centred];
+const FPS_CAPS: [Option<u32>; 5] = [None, Some(30), Some(60), Some(120), Some(144)];
+const THEMES: [Theme; 6] = [
+    Theme::Classic,
+    Theme::Neon,
+    Theme::Deuteranopia,
+    Theme::Protanopia,
+    Theme::Tritanopia,
+    Theme::HighContrast,
+];
+const LANGUAGES: [Language; 2] = [Language::English, Language::Swedish];
+const DIFFICULTIES: [Difficulty; 4] = [
+    Difficulty::Easy,
+    Difficulty::Medium,
+    Difficulty::Hard,
+    Difficulty::Insane,
+];
+
+const AI_LEVELS: [AiLevel; 5] = [
+    AiLevel::Easy,
+    AiLevel::Medium,
+    AiLevel::Hard,
+    AiLevel::Insane,
+    AiLevel::Adaptive,
+];
+
+/// Main menu, the pause menu and the pages reachable from them.
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Toast>()
+            .add_event::<LostGamepad>()
+            .init_resource::<MatchSetup>()
+            .init_resource::<TopScores>()
+            .init_resource::<Profiles>()
+            .init_resource::<NewProfileName>()
+            .init_resource::<Skins>()
+            .init_resource::<ThemePacks>()
+            .init_resource::<DailyBests>()
+            .init_resource::<PendingAi>()
+            .add_system_set(SystemSet::on_enter(AppState::MainMenu).with_system(spawn_main_menu))
+            .add_system_set(
+                SystemSet::on_update(AppState::MainMenu)
+                    .with_system(main_menu_actions.after(widget::navigate_menus)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::MainMenu)
+                    .with_system(despawn_screen::<MainMenuScreen>),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Setup).with_system(spawn_setup_screen))
+            .add_system_set(
+                SystemSet::on_update(AppState::Setup)
+                    .with_system(setup_menu_actions.after(widget::navigate_menus))
+                    .with_system(refresh_setup_menu.after(setup_menu_actions)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Setup).with_system(despawn_screen::<SetupScreen>),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Daily).with_system(spawn_daily_screen))
+            .add_system_set(
+                SystemSet::on_update(AppState::Daily)
+                    .with_system(daily_screen_actions.after(widget::navigate_menus)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Daily).with_system(despawn_screen::<DailyScreen>),
+            )
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(pause_match))
+            .add_system_set(SystemSet::on_enter(AppState::Paused).with_system(spawn_pause_menu))
+            .add_system_set(
+                SystemSet::on_update(AppState::Paused)
+                    .with_system(pause_menu_actions.after(widget::navigate_menus))
+                    .with_system(refresh_pause_menu.after(pause_menu_actions))
+                    .with_system(resume_on_start),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Paused).with_system(despawn_screen::<PauseScreen>),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Video).with_system(spawn_video_screen))
+            .add_system_set(
+                SystemSet::on_update(AppState::Video)
+                    .with_system(video_menu_actions.after(widget::navigate_menus))
+                    .with_system(refresh_video_menu.after(video_menu_actions)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Video).with_system(despawn_screen::<VideoScreen>),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Stats).with_system(spawn_stats_screen))
+            .add_system_set(
+                SystemSet::on_update(AppState::Stats)
+                    .with_system(stats_screen_actions.after(widget::navigate_menus)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Stats).with_system(despawn_screen::<StatsScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::Leaderboard).with_system(spawn_leaderboard_screen),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Leaderboard)
+                    .with_system(leaderboard_screen_actions.after(widget::navigate_menus))
+                    .with_system(show_top_scores),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Leaderboard)
+                    .with_system(despawn_screen::<LeaderboardScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::Achievements).with_system(spawn_achievements_screen),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Achievements)
+                    .with_system(achievements_screen_actions.after(widget::navigate_menus)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Achievements)
+                    .with_system(despawn_screen::<AchievementsScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::Profiles).with_system(spawn_profiles_screen),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Profiles)
+                    .with_system(profiles_screen_actions.after(widget::navigate_menus))
+                    .with_system(refresh_profiles_screen.after(profiles_screen_actions)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Profiles)
+                    .with_system(despawn_screen::<ProfilesScreen>),
+            )
+            .add_system_to_stage(CoreStage::PreUpdate, type_profile_name.after(InputSystem))
+            .add_system_set(
+                SystemSet::on_enter(AppState::InstantReplay)
+                    .with_system(spawn_instant_replay_screen),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::InstantReplay)
+                    .with_system(instant_replay_screen_actions.after(widget::navigate_menus)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::InstantReplay)
+                    .with_system(despawn_screen::<InstantReplayScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::SeriesBreak).with_system(spawn_series_screen),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::SeriesBreak)
+                    .with_system(series_screen_actions.after(widget::navigate_menus)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::SeriesBreak)
+                    .with_system(despawn_screen::<SeriesScreen>),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::LanHost).with_system(spawn_host_screen))
+            .add_system_set(
+                SystemSet::on_update(AppState::LanHost)
+                    .with_system(host_screen_actions.after(widget::navigate_menus))
+                    .with_system(show_room_code),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::LanHost).with_system(despawn_screen::<HostScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::LanLobby)
+                    .with_system(lobby_screen_actions.after(widget::navigate_menus))
+                    .with_system(refresh_lobby_screen.after(lobby_screen_actions)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::LanLobby).with_system(despawn_screen::<LobbyScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::RoomCode).with_system(spawn_room_code_screen),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::RoomCode)
+                    .with_system(room_code_screen_actions.after(widget::navigate_menus))
+                    .with_system(show_typed_code),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::RoomCode)
+                    .with_system(despawn_screen::<RoomCodeScreen>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::Reconnecting).with_system(spawn_reconnect_screen),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Reconnecting)
+                    .with_system(reconnect_screen_actions.after(widget::navigate_menus))
+                    .with_system(count_down_reconnect),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Reconnecting)
+                    .with_system(despawn_screen::<ReconnectScreen>),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MainMenuEntry {
+    Play,
+    TimeAttack,
+    Breakout,
+    Practice,
+    Daily,
+    HostLan,
+    JoinLan,
+    Video,
+    Stats,
+    Leaderboard,
+    Achievements,
+    Profiles,
+    Quit,
+}
+
+const MAIN_MENU: [MainMenuEntry; 13] = [
+    MainMenuEntry::Play,
+    MainMenuEntry::TimeAttack,
+    MainMenuEntry::Breakout,
+    MainMenuEntry::Practice,
+    MainMenuEntry::Daily,
+    MainMenuEntry::HostLan,
+    MainMenuEntry::JoinLan,
+    MainMenuEntry::Video,
+    MainMenuEntry::Stats,
+    MainMenuEntry::Leaderboard,
+    MainMenuEntry::Achievements,
+    MainMenuEntry::Profiles,
+    MainMenuEntry::Quit,
+];
+
+/// The main menu's entries. A [`Kiosk`] can't be quit, which is why quitting
+/// comes last.
+fn main_menu(kiosk: Option<Res<Kiosk>>) -> &'static [MainMenuEntry] {
+    match kiosk {
+        Some(_) => &MAIN_MENU[..MAIN_MENU.len() - 1],
+        None => &MAIN_MENU,
+    }
+}
+
+impl MainMenuEntry {
+    fn label(self, locale: &Locale) -> String {
+        locale.text(match self {
+            MainMenuEntry::Play => "main-play",
+            MainMenuEntry::TimeAttack => "main-time-attack",
+            MainMenuEntry::Breakout => "main-breakout",
+            MainMenuEntry::Practice => "main-practice",
+            MainMenuEntry::Daily => "main-daily",
+            MainMenuEntry::HostLan => "main-host-lan",
+            MainMenuEntry::JoinLan => "main-join-lan",
+            MainMenuEntry::Video => "main-video",
+            MainMenuEntry::Stats => "main-stats",
+            MainMenuEntry::Leaderboard => "main-leaderboard",
+            MainMenuEntry::Achievements => "main-achievements",
+            MainMenuEntry::Profiles => "main-profiles",
+            MainMenuEntry::Quit => "main-quit",
+        })
+    }
+}
+
+/// Lower case, as Fluent selectors are written.
+fn selector(value: impl std::fmt::Debug) -> String {
+    format!("{:?}", value).to_lowercase()
+}
+
+fn on_off(locale: &Locale, on: bool) -> String {
+    locale.text(if on { "on" } else { "off" })
+}
+
+#[derive(Component)]
+struct MainMenuScreen;
+
+/// A step down from the stock setting, the stock setting, or a step up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Preset {
+    Small,
+    Normal,
+    Large,
+}
+
+const PRESETS: [Preset; 3] = [Preset::Small, Preset::Normal, Preset::Large];
+const COURT_SIZES: [CourtSize; 3] = [CourtSize::Small, CourtSize::Classic, CourtSize::Wide];
+
+impl Preset {
+    fn scale(self) -> f32 {
+        match self {
+            Preset::Small => 0.75,
+            Preset::Normal => 1.0,
+            Preset::Large => 1.3,
+        }
+    }
+}
+
+/// Handicaps on offer on the setup screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum HandicapPreset {
+    None,
+    /// Three points up from the start.
+    HeadStart,
+    BigPaddle,
+    SmallPaddle,
+    /// Sends the ball back at four fifths of the speed.
+    SlowReturns,
+}
+
+const HANDICAPS: [HandicapPreset; 5] = [
+    HandicapPreset::None,
+    HandicapPreset::HeadStart,
+    HandicapPreset::BigPaddle,
+    HandicapPreset::SmallPaddle,
+    HandicapPreset::SlowReturns,
+];
+
+impl HandicapPreset {
+    fn handicap(self) -> Handicap {
+        let none = Handicap::default();
+        match self {
+            HandicapPreset::None => none,
+            HandicapPreset::HeadStart => Handicap {
+                head_start: 3,
+                ..none
+            },
+            HandicapPreset::BigPaddle => Handicap {
+                paddle_scale: Preset::Large.scale(),
+                ..none
+            },
+            HandicapPreset::SmallPaddle => Handicap {
+                paddle_scale: Preset::Small.scale(),
+                ..none
+            },
+            HandicapPreset::SlowReturns => Handicap {
+                return_speed: 0.8,
+                ..none
+            },
+        }
+    }
+}
+
+/// Choices on the setup screen that aren't kept in [`MatchRules`] as is.
+/// Remembered until the next match.
+struct MatchSetup {
+    ball_speed: Preset,
+    paddle_size: Preset,
+    p1_handicap: HandicapPreset,
+    p2_handicap: HandicapPreset,
+    /// Name of the [`ArenaLayout`] to play on.
+    arena: String,
+    court_size: CourtSize,
+}
+
+impl Default for MatchSetup {
+    fn default() -> Self {
+        MatchSetup {
+            ball_speed: Preset::Normal,
+            paddle_size: Preset::Normal,
+            p1_handicap: HandicapPreset::None,
+            p2_handicap: HandicapPreset::None,
+            arena: ArenaLayout::default().name,
+            court_size: CourtSize::Classic,
+        }
+    }
+}
+
+impl MatchSetup {
+    fn apply(&self, config: &mut GameConfig, rules: &mut MatchRules) {
+        let stock = GameConfig::default();
+        config.ball_speed = stock.ball_speed * self.ball_speed.scale();
+        config.serve_speed = stock.serve_speed * self.ball_speed.scale();
+        config.paddle_size = stock.paddle_size * Vec2::new(1.0, self.paddle_size.scale());
+        rules.p1_handicap = self.p1_handicap.handicap();
+        rules.p2_handicap = self.p2_handicap.handicap();
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SetupMenuEntry {
+    Arena,
+    CourtSize,
+    PointsToWin,
+    BestOf,
+    BallSpeed,
+    PaddleSize,
+    Difficulty,
+    AdaptiveAi,
+    StickyPaddles,
+    WinByTwo,
+    ShrinkingPaddles,
+    MovingGoals,
+    FogOfWar,
+    MirrorControls,
+    P1Handicap,
+    P2Handicap,
+    P1Profile,
+    P2Profile,
+    Start,
+    Back,
+}
+
+const SETUP_MENU: [SetupMenuEntry; 20] = [
+    SetupMenuEntry::Arena,
+    SetupMenuEntry::CourtSize,
+    SetupMenuEntry::PointsToWin,
+    SetupMenuEntry::BestOf,
+    SetupMenuEntry::BallSpeed,
+    SetupMenuEntry::PaddleSize,
+    SetupMenuEntry::Difficulty,
+    SetupMenuEntry::AdaptiveAi,
+    SetupMenuEntry::StickyPaddles,
+    SetupMenuEntry::WinByTwo,
+    SetupMenuEntry::ShrinkingPaddles,
+    SetupMenuEntry::MovingGoals,
+    SetupMenuEntry::FogOfWar,
+    SetupMenuEntry::MirrorControls,
+    SetupMenuEntry::P1Handicap,
+    SetupMenuEntry::P2Handicap,
+    SetupMenuEntry::P1Profile,
+    SetupMenuEntry::P2Profile,
+    SetupMenuEntry::Start,
+    SetupMenuEntry::Back,
+];
+
+impl SetupMenuEntry {
+    fn label(
+        self,
+        rules: &MatchRules,
+        setup: &MatchSetup,
+        profiles: &Profiles,
+        locale: &Locale,
+    ) -> String {
+        let (id, args) = match self {
+            SetupMenuEntry::Arena => ("setup-arena", fluent_args!["arena" => setup.arena.clone()]),
+            SetupMenuEntry::CourtSize => (
+                "setup-court-size",
+                fluent_args!["size" => selector(setup.court_size)],
+            ),
+            SetupMenuEntry::PointsToWin => (
+                "setup-points-to-win",
+                fluent_args!["points" => rules.points_to_win],
+            ),
+            SetupMenuEntry::BestOf => ("setup-best-of", fluent_args!["games" => rules.best_of]),
+            SetupMenuEntry::BallSpeed => (
+                "setup-ball-speed",
+                fluent_args!["speed" => selector(setup.ball_speed)],
+            ),
+            SetupMenuEntry::PaddleSize => (
+                "setup-paddle-size",
+                fluent_args!["size" => selector(setup.paddle_size)],
+            ),
+            SetupMenuEntry::Difficulty => (
+                "setup-ai",
+                fluent_args!["difficulty" => selector(rules.difficulty)],
+            ),
+            SetupMenuEntry::AdaptiveAi => (
+                "setup-adaptive-ai",
+                fluent_args!["value" => on_off(locale, rules.adaptive_ai)],
+            ),
+            SetupMenuEntry::StickyPaddles => (
+                "setup-sticky-paddles",
+                fluent_args!["value" => on_off(locale, rules.sticky_paddles)],
+            ),
+            SetupMenuEntry::WinByTwo => (
+                "setup-win-by-two",
+                fluent_args!["value" => on_off(locale, rules.win_by_two)],
+            ),
+            SetupMenuEntry::ShrinkingPaddles => (
+                "setup-shrinking-paddles",
+                fluent_args!["value" => on_off(locale, rules.shrinking_paddles)],
+            ),
+            SetupMenuEntry::MovingGoals => (
+                "setup-moving-goals",
+                fluent_args!["value" => on_off(locale, rules.moving_goals)],
+            ),
+            SetupMenuEntry::FogOfWar => (
+                "setup-fog-of-war",
+                fluent_args!["value" => on_off(locale, rules.fog_of_war)],
+            ),
+            SetupMenuEntry::MirrorControls => (
+                "setup-mirror-controls",
+                fluent_args!["value" => on_off(locale, rules.mirror_controls)],
+            ),
+            SetupMenuEntry::P1Handicap => (
+                "setup-p1-handicap",
+                fluent_args!["handicap" => selector(setup.p1_handicap)],
+            ),
+            SetupMenuEntry::P2Handicap => (
+                "setup-p2-handicap",
+                fluent_args!["handicap" => selector(setup.p2_handicap)],
+            ),
+            SetupMenuEntry::P1Profile => (
+                "setup-p1-profile",
+                fluent_args!["profile" => profile_name(profiles, Player::P1, locale)],
+            ),
+            SetupMenuEntry::P2Profile => (
+                "setup-p2-profile",
+                fluent_args!["profile" => profile_name(profiles, Player::P2, locale)],
+            ),
+            SetupMenuEntry::Start => return locale.text("setup-start"),
+            SetupMenuEntry::Back => return locale.text("back"),
+        };
+        locale.text_with(id, &args)
+    }
+}
+
+/// The profile `player` plays as, or who plays without one.
+fn profile_name(profiles: &Profiles, player: Player, locale: &Locale) -> String {
+    match profiles.playing(player) {
+        Some(profile) => profile.name.clone(),
+        None if player == Player::P1 => locale.text("profile-guest"),
+        None => locale.text("profile-ai"),
+    }
+}
+
+/// Profiles `player` could play as, none first. Each profile can only be
+/// on one side.
+fn profile_choices(profiles: &Profiles, player: Player) -> Vec<Option<String>> {
+    let taken = profiles.selected(player.other());
+    let names = profiles
+        .profiles
+        .iter()
+        .map(|profile| profile.name.as_str())
+        .filter(|name| Some(*name) != taken)
+        .map(|name| Some(name.to_string()));
+    std::iter::once(None).chain(names).collect()
+}
+
+#[derive(Component)]
+struct SetupScreen;
+
+/// The option `step` places along from `current`, wrapping around at either
+/// end.
+fn cycle<T: Clone + PartialEq>(options: &[T], current: T, step: isize) -> T {
+    let index = options
+        .iter()
+        .position(|option| *option == current)
+        .unwrap_or(0) as isize;
+    let len = options.len() as isize;
+    options[(index + step).rem_euclid(len) as usize].clone()
+}
+
+/// The difficulties on offer, the learned boss last once there is a policy
+/// to play it.
+fn difficulties(learned: bool) -> Vec<Difficulty> {
+    let mut difficulties = DIFFICULTIES.to_vec();
+    if learned {
+        difficulties.push(Difficulty::Learned);
+    }
+    difficulties
+}
+
+/// Every skin by name, after no skin at all.
+fn skin_options(skins: &Skins) -> Vec<Option<String>> {
+    std::iter::once(None)
+        .chain(skins.names().into_iter().map(Some))
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PauseMenuEntry {
+    Resume,
+    Ai,
+    MainMenu,
+}
+
+const PAUSE_MENU: [PauseMenuEntry; 3] = [
+    PauseMenuEntry::Resume,
+    PauseMenuEntry::Ai,
+    PauseMenuEntry::MainMenu,
+];
+
+impl PauseMenuEntry {
+    fn label(self, rules: &MatchRules, pending: &PendingAi, locale: &Locale) -> String {
+        match self {
+            PauseMenuEntry::Resume => locale.text("pause-resume"),
+            // A pick waiting for the next serve is shown as such.
+            PauseMenuEntry::Ai => match pending.0 {
+                Some(level) => {
+                    locale.text_with("pause-ai-next", &fluent_args!["level" => selector(level)])
+                }
+                None => {
+                    let level = AiLevel::of(rules).map_or(selector(rules.difficulty), selector);
+                    locale.text_with("pause-ai", &fluent_args!["level" => level])
+                }
+            },
+            PauseMenuEntry::MainMenu => locale.text("pause-main-menu"),
+        }
+    }
+}
+
+#[derive(Component)]
+struct PauseScreen;
+
+#[derive(Component)]
+struct StatsScreen;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum VideoMenuEntry {
+    Theme,
+    ThemePack,
+    Camera,
+    BallHeat,
+    Backdrop,
+    Skin,
+    UiScale,
+    HudLayout,
+    Crt,
+    Vsync,
+    FpsCap,
+    LowPower,
+    ReduceMotion,
+    Particles,
+    MaxBrightness,
+    GameSpeed,
+    Announcer,
+    Language,
+    Back,
+}
+
+const VIDEO_MENU: [VideoMenuEntry; 19] = [
+    VideoMenuEntry::Theme,
+    VideoMenuEntry::ThemePack,
+    VideoMenuEntry::Camera,
+    VideoMenuEntry::BallHeat,
+    VideoMenuEntry::Backdrop,
+    VideoMenuEntry::Skin,
+    VideoMenuEntry::UiScale,
+    VideoMenuEntry::HudLayout,
+    VideoMenuEntry::Crt,
+    VideoMenuEntry::Vsync,
+    VideoMenuEntry::FpsCap,
+    VideoMenuEntry::LowPower,
+    VideoMenuEntry::ReduceMotion,
+    VideoMenuEntry::Particles,
+    VideoMenuEntry::MaxBrightness,
+    VideoMenuEntry::GameSpeed,
+    VideoMenuEntry::Announcer,
+    VideoMenuEntry::Language,
+    VideoMenuEntry::Back,
+];
+
+impl VideoMenuEntry {
+    fn label(self, settings: &Settings, locale: &Locale) -> String {
+        let accessibility = &settings.accessibility;
+        let (id, args) = match self {
+            VideoMenuEntry::Theme => (
+                "video-theme",
+                fluent_args!["theme" => selector(settings.video.theme)],
+            ),
+            VideoMenuEntry::ThemePack => {
+                let pack = settings.video.theme_pack.clone();
+                (
+                    "video-theme-pack",
+                    fluent_args!["pack" => pack.unwrap_or_else(|| locale.text("theme-pack-none"))],
+                )
+            }
+            VideoMenuEntry::Camera => (
+                "video-camera",
+                fluent_args!["camera" => selector(settings.video.camera)],
+            ),
+            VideoMenuEntry::BallHeat => (
+                "video-ball-heat",
+                fluent_args!["value" => on_off(locale, settings.video.ball_heat.enabled)],
+            ),
+            VideoMenuEntry::Backdrop => (
+                "video-backdrop",
+                fluent_args!["value" => on_off(locale, settings.video.backdrop)],
+            ),
+            VideoMenuEntry::Skin => {
+                let skin = settings.video.skin.clone();
+                (
+                    "video-skin",
+                    fluent_args!["skin" => skin.unwrap_or_else(|| locale.text("skin-none"))],
+                )
+            }
+            VideoMenuEntry::UiScale => (
+                "video-ui-scale",
+                fluent_args!["percent" => (settings.video.ui_scale * 100.0).round()],
+            ),
+            VideoMenuEntry::HudLayout => (
+                "video-hud-layout",
+                fluent_args!["layout" => selector(settings.video.hud_layout)],
+            ),
+            VideoMenuEntry::Crt => (
+                "video-crt",
+                fluent_args!["value" => on_off(locale, settings.video.crt)],
+            ),
+            VideoMenuEntry::Vsync => (
+                "video-vsync",
+                fluent_args!["value" => on_off(locale, settings.video.vsync)],
+            ),
+            VideoMenuEntry::FpsCap => (
+                "video-fps-cap",
+                fluent_args!["fps" => settings.video.fps_cap.unwrap_or(0)],
+            ),
+            VideoMenuEntry::LowPower => (
+                "video-low-power",
+                fluent_args!["value" => on_off(locale, settings.video.low_power_in_background)],
+            ),
+            VideoMenuEntry::ReduceMotion => (
+                "video-reduce-motion",
+                fluent_args!["value" => on_off(locale, accessibility.reduce_motion)],
+            ),
+            VideoMenuEntry::Particles => (
+                "video-particles",
+                fluent_args!["value" => on_off(locale, accessibility.particles)],
+            ),
+            VideoMenuEntry::MaxBrightness => (
+                "video-max-brightness",
+                fluent_args!["percent" => (accessibility.max_brightness * 100.0).round()],
+            ),
+            VideoMenuEntry::GameSpeed => (
+                "video-game-speed",
+                fluent_args!["percent" => (accessibility.game_speed * 100.0).round()],
+            ),
+            VideoMenuEntry::Announcer => (
+                "video-announcer",
+                fluent_args!["value" => on_off(locale, accessibility.announcer)],
+            ),
+            // Each language is named in its own words, so it can be found
+            // from any other.
+            VideoMenuEntry::Language => (
+                "video-language",
+                fluent_args!["language" => Locale::new(settings.language).text("language-name")],
+            ),
+            VideoMenuEntry::Back => return locale.text("back"),
+        };
+        locale.text_with(id, &args)
+    }
+}
+
+#[derive(Component)]
+struct VideoScreen;
+
+/// A dimmed, full-screen column that menu pages are laid out in.
+fn overlay() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::ColumnReverse,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        color: OVERLAY_COLOR.into(),
+        ..default()
+    }
+}
+
+fn label(font: &UiFont, value: &str, font_size: f32, color: Color) -> TextBundle {
+    TextBundle {
+        text: Text::with_section(
+            value,
+            TextStyle {
+                font: font.0.clone(),
+                font_size,
+                color,
+            },
+            default(),
+        ),
+        style: Style {
+            margin: Rect {
+                bottom: MENU_ITEM_SPACING,
+                ..default()
+            },
+            ..default()
+        },
+        ..default()
+    }
+}
+
+#[derive(Component)]
+struct MenuTitle;
+
+/// Spawns a page with a title and a [`Menu`] of `entries` below it.
+fn spawn_menu_screen(
+    commands: &mut Commands,
+    font: &UiFont,
+    screen: impl Component,
+    title: (&str, f32),
+    entries: impl IntoIterator<Item = (String, f32)>,
+) {
+    commands
+        .spawn_bundle(overlay())
+        .insert(screen)
+        .insert_bundle((Menu::default(), MenuEntity))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(label(font, title.0, title.1, FOREGROUND_COLOR))
+                .insert(MenuTitle);
+            for (index, (text, font_size)) in entries.into_iter().enumerate() {
+                parent
+                    .spawn_bundle(label(font, &text, font_size, UNSELECTED_COLOR))
+                    .insert(MenuButton(index));
+            }
+        });
+}
+
+/// Menu actions taken on the page marked with `T`. Actions meant for a page
+/// that has since been closed are dropped.
+fn actions_on<'a, T: Component>(
+    menu_events: &'a mut EventReader<MenuEvent>,
+    screen_query: &'a Query<(), With<T>>,
+) -> impl Iterator<Item = &'a MenuEvent> {
+    menu_events
+        .iter()
+        .filter(move |event| screen_query.get(event.menu).is_ok())
+}
+
+fn spawn_main_menu(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    locale: Res<Locale>,
+    kiosk: Option<Res<Kiosk>>,
+) {
+    let entries = main_menu(kiosk)
+        .iter()
+        .map(|entry| (entry.label(&locale), MENU_FONT_SIZE));
+    spawn_menu_screen(
+        &mut commands,
+        &font,
+        MainMenuScreen,
+        (&locale.text("main-title"), TITLE_FONT_SIZE),
+        entries,
+    );
+}
+
+fn main_menu_actions(
+    mut menu_events: EventReader<MenuEvent>,
+    screen_query: Query<(), With<MainMenuScreen>>,
+    mut rules: ResMut<MatchRules>,
+    mut state: ResMut<State<AppState>>,
+    mut exit: EventWriter<AppExit>,
+    kiosk: Option<Res<Kiosk>>,
+) {
+    let entries = main_menu(kiosk);
+    for event in actions_on(&mut menu_events, &screen_query) {
+        if event.action != MenuAction::Confirm {
+            continue;
+        }
+        match entries[event.button] {
+            MainMenuEntry::Play => {
+                rules.mode = GameMode::Versus;
+                let _ = state.set(AppState::Setup);
+            }
+            MainMenuEntry::TimeAttack => {
+                rules.mode = GameMode::TimeAttack;
+                let _ = state.set(AppState::Setup);
+            }
+            MainMenuEntry::Breakout => {
+                rules.mode = GameMode::Breakout;
+                let _ = state.set(AppState::Setup);
+            }
+            MainMenuEntry::Practice => {
+                rules.mode = GameMode::Practice;
+                let _ = state.set(AppState::Playing);
+            }
+            MainMenuEntry::Daily => {
+                let _ = state.set(AppState::Daily);
+            }
+            MainMenuEntry::HostLan => {
+                let _ = state.set(AppState::LanHost);
+            }
+            MainMenuEntry::JoinLan => {
+                let _ = state.set(AppState::LanLobby);
+            }
+            MainMenuEntry::Video => {
+                let _ = state.set(AppState::Video);
+            }
+            MainMenuEntry::Stats => {
+                let _ = state.set(AppState::Stats);
+            }
+            MainMenuEntry::Leaderboard => {
+                let _ = state.set(AppState::Leaderboard);
+            }
+            MainMenuEntry::Achievements => {
+                let _ = state.set(AppState::Achievements);
+            }
+            MainMenuEntry::Profiles => {
+                let _ = state.set(AppState::Profiles);
+            }
+            MainMenuEntry::Quit => exit.send(AppExit),
+        }
+    }
+}
+
+fn spawn_setup_screen(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    locale: Res<Locale>,
+    rules: Res<MatchRules>,
+    setup: Res<MatchSetup>,
+    profiles: Res<Profiles>,
+) {
+    let entries = SETUP_MENU.iter().map(|entry| {
+        (
+            entry.label(&rules, &setup, &profiles, &locale),
+            STATS_FONT_SIZE,
+        )
+    });
+    spawn_menu_screen(
+        &mut commands,
+        &font,
+        SetupScreen,
+        (&locale.text("setup-title"), MENU_FONT_SIZE),
+        entries,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn setup_menu_actions(
+    mut menu_events: EventReader<MenuEvent>,
+    screen_query: Query<(), With<SetupScreen>>,
+    mut rules: ResMut<MatchRules>,
+    mut setup: ResMut<MatchSetup>,
+    mut profiles: ResMut<Profiles>,
+    mut config: ResMut<GameConfig>,
+    arenas: Res<Arenas>,
+    mut layout: ResMut<ArenaLayout>,
+    mut state: ResMut<State<AppState>>,
+    policy: Option<Res<Policy>>,
+) {
+    for event in actions_on(&mut menu_events, &screen_query) {
+        let step = match event.action {
+            MenuAction::Decrease => -1,
+            MenuAction::Confirm | MenuAction::Increase => 1,
+            MenuAction::Cancel => {
+                let _ = state.set(AppState::MainMenu);
+                continue;
+            }
+        };
+        match SETUP_MENU[event.button] {
+            SetupMenuEntry::Arena => {
+                setup.arena = cycle(&arenas.names(), setup.arena.clone(), step)
+            }
+            SetupMenuEntry::CourtSize => {
+                setup.court_size = cycle(&COURT_SIZES, setup.court_size, step)
+            }
+            SetupMenuEntry::PointsToWin => {
+                rules.points_to_win = cycle(&POINTS_TO_WIN, rules.points_to_win, step)
+            }
+            SetupMenuEntry::BestOf => rules.best_of = cycle(&BEST_OF, rules.best_of, step),
+            SetupMenuEntry::BallSpeed => setup.ball_speed = cycle(&PRESETS, setup.ball_speed, step),
+            SetupMenuEntry::PaddleSize => {
+                setup.paddle_size = cycle(&PRESETS, setup.paddle_size, step)
+            }
+            SetupMenuEntry::Difficulty => {
+                rules.difficulty = cycle(&difficulties(policy.is_some()), rules.difficulty, step)
+            }
+            SetupMenuEntry::AdaptiveAi => rules.adaptive_ai = !rules.adaptive_ai,
+            SetupMenuEntry::StickyPaddles => rules.sticky_paddles = !rules.sticky_paddles,
+            SetupMenuEntry::WinByTwo => rules.win_by_two = !rules.win_by_two,
+            SetupMenuEntry::ShrinkingPaddles => rules.shrinking_paddles = !rules.shrinking_paddles,
+            SetupMenuEntry::MovingGoals => rules.moving_goals = !rules.moving_goals,
+            SetupMenuEntry::FogOfWar => rules.fog_of_war = !rules.fog_of_war,
+            SetupMenuEntry::MirrorControls => rules.mirror_controls = !rules.mirror_controls,
+            SetupMenuEntry::P1Handicap => {
+                setup.p1_handicap = cycle(&HANDICAPS, setup.p1_handicap, step)
+            }
+            SetupMenuEntry::P2Handicap => {
+                setup.p2_handicap = cycle(&HANDICAPS, setup.p2_handicap, step)
+            }
+            SetupMenuEntry::P1Profile | SetupMenuEntry::P2Profile => {
+                let player = match SETUP_MENU[event.button] {
+                    SetupMenuEntry::P1Profile => Player::P1,
+                    _ => Player::P2,
+                };
+                let current = profiles.selected(player).map(str::to_string);
+                let choice = cycle(&profile_choices(&profiles, player), current, step);
+                profiles.select(player, choice);
+            }
+            SetupMenuEntry::Start if event.action == MenuAction::Confirm => {
+                setup.apply(&mut config, &mut rules);
+                if let Some(arena) = arenas.get(&setup.arena) {
+                    *layout = setup.court_size.apply(arena);
+                }
+                let _ = state.set(AppState::Playing);
+            }
+            SetupMenuEntry::Back if event.action == MenuAction::Confirm => {
+                let _ = state.set(AppState::MainMenu);
+            }
+            SetupMenuEntry::Start | SetupMenuEntry::Back => {}
+        }
+    }
+}
+
+fn refresh_setup_menu(
+    locale: Res<Locale>,
+    rules: Res<MatchRules>,
+    setup: Res<MatchSetup>,
+    profiles: Res<Profiles>,
+    mut query: Query<(&MenuButton, &mut Text)>,
+) {
+    if !rules.is_changed() && !setup.is_changed() && !profiles.is_changed() {
+        return;
+    }
+    for (button, mut text) in query.iter_mut() {
+        text.sections[0].value = SETUP_MENU[button.0].label(&rules, &setup, &profiles, &locale);
+    }
+}
+
+/// Whether start was pressed on any gamepad, keeping the press from
+/// whatever else looks for it.
+fn start_pressed(gamepads: &Gamepads, buttons: &mut Input<GamepadButton>) -> bool {
+    let mut pressed = false;
+    for gamepad in gamepads.iter() {
+        pressed |= buttons.clear_just_pressed(GamepadButton(*gamepad, GamepadButtonType::Start));
+    }
+    pressed
+}
+
+/// Escape or a gamepad's start button stops the match and brings up the
+/// pause menu over it. So does switching to another window or P1's gamepad
+/// dropping out, rather than leaving the AI to score on nobody.
+fn pause_match(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    mut buttons: ResMut<Input<GamepadButton>>,
+    mut focus_events: EventReader<WindowFocused>,
+    mut lost_gamepads: EventReader<LostGamepad>,
+    mut state: ResMut<State<AppState>>,
+) {
+    let escaped =
+        keyboard_input.clear_just_pressed(KeyCode::Escape) | start_pressed(&gamepads, &mut buttons);
+    let unfocused = focus_events.iter().any(|event| !event.focused);
+    let lost_gamepad = lost_gamepads.iter().count() > 0;
+    if escaped || unfocused || lost_gamepad {
+        let _ = state.push(AppState::Paused);
+    }
+}
+
+fn spawn_pause_menu(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    locale: Res<Locale>,
+    rules: Res<MatchRules>,
+    pending: Res<PendingAi>,
+) {
+    let entries = PAUSE_MENU
+        .iter()
+        .map(|entry| (entry.label(&rules, &pending, &locale), MENU_FONT_SIZE));
+    spawn_menu_screen(
+        &mut commands,
+        &font,
+        PauseScreen,
+        (&locale.text("pause-title"), MENU_FONT_SIZE),
+        entries,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pause_menu_actions(
+    mut menu_events: EventReader<MenuEvent>,
+    screen_query: Query<(), With<PauseScreen>>,
+    mut state: ResMut<State<AppState>>,
+    mut rules: ResMut<MatchRules>,
+    mut pending: ResMut<PendingAi>,
+    mut settings: ResMut<Settings>,
+    mut toasts: EventWriter<Toast>,
+    locale: Res<Locale>,
+) {
+    for event in actions_on(&mut menu_events, &screen_query) {
+        let entry = match event.action {
+            MenuAction::Cancel => PauseMenuEntry::Resume,
+            _ => PAUSE_MENU[event.button],
+        };
+        let _ = match entry {
+            PauseMenuEntry::Ai => {
+                let step = if event.action == MenuAction::Decrease {
+                    -1
+                } else {
+                    1
+                };
+                let current = AiLevel::of(&rules);
+                let level = cycle(
+                    &AI_LEVELS,
+                    pending.0.or(current).unwrap_or(AiLevel::Medium),
+                    step,
+                );
+                // The rally in progress is played out against the AI it
+                // started with.
+                pending.0 = (Some(level) != current).then_some(level);
+                settings.ai = Some(level);
+                let path = config_path(SETTINGS_PATH);
+                if let Err(err) = settings.save(&path) {
+                    warn!("Could not save settings to {}: {}", path.display(), err);
+                    toasts.send(Toast(locale.text("toast-settings-not-saved")));
+                }
+                continue;
+            }
+            // Left and right only change values.
+            _ if !matches!(event.action, MenuAction::Confirm | MenuAction::Cancel) => continue,
+            PauseMenuEntry::Resume => state.pop(),
+            // Gives up on the match, with the AI picked for the next one.
+            PauseMenuEntry::MainMenu => {
+                if let Some(level) = pending.0.take() {
+                    level.apply(&mut rules);
+                }
+                state.replace(AppState::MainMenu)
+            }
+        };
+    }
+}
+
+/// Start again carries on, as it does in most games.
+fn resume_on_start(
+    gamepads: Res<Gamepads>,
+    mut buttons: ResMut<Input<GamepadButton>>,
+    mut state: ResMut<State<AppState>>,
+) {
+    if start_pressed(&gamepads, &mut buttons) {
+        let _ = state.pop();
+    }
+}
+
+fn refresh_pause_menu(
+    locale: Res<Locale>,
+    rules: Res<MatchRules>,
+    pending: Res<PendingAi>,
+    mut query: Query<(&MenuButton, &mut Text)>,
+) {
+    if !rules.is_changed() && !pending.is_changed() {
+        return;
+    }
+    for (button, mut text) in query.iter_mut() {
+        text.sections[0].value = PAUSE_MENU[button.0].label(&rules, &pending, &locale);
+    }
+}
+
+fn spawn_video_screen(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
+) {
+    let entries = VIDEO_MENU
+        .iter()
+        .map(|entry| (entry.label(&settings, &locale), STATS_FONT_SIZE));
+    spawn_menu_screen(
+        &mut commands,
+        &font,
+        VideoScreen,
+        (&locale.text("video-title"), MENU_FONT_SIZE),
+        entries,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn video_menu_actions(
+    mut menu_events: EventReader<MenuEvent>,
+    screen_query: Query<(), With<VideoScreen>>,
+    mut settings: ResMut<Settings>,
+    mut accessibility: ResMut<AccessibilitySettings>,
+    mut state: ResMut<State<AppState>>,
+    mut locale: ResMut<Locale>,
+    skins: Res<Skins>,
+    theme_packs: Res<ThemePacks>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for event in actions_on(&mut menu_events, &screen_query) {
+        let entry = match event.action {
+            MenuAction::Cancel => VideoMenuEntry::Back,
+            _ => VIDEO_MENU[event.button],
+        };
+        let step = match event.action {
+            MenuAction::Decrease => -1,
+            _ => 1,
+        };
+        match entry {
+            VideoMenuEntry::Theme => {
+                settings.video.theme = cycle(&THEMES, settings.video.theme, step)
+            }
+            VideoMenuEntry::ThemePack => {
+                let packs: Vec<_> = std::iter::once(None)
+                    .chain(theme_packs.names().into_iter().map(Some))
+                    .collect();
+                settings.video.theme_pack = cycle(&packs, settings.video.theme_pack.clone(), step)
+            }
+            VideoMenuEntry::Camera => {
+                settings.video.camera = match settings.video.camera {
+                    CameraMode::Classic => CameraMode::Follow,
+                    CameraMode::Follow => CameraMode::Dramatic,
+                    CameraMode::Dramatic => CameraMode::Classic,
+                }
+            }
+            VideoMenuEntry::BallHeat => {
+                settings.video.ball_heat.enabled = !settings.video.ball_heat.enabled
+            }
+            VideoMenuEntry::Backdrop => settings.video.backdrop = !settings.video.backdrop,
+            VideoMenuEntry::Skin => {
+                settings.video.skin =
+                    cycle(&skin_options(&skins), settings.video.skin.clone(), step)
+            }
+            VideoMenuEntry::UiScale => {
+                settings.video.ui_scale = cycle(&UI_SCALES, settings.video.ui_scale, step)
+            }
+            VideoMenuEntry::HudLayout => {
+                settings.video.hud_layout = cycle(&HUD_LAYOUTS, settings.video.hud_layout, step)
+            }
+            VideoMenuEntry::Crt => settings.video.crt = !settings.video.crt,
+            VideoMenuEntry::Vsync => settings.video.vsync = !settings.video.vsync,
+            VideoMenuEntry::FpsCap => {
+                settings.video.fps_cap = cycle(&FPS_CAPS, settings.video.fps_cap, step)
+            }
+            VideoMenuEntry::LowPower => {
+                settings.video.low_power_in_background = !settings.video.low_power_in_background
+            }
+            VideoMenuEntry::ReduceMotion => {
+                settings.accessibility.reduce_motion = !settings.accessibility.reduce_motion
+            }
+            VideoMenuEntry::Particles => {
+                settings.accessibility.particles = !settings.accessibility.particles
+            }
+            VideoMenuEntry::MaxBrightness => {
+                settings.accessibility.max_brightness =
+                    cycle(&MAX_BRIGHTNESS, settings.accessibility.max_brightness, step)
+            }
+            VideoMenuEntry::GameSpeed => {
+                settings.accessibility.game_speed =
+                    cycle(&GAME_SPEEDS, settings.accessibility.game_speed, step)
+            }
+            VideoMenuEntry::Announcer => {
+                settings.accessibility.announcer = !settings.accessibility.announcer
+            }
+            VideoMenuEntry::Language => {
+                settings.language = cycle(&LANGUAGES, settings.language, step);
+                // Straight away, so the toast below is in the new language.
+                *locale = Locale::new(settings.language);
+            }
+            VideoMenuEntry::Back => {
+                // Left and right only change values.
+                if matches!(event.action, MenuAction::Confirm | MenuAction::Cancel) {
+                    let _ = state.set(AppState::MainMenu);
+                }
+                continue;
+            }
+        }
+        *accessibility = settings.accessibility.clone();
+        let path = config_path(SETTINGS_PATH);
+        match settings.save(&path) {
+            Ok(()) => toasts.send(Toast(locale.text("toast-settings-saved"))),
+            Err(err) => {
+                warn!("Could not save settings to {}: {}", path.display(), err);
+                toasts.send(Toast(locale.text("toast-settings-not-saved")));
+            }
+        }
+    }
+}
+
+fn refresh_video_menu(
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut button_query: Query<(&MenuButton, &mut Text)>,
+    mut title_query: Query<&mut Text, (With<MenuTitle>, Without<MenuButton>)>,
+) {
+    if !settings.is_changed() && !locale.is_changed() {
+        return;
+    }
+    for (button, mut text) in button_query.iter_mut() {
+        text.sections[0].value = VIDEO_MENU[button.0].label(&settings, &locale);
+    }
+    for mut text in title_query.iter_mut() {
+        text.sections[0].value = locale.text("video-title");
+    }
+}
+
+fn spawn_stats_screen(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    locale: Res<Locale>,
+    stats: Res<Stats>,
+) {
+    let count = |id, count| locale.text_with(id, &fluent_args!["count" => count]);
+    let lines = [
+        count("stats-matches-played", stats.matches_played),
+        count("stats-p1-wins", stats.p1_wins),
+        count("stats-p2-wins", stats.p2_wins),
+        count("stats-longest-rally", stats.longest_rally),
+        locale.text_with(
+            "stats-fastest-ball",
+            &fluent_args!["speed" => stats.fastest_ball.round()],
+        ),
+    ];
+
+    commands
+        .spawn_bundle(overlay())
+        .insert(StatsScreen)
+        .insert_bundle((Menu::default(), MenuEntity))
+        .with_children(|parent| {
+            let title = locale.text("stats-title");
+            parent.spawn_bundle(label(&font, &title, MENU_FONT_SIZE, FOREGROUND_COLOR));
+            for line in lines.iter() {
+                parent.spawn_bundle(label(&font, line, STATS_FONT_SIZE, FOREGROUND_COLOR));
+            }
+            parent
+                .spawn_bundle(label(
+                    &font,
+                    &locale.text("back"),
+                    STATS_FONT_SIZE,
+                    UNSELECTED_COLOR,
+                ))
+                .insert(MenuButton(0));
+        });
+}
+
+fn stats_screen_actions(
+    mut menu_events: EventReader<MenuEvent>,
+    screen_query: Query<(), With<StatsScreen>>,
+    mut state: ResMut<State<AppState>>,
+) {
+    for event in actions_on(&mut menu_events, &screen_query) {
+        if matches!(event.action, MenuAction::Confirm | MenuAction::Cancel) {
+            let _ = state.set(AppState::MainMenu);
+        }
+    }
+}
+
+#[derive(Component)]
+struct LeaderboardScreen;
+
+/// Says whether the leaderboard is loading, or couldn't be reached.
+#[derive(Component)]
+struct LeaderboardStatus;
+
+/// One of the [`LEADERBOARD_COLUMNS`] the top scores are laid out in.
+#[derive(Component)]
+struct LeaderboardColumn(usize);
+
+fn spawn_leaderboard_screen(mut commands: Commands, font: Res<UiFont>, locale: Res<Locale>) {
+    commands
+        .spawn_bundle(overlay())
+        .insert(LeaderboardScreen)
+        .insert_bundle((Menu::default(), MenuEntity))
+        .with_children(|parent| {
+            let title = locale.text("leaderboard-title");
+            parent.spawn_bundle(label(&font, &title, MENU_FONT_SIZE, FOREGROUND_COLOR));
+            parent
+                .spawn_bundle(label(&font, "", STATS_FONT_SIZE, FOREGROUND_COLOR))
+                .insert(LeaderboardStatus);
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::FlexStart,
+                        margin: Rect {
+                            bottom: MENU_ITEM_SPACING,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    color: Color::NONE.into(),
+                    ..default()
+                })
+                .with_children(|row| {
+                    for column in 0..LEADERBOARD_COLUMNS {
+                        let mut text = label(&font, "", LEADERBOARD_FONT_SIZE, FOREGROUND_COLOR);
+                        text.style.margin = Rect {
+                            left: MENU_ITEM_SPACING,
+                            right: MENU_ITEM_SPACING,
+                            ..default()
+                        };
+                        row.spawn_bundle(text).insert(LeaderboardColumn(column));
+                    }
+                });
+            parent
+                .spawn_bundle(label(
+                    &font,
+                    &locale.text("back"),
+                    STATS_FONT_SIZE,
+                    UNSELECTED_COLOR,
+                ))
+                .insert(MenuButton(0));
+        });
+}
+
+/// Fills in the leaderboard as it comes in, a column of ranks at a time.
+fn show_top_scores(
+    locale: Res<Locale>,
+    top_scores: Res<TopScores>,
+    mut status_query: Query<&mut Text, With<LeaderboardStatus>>,
+    mut column_query: Query<(&LeaderboardColumn, &mut Text), Without<LeaderboardStatus>>,
+    spawned_query: Query<(), Added<LeaderboardStatus>>,
+) {
+    if !top_scores.is_changed() && spawned_query.is_empty() {
+        return;
+    }
+
+    let (status, top) = match &*top_scores {
+        TopScores::Unavailable => (locale.text("leaderboard-unavailable"), &[][..]),
+        TopScores::Loading => (locale.text("leaderboard-loading"), &[][..]),
+        TopScores::Loaded(top) if top.is_empty() => (locale.text("leaderboard-empty"), &top[..]),
+        TopScores::Loaded(top) => (String::new(), &top[..]),
+        TopScores::Stale(top) => (locale.text("leaderboard-stale"), &top[..]),
+    };
+    for mut text in status_query.iter_mut() {
+        text.sections[0].value = status.clone();
+    }
+
+    let per_column = TOP_COUNT.div_ceil(LEADERBOARD_COLUMNS);
+    for (column, mut text) in column_query.iter_mut() {
+        let lines: Vec<String> = top
+            .iter()
+            .enumerate()
+            .skip(column.0 * per_column)
+            .take(per_column)
+            .map(|(rank, entry)| {
+                let name: String = entry.name.chars().take(LEADERBOARD_NAME_LENGTH).collect();
+                format!("{:>3}. {} {}", rank + 1, name, entry.score)
+            })
+            .collect();
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+fn leaderboard_screen_actions(
+    mut menu_events: EventReader<MenuEvent>,
+    screen_query: Query<(), With<LeaderboardScreen>>,
+    mut state: ResMut<State<AppState>>,
+) {
+    for event in actions_on(&mut menu_events, &screen_query) {
+        if matches!(event.action, MenuAction::Confirm | MenuAction::Cancel) {
+            let _ = state.set(AppState::MainMenu);
+        }
+    }
+}
+
+#[derive(Component)]
+struct AchievementsScreen;
+
+/// Every achievement with what it takes, those still locked greyed out.
+fn spawn_achievements_screen(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    locale: Res<Locale>,
+    achievements: Res<Achievements>,
+) {
+    commands
+        .spawn_bundle(overlay())
+        .insert(AchievementsScreen)
+        .insert_bundle((Menu::default(), MenuEntity))
+        .with_children(|parent| {
+            let title = locale.text("achievements-title");
+            parent.spawn_bundle(label(&font, &title, MENU_FONT_SIZE, FOREGROUND_COLOR));
+            let unlocked = locale.text_with(
+                "achievements-unlocked",
+                &fluent_args![
+                    "unlocked" => achievements.unlocked.len(),
+                    "total" => Achievement::ALL.len()
+                ],
+            );
+            parent.spawn_bundle(label(&font, &unlocked, STATS_FONT_SIZE, FOREGROUND_COLOR));
+            for achievement in Achievement::ALL {
+                let color = if achievements.unlocked.contains(&achievement) {
+                    FOREGROUND_COLOR
+                } else {
+                    UNSELECTED_COLOR
+                };
+                let id = achievement.locale_id();
+                let line = format!(
+                    "{} - {}",
+                    locale.text(id),
+                    locale.text(&format!("{}-description", id))
+                );
+                parent.spawn_bundle(label(&font, &line, STATS_FONT_SIZE, color));
+            }
+            parent
+                .spawn_bundle(label(
+                    &font,
+                    &locale.text("back"),
+                    STATS_FONT_SIZE,
+                    UNSELECTED_COLOR,
+                ))
+                .insert(MenuButton(0));
+        });
+}
+
+fn achievements_screen_actions(
+    mut menu_events: EventReader<MenuEvent>,
+    screen_query: Query<(), With<AchievementsScreen>>,
+    mut state: ResMut<State<AppState>>,
+) {
+    for event in actions_on(&mut menu_events, &screen_query) {
+        if matches!(event.action, MenuAction::Confirm | MenuAction::Cancel) {
+            let _ = state.set(AppState::MainMenu);
+        }
+    }
+}
+
+/// Modifiers a [`DailyChallenge`] might have on, as named on the setup
+/// screen.
+const DAILY_MODIFIERS: [SetupMenuEntry; 6] = [
+    SetupMenuEntry::StickyPaddles,
+    SetupMenuEntry::WinByTwo,
+    SetupMenuEntry::ShrinkingPaddles,
+    SetupMenuEntry::MovingGoals,
+    SetupMenuEntry::FogOfWar,
+    SetupMenuEntry::MirrorControls,
+];
+
+/// Today's challenge, kept from when the screen opened in case the day
+/// turns over before it is played.
+#[derive(Component)]
+struct DailyScreen(DailyChallenge);
+
+fn daily_modifier_on(rules: &MatchRules, modifier: SetupMenuEntry) -> bool {
+    match modifier {
+        SetupMenuEntry::StickyPaddles => rules.sticky_paddles,
+        SetupMenuEntry::WinByTwo => rules.win_by_two,
+        SetupMenuEntry::ShrinkingPaddles => rules.shrinking_paddles,
+        SetupMenuEntry::MovingGoals => rules.moving_goals,
+        SetupMenuEntry::FogOfWar => rules.fog_of_war,
+        SetupMenuEntry::MirrorControls => rules.mirror_controls,
+        _ => false,
+    }
+}
+
+/// What today's challenge is and the best played here at it so far.
+fn spawn_daily_screen(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    locale: Res<Locale>,
+    bests: Res<DailyBests>,
+) {
+    let challenge = DailyChallenge::today();
+    let rules = &challenge.rules;
+    // Only the rules go into the lines below.
+    let (setup, profiles) = (MatchSetup::default(), Profiles::default());
+    let mut lines = vec![
+        challenge.date_string(),
+        SetupMenuEntry::Difficulty.label(rules, &setup, &profiles, &locale),
+    ];
+    let modifiers: Vec<String> = DAILY_MODIFIERS
+        .into_iter()
+        .filter(|modifier| daily_modifier_on(rules, *modifier))
+        .map(|modifier| modifier.label(rules, &setup, &profiles, &locale))
+        .collect();
+    if modifiers.is_empty() {
+        lines.push(locale.text("daily-no-modifiers"));
+    }
+    lines.extend(modifiers);
+    lines.push(match bests.best.get(&challenge.day) {
+        Some(best) => locale.text_with("daily-best", &fluent_args!["score" => *best]),
+        None => locale.text("daily-not-played"),
+    });
+
+    commands
+        .spawn_bundle(overlay())
+        .insert(DailyScreen(challenge))
+        .insert_bundle((Menu::default(), MenuEntity))
+        .with_children(|parent| {
+            let title = locale.text("daily-title");
+            parent.spawn_bundle(label(&font, &title, MENU_FONT_SIZE, FOREGROUND_COLOR));
+            for line in lines {
+                parent.spawn_bundle(label(&font, &line, STATS_FONT_SIZE, FOREGROUND_COLOR));
+            }
+            for (index, id) in ["daily-play", "back"].into_iter().enumerate() {
+                parent
+                    .spawn_bundle(label(
+                        &font,
+                        &locale.text(id),
+                        MENU_FONT_SIZE,
+                        UNSELECTED_COLOR,
+                    ))
+                    .insert(MenuButton(index));
+            }
+        });
+}
+
+#[allow(clippy::too_many_arguments)]
+fn daily_screen_actions(
+    mut commands: Commands,
+    mut menu_events: EventReader<MenuEvent>,
+    screen_query: Query<(), With<DailyScreen>>,
+    challenge_query: Query<&DailyScreen>,
+    mut rng: ResMut<GameRng>,
+    mut rules: ResMut<MatchRules>,
+    mut config: ResMut<GameConfig>,
+    mut layout: ResMut<ArenaLayout>,
+    mut state: ResMut<State<AppState>>,
+) {
+    for event in actions_on(&mut menu_events, &screen_query) {
+        match (event.action, event.button) {
+            (MenuAction::Confirm, 0) => {
+                let challenge = match challenge_query.get(event.menu) {
+                    Ok(screen) => screen.0.clone(),
+                    Err(_) => continue,
+                };
+                daily::start_daily(&mut commands, &mut rng, &mut rules, challenge);
+                // Everyone plays it with the stock ball, paddles and court.
+                MatchSetup::default().apply(&mut config, &mut rules);
+                *layout = ArenaLayout::default();
+                let _ = state.set(AppState::Playing);
+            }
+            (MenuAction::Confirm, _) | (MenuAction::Cancel, _) => {
+                let _ = state.set(AppState::MainMenu);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Component)]
+struct ProfilesScreen;
+
+/// The name of the next profile, as far as it has been typed.
+#[derive(Default)]
+struct NewProfileName(String);
+
+/// Labels for the profiles screen: the profile being typed in, over the
+/// keyboard to type it with, then each profile with its paddle colour and
+/// stats, then the way back.
+fn profiles_screen_entries(
+    profiles: &Profiles,
+    name: &NewProfileName,
+    keyboard: &OnScreenKeyboard,
+    locale: &Locale,
+) -> Vec<String> {
+    let typed = format!("{}_", name.0);
+    let rows = profiles.profiles.iter().map(|profile| {
+        let stats = &profile.stats;
+        locale.text_with(
+            "profiles-entry",
+            &fluent_args![
+                "name" => profile.name.clone(),
+                "color" => selector(profile.paddle_color),
+                "skin" => profile.skin.clone().unwrap_or_else(|| "none".to_string()),
+                "wins" => stats.wins,
+                "played" => stats.matches_played,
+                "rally" => stats.longest_rally
+            ],
+        )
+    });
+    let new = locale.text_with("profiles-new", &fluent_args!["name" => typed]);
+    std::iter::once(format!("{}\n{}", new, keyboard.render(locale)))
+        .chain(rows)
+        .chain(std::iter::once(locale.text("back")))
+        .collect()
+}
+
+fn spawn_profiles_screen(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    locale: Res<Locale>,
+    profiles: Res<Profiles>,
+    mut name: ResMut<NewProfileName>,
+    mut keyboard: ResMut<OnScreenKeyboard>,
+) {
+    name.0.clear();
+    *keyboard = OnScreenKeyboard::alphanumeric();
+    let entries = profiles_screen_entries(&profiles, &name, &keyboard, &locale);
+    spawn_menu_screen(
+        &mut commands,
+        &font,
+        ProfilesScreen,
+        (&locale.text("profiles-title"), MENU_FONT_SIZE),
+        entries.into_iter().map(|entry| (entry, STATS_FONT_SIZE)),
+    );
+}
+
+/// Types the name of a new profile, keeping letters from the menus. The
+/// arrow keys are still there to get around with.
+fn type_profile_name(
+    state: Res<State<AppState>>,
+    mut name: ResMut<NewProfileName>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+) {
+    let typed: Vec<char> = characters.iter().map(|character| character.char).collect();
+    if *state.current() != AppState::Profiles {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Back) && !name.0.is_empty() {
+        name.0.pop();
+        keyboard_input.reset(KeyCode::Back);
+    }
+    for character in typed
+        .into_iter()
+        .filter(|c| c.is_alphanumeric() || *c == ' ')
+    {
+        if name.0.chars().count() < MAX_NAME_LENGTH {
+            name.0.push(character);
+        }
+    }
+
+    let keys: Vec<KeyCode> = keyboard_input
+        .get_pressed()
+        .chain(keyboard_input.get_just_pressed())
+        .chain(keyboard_input.get_just_released())
+        .copied()
+        .filter(|key| !matches!(key, KeyCode::Return | KeyCode::Escape | KeyCode::Back))
+        .filter(|key| !matches!(key, KeyCode::Up | KeyCode::Down))
+        .filter(|key| !matches!(key, KeyCode::Left | KeyCode::Right))
+        .collect();
+    for key in keys {
+        keyboard_input.reset(key);
+    }
+}
+
+fn profiles_screen_actions(
+    mut menu_events: EventReader<MenuEvent>,
+    screen_query: Query<(), With<ProfilesScreen>>,
+    mut profiles: ResMut<Profiles>,
+    mut name: ResMut<NewProfileName>,
+    mut keyboard: ResMut<OnScreenKeyboard>,
+    skins: Res<Skins>,
+    mut state: ResMut<State<AppState>>,
+) {
+    let back = profiles.profiles.len() + 1;
+    for event in actions_on(&mut menu_events, &screen_query) {
+        let step = match event.action {
+            MenuAction::Cancel => {
+                let _ = state.set(AppState::MainMenu);
+                continue;
+            }
+            MenuAction::Confirm if event.button == 0 && keyboard.focused() != OnScreenKey::Done => {
+                keyboard.press(&mut name.0, MAX_NAME_LENGTH);
+                continue;
+            }
+            MenuAction::Confirm if event.button == 0 => {
+                if profiles.add(&name.0) {
+                    name.0.clear();
+                }
+                continue;
+            }
+            MenuAction::Decrease if event.button == 0 => {
+                keyboard.nudge(-1);
+                continue;
+            }
+            MenuAction::Increase if event.button == 0 => {
+                keyboard.nudge(1);
+                continue;
+            }
+            MenuAction::Confirm if event.button == back => {
+                let _ = state.set(AppState::MainMenu);
+                continue;
+            }
+            MenuAction::Decrease => -1,
+            MenuAction::Confirm | MenuAction::Increase => 1,
+        };
+        // Left and right pick the paddle's colour, and confirming its skin.
+        if let Some(profile) = profiles.profiles.get_mut(event.button.wrapping_sub(1)) {
+            if event.action == MenuAction::Confirm {
+                profile.skin = cycle(&skin_options(&skins), profile.skin.clone(), 1);
+            } else {
+                profile.paddle_color = cycle(&PaddleColor::ALL, profile.paddle_color, step);
+            }
+        }
+    }
+}
+
+/// Keeps the profiles screen up to date, spawning it again to fit a profile
+/// just added.
+#[allow(clippy::too_many_arguments)]
+fn refresh_profiles_screen(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    locale: Res<Locale>,
+    profiles: Res<Profiles>,
+    name: Res<NewProfileName>,
+    keyboard: Res<OnScreenKeyboard>,
+    screen_query: Query<Entity, With<ProfilesScreen>>,
+    mut button_query: Query<(&Parent, &MenuButton, &mut Text)>,
+) {
+    let screen = match screen_query.get_single() {
+        Ok(screen) => screen,
+        Err(_) => return,
+    };
+    if !profiles.is_changed() && !name.is_changed() && !keyboard.is_changed() {
+        return;
+    }
+    let entries = profiles_screen_entries(&profiles, &name, &keyboard, &locale);
+    let mut buttons: Vec<_> = button_query
+        .iter_mut()
+        .filter(|(parent, _, _)| parent.0 == screen)
+        .map(|(_, button, text)| (button, text))
+        .collect();
+    if buttons.len() != entries.len() {
+        commands.entity(screen).despawn_recursive();
+        spawn_menu_screen(
+            &mut commands,
+            &font,
+            ProfilesScreen,
+            (&locale.text("profiles-title"), MENU_FONT_SIZE),
+            entries.into_iter().map(|entry| (entry, STATS_FONT_SIZE)),
+        );
+        return;
+    }
+    for (button, text) in buttons.iter_mut() {
+        text.sections[0].value = entries[button.0].clone();
+    }
+}
+
+#[derive(Component)]
+struct InstantReplayScreen;
+
+/// A banner along the top while the instant replay plays, leaving the court
+/// clear to watch, with a button to skip it.
+fn spawn_instant_replay_screen(mut commands: Commands, font: Res<UiFont>, locale: Res<Locale>) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: MENU_ITEM_SPACING,
+                    ..default()
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(InstantReplayScreen)
+        .insert(Menu::default())
+        .with_children(|parent| {
+            let title = locale.text("instant-replay-title");
+            let skip = locale.text("instant-replay-skip");
+            parent.spawn_bundle(label(&font, &title, MENU_FONT_SIZE, FOREGROUND_COLOR));
+            parent
+                .spawn_bundle(label(&font, &skip, STATS_FONT_SIZE, UNSELECTED_COLOR))
+                .insert(MenuButton(0));
+        });
+}
+
+fn instant_replay_screen_actions(
+    mut menu_events: EventReader<MenuEvent>,
+    screen_query: Query<(), With<InstantReplayScreen>>,
+    after: Res<AfterReplay>,
+    mut state: ResMut<State<AppState>>,
+) {
+    for event in actions_on(&mut menu_events, &screen_query) {
+        if let MenuAction::Confirm | MenuAction::Cancel = event.action {
+            let _ = state.set(after.0);
+        }
+    }
+}
+
+#[derive(Component)]
+struct SeriesScreen;
+
+/// The score of the series so far, after each of its games. Players switch
+/// ends before the next one.
+fn spawn_series_screen(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    locale: Res<Locale>,
+    rules: Res<MatchRules>,
+    series: Res<Series>,
+) {
+    let player = |player: Option<_>| player.map(|player| format!("{:?}", player));
+    let title = locale.text_with(
+        "series-game-won",
+        &fluent_args![
+            "player" => player(series.last_winner).unwrap_or_default(),
+            "game" => series.games_played(),
+        ],
+    );
+    let score = locale.text_with(
+        "series-score",
+        &fluent_args!["p1" => series.p1_games, "p2" => series.p2_games],
+    );
+    let (next, button) = match player(series.winner(&rules)) {
+        Some(winner) => (
+            locale.text_with("series-won", &fluent_args!["player" => winner]),
+            locale.text("pause-main-menu"),
+        ),
+        None => (
+            locale.text("series-switch-ends"),
+            locale.text("series-next"),
+        ),
+    };
+
+    commands
+        .spawn_bundle(overlay())
+        .insert(SeriesScreen)
+        .insert_bundle((Menu::default(), MenuEntity))
+        .with_children(|parent| {
+            parent.spawn_bundle(label(&font, &title, MENU_FONT_SIZE, FOREGROUND_COLOR));
+            parent.spawn_bundle(label(&font, &score, MENU_FONT_SIZE, FOREGROUND_COLOR));
+            parent.spawn_bundle(label(&font, &next, STATS_FONT_SIZE, FOREGROUND_COLOR));
+            parent
+                .spawn_bundle(label(&font, &button, STATS_FONT_SIZE, UNSELECTED_COLOR))
+                .insert(MenuButton(0));
+        });
+}
+
+fn series_screen_actions(
+    mut menu_events: EventReader<MenuEvent>,
+    screen_query: Query<(), With<SeriesScreen>>,
+    rules: Res<MatchRules>,
+    series: Res<Series>,
+    mut state: ResMut<State<AppState>>,
+) {
+    for event in actions_on(&mut menu_events, &screen_query) {
+        let _ = match event.action {
+            MenuAction::Confirm if series.winner(&rules).is_none() => state.set(AppState::Playing),
+            // Backing out gives up on the rest of the series.
+            MenuAction::Confirm | MenuAction::Cancel => state.set(AppState::MainMenu),
+            _ => continue,
+        };
+    }
+}
+
+#[derive(Component)]
+struct HostScreen;
+
+#[derive(Component)]
+struct RoomCodeText;
+
+/// Shown while a LAN game waits for someone to join it.
+fn spawn_host_screen(mut commands: Commands, font: Res<UiFont>, locale: Res<Locale>) {
+    commands
+        .spawn_bundle(overlay())
+        .insert(HostScreen)
+        .insert_bundle((Menu::default(), MenuEntity))
+        .with_children(|parent| {
+            let title = locale.text("lan-host-title");
+            let hint = locale.text("lan-host-hint");
+            parent.spawn_bundle(label(&font, &title, MENU_FONT_SIZE, FOREGROUND_COLOR));
+            parent.spawn_bundle(label(&font, &hint, STATS_FONT_SIZE, FOREGROUND_COLOR));
+            parent
+                .spawn_bundle(label(&font, "", MENU_FONT_SIZE, FOREGROUND_COLOR))
+                .insert(RoomCodeText);
+            parent
+                .spawn_bundle(label(
+                    &font,
+                    &locale.text("back"),
+                    STATS_FONT_SIZE,
+                    UNSELECTED_COLOR,
+                ))
+                .insert(MenuButton(0));
+        });
+}
+
+fn host_screen_actions(
+    mut menu_events: EventReader<MenuEvent>,
+    screen_query: Query<(), With<HostScreen>>,
+    mut state: ResMut<State<AppState>>,
+) {
+    for event in actions_on(&mut menu_events, &screen_query) {
+        if matches!(event.action, MenuAction::Confirm | MenuAction::Cancel) {
+            let _ = state.set(AppState::MainMenu);
+        }
+    }
+}
+
+/// The room code friends further away can join by, once the lobby server has
+/// given one.
+fn show_room_code(
+    locale: Res<Locale>,
+    game: Option<Res<HostedGame>>,
+    mut query: Query<&mut Text, With<RoomCodeText>>,
+) {
+    let code = match game.as_ref().and_then(|game| game.room_code()) {
+        Some(code) => code,
+        None => return,
+    };
+    for mut text in query.iter_mut() {
+        if text.sections[0].value.is_empty() {
+            text.sections[0].value =
+                locale.text_with("lan-host-room-code", &fluent_args!["code" => code]);
+        }
+    }
+}
+
+#[derive(Component)]
+struct LobbyScreen;
+
+/// Games found on the LAN, any of which can be joined, and a way back.
+fn spawn_lobby_screen(commands: &mut Commands, font: &UiFont, locale: &Locale, lobby: &Lobby) {
+    commands
+        .spawn_bundle(overlay())
+        .insert(LobbyScreen)
+        .insert_bundle((Menu::default(), MenuEntity))
+        .with_children(|parent| {
+            let title = locale.text("lan-lobby-title");
+            parent.spawn_bundle(label(font, &title, MENU_FONT_SIZE, FOREGROUND_COLOR));
+            if lobby.games.is_empty() {
+                let searching = locale.text("lan-lobby-searching");
+                parent.spawn_bundle(label(font, &searching, STATS_FONT_SIZE, FOREGROUND_COLOR));
+            }
+            for (index, game) in lobby.games.iter().enumerate() {
+                let text = locale.text_with(
+                    "lan-lobby-game",
+                    &fluent_args!["name" => game.name.clone(), "addr" => game.addr.to_string()],
+                );
+                parent
+                    .spawn_bundle(label(font, &text, STATS_FONT_SIZE, UNSELECTED_COLOR))
+                    .insert(MenuButton(index));
+            }
+            let room_code = locale.text("lan-lobby-room-code");
+            parent
+                .spawn_bundle(label(font, &room_code, STATS_FONT_SIZE, UNSELECTED_COLOR))
+                .insert(MenuButton(lobby.games.len()));
+            parent
+                .spawn_bundle(label(
+                    font,
+                    &locale.text("back"),
+                    STATS_FONT_SIZE,
+                    UNSELECTED_COLOR,
+                ))
+                .insert(MenuButton(lobby.games.len() + 1));
+        });
+}
+
+/// Puts the lobby up once it is open, and again whenever games come or go.
+fn refresh_lobby_screen(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    locale: Res<Locale>,
+    lobby: Option<Res<Lobby>>,
+    screen_query: Query<Entity, With<LobbyScreen>>,
+    mut shown: Local<Option<Vec<SocketAddr>>>,
+) {
+    let lobby = match lobby {
+        Some(lobby) => lobby,
+        None => {
+            *shown = None;
+            return;
+        }
+    };
+    let games: Vec<SocketAddr> = lobby.games.iter().map(|game| game.addr).collect();
+    if shown.as_ref() == Some(&games) && !screen_query.is_empty() {
+        return;
+    }
+
+    for screen in screen_query.iter() {
+        commands.entity(screen).despawn_recursive();
+    }
+    spawn_lobby_screen(&mut commands, &font, &locale, &lobby);
+    *shown = Some(games);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn lobby_screen_actions(
+    mut commands: Commands,
+    mut menu_events: EventReader<MenuEvent>,
+    screen_query: Query<(), With<LobbyScreen>>,
+    locale: Res<Locale>,
+    lobby: Option<Res<Lobby>>,
+    mut state: ResMut<State<AppState>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for event in actions_on(&mut menu_events, &screen_query) {
+        let games = lobby.as_ref().map_or(0, |lobby| lobby.games.len());
+        let game = lobby
+            .as_ref()
+            .and_then(|lobby| lobby.games.get(event.button));
+        match (event.action, game) {
+            (MenuAction::Confirm, Some(game)) => {
+                lan::join(&mut commands, game.addr);
+                toasts.send(Toast(locale.text("toast-lan-joining")));
+            }
+            (MenuAction::Confirm, None) if event.button == games => {
+                let _ = state.set(AppState::RoomCode);
+            }
+            (MenuAction::Confirm, None) | (MenuAction::Cancel, _) => {
+                let _ = state.set(AppState::MainMenu);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Component)]
+struct RoomCodeScreen;
+
+#[derive(Component)]
+struct TypedCode;
+
+/// For typing in the code of a room, to join a game through the lobby
+/// server.
+fn spawn_room_code_screen(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    locale: Res<Locale>,
+    mut keyboard: ResMut<OnScreenKeyboard>,
+) {
+    *keyboard = OnScreenKeyboard::letters();
+    commands
+        .spawn_bundle(overlay())
+        .insert(RoomCodeScreen)
+        .insert_bundle((Menu::default(), MenuEntity))
+        .with_children(|parent| {
+            let title = locale.text("lan-room-code-title");
+            parent.spawn_bundle(label(&font, &title, MENU_FONT_SIZE, FOREGROUND_COLOR));
+            parent
+                .spawn_bundle(label(&font, "", MENU_FONT_SIZE, UNSELECTED_COLOR))
+                .insert(TypedCode)
+                .insert(MenuButton(0));
+            parent
+                .spawn_bundle(label(
+                    &font,
+                    &locale.text("back"),
+                    STATS_FONT_SIZE,
+                    UNSELECTED_COLOR,
+                ))
+                .insert(MenuButton(1));
+        });
+}
+
+fn show_typed_code(
+    locale: Res<Locale>,
+    code: Res<RoomCode>,
+    keyboard: Res<OnScreenKeyboard>,
+    mut query: Query<&mut Text, With<TypedCode>>,
+) {
+    // Blanks for the letters still to come.
+    let shown = format!("{:_<width$}", code.0, width = CODE_LENGTH);
+    for mut text in query.iter_mut() {
+        let join = locale.text_with("lan-room-code-join", &fluent_args!["code" => shown.clone()]);
+        let value = format!("{}\n{}", join, keyboard.render(&locale));
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn room_code_screen_actions(
+    mut commands: Commands,
+    mut menu_events: EventReader<MenuEvent>,
+    screen_query: Query<(), With<RoomCodeScreen>>,
+    locale: Res<Locale>,
+    lobby: Option<Res<LobbyServer>>,
+    signalling: Option<Res<Signalling>>,
+    mut code: ResMut<RoomCode>,
+    mut keyboard: ResMut<OnScreenKeyboard>,
+    mut state: ResMut<State<AppState>>,
+    mut toasts: EventWriter<Toast>,
+) {
+    for event in actions_on(&mut menu_events, &screen_query) {
+        match (event.action, event.button) {
+            (MenuAction::Decrease, 0) => keyboard.nudge(-1),
+            (MenuAction::Increase, 0) => keyboard.nudge(1),
+            (MenuAction::Confirm, 0) if keyboard.focused() != OnScreenKey::Done => {
+                keyboard.press(&mut code.0, CODE_LENGTH);
+            }
+            (MenuAction::Confirm, 0) if code.0.len() < CODE_LENGTH => {}
+            (MenuAction::Confirm, 0) => match (signalling.as_deref(), lobby.as_deref()) {
+                (Some(signalling), _) => {
+                    lan::join_web_room(&mut commands, signalling, &code.0);
+                    toasts.send(Toast(locale.text("toast-lan-joining")));
+                }
+                (None, Some(lobby)) => {
+                    lan::join_room(&mut commands, lobby, &code.0);
+                    toasts.send(Toast(locale.text("toast-lan-joining")));
+                }
+                (None, None) => toasts.send(Toast(locale.text("toast-lobby-unset"))),
+            },
+            (MenuAction::Confirm, _) | (MenuAction::Cancel, _) => {
+                let _ = state.set(AppState::LanLobby);
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Component)]
+struct ReconnectScreen;
+
+#[derive(Component)]
+struct ReconnectCountdown;
+
+/// Laid over a LAN match while the line is down, counting down to the
+/// forfeit.
+fn spawn_reconnect_screen(mut commands: Commands, font: Res<UiFont>, locale: Res<Locale>) {
+    commands
+        .spawn_bundle(overlay())
+        .insert(ReconnectScreen)
+        .insert_bundle((Menu::default(), MenuEntity))
+        .with_children(|parent| {
+            let title = locale.text("lan-reconnect-title");
+            parent.spawn_bundle(label(&font, &title, MENU_FONT_SIZE, FOREGROUND_COLOR));
+            parent
+                .spawn_bundle(label(&font, "", STATS_FONT_SIZE, FOREGROUND_COLOR))
+                .insert(ReconnectCountdown);
+            parent
+                .spawn_bundle(label(
+                    &font,
+                    &locale.text("lan-reconnect-give-up"),
+                    STATS_FONT_SIZE,
+                    UNSELECTED_COLOR,
+                ))
+                .insert(MenuButton(0));
+        });
+}
+
+fn count_down_reconnect(
+    locale: Res<Locale>,
+    reconnect: Option<Res<Reconnect>>,
+    mut query: Query<&mut Text, With<ReconnectCountdown>>,
+) {
+    let reconnect = match reconnect {
+        Some(reconnect) => reconnect,
+        None => return,
+    };
+    let seconds = reconnect.seconds_left().ceil() as u32;
+    for mut text in query.iter_mut() {
+        text.sections[0].value = locale.text_with(
+            "lan-reconnect-countdown",
+            &fluent_args!["seconds" => seconds],
+        );
+    }
+}
+
+fn reconnect_screen_actions(
+    mut menu_events: EventReader<MenuEvent>,
+    screen_query: Query<(), With<ReconnectScreen>>,
+    mut state: ResMut<State<AppState>>,
+) {
+    for event in actions_on(&mut menu_events, &screen_query) {
+        if matches!(event.action, MenuAction::Confirm | MenuAction::Cancel) {
+            let _ = state.replace(AppState::MainMenu);
+        }
+    }
+}
+
+fn despawn_screen<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
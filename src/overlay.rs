@@ -0,0 +1,145 @@
+use bevy::{
+    diagnostic::{
+        Diagnostic, DiagnosticId, Diagnostics, EntityCountDiagnosticsPlugin,
+        FrameTimeDiagnosticsPlugin,
+    },
+    prelude::*,
+};
+
+use crate::{fonts::UiFont, Ball, Scoreboard, SimTick, Velocity};
+
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+const OVERLAY_FONT_SIZE: f32 = 14.0;
+const OVERLAY_PADDING: Val = Val::Px(15.0);
+const OVERLAY_COLOR: Color = Color::rgb(0.6, 1.0, 0.6);
+/// Frames the simulation gauges are averaged over.
+const HISTORY: usize = 20;
+
+/// Simulation ticks run per second of real time.
+const TICK_RATE: DiagnosticId = DiagnosticId::from_u128(0x5e1c_2b0f_93a4_4d71_8c66_0f2a_71d3_b948);
+/// How fast the ball is going, in units per second.
+const BALL_SPEED: DiagnosticId = DiagnosticId::from_u128(0x9a37_e4d2_1f58_4b06_a3c1_7d90_5e2b_c614);
+/// Paddle hits since the last goal.
+const FJONGS: DiagnosticId = DiagnosticId::from_u128(0x2c84_f7a1_6e03_49d5_b218_c5f9_0a6e_37d2);
+
+/// Frame rate, tick rate, entity count, ball speed and the fjongs counter in
+/// a corner of the screen, toggled with F3, for seeing where the time goes.
+///
+/// The numbers are ordinary [`Diagnostics`], so `LogDiagnosticsPlugin` can
+/// print them too.
+pub struct OverlayPlugin;
+
+impl Plugin for OverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Diagnostics>()
+            .init_resource::<SimTick>()
+            .init_resource::<Overlay>()
+            .add_plugin(FrameTimeDiagnosticsPlugin)
+            .add_plugin(EntityCountDiagnosticsPlugin)
+            .add_startup_system(add_gauges)
+            .add_system(measure_gauges)
+            .add_system(toggle_overlay)
+            .add_system(show_overlay.after(toggle_overlay));
+    }
+}
+
+#[derive(Default)]
+struct Overlay {
+    shown: bool,
+}
+
+fn add_gauges(mut diagnostics: ResMut<Diagnostics>) {
+    diagnostics.add(Diagnostic::new(TICK_RATE, "tick_rate", HISTORY));
+    diagnostics.add(Diagnostic::new(BALL_SPEED, "ball_speed", HISTORY));
+    diagnostics.add(Diagnostic::new(FJONGS, "fjongs", 1));
+}
+
+fn measure_gauges(
+    time: Res<Time>,
+    tick: Res<SimTick>,
+    scoreboard: Option<Res<Scoreboard>>,
+    ball_query: Query<&Velocity, With<Ball>>,
+    mut last_tick: Local<u64>,
+    mut diagnostics: ResMut<Diagnostics>,
+) {
+    let delta = time.delta_seconds_f64();
+    // A new match starts counting from zero again.
+    let ticks = tick.0.saturating_sub(*last_tick);
+    *last_tick = tick.0;
+    if delta > 0.0 {
+        diagnostics.add_measurement(TICK_RATE, ticks as f64 / delta);
+    }
+    if let Ok(velocity) = ball_query.get_single() {
+        diagnostics.add_measurement(BALL_SPEED, velocity.0.length() as f64);
+    }
+    if let Some(scoreboard) = scoreboard {
+        diagnostics.add_measurement(FJONGS, scoreboard.fjongs as f64);
+    }
+}
+
+fn toggle_overlay(keyboard_input: Res<Input<KeyCode>>, mut overlay: ResMut<Overlay>) {
+    if keyboard_input.just_pressed(TOGGLE_KEY) {
+        overlay.shown = !overlay.shown;
+    }
+}
+
+#[derive(Component)]
+struct OverlayText;
+
+fn show_overlay(
+    mut commands: Commands,
+    font: Res<UiFont>,
+    overlay: Res<Overlay>,
+    diagnostics: Res<Diagnostics>,
+    mut text_query: Query<(Entity, &mut Text), With<OverlayText>>,
+) {
+    if !overlay.shown {
+        for (entity, _) in text_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    let gauge = |id| {
+        diagnostics
+            .get(id)
+            .and_then(|diagnostic| diagnostic.average())
+            .unwrap_or_default()
+    };
+    let readout = format!(
+        "fps {:.0}\ntick rate {:.0}\nentities {:.0}\nball speed {:.0}\nfjongs {:.0}",
+        gauge(FrameTimeDiagnosticsPlugin::FPS),
+        gauge(TICK_RATE),
+        gauge(EntityCountDiagnosticsPlugin::ENTITY_COUNT),
+        gauge(BALL_SPEED),
+        gauge(FJONGS),
+    );
+
+    if let Ok((_, mut text)) = text_query.get_single_mut() {
+        text.sections[0].value = readout;
+        return;
+    }
+    commands
+        .spawn_bundle(TextBundle {
+            text: Text::with_section(
+                readout,
+                TextStyle {
+                    font: font.0.clone(),
+                    font_size: OVERLAY_FONT_SIZE,
+                    color: OVERLAY_COLOR,
+                },
+                default(),
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: OVERLAY_PADDING,
+                    right: OVERLAY_PADDING,
+                    ..default()
+                },
+                ..default()
+            },
+            ..default()
+        })
+        .insert(OverlayText);
+}
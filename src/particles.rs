@@ -0,0 +1,100 @@
+use std::f32::consts::PI;
+
+use bevy::{math::const_vec2, prelude::*};
+use rand::Rng;
+
+use crate::{settings::AccessibilitySettings, Ball, HitEvent, Player};
+
+const SMASH_PARTICLES: usize = 24;
+const PARTICLE_SIZE: Vec2 = const_vec2!([5.0, 5.0]);
+const PARTICLE_SECONDS: f32 = 0.4;
+const MIN_PARTICLE_SPEED: f32 = 150.0;
+const MAX_PARTICLE_SPEED: f32 = 450.0;
+/// How far either side of straight back towards the opponent the sparks fly.
+const SPRAY_ANGLE: f32 = PI / 3.0;
+/// Just behind the ball.
+const PARTICLE_Z: f32 = 0.9;
+
+/// Bursts of sparks off the ball when it is smashed.
+///
+/// Sparks are only for show, so they run on the frame clock and the thread
+/// rng rather than the simulation's.
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AccessibilitySettings>()
+            .add_system_to_stage(CoreStage::PostUpdate, burst_on_smash)
+            .add_system(fly_particles);
+    }
+}
+
+#[derive(Component)]
+struct Particle {
+    velocity: Vec2,
+    remaining: f32,
+}
+
+fn burst_on_smash(
+    mut commands: Commands,
+    accessibility: Res<AccessibilitySettings>,
+    mut hit_events: EventReader<HitEvent>,
+    ball_query: Query<(&Transform, &Sprite), With<Ball>>,
+) {
+    if !accessibility.particles {
+        return;
+    }
+    let mut rng = rand::thread_rng();
+    for hit in hit_events.iter().filter(|hit| hit.smash) {
+        let (transform, sprite) = match ball_query.get_single() {
+            Ok(ball) => ball,
+            Err(_) => return,
+        };
+        let heading = match hit.player {
+            Player::P1 => 0.0,
+            Player::P2 => PI,
+        };
+
+        for _ in 0..SMASH_PARTICLES {
+            let angle = heading + rng.gen_range(-SPRAY_ANGLE..SPRAY_ANGLE);
+            let speed = rng.gen_range(MIN_PARTICLE_SPEED..MAX_PARTICLE_SPEED);
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: sprite.color,
+                        custom_size: Some(PARTICLE_SIZE),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(
+                        transform.translation.truncate().extend(PARTICLE_Z),
+                    ),
+                    ..default()
+                })
+                .insert(Particle {
+                    velocity: Vec2::new(angle.cos(), angle.sin()) * speed,
+                    remaining: PARTICLE_SECONDS,
+                });
+        }
+    }
+}
+
+/// Moves sparks along, fading and shrinking them until they burn out.
+fn fly_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut particle, mut transform, mut sprite) in query.iter_mut() {
+        particle.remaining -= delta;
+        if particle.remaining <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation += (particle.velocity * delta).extend(0.0);
+        let life = particle.remaining / PARTICLE_SECONDS;
+        sprite.color.set_a(life);
+        sprite.custom_size = Some(PARTICLE_SIZE * life);
+    }
+}
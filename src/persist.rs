@@ -0,0 +1,38 @@
+use std::{fs, io, path::Path};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Reads a RON file, falling back to the default value if it is missing or
+/// broken. A missing file is created so it can be edited by hand.
+pub(crate) fn load_or_create<T>(path: &Path) -> T
+where
+    T: Default + Serialize + DeserializeOwned,
+{
+    match fs::read_to_string(path) {
+        Ok(contents) => ron::from_str(&contents).unwrap_or_else(|err| {
+            warn!("Ignoring invalid {}: {}", path.display(), err);
+            T::default()
+        }),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            let value = T::default();
+            if let Err(err) = save(&value, path) {
+                warn!("Could not write {}: {}", path.display(), err);
+            }
+            value
+        }
+        Err(err) => {
+            warn!("Could not read {}: {}", path.display(), err);
+            T::default()
+        }
+    }
+}
+
+pub(crate) fn save<T: Serialize>(value: &T, path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let contents = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    fs::write(path, contents)
+}
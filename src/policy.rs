@@ -0,0 +1,170 @@
+use std::{fs, io, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ai::{self, BallView, Brain, Difficulty, PaddleBrain, PaddleView, Predictor},
+    Arena, GameRng, MatchRules, Observation, SimulationStage, TickRate, PADDLE_SPEED,
+};
+
+/// A small feed-forward network mapping an [`Observation`] to a paddle
+/// action, as trained on [`FjongEnv`](crate::FjongEnv).
+///
+/// Every layer but the last uses `tanh`; the last has a single output, also
+/// squashed by `tanh`, so the action always lands between -1 and 1. Stored as
+/// RON, with `weights[i]` holding the inputs' weights for output `i`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Policy {
+    pub layers: Vec<Layer>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Layer {
+    pub weights: Vec<Vec<f32>>,
+    pub biases: Vec<f32>,
+}
+
+impl Policy {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Policy> {
+        let contents = fs::read_to_string(path)?;
+        let policy: Policy = ron::from_str(&contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        policy.check()?;
+        Ok(policy)
+    }
+
+    /// Makes sure every layer fits the one before and the network ends in a
+    /// single action.
+    fn check(&self) -> io::Result<()> {
+        let invalid = |message: &str| Err(io::Error::new(io::ErrorKind::InvalidData, message));
+        let mut inputs = Observation::SIZE;
+        for layer in &self.layers {
+            if layer.weights.len() != layer.biases.len() {
+                return invalid("a layer has a different number of weights and biases");
+            }
+            if layer.weights.iter().any(|row| row.len() != inputs) {
+                return invalid("a layer doesn't fit the layer before it");
+            }
+            inputs = layer.biases.len();
+        }
+        if self.layers.is_empty() || inputs != 1 {
+            return invalid("the last layer must have a single output");
+        }
+        Ok(())
+    }
+
+    /// The action, from -1 to 1, the policy takes on seeing `observation`.
+    pub fn act(&self, observation: &Observation) -> f32 {
+        let mut values = observation.to_array().to_vec();
+        for layer in &self.layers {
+            values = layer
+                .weights
+                .iter()
+                .zip(&layer.biases)
+                .map(|(row, bias)| {
+                    let sum: f32 = row.iter().zip(&values).map(|(w, v)| w * v).sum();
+                    (sum + bias).tanh()
+                })
+                .collect();
+        }
+        values.first().copied().unwrap_or(0.0)
+    }
+}
+
+/// Plays a trained [`Policy`] as an in-game opponent.
+pub struct PolicyBrain {
+    pub policy: Policy,
+    /// Paddle speed at an action of 1, a player's top speed by default.
+    pub max_speed: f32,
+}
+
+impl PolicyBrain {
+    pub fn new(policy: Policy) -> PolicyBrain {
+        PolicyBrain {
+            policy,
+            max_speed: PADDLE_SPEED,
+        }
+    }
+}
+
+impl PaddleBrain for PolicyBrain {
+    fn decide(
+        &mut self,
+        ball: &BallView,
+        paddle: &PaddleView,
+        arena: &Arena,
+        _tick_rate: TickRate,
+        _rng: &mut GameRng,
+    ) -> f32 {
+        let observation = Observation::new(ball, paddle, arena);
+        self.policy.act(&observation) * self.max_speed
+    }
+}
+
+/// Loads the policy stored at `path` as the [`Difficulty::Learned`] boss,
+/// and starts on it, with P2 played by the policy.
+///
+/// Picking any other difficulty hands P2 back to a [`Predictor`], and picking
+/// the boss again hands it back to the policy.
+pub struct PolicyPlugin {
+    pub path: String,
+}
+
+impl Plugin for PolicyPlugin {
+    fn build(&self, app: &mut App) {
+        match Policy::load(&self.path) {
+            Ok(policy) => {
+                info!("Loaded policy {}", self.path);
+                app.insert_resource(policy)
+                    .init_resource::<MatchRules>()
+                    .add_startup_system(pick_learned)
+                    .add_system_to_stage(
+                        SimulationStage,
+                        play_learned.before(ai::apply_difficulty),
+                    );
+            }
+            Err(err) => error!("Could not load policy {}: {}", self.path, err),
+        }
+    }
+}
+
+/// Marks a paddle played by the policy.
+#[derive(Component)]
+struct Learned;
+
+fn pick_learned(mut rules: ResMut<MatchRules>) {
+    rules.difficulty = Difficulty::Learned;
+}
+
+/// Swaps the AI between the policy and a [`Predictor`] as the difficulty goes
+/// to and from [`Difficulty::Learned`], at whichever end it is playing. Only
+/// paddles the AI already plays are touched, so a second player or a guest
+/// keeps theirs.
+fn play_learned(
+    mut commands: Commands,
+    rules: Res<MatchRules>,
+    policy: Res<Policy>,
+    brain_query: Query<(Entity, Option<&Learned>), With<Brain>>,
+    handed_over_query: Query<Entity, (With<Learned>, Without<Brain>)>,
+) {
+    // Whoever took the paddle over may give it back to a fresh brain.
+    for entity in handed_over_query.iter() {
+        commands.entity(entity).remove::<Learned>();
+    }
+
+    let learned = rules.difficulty == Difficulty::Learned;
+    for (entity, marker) in brain_query.iter() {
+        if learned && marker.is_none() {
+            commands
+                .entity(entity)
+                .insert(Brain::new(PolicyBrain::new(policy.clone())))
+                .insert(Learned);
+        } else if !learned && marker.is_some() {
+            commands
+                .entity(entity)
+                .insert(Brain::new(Predictor::new(rules.difficulty)))
+                .remove::<Learned>();
+        }
+    }
+}
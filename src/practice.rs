@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+
+use crate::{
+    Arena, Ball, Collider, CourtPiece, GameEntity, GameMode, GoalEvent, HitEvent, MatchRules,
+    P2Paddle, Player, Velocity, WallBundle, WallLocation,
+};
+
+/// How much faster each return in a streak sends the ball back.
+const SPEED_UP_PER_RETURN: f32 = 0.03;
+const MAX_SPEED_UP: f32 = 2.0;
+
+/// Returns in the current practice streak, and the best streak so far.
+#[derive(Default)]
+pub struct PracticeScore {
+    pub returns: usize,
+    pub best: usize,
+}
+
+/// Sent when P1 misses in practice, ending a streak of `returns`.
+pub struct StreakEvent {
+    pub returns: usize,
+}
+
+/// Stands in for P2 in practice.
+#[derive(Component)]
+pub struct ReturnWall;
+
+/// Puts a wall in front of P2's goal and takes P2 out of play for practice,
+/// and undoes that for a versus match. The wall goes with the rest of the
+/// court when another arena is picked, and is put back up in the new one.
+pub fn arrange_court(
+    mut commands: Commands,
+    rules: Res<MatchRules>,
+    arena: Res<Arena>,
+    return_wall_query: Query<Entity, With<ReturnWall>>,
+    mut p2_query: Query<(Entity, &mut Visibility), With<P2Paddle>>,
+) {
+    if !rules.is_changed() && !arena.is_changed() {
+        return;
+    }
+
+    let practice = rules.mode == GameMode::Practice;
+    let has_return_wall = !return_wall_query.is_empty();
+    if practice && !has_return_wall {
+        commands
+            .spawn_bundle(WallBundle::new(WallLocation::Return, &arena))
+            .insert(ReturnWall)
+            .insert_bundle((CourtPiece, GameEntity));
+    }
+    if !practice {
+        for wall in return_wall_query.iter() {
+            commands.entity(wall).despawn();
+        }
+    }
+
+    for (p2, mut visibility) in p2_query.iter_mut() {
+        visibility.is_visible = !practice;
+        if practice {
+            commands.entity(p2).remove::<Collider>();
+        } else {
+            commands.entity(p2).insert(Collider);
+        }
+    }
+}
+
+/// Counts P1's returns and sends the ball back a little faster each time,
+/// until P1 misses.
+pub fn count_returns(
+    rules: Res<MatchRules>,
+    mut practice_score: ResMut<PracticeScore>,
+    mut hit_events: EventReader<HitEvent>,
+    mut goal_events: EventReader<GoalEvent>,
+    mut streak_events: EventWriter<StreakEvent>,
+    mut ball_query: Query<&mut Velocity, With<Ball>>,
+) {
+    if rules.mode != GameMode::Practice {
+        return;
+    }
+
+    for hit in hit_events.iter() {
+        if hit.player != Player::P1 {
+            continue;
+        }
+        practice_score.returns += 1;
+        let speed_up =
+            (1.0 + practice_score.returns as f32 * SPEED_UP_PER_RETURN).min(MAX_SPEED_UP);
+        for mut velocity in ball_query.iter_mut() {
+            velocity.0 *= speed_up;
+        }
+    }
+
+    if goal_events.iter().count() > 0 {
+        practice_score.best = practice_score.best.max(practice_score.returns);
+        streak_events.send(StreakEvent {
+            returns: std::mem::take(&mut practice_score.returns),
+        });
+    }
+}
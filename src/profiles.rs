@@ -0,0 +1,326 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ai::{BaseSkill, Brain, Predictor},
+    persist, AppState, GoalEvent, HitEvent, KeyBindings, MatchOverEvent, MatchRules, P1Paddle,
+    P2Paddle, PaddleKeys, Player, Series,
+};
+
+/// Where profiles live, under [`data_path`](crate::data_path).
+pub const PROFILES_PATH: &str = "profiles.ron";
+/// As many as fit on the profiles screen.
+pub const MAX_PROFILES: usize = 10;
+/// Longer names are cut short when typed in.
+pub const MAX_NAME_LENGTH: usize = 12;
+
+/// Colours a profile can have its paddle painted, over the theme's own.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaddleColor {
+    /// Whatever the theme paints paddles.
+    #[default]
+    Theme,
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Cyan,
+    Blue,
+    Purple,
+    Pink,
+}
+
+impl PaddleColor {
+    pub const ALL: [PaddleColor; 9] = [
+        PaddleColor::Theme,
+        PaddleColor::Red,
+        PaddleColor::Orange,
+        PaddleColor::Yellow,
+        PaddleColor::Green,
+        PaddleColor::Cyan,
+        PaddleColor::Blue,
+        PaddleColor::Purple,
+        PaddleColor::Pink,
+    ];
+
+    pub fn color(self) -> Option<Color> {
+        match self {
+            PaddleColor::Theme => None,
+            PaddleColor::Red => Some(Color::rgb(0.95, 0.25, 0.25)),
+            PaddleColor::Orange => Some(Color::rgb(1.0, 0.6, 0.1)),
+            PaddleColor::Yellow => Some(Color::rgb(1.0, 0.9, 0.2)),
+            PaddleColor::Green => Some(Color::rgb(0.3, 0.9, 0.35)),
+            PaddleColor::Cyan => Some(Color::rgb(0.2, 0.9, 0.95)),
+            PaddleColor::Blue => Some(Color::rgb(0.3, 0.5, 1.0)),
+            PaddleColor::Purple => Some(Color::rgb(0.65, 0.35, 1.0)),
+            PaddleColor::Pink => Some(Color::rgb(1.0, 0.45, 0.8)),
+        }
+    }
+}
+
+/// A profile's record, over every match it has played.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileStats {
+    pub matches_played: u32,
+    pub wins: u32,
+    /// Most paddle hits in a single point.
+    pub longest_rally: u32,
+}
+
+/// A local player, whichever side of the court they play on.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    /// Keys to play with, or the usual keys of whichever side the profile
+    /// plays on. Only set by editing [`PROFILES_PATH`].
+    #[serde(default)]
+    pub keys: Option<PaddleKeys>,
+    #[serde(default)]
+    pub paddle_color: PaddleColor,
+    /// Name of the [`Skin`](crate::Skin) the profile's paddle is drawn with,
+    /// over the one picked in the settings.
+    #[serde(default)]
+    pub skin: Option<String>,
+    #[serde(default)]
+    pub stats: ProfileStats,
+}
+
+impl Profile {
+    pub fn new(name: impl Into<String>) -> Profile {
+        Profile {
+            name: name.into(),
+            keys: None,
+            paddle_color: PaddleColor::default(),
+            skin: None,
+            stats: ProfileStats::default(),
+        }
+    }
+}
+
+/// Every profile, and who is playing as P1 and P2, kept in
+/// [`PROFILES_PATH`] between runs.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profiles {
+    pub profiles: Vec<Profile>,
+    /// Name of the profile P1 plays as, or none for a guest.
+    pub p1: Option<String>,
+    /// Name of the profile P2 plays as, or none for the AI.
+    pub p2: Option<String>,
+}
+
+impl Profiles {
+    pub fn load(path: impl AsRef<Path>) -> Profiles {
+        persist::load_or_create(path.as_ref())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        persist::save(self, path.as_ref())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Profile> {
+        self.profiles
+            .iter_mut()
+            .find(|profile| profile.name == name)
+    }
+
+    /// Name of the profile `player` plays as.
+    pub fn selected(&self, player: Player) -> Option<&str> {
+        match player {
+            Player::P1 => self.p1.as_deref(),
+            Player::P2 => self.p2.as_deref(),
+        }
+    }
+
+    pub fn select(&mut self, player: Player, name: Option<String>) {
+        match player {
+            Player::P1 => self.p1 = name,
+            Player::P2 => self.p2 = name,
+        }
+    }
+
+    /// The profile `player` plays as, if it is still there.
+    pub fn playing(&self, player: Player) -> Option<&Profile> {
+        self.selected(player).and_then(|name| self.get(name))
+    }
+
+    /// Adds a profile called `name`, unless the name is blank or taken or
+    /// there are already [`MAX_PROFILES`].
+    pub fn add(&mut self, name: &str) -> bool {
+        let name = name.trim();
+        if name.is_empty() || self.get(name).is_some() || self.profiles.len() >= MAX_PROFILES {
+            return false;
+        }
+        self.profiles.push(Profile::new(name));
+        true
+    }
+}
+
+/// Loads [`Profiles`] from `path` and plays each match with the keys and
+/// paddle colours of the profiles picked for it, adding up their stats.
+/// Picking a profile for P2 on the setup screen takes P2's paddle from the
+/// AI for a second player on the same keyboard, until the match is over.
+pub struct ProfilesPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for ProfilesPlugin {
+    fn build(&self, app: &mut App) {
+        // Profiles without keys of their own play with these.
+        let stock = app
+            .world
+            .get_resource::<KeyBindings>()
+            .cloned()
+            .unwrap_or_default();
+        app.insert_resource(Profiles::load(&self.path))
+            .insert_resource(ProfilesFile(self.path.clone()))
+            .insert_resource(StockBindings(stock))
+            .init_resource::<KeyBindings>()
+            .init_resource::<MatchRules>()
+            .init_resource::<Series>()
+            .init_resource::<PaddleColors>()
+            .init_resource::<MatchRallies>()
+            .add_system_set(SystemSet::on_exit(AppState::Setup).with_system(seat_second_player))
+            .add_system_set(SystemSet::on_enter(AppState::MainMenu).with_system(send_home))
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(apply_profiles))
+            .add_system(record_matches)
+            .add_system(save_profiles.after(record_matches));
+    }
+}
+
+struct ProfilesFile(PathBuf);
+
+struct StockBindings(KeyBindings);
+
+/// Paddle colours at P1's and P2's ends, from the profiles playing there.
+#[derive(Default)]
+pub(crate) struct PaddleColors(pub(crate) [Option<Color>; 2]);
+
+/// A paddle taken from the AI for a second player.
+#[derive(Component)]
+struct SecondPlayer;
+
+/// Rallies of the match so far.
+#[derive(Default)]
+struct MatchRallies {
+    rally: u32,
+    longest: u32,
+}
+
+fn seat_second_player(
+    mut commands: Commands,
+    profiles: Res<Profiles>,
+    ai_query: Query<Entity, With<Brain>>,
+) {
+    if profiles.playing(Player::P2).is_none() {
+        return;
+    }
+    // The AI's paddle, which is P1's once a series has switched ends.
+    for paddle in ai_query.iter() {
+        commands
+            .entity(paddle)
+            .remove::<Brain>()
+            .remove::<BaseSkill>()
+            .insert(SecondPlayer);
+    }
+}
+
+/// Gives the second player's paddle back to the AI.
+fn send_home(
+    mut commands: Commands,
+    rules: Res<MatchRules>,
+    query: Query<Entity, With<SecondPlayer>>,
+) {
+    for paddle in query.iter() {
+        commands
+            .entity(paddle)
+            .remove::<SecondPlayer>()
+            .insert(Brain::new(Predictor::new(rules.difficulty)));
+    }
+}
+
+fn apply_profiles(
+    profiles: Res<Profiles>,
+    stock: Res<StockBindings>,
+    series: Res<Series>,
+    mut bindings: ResMut<KeyBindings>,
+    mut paddle_colors: ResMut<PaddleColors>,
+    mut rallies: ResMut<MatchRallies>,
+) {
+    let keys = |player| {
+        let profile_keys = profiles
+            .playing(player)
+            .and_then(|profile| profile.keys.clone());
+        profile_keys.unwrap_or_else(|| stock.0.keys(player).clone())
+    };
+    bindings.p1 = keys(Player::P1);
+    bindings.p2 = keys(Player::P2);
+
+    let color = |end| {
+        let profile = profiles.playing(series.player_on(end));
+        profile.and_then(|profile| profile.paddle_color.color())
+    };
+    paddle_colors.0 = [color(Player::P1), color(Player::P2)];
+    *rallies = MatchRallies::default();
+}
+
+/// Paddles nobody but a player is driving.
+type PlayerPaddle = (Or<(With<P1Paddle>, With<P2Paddle>)>, Without<Brain>);
+
+/// Adds each match to the stats of the profiles that played in it. A bot
+/// playing as a profile, across the LAN say, doesn't count.
+fn record_matches(
+    series: Res<Series>,
+    mut profiles: ResMut<Profiles>,
+    mut rallies: ResMut<MatchRallies>,
+    paddle_query: Query<Option<&P1Paddle>, PlayerPaddle>,
+    mut hit_events: EventReader<HitEvent>,
+    mut goal_events: EventReader<GoalEvent>,
+    mut match_over_events: EventReader<MatchOverEvent>,
+) {
+    rallies.rally += hit_events.iter().count() as u32;
+    rallies.longest = rallies.longest.max(rallies.rally);
+    if goal_events.iter().count() > 0 {
+        rallies.rally = 0;
+    }
+
+    for match_over in match_over_events.iter() {
+        let winner = series.player_on(match_over.winner);
+        for p1 in paddle_query.iter() {
+            let end = if p1.is_some() { Player::P1 } else { Player::P2 };
+            let player = series.player_on(end);
+            let name = match profiles.selected(player) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            if let Some(profile) = profiles.get_mut(&name) {
+                let stats = &mut profile.stats;
+                stats.matches_played += 1;
+                if player == winner {
+                    stats.wins += 1;
+                }
+                stats.longest_rally = stats.longest_rally.max(rallies.longest);
+            }
+        }
+        *rallies = MatchRallies::default();
+    }
+}
+
+fn save_profiles(file: Res<ProfilesFile>, profiles: Res<Profiles>) {
+    if !profiles.is_changed() || profiles.is_added() {
+        return;
+    }
+    if let Err(err) = profiles.save(&file.0) {
+        warn!("Could not save {}: {}", file.0.display(), err);
+    }
+}
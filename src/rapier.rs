@@ -0,0 +1,346 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_rapier2d::prelude::{
+    self as rapier, CoefficientCombineRule, NoUserData, PhysicsStages, RapierConfiguration,
+    RapierPhysicsPlugin, TimestepMode,
+};
+
+use crate::{
+    collision, drag_ball, return_ball, Ball, Brick, BrickEvent, Catch, Collider, CollisionEvent,
+    GameConfig, GoalEvent, HitEvent, MatchRules, P1Goal, P1Paddle, P2Goal, P2Paddle, Player,
+    Scoreboard, Serve, SimulationLabel, SimulationStage, Spin, TickRate, Velocity, WallBounceEvent,
+};
+
+/// World units to a metre, which Rapier's tolerances are tuned for.
+const PIXELS_PER_METER: f32 = 100.0;
+
+/// Rapier's physics in place of the game's own collisions. The ball is a
+/// dynamic body, the paddles kinematic ones following wherever the game
+/// moves them, the walls, obstacles and bricks fixed, and the goals sensors.
+///
+/// Rapier steps once per simulation tick, as part of the simulation, and
+/// what it reports is sent on as the same events the game's own collisions
+/// send. Paddle returns are angled by the same rules too, so matches play
+/// out alike but for the finer points of the bounces. Bodies are only added
+/// to Rapier the tick after they are spawned.
+///
+/// All of it runs as [`SimulationLabel::Collisions`], so whatever places the
+/// paddles and ball ahead of the collisions has done so before Rapier looks.
+///
+/// Only built with the `rapier` feature, which adds it to every
+/// [`SimulationPlugin`](crate::SimulationPlugin).
+pub struct RapierPlugin;
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
+enum RapierLabel {
+    Prepare,
+    Sync,
+    Step,
+    Writeback,
+    Restore,
+    Despawn,
+}
+
+impl Plugin for RapierPlugin {
+    fn build(&self, app: &mut App) {
+        let step = app
+            .world
+            .get_resource::<TickRate>()
+            .copied()
+            .unwrap_or_default()
+            .step();
+        let systems = |stage, label, after| {
+            RapierPhysicsPlugin::<NoUserData>::get_systems(stage)
+                .label(label)
+                .label(SimulationLabel::Collisions)
+                .after(after)
+        };
+        app.add_plugin(
+            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(PIXELS_PER_METER)
+                .with_default_system_setup(false),
+        )
+        .insert_resource(RapierConfiguration {
+            gravity: Vec2::ZERO,
+            timestep_mode: TimestepMode::Fixed {
+                dt: step,
+                substeps: 1,
+            },
+            ..default()
+        })
+        .init_resource::<Placement>()
+        .add_system_set_to_stage(
+            SimulationStage,
+            SystemSet::new()
+                .label(RapierLabel::Prepare)
+                .label(SimulationLabel::Collisions)
+                .after(SimulationLabel::Movement)
+                .after(SimulationLabel::Velocity)
+                .with_system(add_bodies)
+                .with_system(push_ball_velocity)
+                .with_system(remember_placement),
+        )
+        .add_system_set_to_stage(
+            SimulationStage,
+            systems(
+                PhysicsStages::SyncBackend,
+                RapierLabel::Sync,
+                RapierLabel::Prepare,
+            ),
+        )
+        .add_system_set_to_stage(
+            SimulationStage,
+            systems(
+                PhysicsStages::StepSimulation,
+                RapierLabel::Step,
+                RapierLabel::Sync,
+            ),
+        )
+        .add_system_set_to_stage(
+            SimulationStage,
+            systems(
+                PhysicsStages::Writeback,
+                RapierLabel::Writeback,
+                RapierLabel::Step,
+            ),
+        )
+        .add_system_set_to_stage(
+            SimulationStage,
+            systems(
+                PhysicsStages::DetectDespawn,
+                RapierLabel::Despawn,
+                RapierLabel::Writeback,
+            ),
+        )
+        .add_system_to_stage(
+            SimulationStage,
+            restore_placement
+                .label(RapierLabel::Restore)
+                .label(SimulationLabel::Collisions)
+                .after(RapierLabel::Writeback),
+        )
+        .add_system_to_stage(
+            SimulationStage,
+            report_collisions
+                .label(SimulationLabel::Collisions)
+                .after(RapierLabel::Restore),
+        );
+    }
+}
+
+/// Where the game put everything but the ball this tick. Rapier writes back
+/// the transform of every body it has, rounded through its own units, but
+/// only the ball is Rapier's to move.
+#[derive(Default)]
+struct Placement(HashMap<Entity, Vec3>);
+
+type Placed = (With<rapier::RigidBody>, Without<Ball>);
+
+fn remember_placement(
+    mut placement: ResMut<Placement>,
+    query: Query<(Entity, &Transform), Placed>,
+) {
+    placement.0.clear();
+    placement.0.extend(
+        query
+            .iter()
+            .map(|(entity, transform)| (entity, transform.translation)),
+    );
+}
+
+fn restore_placement(
+    placement: Res<Placement>,
+    mut query: Query<(Entity, &mut Transform), Placed>,
+) {
+    for (entity, mut transform) in query.iter_mut() {
+        if let Some(&translation) = placement.0.get(&entity) {
+            if transform.translation != translation {
+                transform.translation = translation;
+            }
+        }
+    }
+}
+
+type Unbodied = (Or<(With<Collider>, With<Ball>)>, Without<rapier::RigidBody>);
+
+/// Gives everything that collides a body and collider of Rapier's.
+#[allow(clippy::type_complexity)]
+fn add_bodies(
+    mut commands: Commands,
+    query: Query<
+        (
+            Entity,
+            Option<&Ball>,
+            Option<&P1Paddle>,
+            Option<&P2Paddle>,
+            Option<&P1Goal>,
+            Option<&P2Goal>,
+        ),
+        Unbodied,
+    >,
+) {
+    for (entity, ball, p1_paddle, p2_paddle, p1_goal, p2_goal) in query.iter() {
+        let mut entity = commands.entity(entity);
+        // A unit box, stretched to size by the transform like the sprite is.
+        entity.insert(rapier::Collider::cuboid(0.5, 0.5));
+        if ball.is_some() {
+            entity.insert_bundle((
+                rapier::RigidBody::Dynamic,
+                rapier::Velocity::zero(),
+                rapier::GravityScale(0.0),
+                rapier::LockedAxes::ROTATION_LOCKED,
+                rapier::Ccd::enabled(),
+                rapier::ActiveEvents::COLLISION_EVENTS,
+                // Whatever it hits, it keeps all its speed and slides off.
+                rapier::Restitution {
+                    coefficient: 1.0,
+                    combine_rule: CoefficientCombineRule::Max,
+                },
+                rapier::Friction {
+                    coefficient: 0.0,
+                    combine_rule: CoefficientCombineRule::Min,
+                },
+            ));
+        } else if p1_paddle.is_some() || p2_paddle.is_some() {
+            entity.insert(rapier::RigidBody::KinematicPositionBased);
+        } else {
+            entity.insert(rapier::RigidBody::Fixed);
+            if p1_goal.is_some() || p2_goal.is_some() {
+                entity.insert(rapier::Sensor(true));
+            }
+        }
+    }
+}
+
+/// The game steers the ball by its own [`Velocity`], with spin, serves and
+/// catches, so that is what Rapier goes on.
+fn push_ball_velocity(mut query: Query<(&Velocity, &mut rapier::Velocity), With<Ball>>) {
+    for (velocity, mut body) in query.iter_mut() {
+        body.linvel = velocity.0;
+    }
+}
+
+type Collided<'a> = (
+    &'a Transform,
+    Option<&'a Velocity>,
+    Option<&'a P1Goal>,
+    Option<&'a P2Goal>,
+    Option<&'a P1Paddle>,
+    Option<&'a P2Paddle>,
+    Option<&'a Brick>,
+);
+
+/// Takes the ball's velocity back from Rapier, and sends on what it hit as
+/// the game's own collisions would.
+#[allow(clippy::too_many_arguments)]
+fn report_collisions(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    rules: Res<MatchRules>,
+    mut scoreboard: ResMut<Scoreboard>,
+    serve: Res<Serve>,
+    catch: Res<Catch>,
+    mut contacts: EventReader<rapier::CollisionEvent>,
+    mut ball_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &mut rapier::Velocity,
+            &mut Spin,
+        ),
+        With<Ball>,
+    >,
+    collider_query: Query<Collided, (With<Collider>, Without<Ball>)>,
+    mut collision_events: EventWriter<CollisionEvent>,
+    mut goal_events: EventWriter<GoalEvent>,
+    mut wall_bounce_events: EventWriter<WallBounceEvent>,
+    mut hit_events: EventWriter<HitEvent>,
+    mut brick_events: EventWriter<BrickEvent>,
+) {
+    let (ball, mut ball_transform, mut velocity, mut body, mut spin) =
+        match ball_query.get_single_mut() {
+            Ok(ball) => ball,
+            // Not in Rapier until the tick after it is spawned.
+            Err(_) => return,
+        };
+    velocity.0 = body.linvel;
+
+    for contact in contacts.iter() {
+        let other = match contact {
+            rapier::CollisionEvent::Started(a, b, _) if *a == ball => *b,
+            rapier::CollisionEvent::Started(a, b, _) if *b == ball => *a,
+            _ => continue,
+        };
+        // The ball is out of play until it has been served, and while it
+        // is held.
+        if serve.in_progress() || catch.holding() {
+            continue;
+        }
+        let (transform, paddle_velocity, p1_goal, p2_goal, p1_paddle, p2_paddle, brick) =
+            match collider_query.get(other) {
+                Ok(collided) => collided,
+                Err(_) => continue,
+            };
+        collision_events.send_default();
+
+        if p1_goal.is_some() {
+            goal_events.send(GoalEvent { scorer: Player::P2 });
+            continue;
+        }
+        if p2_goal.is_some() {
+            goal_events.send(GoalEvent { scorer: Player::P1 });
+            continue;
+        }
+        // Rapier only bounces the ball off a contact on its next step, with
+        // the ball sunk into whatever it hit meanwhile, and a brick is gone
+        // by then. It is put clear and sent back at once instead, as the
+        // game's own collisions do.
+        if let Some(contact) = collision::contact(
+            ball_transform.translation.truncate(),
+            ball_transform.scale.truncate(),
+            transform.translation.truncate(),
+            transform.scale.truncate(),
+        ) {
+            ball_transform.translation += contact.push_out.extend(0.0);
+            velocity.0 = contact.reflect(velocity.0);
+        }
+        if p1_paddle.is_none() && p2_paddle.is_none() {
+            wall_bounce_events.send_default();
+        }
+        if brick.is_some() {
+            commands.entity(other).despawn();
+            brick_events.send(BrickEvent {
+                position: transform.translation.truncate(),
+            });
+            continue;
+        }
+
+        // Only hits on the face of a paddle get angled returns, as with the
+        // game's own collisions.
+        let beside = ball_transform.translation.x - transform.translation.x;
+        let half_width = transform.scale.x / 2.0;
+        let face = match (p1_paddle, p2_paddle) {
+            (Some(_), _) if beside > half_width => Some((Player::P1, 1.0)),
+            (_, Some(_)) if beside < -half_width => Some((Player::P2, -1.0)),
+            _ => None,
+        };
+        if let Some((player, towards)) = face {
+            let (returned, hit) = return_ball(
+                &config,
+                &rules,
+                &mut scoreboard,
+                player,
+                towards,
+                transform,
+                &ball_transform,
+            );
+            velocity.0 = returned;
+            hit_events.send(hit);
+        }
+        if p1_paddle.is_some() || p2_paddle.is_some() {
+            if let Some(paddle_velocity) = paddle_velocity {
+                drag_ball(&config, paddle_velocity.0, &mut velocity.0, &mut spin);
+            }
+        }
+        body.linvel = velocity.0;
+    }
+}
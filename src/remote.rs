@@ -0,0 +1,249 @@
+use std::{
+    io,
+    net::{SocketAddr, TcpListener},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use bevy::prelude::*;
+use fluent::fluent_args;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ai::{BallView, Brain, PaddleBrain, PaddleView, AI_MAX_SPEED},
+    emote::{Emote, RemoteEmotes},
+    transport::{self, Transport},
+    Arena, GameRng, Locale, P2Paddle, TickRate, Toast,
+};
+
+/// Lets an outside program play P2 over TCP, for bot battles.
+///
+/// Listens on `addr`, and hands P2's paddle to whichever bot connected last.
+/// Each tick the game sends one line of JSON:
+///
+/// ```json
+/// {"ball":{"position":[0.0,0.0],"velocity":[400.0,50.0]},
+///  "paddle":{"position":[390.0,0.0],"velocity":[0.0,0.0],"size":[20.0,120.0]},
+///  "arena":{"left":-450.0,"right":450.0,"bottom":-300.0,"top":300.0}}
+/// ```
+///
+/// and the bot answers, whenever it likes, with lines such as
+/// `{"velocity":-250.0}` giving the speed it wants the paddle to move at.
+/// Bots are as fast as the fastest built-in AI, and no faster.
+pub struct RemoteBotPlugin {
+    pub addr: SocketAddr,
+}
+
+impl Plugin for RemoteBotPlugin {
+    fn build(&self, app: &mut App) {
+        let listener = match TcpListener::bind(self.addr) {
+            Ok(listener) => listener,
+            Err(err) => {
+                error!("Could not listen for bots on {}: {}", self.addr, err);
+                return;
+            }
+        };
+        if let Err(err) = listener.set_nonblocking(true) {
+            error!("Could not listen for bots on {}: {}", self.addr, err);
+            return;
+        }
+        info!("Listening for bots on {}", self.addr);
+
+        app.add_event::<Toast>()
+            .init_resource::<Locale>()
+            .insert_resource(BotListener(listener))
+            .add_system(accept_bots);
+    }
+}
+
+struct BotListener(TcpListener);
+
+fn accept_bots(
+    mut commands: Commands,
+    listener: Res<BotListener>,
+    p2_query: Query<Entity, With<P2Paddle>>,
+    locale: Res<Locale>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let (stream, peer) = match listener.0.accept() {
+        Ok(connection) => connection,
+        Err(err) if err.kind() == io::ErrorKind::WouldBlock => return,
+        Err(err) => {
+            warn!("Could not accept bot: {}", err);
+            return;
+        }
+    };
+    let brain = match RemoteBrain::new(stream) {
+        Ok(brain) => brain,
+        Err(err) => {
+            warn!("Could not set up bot {}: {}", peer, err);
+            return;
+        }
+    };
+
+    info!("Bot connected from {}", peer);
+    let message = locale.text_with(
+        "toast-bot-connected",
+        &fluent_args!["peer" => peer.to_string()],
+    );
+    toasts.send(Toast(message));
+    if let Some(entity) = p2_query.iter().next() {
+        commands.entity(entity).insert(Brain::new(brain));
+    }
+}
+
+#[derive(Serialize)]
+struct BotState<'a> {
+    ball: &'a BallView,
+    paddle: &'a PaddleView,
+    arena: &'a Arena,
+}
+
+#[derive(Deserialize)]
+struct BotCommand {
+    velocity: f32,
+    /// Sent now and then by people playing from another machine, to be
+    /// echoed back so they can time the round trip.
+    #[serde(default)]
+    ping: Option<u64>,
+    /// Sent by people playing from another machine when they emote.
+    #[serde(default)]
+    emote: Option<Emote>,
+}
+
+/// The latest ping a remote player sent, shared between the brain that reads
+/// it and whatever sends their snapshots, which echo it back.
+#[derive(Clone, Default)]
+pub struct Echo(Arc<AtomicU64>);
+
+impl Echo {
+    fn set(&self, ping: u64) {
+        // Nothing heard yet is kept as 0.
+        self.0.store(ping.saturating_add(1), Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> Option<u64> {
+        self.0.load(Ordering::Relaxed).checked_sub(1)
+    }
+}
+
+/// Echoes are the same if they are for the same player.
+impl PartialEq for Echo {
+    fn eq(&self, other: &Echo) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// A brain that asks a program on the other end of a line what to do.
+///
+/// See [`RemoteBotPlugin`] for what goes over the wire. The paddle keeps the
+/// last velocity it was sent, and stops once the bot goes away.
+pub struct RemoteBrain {
+    line: Option<Box<dyn Transport>>,
+    incoming: Vec<u8>,
+    outgoing: Vec<u8>,
+    velocity: f32,
+    /// Sends the bot what the paddle sees every tick.
+    sends_state: bool,
+    echo: Echo,
+    emotes: RemoteEmotes,
+}
+
+impl RemoteBrain {
+    pub fn new(line: impl Into<Box<dyn Transport>>) -> io::Result<RemoteBrain> {
+        let line = line.into();
+        line.set_up()?;
+        Ok(RemoteBrain {
+            line: Some(line),
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+            velocity: 0.0,
+            sends_state: true,
+            echo: Echo::default(),
+            emotes: RemoteEmotes::default(),
+        })
+    }
+
+    /// Only takes commands, for a person on another machine who sees the
+    /// match through [`Snapshot`](crate::Snapshot)s sent down the same line.
+    /// Their pings are left in `echo` for the snapshots to send back.
+    pub fn listening(line: impl Into<Box<dyn Transport>>, echo: Echo) -> io::Result<RemoteBrain> {
+        Ok(RemoteBrain {
+            sends_state: false,
+            echo,
+            ..RemoteBrain::new(line)?
+        })
+    }
+
+    /// Where the emotes of whoever is on the other end are left, to go on
+    /// their paddle.
+    pub(crate) fn emotes(&self) -> RemoteEmotes {
+        self.emotes.clone()
+    }
+
+    /// Reads whatever the bot has sent and acts on every complete command.
+    fn receive(&mut self, line: &mut dyn Transport) -> io::Result<()> {
+        transport::fill(&mut self.incoming, line)?;
+
+        while let Some(end) = self.incoming.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.incoming.drain(..=end).collect();
+            match serde_json::from_slice::<BotCommand>(&line) {
+                Ok(command) => {
+                    self.velocity = command.velocity;
+                    if let Some(ping) = command.ping {
+                        self.echo.set(ping);
+                    }
+                    if let Some(emote) = command.emote {
+                        self.emotes.push(emote);
+                    }
+                }
+                Err(err) => warn!("Ignoring bad bot command: {}", err),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PaddleBrain for RemoteBrain {
+    fn decide(
+        &mut self,
+        ball: &BallView,
+        paddle: &PaddleView,
+        arena: &Arena,
+        _tick_rate: TickRate,
+        _rng: &mut GameRng,
+    ) -> f32 {
+        let mut line = match self.line.take() {
+            Some(line) => line,
+            None => return 0.0,
+        };
+
+        if self.sends_state {
+            let state = BotState {
+                ball,
+                paddle,
+                arena,
+            };
+            // Plain numbers and field names always serialize.
+            serde_json::to_writer(&mut self.outgoing, &state).unwrap();
+            self.outgoing.push(b'\n');
+        }
+
+        let sent = transport::flush(&mut self.outgoing, &mut *line);
+        match sent.and_then(|_| self.receive(&mut *line)) {
+            Ok(()) => self.line = Some(line),
+            Err(err) => {
+                warn!("Bot disconnected: {}", err);
+                self.velocity = 0.0;
+            }
+        }
+
+        if self.velocity.is_finite() {
+            self.velocity.clamp(-AI_MAX_SPEED, AI_MAX_SPEED)
+        } else {
+            0.0
+        }
+    }
+}
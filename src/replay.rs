@@ -0,0 +1,294 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    capture, AppState, Arena, Ball, GameConfig, MatchOverEvent, P1Paddle, P2Paddle, Scoreboard,
+    SimTick, Snapshot, TickRate, BACKGROUND_COLOR, BALL_SIZE, FOREGROUND_COLOR,
+};
+
+/// Where replays go, under [`data_path`](crate::data_path).
+pub const REPLAYS_DIR: &str = "replays";
+/// Size of a block of the score digits, in world units.
+const SCORE_BLOCK: f32 = 8.0;
+/// How far in from the top wall the scores are drawn.
+const SCORE_INSET: f32 = 30.0;
+/// How far either side of the centre line the scores are drawn.
+const SCORE_GAP: f32 = 40.0;
+/// Score digits three blocks wide and five high, a row to a byte.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+
+/// A match as it was played, a [`Snapshot`] for every tick, to be rendered
+/// again later without the game that played it.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub arena: Arena,
+    pub paddle_width: f32,
+    /// What the match ticked at. Older replays were all played at 60.
+    #[serde(default)]
+    pub tick_rate: TickRate,
+    pub snapshots: Vec<Snapshot>,
+}
+
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Replay> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, serde_json::to_vec(self)?)
+    }
+
+    /// Renders every tick into `dir` as `frame-000001.png` and on, returning
+    /// how many frames there were.
+    pub fn export_frames(&self, width: u32, height: u32, dir: &Path) -> io::Result<usize> {
+        fs::create_dir_all(dir)?;
+        let renderer = ReplayRenderer::new(self, width, height);
+        for (number, snapshot) in self.snapshots.iter().enumerate() {
+            let path = dir.join(format!("frame-{:06}.png", number + 1));
+            fs::write(
+                path,
+                capture::png(width, height, &renderer.frame(snapshot))?,
+            )?;
+        }
+        Ok(self.snapshots.len())
+    }
+
+    /// Renders every tick and pipes it to `ffmpeg` to be encoded into
+    /// `video`, at a frame a tick.
+    pub fn export_video(&self, width: u32, height: u32, video: &Path) -> io::Result<()> {
+        let mut ffmpeg = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo"])
+            .args(["-pixel_format", "rgba"])
+            .args(["-video_size", &format!("{}x{}", width, height)])
+            .args(["-framerate", &self.tick_rate.0.to_string()])
+            .args(["-i", "-", "-pix_fmt", "yuv420p"])
+            .arg(video)
+            .stdin(Stdio::piped())
+            .spawn()?;
+        let renderer = ReplayRenderer::new(self, width, height);
+        let mut stdin = ffmpeg.stdin.take().unwrap();
+        for snapshot in &self.snapshots {
+            stdin.write_all(&renderer.frame(snapshot))?;
+        }
+        drop(stdin);
+        let status = ffmpeg.wait()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("ffmpeg failed: {}", status)));
+        }
+        Ok(())
+    }
+}
+
+/// Draws the court of a [`Replay`] as it was on a given tick, in software,
+/// so a replay renders the same wherever it is rendered and at any size.
+///
+/// The court is drawn plainly, in the classic colours, and scaled to fit
+/// the frame with bars either side as needed.
+pub struct ReplayRenderer {
+    width: u32,
+    height: u32,
+    arena: Arena,
+    paddle_width: f32,
+    /// Pixels per world unit.
+    scale: f32,
+}
+
+impl ReplayRenderer {
+    pub fn new(replay: &Replay, width: u32, height: u32) -> ReplayRenderer {
+        let arena = replay.arena.clone();
+        let court_width = arena.width() + arena.wall_thickness;
+        let court_height = arena.height() + arena.wall_thickness;
+        let scale = (width as f32 / court_width).min(height as f32 / court_height);
+        ReplayRenderer {
+            width,
+            height,
+            arena,
+            paddle_width: replay.paddle_width,
+            scale,
+        }
+    }
+
+    /// The frame for `snapshot`, RGBA row by row from the top.
+    pub fn frame(&self, snapshot: &Snapshot) -> Vec<u8> {
+        let background = rgba8(BACKGROUND_COLOR);
+        let mut rgba = background.repeat((self.width * self.height) as usize);
+
+        let arena = &self.arena;
+        let wall = Vec2::new(arena.width() + arena.wall_thickness, arena.wall_thickness);
+        self.fill(&mut rgba, Vec2::new(0.0, arena.top), wall);
+        self.fill(&mut rgba, Vec2::new(0.0, arena.bottom), wall);
+
+        for paddle in [snapshot.p1, snapshot.p2] {
+            let size = Vec2::new(self.paddle_width, paddle.height);
+            self.fill(&mut rgba, paddle.position, size);
+        }
+        self.fill(&mut rgba, snapshot.ball, BALL_SIZE.truncate());
+
+        let top = arena.top - SCORE_INSET;
+        let p1_digits = snapshot.p1_score.to_string();
+        let p1_width = p1_digits.len() as f32 * 4.0 * SCORE_BLOCK - SCORE_BLOCK;
+        self.draw_number(&mut rgba, &p1_digits, -SCORE_GAP - p1_width, top);
+        self.draw_number(&mut rgba, &snapshot.p2_score.to_string(), SCORE_GAP, top);
+        rgba
+    }
+
+    /// Draws `digits` from `left` along, hanging down from `top`.
+    fn draw_number(&self, rgba: &mut [u8], digits: &str, left: f32, top: f32) {
+        let block = Vec2::splat(SCORE_BLOCK);
+        for (place, digit) in digits.bytes().enumerate() {
+            let rows = DIGITS[(digit - b'0') as usize];
+            let digit_left = left + place as f32 * 4.0 * SCORE_BLOCK;
+            for (row, bits) in rows.iter().enumerate() {
+                for column in 0..3 {
+                    if bits & (0b100 >> column) == 0 {
+                        continue;
+                    }
+                    let centre = Vec2::new(
+                        digit_left + (column as f32 + 0.5) * SCORE_BLOCK,
+                        top - (row as f32 + 0.5) * SCORE_BLOCK,
+                    );
+                    self.fill(rgba, centre, block);
+                }
+            }
+        }
+    }
+
+    /// Fills a rectangle of the court, given in world units, with the
+    /// foreground colour.
+    fn fill(&self, rgba: &mut [u8], centre: Vec2, size: Vec2) {
+        let to_pixels = |point: Vec2| {
+            Vec2::new(
+                self.width as f32 / 2.0 + point.x * self.scale,
+                self.height as f32 / 2.0 - point.y * self.scale,
+            )
+        };
+        let top_left = to_pixels(centre + Vec2::new(-size.x, size.y) / 2.0).round();
+        let bottom_right = to_pixels(centre + Vec2::new(size.x, -size.y) / 2.0).round();
+        let clamp = |value: f32, max: u32| value.clamp(0.0, max as f32) as usize;
+        let (left, right) = (
+            clamp(top_left.x, self.width),
+            clamp(bottom_right.x, self.width),
+        );
+        let (top, bottom) = (
+            clamp(top_left.y, self.height),
+            clamp(bottom_right.y, self.height),
+        );
+
+        let color = rgba8(FOREGROUND_COLOR);
+        let row = self.width as usize * 4;
+        for y in top..bottom {
+            for pixel in rgba[y * row + left * 4..y * row + right * 4].chunks_exact_mut(4) {
+                pixel.copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+fn rgba8(color: Color) -> [u8; 4] {
+    color
+        .as_rgba_f32()
+        .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+/// Records a [`Replay`] of every match and writes it as JSON into `dir` once
+/// the match is over, named after when it finished. Needs the
+/// [`SimulationPlugin`](crate::SimulationPlugin).
+pub struct ReplayPlugin {
+    pub dir: PathBuf,
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Arena>()
+            .init_resource::<GameConfig>()
+            .init_resource::<TickRate>()
+            .init_resource::<Recording>()
+            .insert_resource(ReplaysDir(self.dir.clone()))
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(start_recording))
+            // Once the tick is over, as spectators are sent it.
+            .add_system_to_stage(CoreStage::PostUpdate, record_replay);
+    }
+}
+
+struct ReplaysDir(PathBuf);
+
+/// The replay of the match so far.
+#[derive(Default)]
+struct Recording(Replay);
+
+fn start_recording(
+    arena: Res<Arena>,
+    config: Res<GameConfig>,
+    tick_rate: Res<TickRate>,
+    mut recording: ResMut<Recording>,
+) {
+    recording.0 = Replay {
+        arena: arena.clone(),
+        paddle_width: config.paddle_size.x,
+        tick_rate: *tick_rate,
+        snapshots: Vec::new(),
+    };
+}
+
+#[allow(clippy::too_many_arguments)]
+fn record_replay(
+    tick: Res<SimTick>,
+    scoreboard: Res<Scoreboard>,
+    dir: Res<ReplaysDir>,
+    mut recording: ResMut<Recording>,
+    ball_query: Query<&Transform, With<Ball>>,
+    p1_query: Query<&Transform, With<P1Paddle>>,
+    p2_query: Query<&Transform, With<P2Paddle>>,
+    mut match_over_events: EventReader<MatchOverEvent>,
+) {
+    if tick.is_changed() && !tick.is_added() {
+        recording.0.snapshots.push(Snapshot::take(
+            &tick,
+            &scoreboard,
+            ball_query.get_single().ok(),
+            p1_query.iter().next(),
+            p2_query.iter().next(),
+        ));
+    }
+
+    for _ in match_over_events.iter() {
+        match write_replay(&dir.0, &recording.0) {
+            Ok(path) => info!("Wrote the replay to {}", path.display()),
+            Err(err) => warn!("Could not write the replay: {}", err),
+        }
+    }
+}
+
+fn write_replay(dir: &Path, replay: &Replay) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let finished_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let mut path = dir.join(format!("match-{}.json", finished_at));
+    let mut number = 1;
+    while path.exists() {
+        number += 1;
+        path = dir.join(format!("match-{}-{}.json", finished_at, number));
+    }
+    replay.save(&path)?;
+    Ok(path)
+}
@@ -0,0 +1,154 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use instant::SystemTime;
+use serde::Serialize;
+
+use crate::{
+    AppState, GameConfig, GameRng, GoalEvent, HitEvent, MatchOverEvent, MatchRules, Player,
+    Scoreboard, Series, SimTick,
+};
+
+/// Where match results go, under [`data_path`](crate::data_path).
+pub const RESULTS_DIR: &str = "results";
+
+/// One goal of a match.
+#[derive(Clone, Debug, Serialize)]
+pub struct PointResult {
+    pub scorer: Player,
+    /// Paddle hits in the rally that ended in the goal.
+    pub rally: u32,
+    /// The [`SimTick`] the goal was scored on.
+    pub tick: u64,
+}
+
+/// Everything about a finished match worth keeping, for leagues and for
+/// looking back over how it went.
+///
+/// Players are named as they were at the start of the match, whichever end
+/// a [`Series`] has them at since.
+#[derive(Clone, Debug, Serialize)]
+pub struct MatchResult {
+    /// Seconds since the Unix epoch.
+    pub started_at: u64,
+    pub finished_at: u64,
+    pub winner: Player,
+    pub p1_score: usize,
+    pub p2_score: usize,
+    pub points: Vec<PointResult>,
+    /// Plays the match out again, given the same moves.
+    pub seed: u64,
+    pub rules: MatchRules,
+    pub config: GameConfig,
+}
+
+/// Writes a [`MatchResult`] as JSON into `dir` after every match, naming the
+/// file after when the match finished. Needs the
+/// [`SimulationPlugin`](crate::SimulationPlugin).
+pub struct ResultsPlugin {
+    pub dir: PathBuf,
+}
+
+impl Plugin for ResultsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MatchRules>()
+            .init_resource::<GameConfig>()
+            .init_resource::<Series>()
+            .init_resource::<MatchLog>()
+            .insert_resource(ResultsDir(self.dir.clone()))
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(start_log))
+            .add_system(log_match);
+    }
+}
+
+struct ResultsDir(PathBuf);
+
+/// The match so far.
+#[derive(Default)]
+struct MatchLog {
+    started_at: u64,
+    points: Vec<PointResult>,
+    rally: u32,
+}
+
+fn unix_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+fn start_log(mut log: ResMut<MatchLog>) {
+    *log = MatchLog {
+        started_at: unix_seconds(),
+        ..default()
+    };
+}
+
+/// P1's and P2's scores, whichever ends they are at.
+fn by_player(series: &Series, scoreboard: &Scoreboard) -> (usize, usize) {
+    match series.player_on(Player::P1) {
+        Player::P1 => (scoreboard.p1_score, scoreboard.p2_score),
+        Player::P2 => (scoreboard.p2_score, scoreboard.p1_score),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn log_match(
+    tick: Res<SimTick>,
+    rng: Res<GameRng>,
+    rules: Res<MatchRules>,
+    config: Res<GameConfig>,
+    series: Res<Series>,
+    scoreboard: Res<Scoreboard>,
+    dir: Res<ResultsDir>,
+    mut log: ResMut<MatchLog>,
+    mut hit_events: EventReader<HitEvent>,
+    mut goal_events: EventReader<GoalEvent>,
+    mut match_over_events: EventReader<MatchOverEvent>,
+) {
+    log.rally += hit_events.iter().count() as u32;
+    for goal in goal_events.iter() {
+        let point = PointResult {
+            scorer: series.player_on(goal.scorer),
+            rally: std::mem::take(&mut log.rally),
+            tick: tick.0,
+        };
+        log.points.push(point);
+    }
+
+    for match_over in match_over_events.iter() {
+        let (p1_score, p2_score) = by_player(&series, &scoreboard);
+        let result = MatchResult {
+            started_at: log.started_at,
+            finished_at: unix_seconds(),
+            winner: series.player_on(match_over.winner),
+            p1_score,
+            p2_score,
+            points: log.points.clone(),
+            seed: rng.seed(),
+            rules: rules.clone(),
+            config: config.clone(),
+        };
+        match write_result(&dir.0, &result) {
+            Ok(path) => info!("Wrote the match result to {}", path.display()),
+            Err(err) => warn!("Could not write the match result: {}", err),
+        }
+    }
+}
+
+fn write_result(dir: &Path, result: &MatchResult) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    // Two matches hardly ever finish in the same second, but if they do,
+    // the second doesn't overwrite the first.
+    let mut path = dir.join(format!("match-{}.json", result.finished_at));
+    let mut number = 1;
+    while path.exists() {
+        number += 1;
+        path = dir.join(format!("match-{}-{}.json", result.finished_at, number));
+    }
+    fs::write(&path, serde_json::to_vec_pretty(result)?)?;
+    Ok(path)
+}
@@ -0,0 +1,136 @@
+use std::{fs, path::Path};
+
+use bevy::prelude::*;
+use rhai::{Engine, Scope, AST};
+
+use crate::{GameConfig, GoalEvent, HitEvent, MatchRules, Player};
+
+/// Folder, relative to the working directory, that mods are loaded from.
+pub const MODS_PATH: &str = "mods";
+
+/// Most operations a mod may run per event, so a runaway loop can't hang the
+/// game.
+const MAX_OPERATIONS: u64 = 10_000;
+
+/// Custom rule sets written in Rhai, loaded from every `.rhai` file in
+/// [`MODS_PATH`].
+///
+/// Each mod runs from top to bottom whenever a goal is scored or the ball is
+/// hit. It can read what happened from `event` (`"goal"` or `"hit"`),
+/// `player` (`"P1"` or `"P2"`, the scorer or the hitter) and `smash`, and can
+/// change these, which carry over to the game afterwards:
+///
+/// - `ball_speed`: speed of the ball coming off a paddle.
+/// - `paddle_height`: height of both paddles.
+/// - `goal_points`: points a goal is worth.
+///
+/// ```rhai
+/// // Every goal makes the next rally a little faster.
+/// if event == "goal" {
+///     ball_speed += 25.0;
+/// }
+/// ```
+///
+/// Only built with the `scripting` feature.
+pub struct ScriptingPlugin;
+
+impl Plugin for ScriptingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Mods::load(MODS_PATH))
+            .add_system(run_mods);
+    }
+}
+
+struct Mods {
+    engine: Engine,
+    scripts: Vec<(String, AST)>,
+}
+
+impl Mods {
+    fn load(folder: impl AsRef<Path>) -> Mods {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+
+        let mut scripts = Vec::new();
+        let entries = match fs::read_dir(folder.as_ref()) {
+            Ok(entries) => entries,
+            // No mods folder, no mods.
+            Err(_) => return Mods { engine, scripts },
+        };
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "rhai"))
+            .collect();
+        // Mods run in name order, so their effects stack the same every time.
+        paths.sort();
+
+        for path in paths {
+            match engine.compile_file(path.clone()) {
+                Ok(ast) => {
+                    info!("Loaded mod {}", path.display());
+                    scripts.push((path.display().to_string(), ast));
+                }
+                Err(err) => warn!("Ignoring mod {}: {}", path.display(), err),
+            }
+        }
+        Mods { engine, scripts }
+    }
+
+    /// Runs every mod for one event, letting them change the whitelisted
+    /// parameters.
+    fn run(
+        &self,
+        event: &str,
+        player: Player,
+        smash: bool,
+        config: &mut GameConfig,
+        rules: &mut MatchRules,
+    ) {
+        for (name, ast) in &self.scripts {
+            let mut scope = Scope::new();
+            scope.push_constant("event", event.to_string());
+            scope.push_constant("player", format!("{:?}", player));
+            scope.push_constant("smash", smash);
+            scope.push("ball_speed", config.ball_speed as f64);
+            scope.push("paddle_height", config.paddle_size.y as f64);
+            scope.push("goal_points", rules.points_per_goal as i64);
+
+            if let Err(err) = self.engine.run_ast_with_scope(&mut scope, ast) {
+                warn!("Mod {} failed: {}", name, err);
+                continue;
+            }
+
+            // Kept within bounds the game can still be played in.
+            if let Some(speed) = scope.get_value::<f64>("ball_speed") {
+                config.ball_speed = (speed as f32).clamp(50.0, 3000.0);
+            }
+            if let Some(height) = scope.get_value::<f64>("paddle_height") {
+                config.paddle_size.y = (height as f32).clamp(10.0, 500.0);
+            }
+            if let Some(points) = scope.get_value::<i64>("goal_points") {
+                rules.points_per_goal = points.clamp(0, 100) as usize;
+            }
+        }
+    }
+}
+
+fn run_mods(
+    mods: Res<Mods>,
+    mut config: ResMut<GameConfig>,
+    mut rules: ResMut<MatchRules>,
+    mut goal_events: EventReader<GoalEvent>,
+    mut hit_events: EventReader<HitEvent>,
+) {
+    if mods.scripts.is_empty() {
+        return;
+    }
+
+    // Only touch the resources when something happened, so systems watching
+    // them for changes don't fire every frame.
+    for goal in goal_events.iter() {
+        mods.run("goal", goal.scorer, false, &mut config, &mut rules);
+    }
+    for hit in hit_events.iter() {
+        mods.run("hit", hit.player, hit.smash, &mut config, &mut rules);
+    }
+}
@@ -0,0 +1,98 @@
+use bevy::{
+    ecs::event::{Events, ManualEventReader},
+    prelude::*,
+};
+
+use crate::{
+    ai::{Brain, Predictor, Skill},
+    MatchOverEvent, MatchRules, P1Paddle, P2Paddle, Player, SimulationPlugin,
+};
+
+/// Longest a match may run before it is called off unfinished, in ticks:
+/// ten minutes at the default tick rate. Two brains that never miss would
+/// otherwise rally forever.
+pub const MAX_MATCH_TICKS: u64 = 36_000;
+
+/// How a run of matches went for one skill against another.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Record {
+    pub wins: usize,
+    pub losses: usize,
+    /// Matches still going after [`MAX_MATCH_TICKS`].
+    pub unfinished: usize,
+}
+
+impl Record {
+    pub fn played(&self) -> usize {
+        self.wins + self.losses + self.unfinished
+    }
+
+    /// Share of the finished matches that were won, if any finished.
+    pub fn win_rate(&self) -> Option<f32> {
+        let finished = self.wins + self.losses;
+        (finished > 0).then(|| self.wins as f32 / finished as f32)
+    }
+}
+
+/// Plays one match between two [`Predictor`]s on a headless simulation, as
+/// fast as it will tick, and returns who won it, if anyone did within
+/// [`MAX_MATCH_TICKS`]. The same skills, rules and seed always play out the
+/// same.
+pub fn play_match(p1: Skill, p2: Skill, rules: &MatchRules, seed: u64) -> Option<Player> {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(SimulationPlugin::stepped(seed))
+        .insert_resource(rules.clone());
+    // The paddles are only there after the first tick, which also gives P2
+    // the skill for the rules' difficulty, to be swapped out here.
+    app.update();
+    let paddles: Vec<(Entity, bool)> = app
+        .world
+        .query_filtered::<(Entity, Option<&P1Paddle>), Or<(With<P1Paddle>, With<P2Paddle>)>>()
+        .iter(&app.world)
+        .map(|(entity, p1_paddle)| (entity, p1_paddle.is_some()))
+        .collect();
+    for (entity, is_p1) in paddles {
+        let skill = if is_p1 { p1 } else { p2 };
+        app.world
+            .entity_mut(entity)
+            .insert(Brain::new(Predictor::with_skill(skill)));
+    }
+
+    let mut match_over = ManualEventReader::<MatchOverEvent>::default();
+    for _ in 0..MAX_MATCH_TICKS {
+        app.update();
+        let events = app.world.resource::<Events<MatchOverEvent>>();
+        if let Some(over) = match_over.iter(events).next() {
+            return Some(over.winner);
+        }
+    }
+    None
+}
+
+/// Plays `matches` matches of `skill` against `against`, seeded from `seed`
+/// on, and swapping ends every other match so neither has the better of
+/// the opening serve.
+pub fn play_matches(
+    skill: Skill,
+    against: Skill,
+    rules: &MatchRules,
+    matches: usize,
+    seed: u64,
+) -> Record {
+    let mut record = Record::default();
+    for i in 0..matches {
+        let seed = seed.wrapping_add(i as u64);
+        let (p1, p2, ours) = if i % 2 == 0 {
+            (skill, against, Player::P1)
+        } else {
+            (against, skill, Player::P2)
+        };
+        match play_match(p1, p2, rules, seed) {
+            Some(winner) if winner == ours => record.wins += 1,
+            Some(_) => record.losses += 1,
+            None => record.unfinished += 1,
+        }
+    }
+    record
+}
@@ -0,0 +1,117 @@
+use bevy::prelude::*;
+
+use crate::{
+    ai::{BaseSkill, Brain},
+    MatchRules, P1Paddle, P2Paddle, Player, Scoreboard,
+};
+
+/// Games won so far in a series of [`MatchRules::best_of`] games, and
+/// whether the players are playing from each other's ends.
+///
+/// Paddles, goals and the scoreboard's scores stay named after the end of
+/// the court they are at, which is where P1 and P2 start the series. After
+/// a switch, [`Series::player_on`] says who is at which end now, and their
+/// controls, AI and handicap go with them.
+#[derive(Default)]
+pub struct Series {
+    pub p1_games: usize,
+    pub p2_games: usize,
+    /// Who won the game just played.
+    pub last_winner: Option<Player>,
+    pub switched: bool,
+}
+
+impl Series {
+    /// Who is playing at P1's or P2's end of the court.
+    pub fn player_on(&self, end: Player) -> Player {
+        if self.switched {
+            end.other()
+        } else {
+            end
+        }
+    }
+
+    pub fn games_played(&self) -> usize {
+        self.p1_games + self.p2_games
+    }
+
+    /// Whoever has won more than half of the games, once someone has.
+    pub fn winner(&self, rules: &MatchRules) -> Option<Player> {
+        let needed = rules.best_of / 2 + 1;
+        if self.p1_games >= needed {
+            Some(Player::P1)
+        } else if self.p2_games >= needed {
+            Some(Player::P2)
+        } else {
+            None
+        }
+    }
+
+    /// The next goal for whoever is at `end` would win them the game, and
+    /// with it the series. Every game point of a single game is one.
+    pub fn match_point(&self, rules: &MatchRules, scoreboard: &Scoreboard, end: Player) -> bool {
+        let games = match self.player_on(end) {
+            Player::P1 => self.p1_games,
+            Player::P2 => self.p2_games,
+        };
+        rules.game_point_for(scoreboard, end) && games + 1 > rules.best_of / 2
+    }
+
+    /// Counts a game to whoever was at the `winner`'s end.
+    pub fn record(&mut self, winner: Player) {
+        let player = self.player_on(winner);
+        match player {
+            Player::P1 => self.p1_games += 1,
+            Player::P2 => self.p2_games += 1,
+        }
+        self.last_winner = Some(player);
+    }
+}
+
+/// Sends the players to each other's ends, taking any AI driving a paddle
+/// and their handicaps along with them.
+fn switch_ends(world: &mut World) {
+    let mut paddles = world.query_filtered::<Entity, Or<(With<P1Paddle>, With<P2Paddle>)>>();
+    let paddles: Vec<Entity> = paddles.iter(world).collect();
+    let drivers: Vec<_> = paddles
+        .iter()
+        .map(|&paddle| {
+            let mut paddle = world.entity_mut(paddle);
+            (paddle.remove::<Brain>(), paddle.remove::<BaseSkill>())
+        })
+        .collect();
+    for (&paddle, (brain, base_skill)) in paddles.iter().rev().zip(drivers) {
+        let mut paddle = world.entity_mut(paddle);
+        if let Some(brain) = brain {
+            paddle.insert(brain);
+        }
+        if let Some(base_skill) = base_skill {
+            paddle.insert(base_skill);
+        }
+    }
+
+    let mut rules = world.resource_mut::<MatchRules>();
+    let rules = &mut *rules;
+    std::mem::swap(&mut rules.p1_handicap, &mut rules.p2_handicap);
+    let mut series = world.resource_mut::<Series>();
+    series.switched = !series.switched;
+}
+
+/// Switches ends between the games of a series.
+pub fn next_game(world: &mut World) {
+    let decided = world
+        .resource::<Series>()
+        .winner(world.resource::<MatchRules>())
+        .is_some();
+    if !decided {
+        switch_ends(world);
+    }
+}
+
+/// Puts everyone back at their own end, ready for the next series.
+pub fn end_series(world: &mut World) {
+    if world.resource::<Series>().switched {
+        switch_ends(world);
+    }
+    *world.resource_mut::<Series>() = Series::default();
+}
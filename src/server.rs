@@ -0,0 +1,215 @@
+use std::{
+    io,
+    net::{SocketAddr, TcpListener},
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    lan::{self, Reconnect},
+    remote::Echo,
+    spectate::Spectators,
+    AppState, Locale, P1Paddle, P2Paddle, SpectatePlugin,
+};
+
+/// Runs matches between two players connecting to `addr` from elsewhere,
+/// with nobody playing on this machine. Only the server simulates the match:
+/// players send it their moves and are sent snapshots back, the same as a
+/// LAN guest, so neither has to trust the other's game.
+///
+/// A match starts once both seats are taken and is paused if either player
+/// drops, until they get back in. Whoever stays away too long forfeits, and
+/// the seats are opened up again, as they are once a match is over.
+///
+/// Needs the [`SimulationPlugin`](crate::SimulationPlugin), and runs
+/// headless on `MinimalPlugins`, see `fjong-server`.
+pub struct ServerPlugin {
+    pub addr: SocketAddr,
+}
+
+impl Plugin for ServerPlugin {
+    fn build(&self, app: &mut App) {
+        let listener = match TcpListener::bind(self.addr) {
+            Ok(listener) => listener,
+            Err(err) => {
+                error!("Could not listen for players on {}: {}", self.addr, err);
+                return;
+            }
+        };
+        if let Err(err) = listener.set_nonblocking(true) {
+            error!("Could not listen for players on {}: {}", self.addr, err);
+            return;
+        }
+        info!("Listening for players on {}", self.addr);
+
+        app.add_state(AppState::MainMenu)
+            .insert_resource(PlayerListener(listener))
+            .init_resource::<Seats>()
+            // Snapshots only, nothing here is ever watched.
+            .init_resource::<Locale>()
+            .add_plugin(SpectatePlugin)
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(crate::start_match))
+            .add_system(crate::end_match)
+            .add_system_set(SystemSet::on_enter(AppState::MainMenu).with_system(open_seats))
+            .add_system_set(SystemSet::on_update(AppState::MainMenu).with_system(take_seats))
+            .add_system_set(SystemSet::on_update(AppState::Playing).with_system(lose_players))
+            .add_system_set(
+                SystemSet::on_update(AppState::Reconnecting)
+                    .with_system(take_seats)
+                    .with_system(forfeit),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Reconnecting).with_system(stop_reconnecting),
+            );
+    }
+}
+
+struct PlayerListener(TcpListener);
+
+/// A player's place in the match.
+struct Seat {
+    /// Names the seat when its player comes back after dropping.
+    token: u64,
+    /// What they ping while they are on the line.
+    echo: Option<Echo>,
+}
+
+/// P1's and P2's seats, in that order.
+#[derive(Default)]
+struct Seats([Option<Seat>; 2]);
+
+impl Seats {
+    fn full(&self) -> bool {
+        self.0
+            .iter()
+            .all(|seat| matches!(seat, Some(Seat { echo: Some(_), .. })))
+    }
+}
+
+/// Sends the players of the last match away, making room for the next two.
+fn open_seats(mut seats: ResMut<Seats>, mut spectators: ResMut<Spectators>) {
+    spectators.hang_up_players();
+    *seats = Seats::default();
+}
+
+/// Seats whoever connects: anyone new in an empty seat before the match, and
+/// players coming back in the seat they left.
+#[allow(clippy::too_many_arguments)]
+fn take_seats(
+    mut commands: Commands,
+    listener: Res<PlayerListener>,
+    mut seats: ResMut<Seats>,
+    mut spectators: ResMut<Spectators>,
+    mut state: ResMut<State<AppState>>,
+    p1_query: Query<Entity, With<P1Paddle>>,
+    p2_query: Query<Entity, With<P2Paddle>>,
+) {
+    let before_match = *state.current() == AppState::MainMenu;
+    if before_match {
+        // Anyone who drops before the match starts gives up their seat.
+        for seat in seats.0.iter_mut() {
+            if matches!(seat, Some(Seat { echo: Some(echo), .. }) if !spectators.is_connected(echo))
+            {
+                *seat = None;
+            }
+        }
+    }
+
+    let (mut stream, peer) = match listener.0.accept() {
+        Ok(connection) => connection,
+        Err(err) if err.kind() == io::ErrorKind::WouldBlock => return,
+        Err(err) => {
+            warn!("Could not accept player: {}", err);
+            return;
+        }
+    };
+    let seated = lan::hear_hello(&mut stream).and_then(|session| {
+        let index = seats
+            .0
+            .iter()
+            .position(|seat| match (seat, session) {
+                (None, None) => before_match,
+                (Some(seat), Some(token)) => seat.echo.is_none() && seat.token == token,
+                _ => false,
+            })
+            .ok_or_else(lan::not_in_session)?;
+        let paddle = match index {
+            0 => p1_query.get_single(),
+            _ => p2_query.get_single(),
+        }
+        .map_err(|_| io::Error::new(io::ErrorKind::NotFound, "no paddle"))?;
+
+        let token = session.unwrap_or_else(rand::random);
+        lan::welcome(&mut stream, token)?;
+        let echo = lan::seat_player(
+            &mut commands,
+            &mut spectators,
+            paddle,
+            stream.into(),
+            peer.to_string(),
+        )?;
+        seats.0[index] = Some(Seat {
+            token,
+            echo: Some(echo),
+        });
+        Ok(index)
+    });
+    match seated {
+        Ok(index) => info!("{} took P{}'s seat", peer, index + 1),
+        Err(err) => {
+            warn!("Turned away {}: {}", peer, err);
+            return;
+        }
+    }
+
+    if seats.full() {
+        if before_match {
+            info!("Both seats taken, starting the match");
+            let _ = state.set(AppState::Playing);
+        } else {
+            info!("Both players are back, carrying on");
+            let _ = state.pop();
+        }
+    }
+}
+
+/// Pauses the match when a player drops, to wait for them to come back.
+fn lose_players(
+    mut commands: Commands,
+    mut seats: ResMut<Seats>,
+    spectators: Res<Spectators>,
+    mut state: ResMut<State<AppState>>,
+) {
+    let mut lost = false;
+    for seat in seats.0.iter_mut().flatten() {
+        if matches!(&seat.echo, Some(echo) if !spectators.is_connected(echo)) {
+            seat.echo = None;
+            lost = true;
+        }
+    }
+    if lost {
+        info!("Lost a player, waiting for them to come back");
+        commands.insert_resource(Reconnect::new());
+        let _ = state.push(AppState::Reconnecting);
+    }
+}
+
+/// Calls the match off once a player has been gone too long.
+fn forfeit(
+    time: Res<Time>,
+    reconnect: Option<ResMut<Reconnect>>,
+    mut state: ResMut<State<AppState>>,
+) {
+    let mut reconnect = match reconnect {
+        Some(reconnect) => reconnect,
+        None => return,
+    };
+    if reconnect.timer.tick(time.delta()).just_finished() {
+        info!("A player never came back and forfeits");
+        let _ = state.replace(AppState::MainMenu);
+    }
+}
+
+fn stop_reconnecting(mut commands: Commands) {
+    commands.remove_resource::<Reconnect>();
+}
@@ -0,0 +1,274 @@
+use std::{io, path::Path};
+
+use bevy::prelude::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::{ai::AiLevel, persist};
+
+/// Where the player's settings live, under [`config_path`](crate::config_path).
+pub const SETTINGS_PATH: &str = "settings.ron";
+
+/// How the analog stick moves the paddle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StickMode {
+    /// The stick's position is the paddle's position.
+    Absolute,
+    /// The stick pushes the paddle, like holding a key down.
+    Relative,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlSettings {
+    pub stick_mode: StickMode,
+    /// Stick deflection, from 0 to 1, that is ignored as noise.
+    pub dead_zone: f32,
+    /// Multiplier on the stick's deflection once outside the dead zone.
+    pub sensitivity: f32,
+}
+
+impl Default for ControlSettings {
+    fn default() -> Self {
+        ControlSettings {
+            stick_mode: StickMode::Relative,
+            dead_zone: 0.15,
+            sensitivity: 1.0,
+        }
+    }
+}
+
+impl ControlSettings {
+    /// Rescales a raw axis value so the dead zone reads as 0 and the edge of
+    /// it starts from 0 rather than jumping straight to `dead_zone`.
+    pub fn shape_axis(&self, value: f32) -> f32 {
+        let magnitude = value.abs();
+        if magnitude <= self.dead_zone {
+            return 0.0;
+        }
+
+        let live = (magnitude - self.dead_zone) / (1.0 - self.dead_zone);
+        (live * self.sensitivity).min(1.0).copysign(value)
+    }
+}
+
+/// Look of the court.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    /// White on black.
+    #[default]
+    Classic,
+    /// Bright colours with a glow around the court, paddles and ball.
+    Neon,
+    /// Paddles told apart without relying on red and green, for
+    /// deuteranopia.
+    Deuteranopia,
+    /// As [`Theme::Deuteranopia`], but without dark reds either, for
+    /// protanopia.
+    Protanopia,
+    /// Paddles told apart without relying on blue and yellow, for
+    /// tritanopia.
+    Tritanopia,
+    /// Pure white and yellow on black.
+    HighContrast,
+}
+
+/// How the camera follows the game.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CameraMode {
+    /// The whole court, never moving.
+    #[default]
+    Classic,
+    /// Leans a little towards the ball.
+    Follow,
+    /// Leans towards the ball and punches in on every goal.
+    Dramatic,
+}
+
+/// Where the scores sit at the top of the screen.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HudLayout {
+    /// Each player's score in their own top corner.
+    #[default]
+    Corners,
+    /// Both scores side by side in the middle, with the clock and calls
+    /// underneath.
+    Centred,
+}
+
+/// The ball warming up from colour to colour as a rally speeds it up.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BallHeat {
+    pub enabled: bool,
+    /// Colours spread evenly from the start of a rally to the hottest it
+    /// gets. The ball stays its theme's colour while this is empty.
+    pub gradient: Vec<Color>,
+    /// Rally speed multiplier, see [`GameConfig::rally_speed_multiplier`],
+    /// at which the ball reaches the end of the gradient.
+    ///
+    /// [`GameConfig::rally_speed_multiplier`]: crate::GameConfig::rally_speed_multiplier
+    pub hottest: f32,
+}
+
+impl Default for BallHeat {
+    fn default() -> Self {
+        BallHeat {
+            enabled: true,
+            gradient: vec![
+                Color::WHITE,
+                Color::rgb(1.0, 0.9, 0.2),
+                Color::rgb(1.0, 0.2, 0.1),
+            ],
+            hottest: 1.25,
+        }
+    }
+}
+
+impl BallHeat {
+    /// The ball's colour at a rally speed `multiplier`, or nothing if the
+    /// gradient is empty.
+    pub fn color(&self, multiplier: f32) -> Option<Color> {
+        let last = self.gradient.len().checked_sub(1)?;
+        let heat = (multiplier - 1.0) / (self.hottest - 1.0).max(f32::EPSILON);
+        let position = heat.clamp(0.0, 1.0) * last as f32;
+        let from = (position as usize).min(last.saturating_sub(1));
+        let t = position - from as f32;
+        let to = (from + 1).min(last);
+        let from = self.gradient[from].as_rgba_f32();
+        let to = self.gradient[to].as_rgba_f32();
+        let [r, g, b, a] = [0, 1, 2, 3].map(|channel| from[channel] * (1.0 - t) + to[channel] * t);
+        Some(Color::rgba(r, g, b, a))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VideoSettings {
+    pub theme: Theme,
+    /// Name of the [`ThemePack`](crate::ThemePack) to dress the game in, or
+    /// the game's own look and sound when unset.
+    pub theme_pack: Option<String>,
+    pub camera: CameraMode,
+    pub ball_heat: BallHeat,
+    /// Name of the [`Skin`](crate::Skin) to draw the paddles and ball with,
+    /// or flat colours when unset.
+    pub skin: Option<String>,
+    /// A grid behind the court that lights up with the play.
+    pub backdrop: bool,
+    /// How big the HUD's text and spacing are drawn, from 0.75 to 2.
+    pub ui_scale: f32,
+    pub hud_layout: HudLayout,
+    /// Draw scanlines and a curved bezel over the game, like an old CRT.
+    pub crt: bool,
+    /// Wait for the display before showing each frame, so it never tears.
+    pub vsync: bool,
+    /// Most frames to draw a second, or as many as possible when unset.
+    pub fps_cap: Option<u32>,
+    /// Drop to a few frames a second while the window is unfocused or
+    /// minimized.
+    pub low_power_in_background: bool,
+}
+
+impl Default for VideoSettings {
+    fn default() -> Self {
+        VideoSettings {
+            theme: Theme::default(),
+            theme_pack: None,
+            camera: CameraMode::default(),
+            ball_heat: BallHeat::default(),
+            skin: None,
+            backdrop: true,
+            ui_scale: 1.0,
+            hud_layout: HudLayout::default(),
+            crt: false,
+            vsync: true,
+            fps_cap: None,
+            low_power_in_background: true,
+        }
+    }
+}
+
+/// Toning down the effects for players sensitive to motion or light. Every
+/// system that only adds juice checks these first.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    /// Keep the camera still and the ball and paddles their true shape.
+    pub reduce_motion: bool,
+    /// Sparks off the ball when it is smashed.
+    pub particles: bool,
+    /// Brightest any colour on the court may be, from 0 to 1, glows
+    /// included.
+    pub max_brightness: f32,
+    /// How fast matches play, from [`MIN_GAME_SPEED`] to
+    /// [`MAX_GAME_SPEED`] times normal speed.
+    pub game_speed: f32,
+    /// Call out the score after every goal.
+    pub announcer: bool,
+}
+
+pub const MIN_GAME_SPEED: f32 = 0.5;
+pub const MAX_GAME_SPEED: f32 = 1.5;
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        AccessibilitySettings {
+            reduce_motion: false,
+            particles: true,
+            max_brightness: 1.0,
+            game_speed: 1.0,
+            announcer: false,
+        }
+    }
+}
+
+impl AccessibilitySettings {
+    /// Dims `color` so none of its channels are brighter than allowed.
+    pub fn cap(&self, color: Color) -> Color {
+        let [r, g, b, a] = color.as_rgba_f32();
+        let brightest = r.max(g).max(b);
+        if brightest <= self.max_brightness {
+            return color;
+        }
+        let scale = self.max_brightness.max(0.0) / brightest;
+        Color::rgba(r * scale, g * scale, b * scale, a)
+    }
+}
+
+/// Language the game's text is shown in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Swedish,
+}
+
+/// Player preferences, kept in [`SETTINGS_PATH`] between runs.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub controls: ControlSettings,
+    pub video: VideoSettings,
+    pub accessibility: AccessibilitySettings,
+    pub language: Language,
+    /// Seed for every match, to replay one that was worth sharing. A new
+    /// seed is picked each run when unset.
+    pub seed: Option<u64>,
+    /// What to go by on leaderboards, the machine's name when unset.
+    pub name: Option<String>,
+    /// How the AI was last set to play from the pause menu, which matches
+    /// start with from then on. The rules' own when unset.
+    pub ai: Option<AiLevel>,
+}
+
+impl Settings {
+    /// Reads settings from `path`, falling back to the defaults if the file
+    /// is missing or broken.
+    pub fn load(path: impl AsRef<Path>) -> Settings {
+        persist::load_or_create(path.as_ref())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        persist::save(self, path.as_ref())
+    }
+}
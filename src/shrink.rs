@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+
+use crate::{GoalEvent, HitEvent, MatchRules, P1Paddle, Player};
+
+/// Share of its size a paddle keeps each time it returns the ball.
+const SHRINK_PER_RETURN: f32 = 0.94;
+/// Smallest a paddle gets, however long the rally.
+const MIN_SHRINK: f32 = 0.4;
+
+/// How much of its size a paddle has left this rally under
+/// [`MatchRules::shrinking_paddles`], from 1 down to [`MIN_SHRINK`].
+#[derive(Component, Clone, Copy, Debug, Deref, DerefMut)]
+pub struct Shrink(pub f32);
+
+impl Default for Shrink {
+    fn default() -> Self {
+        Shrink(1.0)
+    }
+}
+
+/// Shrinks each paddle a little every time it returns the ball, and grows
+/// them back to full size when someone scores.
+pub fn shrink_on_return(
+    rules: Res<MatchRules>,
+    mut hit_events: EventReader<HitEvent>,
+    mut goal_events: EventReader<GoalEvent>,
+    mut paddle_query: Query<(&mut Shrink, Option<&P1Paddle>)>,
+) {
+    let scored = goal_events.iter().count() > 0;
+    let hits: Vec<Player> = hit_events.iter().map(|hit| hit.player).collect();
+    for (mut shrink, p1) in paddle_query.iter_mut() {
+        if scored || !rules.shrinking_paddles {
+            if shrink.0 != 1.0 {
+                shrink.0 = 1.0;
+            }
+            continue;
+        }
+        let player = if p1.is_some() { Player::P1 } else { Player::P2 };
+        for _ in hits.iter().filter(|hit| **hit == player) {
+            shrink.0 = (shrink.0 * SHRINK_PER_RETURN).max(MIN_SHRINK);
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use std::path::Path;
+
+use bevy::{
+    asset::{AssetLoader, AssetPath, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    render::texture::DEFAULT_IMAGE_HANDLE,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{
+    settings::Settings, theme_packs::ActiveThemePack, Ball, P1Paddle, P2Paddle, Player, Profiles,
+    Series,
+};
+
+/// Folder under `assets/` that skins are loaded from.
+pub const SKINS_FOLDER: &str = "skins";
+
+/// Images to draw the paddles and the ball with, in place of the theme's
+/// flat colours.
+///
+/// Skins are kept as `.skin.ron` files anywhere under `assets/skins/`,
+/// naming images next to them. Images are stretched to fit, so a paddle
+/// image is best drawn as tall and thin as a paddle is.
+#[derive(Clone, Debug, Default, TypeUuid)]
+#[uuid = "c6a2e9d4-3f18-4b7c-8e51-0d9b6f2a7e13"]
+pub struct Skin {
+    pub name: String,
+    pub paddle: Option<Handle<Image>>,
+    pub ball: Option<Handle<Image>>,
+}
+
+/// A skin as it is written, with its images named relative to the file.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct SkinFile {
+    name: String,
+    paddle: Option<String>,
+    ball: Option<String>,
+}
+
+/// Loads every skin in [`SKINS_FOLDER`] into [`Skins`], and draws the
+/// paddles and the ball with the ones picked: P1 and P2 with their
+/// profiles' skins, or else the one picked in the settings, which the ball
+/// always uses. With no skin picked, the picked
+/// [`ThemePack`](crate::ThemePack)'s skin is used, if it has one.
+pub struct SkinsPlugin;
+
+impl Plugin for SkinsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Skin>()
+            .init_asset_loader::<SkinLoader>()
+            .init_resource::<Skins>()
+            .init_resource::<Settings>()
+            .init_resource::<ActiveThemePack>()
+            .init_resource::<Profiles>()
+            .init_resource::<Series>()
+            .add_startup_system(load_skins)
+            .add_system(list_skins)
+            .add_system(apply_skins.after(list_skins));
+    }
+}
+
+/// Every skin that has loaded, by name.
+#[derive(Default)]
+pub struct Skins(pub Vec<Skin>);
+
+impl Skins {
+    pub fn get(&self, name: &str) -> Option<&Skin> {
+        self.0.iter().find(|skin| skin.name == name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.0.iter().map(|skin| skin.name.clone()).collect()
+    }
+}
+
+/// Whether a paddle or the ball is drawn with a skin rather than a flat
+/// colour, for the theme to leave its colour alone.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Skinned(pub(crate) bool);
+
+#[derive(Default)]
+struct SkinLoader;
+
+impl AssetLoader for SkinLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let file: SkinFile = ron::de::from_bytes(bytes)?;
+            let folder = load_context
+                .path()
+                .parent()
+                .unwrap_or_else(|| Path::new(""));
+            let paths: Vec<Option<AssetPath<'static>>> = [file.paddle, file.ball]
+                .into_iter()
+                .map(|image| image.map(|image| AssetPath::new(folder.join(image), None)))
+                .collect();
+            let handle = |path: &Option<AssetPath>| {
+                path.as_ref()
+                    .map(|path| load_context.get_handle(path.clone()))
+            };
+            let skin = Skin {
+                name: file.name,
+                paddle: handle(&paths[0]),
+                ball: handle(&paths[1]),
+            };
+            let dependencies = paths.into_iter().flatten().collect();
+            load_context.set_default_asset(LoadedAsset::new(skin).with_dependencies(dependencies));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["skin.ron"]
+    }
+}
+
+/// Keeps the skins loaded for as long as the game runs.
+struct SkinHandles(Vec<Handle<Skin>>);
+
+fn load_skins(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let handles = asset_server
+        .load_folder(SKINS_FOLDER)
+        .unwrap_or_else(|err| {
+            warn!("Could not load skins: {:?}", err);
+            Vec::new()
+        });
+    // The images are in the folder too, and are loaded by their skins.
+    let handles = handles
+        .into_iter()
+        .filter(|handle| match asset_server.get_handle_path(handle) {
+            Some(path) => path.path().to_string_lossy().ends_with(".skin.ron"),
+            None => false,
+        })
+        .map(HandleUntyped::typed)
+        .collect();
+    commands.insert_resource(SkinHandles(handles));
+}
+
+fn list_skins(
+    mut asset_events: EventReader<AssetEvent<Skin>>,
+    handles: Res<SkinHandles>,
+    skins: Res<Assets<Skin>>,
+    mut listed: ResMut<Skins>,
+) {
+    if asset_events.iter().count() == 0 {
+        return;
+    }
+
+    let mut loaded: Vec<_> = handles
+        .0
+        .iter()
+        .filter_map(|handle| skins.get(handle).cloned())
+        .collect();
+    loaded.sort_by(|a, b| a.name.cmp(&b.name));
+    listed.0 = loaded;
+}
+
+type Skinnable<'a> = (
+    Entity,
+    &'a mut Sprite,
+    &'a mut Handle<Image>,
+    Option<&'a Skinned>,
+    Option<&'a P1Paddle>,
+    Option<&'a P2Paddle>,
+);
+type IsSkinnable = Or<(With<Ball>, With<P1Paddle>, With<P2Paddle>)>;
+
+#[allow(clippy::too_many_arguments)]
+fn apply_skins(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    profiles: Res<Profiles>,
+    series: Res<Series>,
+    skins: Res<Skins>,
+    theme_pack: Res<ActiveThemePack>,
+    mut query: Query<Skinnable, IsSkinnable>,
+    unskinned: Query<(), (IsSkinnable, Without<Skinned>)>,
+) {
+    let changed = settings.is_changed()
+        || profiles.is_changed()
+        || series.is_changed()
+        || skins.is_changed()
+        || theme_pack.is_changed();
+    if !changed && unskinned.is_empty() {
+        return;
+    }
+
+    let picked = match &settings.video.skin {
+        Some(name) => skins.get(name),
+        None => theme_pack.skin.as_ref(),
+    };
+    let skin = |end| {
+        let profile = profiles.playing(series.player_on(end));
+        match profile.and_then(|profile| profile.skin.as_deref()) {
+            Some(name) => skins.get(name),
+            None => picked,
+        }
+    };
+    let paddle_images =
+        [Player::P1, Player::P2].map(|end| skin(end).and_then(|skin| skin.paddle.clone()));
+    let ball_image = picked.and_then(|skin| skin.ball.clone());
+
+    for (entity, mut sprite, mut texture, skinned, p1, p2) in query.iter_mut() {
+        let image = if p1.is_some() {
+            paddle_images[0].clone()
+        } else if p2.is_some() {
+            paddle_images[1].clone()
+        } else {
+            ball_image.clone()
+        };
+        let now = Skinned(image.is_some());
+        match image {
+            // Stretched over the whole sprite, whatever the image's size.
+            Some(image) => {
+                sprite.custom_size = Some(Vec2::ONE);
+                *texture = image;
+            }
+            None if skinned == Some(&Skinned(true)) => {
+                sprite.custom_size = None;
+                *texture = DEFAULT_IMAGE_HANDLE.typed();
+            }
+            None => {}
+        }
+        if skinned != Some(&now) {
+            commands.entity(entity).insert(now);
+        }
+    }
+}
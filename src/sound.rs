@@ -0,0 +1,183 @@
+use std::{f32::consts::FRAC_PI_4, io::Cursor};
+
+use bevy::{
+    audio::{play_queued_audio_system, AudioOutput, Decodable},
+    prelude::*,
+    reflect::TypeUuid,
+};
+use rodio::source::ChannelVolume;
+
+use crate::{
+    Arena, Ball, GoalEvent, HitEvent, MatchOverEvent, MatchRules, Player, Scoreboard, Series,
+    Velocity, WallBounceEvent,
+};
+
+/// How much higher each fjong in a rally plays the hit sound.
+const PITCH_PER_FJONG: f32 = 0.06;
+const MAX_PITCH: f32 = 2.0;
+/// Hits play this much higher again at deuce, to wind up the tension.
+const DEUCE_PITCH: f32 = 1.2;
+
+/// Ball speed at which bounces play at full volume.
+const LOUD_BALL_SPEED: f32 = 800.0;
+const MIN_BOUNCE_VOLUME: f32 = 0.3;
+
+/// Sound cues for hits and goals. Hits climb in pitch as the rally grows,
+/// smashes crack instead, and game point, match point and the winning goal
+/// get stingers of their own. At deuce every hit plays higher still.
+///
+/// Bounces are panned to follow the ball across the court and get louder the
+/// faster it goes.
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<PannedSound>()
+            .init_resource::<Audio<PannedSound>>()
+            .init_non_send_resource::<AudioOutput<PannedSound>>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                play_queued_audio_system::<PannedSound>.exclusive_system(),
+            )
+            .add_startup_system(load_sounds)
+            .add_system_to_stage(CoreStage::PostUpdate, play_bounce_sounds)
+            .add_system_to_stage(CoreStage::PostUpdate, play_goal_sounds);
+    }
+}
+
+/// The sound for each cue, which a theme pack may swap for its own.
+#[derive(Clone)]
+pub(crate) struct Sounds {
+    pub(crate) hit: Handle<AudioSource>,
+    pub(crate) smash: Handle<AudioSource>,
+    pub(crate) wall: Handle<AudioSource>,
+    pub(crate) goal: Handle<AudioSource>,
+    pub(crate) game_point: Handle<AudioSource>,
+    pub(crate) match_point: Handle<AudioSource>,
+    pub(crate) match_win: Handle<AudioSource>,
+}
+
+fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Sounds {
+        hit: asset_server.load("sounds/hit.wav"),
+        smash: asset_server.load("sounds/smash.wav"),
+        wall: asset_server.load("sounds/wall.wav"),
+        goal: asset_server.load("sounds/goal.wav"),
+        game_point: asset_server.load("sounds/game_point.wav"),
+        match_point: asset_server.load("sounds/match_point.wav"),
+        match_win: asset_server.load("sounds/match_win.wav"),
+    });
+}
+
+/// A sound played with a fixed volume in each of the left and right speakers.
+#[derive(TypeUuid)]
+#[uuid = "2692aea7-ee1c-4e10-a9a0-053f24c8fd27"]
+struct PannedSound {
+    source: AudioSource,
+    left: f32,
+    right: f32,
+}
+
+impl PannedSound {
+    /// `pan` runs from -1 for hard left to 1 for hard right.
+    fn new(source: AudioSource, pan: f32, volume: f32) -> PannedSound {
+        // Equal power, so the sound doesn't dip in the middle.
+        let angle = (pan.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4;
+        PannedSound {
+            source,
+            left: angle.cos() * volume,
+            right: angle.sin() * volume,
+        }
+    }
+}
+
+impl Decodable for PannedSound {
+    type Decoder = ChannelVolume<rodio::Decoder<Cursor<AudioSource>>>;
+    type DecoderItem = i16;
+
+    fn decoder(&self) -> Self::Decoder {
+        ChannelVolume::new(self.source.decoder(), vec![self.left, self.right])
+    }
+}
+
+fn rally_pitch(fjongs: usize) -> f32 {
+    (1.0 + fjongs as f32 * PITCH_PER_FJONG).min(MAX_PITCH)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn play_bounce_sounds(
+    audio: Res<Audio<PannedSound>>,
+    sounds: Res<Sounds>,
+    sources: Res<Assets<AudioSource>>,
+    mut panned_sounds: ResMut<Assets<PannedSound>>,
+    rules: Res<MatchRules>,
+    scoreboard: Res<Scoreboard>,
+    arena: Res<Arena>,
+    ball_query: Query<(&Transform, &Velocity), With<Ball>>,
+    mut wall_bounce_events: EventReader<WallBounceEvent>,
+    mut hit_events: EventReader<HitEvent>,
+) {
+    // Several ticks can land in one frame; one sound of each is plenty.
+    let wall_bounced = wall_bounce_events.iter().count() > 0;
+    let mut hit = false;
+    let mut smash = false;
+    for event in hit_events.iter() {
+        hit = true;
+        smash |= event.smash;
+    }
+    if !wall_bounced && !hit {
+        return;
+    }
+
+    let (transform, velocity) = ball_query.single();
+    let pan = transform.translation.x / arena.right;
+    let volume = (velocity.length() / LOUD_BALL_SPEED).clamp(MIN_BOUNCE_VOLUME, 1.0);
+    let mut play = |handle: &Handle<AudioSource>, settings: PlaybackSettings| {
+        // Not loaded yet, so nothing to play.
+        if let Some(source) = sources.get(handle) {
+            let sound = panned_sounds.add(PannedSound::new(source.clone(), pan, volume));
+            audio.play_with_settings(sound, settings);
+        }
+    };
+
+    if wall_bounced {
+        play(&sounds.wall, PlaybackSettings::ONCE);
+    }
+    if smash {
+        play(&sounds.smash, PlaybackSettings::ONCE);
+    } else if hit {
+        let mut pitch = rally_pitch(scoreboard.fjongs);
+        if rules.deuce(&scoreboard) {
+            pitch *= DEUCE_PITCH;
+        }
+        play(&sounds.hit, PlaybackSettings::ONCE.with_speed(pitch));
+    }
+}
+
+fn play_goal_sounds(
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    rules: Res<MatchRules>,
+    series: Res<Series>,
+    scoreboard: Res<Scoreboard>,
+    mut goal_events: EventReader<GoalEvent>,
+    mut match_over_events: EventReader<MatchOverEvent>,
+) {
+    if goal_events.iter().count() == 0 {
+        return;
+    }
+
+    let match_point = [Player::P1, Player::P2]
+        .into_iter()
+        .any(|end| series.match_point(&rules, &scoreboard, end));
+    let sound = if match_over_events.iter().count() > 0 {
+        &sounds.match_win
+    } else if match_point {
+        &sounds.match_point
+    } else if rules.game_point(&scoreboard) {
+        &sounds.game_point
+    } else {
+        &sounds.goal
+    };
+    audio.play(sound.clone());
+}
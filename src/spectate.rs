@@ -0,0 +1,581 @@
+use std::{
+    collections::VecDeque,
+    io,
+    net::{SocketAddr, TcpListener, TcpStream},
+};
+
+use bevy::prelude::*;
+use fluent::fluent_args;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    emote::{self, Emote, EmoteEvent},
+    lan::{GuestSession, Reconnect},
+    remote::Echo,
+    transport::{self, Transport},
+    AppState, Ball, Locale, P1Paddle, P2Paddle, Player, Scoreboard, SimTick, TickRate, Toast,
+};
+
+/// Round trips slower than this make for a poor connection.
+const POOR_PING_MS: f32 = 150.0;
+/// As does missing more than this share of the host's ticks.
+const POOR_LOSS: f32 = 0.1;
+/// Seconds of snapshots that loss is worked out over.
+const LOSS_WINDOW_SECONDS: f32 = 1.0;
+/// How much of each new ping goes into the running average.
+const PING_SMOOTHING: f32 = 0.2;
+
+/// People watching the match over the network, shown on the HUD while there
+/// are any.
+#[derive(Default)]
+pub struct SpectatorCount(pub usize);
+
+/// Everything a spectator sees of the match, as the game hosting it has it.
+/// One of these goes out as a line of JSON every tick.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// The host's [`SimTick`].
+    pub tick: u64,
+    pub ball: Vec2,
+    pub p1: PaddleSnapshot,
+    pub p2: PaddleSnapshot,
+    pub p1_score: usize,
+    pub p2_score: usize,
+    pub spectators: usize,
+    /// The latest ping from a player on the other end, sent back for them
+    /// to time the round trip.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub echo: Option<u64>,
+    /// Sent to players once their match is over, just before hanging up.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub finished: bool,
+}
+
+impl Snapshot {
+    /// The match as it stands after the latest tick, with nobody emoting or
+    /// watching.
+    pub(crate) fn take(
+        tick: &SimTick,
+        scoreboard: &Scoreboard,
+        ball: Option<&Transform>,
+        p1: Option<&Transform>,
+        p2: Option<&Transform>,
+    ) -> Snapshot {
+        let paddle = |transform: Option<&Transform>| {
+            transform.map_or_else(PaddleSnapshot::default, |transform| PaddleSnapshot {
+                position: transform.translation.truncate(),
+                height: transform.scale.y,
+                emote: None,
+            })
+        };
+        Snapshot {
+            tick: tick.0,
+            ball: ball.map_or(Vec2::ZERO, |transform| transform.translation.truncate()),
+            p1: paddle(p1),
+            p2: paddle(p2),
+            p1_score: scoreboard.p1_score,
+            p2_score: scoreboard.p2_score,
+            spectators: 0,
+            echo: None,
+            finished: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PaddleSnapshot {
+    pub position: Vec2,
+    pub height: f32,
+    /// Whatever the player at this end emoted since the last snapshot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emote: Option<Emote>,
+}
+
+/// How the connection to the game being watched or played from here is
+/// holding up, shown on the HUD.
+///
+/// There is no rollback: the court is shown as the host last had it, so
+/// `delay_ticks` is how far behind the host that is, half the round trip
+/// plus the tick spent moving between snapshots.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NetStats {
+    /// Smoothed round trip to the host, only known when playing.
+    pub ping_ms: Option<f32>,
+    pub delay_ticks: f32,
+    /// Share of the host's recent ticks that never made it here.
+    pub loss: f32,
+}
+
+impl NetStats {
+    pub fn poor(&self) -> bool {
+        matches!(self.ping_ms, Some(ping) if ping > POOR_PING_MS) || self.loss > POOR_LOSS
+    }
+}
+
+/// Sends snapshots of the match to anyone watching it from another game,
+/// and shows a match being watched from here. Who can connect is up to
+/// [`SpectatorServerPlugin`], and [`WatchPlugin`] or a LAN game does the
+/// watching.
+pub struct SpectatePlugin;
+
+impl Plugin for SpectatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Toast>()
+            .init_resource::<Locale>()
+            .init_resource::<SpectatorCount>()
+            .init_resource::<Spectators>()
+            .init_resource::<NetStats>()
+            .init_resource::<TickRate>()
+            .add_event::<EmoteEvent>()
+            .add_system(emote::hear_remote_emotes)
+            .add_system_to_stage(CoreStage::PostUpdate, send_snapshots)
+            .add_system_set(
+                SystemSet::on_update(AppState::Spectating)
+                    .with_system(receive_snapshots)
+                    .with_system(show_snapshots.after(receive_snapshots))
+                    .with_system(stop_watching),
+            )
+            .add_system_set(SystemSet::on_exit(AppState::Spectating).with_system(forget_match));
+    }
+}
+
+/// Lets anyone watch this game's matches by connecting to `addr`, for as long
+/// as they like. Spectators can't touch the match, and how many are watching
+/// is shown to the players. Needs the [`SpectatePlugin`].
+pub struct SpectatorServerPlugin {
+    pub addr: SocketAddr,
+}
+
+impl Plugin for SpectatorServerPlugin {
+    fn build(&self, app: &mut App) {
+        let listener = match TcpListener::bind(self.addr) {
+            Ok(listener) => listener,
+            Err(err) => {
+                error!("Could not listen for spectators on {}: {}", self.addr, err);
+                return;
+            }
+        };
+        if let Err(err) = listener.set_nonblocking(true) {
+            error!("Could not listen for spectators on {}: {}", self.addr, err);
+            return;
+        }
+        info!("Listening for spectators on {}", self.addr);
+
+        app.insert_resource(SpectatorListener(listener))
+            .add_system(accept_spectators);
+    }
+}
+
+struct SpectatorListener(TcpListener);
+
+struct Spectator {
+    line: Box<dyn Transport>,
+    peer: String,
+    outgoing: Vec<u8>,
+    /// Pings from someone playing rather than watching, who isn't counted
+    /// as a spectator.
+    echo: Option<Echo>,
+    /// Hung up on after the next snapshot.
+    leaving: bool,
+}
+
+/// Everyone being sent snapshots of the match.
+#[derive(Default)]
+pub(crate) struct Spectators(Vec<Spectator>);
+
+impl Spectators {
+    /// Sends `line` snapshots until it goes away.
+    pub(crate) fn add(&mut self, line: Box<dyn Transport>, peer: String) {
+        self.0.push(Spectator {
+            line,
+            peer,
+            outgoing: Vec::new(),
+            echo: None,
+            leaving: false,
+        });
+    }
+
+    /// Sends a player on another machine snapshots, along with whatever
+    /// pings they leave in `echo`.
+    pub(crate) fn add_player(&mut self, line: Box<dyn Transport>, peer: String, echo: Echo) {
+        self.0.push(Spectator {
+            line,
+            peer,
+            outgoing: Vec::new(),
+            echo: Some(echo),
+            leaving: false,
+        });
+    }
+
+    /// Sends anyone playing one last snapshot and hangs up, as their match
+    /// is over, telling them so they don't try to get back in.
+    pub(crate) fn hang_up_players(&mut self) {
+        for spectator in self.0.iter_mut() {
+            spectator.leaving = spectator.echo.is_some();
+        }
+    }
+
+    /// Whether anyone playing from another machine is still on the line.
+    pub(crate) fn has_players(&self) -> bool {
+        self.0.iter().any(|spectator| spectator.echo.is_some())
+    }
+
+    /// Whether the player pinging into `echo` is still on the line.
+    pub(crate) fn is_connected(&self, echo: &Echo) -> bool {
+        self.0
+            .iter()
+            .any(|spectator| spectator.echo.as_ref() == Some(echo))
+    }
+
+    fn watching(&self) -> usize {
+        self.0
+            .iter()
+            .filter(|spectator| spectator.echo.is_none())
+            .count()
+    }
+}
+
+fn accept_spectators(
+    listener: Res<SpectatorListener>,
+    locale: Res<Locale>,
+    mut spectators: ResMut<Spectators>,
+    mut toasts: EventWriter<Toast>,
+) {
+    let (stream, peer) = match listener.0.accept() {
+        Ok(connection) => connection,
+        Err(err) if err.kind() == io::ErrorKind::WouldBlock => return,
+        Err(err) => {
+            warn!("Could not accept spectator: {}", err);
+            return;
+        }
+    };
+    if let Err(err) = stream.set_up() {
+        warn!("Could not set up spectator {}: {}", peer, err);
+        return;
+    }
+
+    info!("Spectator connected from {}", peer);
+    let message = locale.text_with(
+        "toast-spectator-joined",
+        &fluent_args!["peer" => peer.to_string()],
+    );
+    toasts.send(Toast(message));
+    spectators.add(stream.into(), peer.to_string());
+}
+
+/// Sends every spectator the match as it stands after each tick, along with
+/// any emotes, dropping any spectators that have gone away.
+#[allow(clippy::too_many_arguments)]
+fn send_snapshots(
+    tick: Res<SimTick>,
+    scoreboard: Res<Scoreboard>,
+    mut spectators: ResMut<Spectators>,
+    mut count: ResMut<SpectatorCount>,
+    ball_query: Query<&Transform, With<Ball>>,
+    p1_query: Query<&Transform, With<P1Paddle>>,
+    p2_query: Query<&Transform, With<P2Paddle>>,
+    mut emotes: EventReader<EmoteEvent>,
+) {
+    let (mut p1_emote, mut p2_emote) = (None, None);
+    for event in emotes.iter() {
+        match event.end {
+            Player::P1 => p1_emote = Some(event.emote),
+            Player::P2 => p2_emote = Some(event.emote),
+        }
+    }
+    if spectators.0.is_empty() {
+        return;
+    }
+    let emoted = p1_emote.is_some() || p2_emote.is_some();
+    if !tick.is_changed() && !scoreboard.is_changed() && !spectators.is_changed() && !emoted {
+        return;
+    }
+
+    let mut snapshot = Snapshot::take(
+        &tick,
+        &scoreboard,
+        ball_query.get_single().ok(),
+        p1_query.iter().next(),
+        p2_query.iter().next(),
+    );
+    snapshot.p1.emote = p1_emote;
+    snapshot.p2.emote = p2_emote;
+    snapshot.spectators = spectators.watching();
+
+    spectators.0.retain_mut(|spectator| {
+        let snapshot = Snapshot {
+            echo: spectator.echo.as_ref().and_then(Echo::get),
+            ..snapshot
+        };
+        // Plain numbers and field names always serialize.
+        serde_json::to_writer(&mut spectator.outgoing, &snapshot).unwrap();
+        spectator.outgoing.push(b'\n');
+        if spectator.leaving {
+            let last = Snapshot {
+                finished: true,
+                ..snapshot
+            };
+            serde_json::to_writer(&mut spectator.outgoing, &last).unwrap();
+            spectator.outgoing.push(b'\n');
+        }
+        match transport::flush(&mut spectator.outgoing, &mut *spectator.line) {
+            // Whatever didn't fit is lost, but they are going either way.
+            Ok(()) if spectator.leaving => false,
+            Ok(()) => true,
+            Err(err) => {
+                info!("Spectator {} left: {}", spectator.peer, err);
+                false
+            }
+        }
+    });
+    if count.0 != spectators.watching() {
+        count.0 = spectators.watching();
+    }
+}
+
+/// Watches a match hosted by another game at `addr` instead of playing, see
+/// [`SpectatorServerPlugin`]. Needs the [`SpectatePlugin`].
+pub struct WatchPlugin {
+    pub addr: SocketAddr,
+}
+
+impl Plugin for WatchPlugin {
+    fn build(&self, app: &mut App) {
+        let watching = match TcpStream::connect(self.addr).and_then(Watching::new) {
+            Ok(watching) => watching,
+            Err(err) => {
+                error!("Could not watch the match at {}: {}", self.addr, err);
+                return;
+            }
+        };
+        info!("Watching the match at {}", self.addr);
+
+        app.insert_resource(watching).add_system(start_watching);
+    }
+}
+
+/// The connection to the game being watched, and the two latest snapshots
+/// from it. The court moves smoothly from one to the next, a tick behind the
+/// host, and goes back to the main menu when the host goes away or Escape is
+/// pressed.
+pub(crate) struct Watching {
+    line: Box<dyn Transport>,
+    incoming: Vec<u8>,
+    from: Option<Snapshot>,
+    to: Option<Snapshot>,
+    /// Seconds since `to` arrived.
+    since: f32,
+    /// Ticks of the host's that snapshots arrived for, over the last
+    /// [`LOSS_WINDOW_SECONDS`].
+    ticks: VecDeque<u64>,
+    /// The last ping the host echoed back.
+    echo: Option<u64>,
+    stats: NetStats,
+    /// The host said the match is over, so there is no getting back in.
+    finished: bool,
+    /// Emotes that came with the snapshots, yet to be shown.
+    emotes: Vec<EmoteEvent>,
+}
+
+impl Watching {
+    pub(crate) fn new(line: impl Into<Box<dyn Transport>>) -> io::Result<Watching> {
+        let line = line.into();
+        line.set_up()?;
+        Ok(Watching {
+            line,
+            incoming: Vec::new(),
+            from: None,
+            to: None,
+            since: 0.0,
+            ticks: VecDeque::new(),
+            echo: None,
+            stats: NetStats {
+                delay_ticks: 1.0,
+                ..default()
+            },
+            finished: false,
+            emotes: Vec::new(),
+        })
+    }
+
+    /// Reads whatever the host has sent, keeping the latest two snapshots.
+    /// `now_ms` times the round trip of any ping echoed back, and the delay
+    /// and loss are counted in ticks of `tick_rate`.
+    fn receive(&mut self, now_ms: u64, tick_rate: TickRate) -> io::Result<()> {
+        // A host sending faster than this keeps up has its oldest snapshots
+        // dropped rather than piling up.
+        transport::fill(&mut self.incoming, &mut *self.line)?;
+
+        while let Some(end) = self.incoming.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.incoming.drain(..=end).collect();
+            match serde_json::from_slice::<Snapshot>(&line) {
+                Ok(snapshot) if snapshot.finished => self.finished = true,
+                Ok(snapshot) => {
+                    self.from = self.to.or(Some(snapshot));
+                    self.to = Some(snapshot);
+                    self.since = 0.0;
+                    self.measure(&snapshot, now_ms, tick_rate);
+                    let paddles = [(Player::P1, snapshot.p1), (Player::P2, snapshot.p2)];
+                    for (end, paddle) in paddles {
+                        if let Some(emote) = paddle.emote {
+                            self.emotes.push(EmoteEvent { end, emote });
+                        }
+                    }
+                }
+                Err(err) => warn!("Ignoring bad snapshot: {}", err),
+            }
+        }
+        Ok(())
+    }
+
+    fn measure(&mut self, snapshot: &Snapshot, now_ms: u64, tick_rate: TickRate) {
+        if let Some(echo) = snapshot.echo.filter(|&echo| Some(echo) != self.echo) {
+            self.echo = Some(echo);
+            let ping = now_ms.saturating_sub(echo) as f32;
+            let smoothed = self
+                .stats
+                .ping_ms
+                .map_or(ping, |old| old + (ping - old) * PING_SMOOTHING);
+            self.stats.ping_ms = Some(smoothed);
+            self.stats.delay_ticks = smoothed / 2.0 / (tick_rate.step() * 1000.0) + 1.0;
+        }
+
+        // The host sends a snapshot a frame, so a tick can come twice.
+        if self.ticks.back() != Some(&snapshot.tick) {
+            self.ticks.push_back(snapshot.tick);
+        }
+        let window = u64::from(tick_rate.ticks(LOSS_WINDOW_SECONDS));
+        while let Some(&oldest) = self.ticks.front() {
+            if oldest + window > snapshot.tick {
+                break;
+            }
+            self.ticks.pop_front();
+        }
+        if let Some(&oldest) = self.ticks.front() {
+            let expected = snapshot.tick.saturating_sub(oldest) + 1;
+            self.stats.loss = 1.0 - self.ticks.len() as f32 / expected as f32;
+        }
+    }
+}
+
+/// Skips the main menu and goes straight to watching.
+fn start_watching(mut started: Local<bool>, mut state: ResMut<State<AppState>>) {
+    if !*started {
+        *started = true;
+        let _ = state.set(AppState::Spectating);
+    }
+}
+
+/// Milliseconds since startup, which is all pings need.
+pub(crate) fn now_ms(time: &Time) -> u64 {
+    (time.seconds_since_startup() * 1000.0) as u64
+}
+
+/// Keeps up with the host, showing whatever either player emotes, and when
+/// the line drops, goes back to the main menu or, for a LAN guest, waits to
+/// get back into the match.
+#[allow(clippy::too_many_arguments)]
+fn receive_snapshots(
+    mut commands: Commands,
+    time: Res<Time>,
+    tick_rate: Res<TickRate>,
+    locale: Res<Locale>,
+    session: Option<Res<GuestSession>>,
+    mut watching: ResMut<Watching>,
+    mut stats: ResMut<NetStats>,
+    mut state: ResMut<State<AppState>>,
+    mut toasts: EventWriter<Toast>,
+    mut emotes: EventWriter<EmoteEvent>,
+) {
+    let received = watching.receive(now_ms(&time), *tick_rate);
+    emotes.send_batch(watching.emotes.drain(..));
+    if let Err(err) = received {
+        if session.is_some() && !watching.finished {
+            info!("Lost the LAN host, trying to get back: {}", err);
+            toasts.send(Toast(locale.text("toast-lan-lost-host")));
+            commands.insert_resource(Reconnect::new());
+            let _ = state.push(AppState::Reconnecting);
+        } else {
+            info!("Stopped watching: {}", err);
+            toasts.send(Toast(locale.text("toast-spectating-ended")));
+            let _ = state.set(AppState::MainMenu);
+        }
+        return;
+    }
+    if *stats != watching.stats {
+        *stats = watching.stats;
+    }
+}
+
+pub(crate) type Watched<'a> = (&'a mut Transform, Option<&'a Ball>, Option<&'a P1Paddle>);
+pub(crate) type IsWatched = Or<(With<Ball>, With<P1Paddle>, With<P2Paddle>)>;
+
+fn show_snapshots(
+    time: Res<Time>,
+    tick_rate: Res<TickRate>,
+    mut watching: ResMut<Watching>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut count: ResMut<SpectatorCount>,
+    mut query: Query<Watched, IsWatched>,
+) {
+    let (from, to) = match (watching.from, watching.to) {
+        (Some(from), Some(to)) => (from, to),
+        _ => return,
+    };
+    watching.since += time.delta_seconds();
+    let t = (watching.since / tick_rate.step()).min(1.0);
+    show_between(&from, &to, t, &mut query);
+
+    if scoreboard.p1_score != to.p1_score || scoreboard.p2_score != to.p2_score {
+        scoreboard.p1_score = to.p1_score;
+        scoreboard.p2_score = to.p2_score;
+    }
+    if count.0 != to.spectators {
+        count.0 = to.spectators;
+    }
+}
+
+/// Puts the ball and paddles `t` of the way from where they are in `from`
+/// to where they are in `to`.
+pub(crate) fn show_between(
+    from: &Snapshot,
+    to: &Snapshot,
+    t: f32,
+    query: &mut Query<Watched, IsWatched>,
+) {
+    for (mut transform, ball, p1) in query.iter_mut() {
+        if ball.is_some() {
+            let position = from.ball.lerp(to.ball, t);
+            transform.translation = position.extend(transform.translation.z);
+            continue;
+        }
+        let (from, to) = if p1.is_some() {
+            (from.p1, to.p1)
+        } else {
+            (from.p2, to.p2)
+        };
+        let position = from.position.lerp(to.position, t);
+        transform.translation = position.extend(transform.translation.z);
+        transform.scale.y = from.height + (to.height - from.height) * t;
+    }
+}
+
+fn stop_watching(mut keyboard_input: ResMut<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        keyboard_input.reset(KeyCode::Escape);
+        let _ = state.set(AppState::MainMenu);
+    }
+}
+
+/// Hangs up on the host, leaving the court free for a match of our own.
+fn forget_match(
+    mut commands: Commands,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut count: ResMut<SpectatorCount>,
+    mut stats: ResMut<NetStats>,
+) {
+    commands.remove_resource::<Watching>();
+    *scoreboard = Scoreboard::default();
+    count.0 = 0;
+    *stats = NetStats::default();
+}
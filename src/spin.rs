@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+
+use crate::{GameConfig, TickRate, Velocity};
+
+/// Spin slower than this has worn off.
+const SPUN_OUT: f32 = 0.05;
+
+/// How fast the ball is spinning, in radians per second, counter-clockwise.
+///
+/// Paddles moving across the ball as they hit it set it spinning, and the
+/// spin bends the ball's path until it wears off.
+#[derive(Component, Default, Deref, DerefMut)]
+pub struct Spin(pub f32);
+
+impl Spin {
+    /// Spin picked up from a paddle brushing past the ball at
+    /// `paddle_velocity` as it sends it off at `ball_velocity`.
+    pub fn from_hit(config: &GameConfig, paddle_velocity: Vec2, ball_velocity: Vec2) -> Spin {
+        // The paddle drags the near side of the ball along with it, so the
+        // ball turns the opposite way to how the paddle is moving relative to
+        // the ball's heading.
+        let direction = ball_velocity.x.signum();
+        Spin(-direction * paddle_velocity.y * config.spin_per_paddle_speed)
+    }
+}
+
+/// Bends the path of spinning balls sideways, Magnus-style, and lets the spin
+/// wear off.
+pub fn curve_ball(
+    config: Res<GameConfig>,
+    tick_rate: Res<TickRate>,
+    mut query: Query<(&mut Velocity, &mut Spin)>,
+) {
+    let step = tick_rate.step();
+    for (mut velocity, mut spin) in query.iter_mut() {
+        if spin.0 == 0.0 {
+            continue;
+        }
+
+        // Turns the ball without speeding it up or slowing it down.
+        let speed = velocity.length();
+        let bent = velocity.0 + velocity.perp() * spin.0 * config.magnus * step;
+        velocity.0 = bent.normalize_or_zero() * speed;
+        spin.0 *= (1.0 - config.spin_decay * step).max(0.0);
+        if spin.0.abs() < SPUN_OUT {
+            spin.0 = 0.0;
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::{io, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data_path, persist, AppState, Attract, Ball, GoalEvent, HitEvent, MatchOverEvent, Player,
+    Series, Velocity,
+};
+
+/// Where lifetime stats live, under [`data_path`].
+pub const STATS_PATH: &str = "stats.ron";
+
+/// Lifetime records, kept in [`STATS_PATH`] between runs.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    pub matches_played: u32,
+    pub p1_wins: u32,
+    pub p2_wins: u32,
+    /// Most paddle hits in a single point.
+    pub longest_rally: u32,
+    /// Top ball speed ever reached, in units per second.
+    pub fastest_ball: f32,
+}
+
+impl Stats {
+    pub fn load(path: impl AsRef<Path>) -> Stats {
+        persist::load_or_create(path.as_ref())
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        persist::save(self, path.as_ref())
+    }
+}
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Stats::load(data_path(STATS_PATH)))
+            .init_resource::<Series>()
+            .add_system_set(
+                SystemSet::on_update(AppState::Playing)
+                    .with_system(track_rallies)
+                    .with_system(track_ball_speed),
+            )
+            .add_system(record_match);
+    }
+}
+
+fn track_rallies(
+    mut stats: ResMut<Stats>,
+    attract: Option<Res<Attract>>,
+    mut rally: Local<u32>,
+    mut hit_events: EventReader<HitEvent>,
+    mut goal_events: EventReader<GoalEvent>,
+) {
+    // The AI playing itself for the attract demo sets no records.
+    if attract.is_some() {
+        hit_events.iter().count();
+        goal_events.iter().count();
+        *rally = 0;
+        return;
+    }
+    *rally += hit_events.iter().count() as u32;
+    stats.longest_rally = stats.longest_rally.max(*rally);
+
+    if goal_events.iter().count() > 0 {
+        *rally = 0;
+    }
+}
+
+fn track_ball_speed(mut stats: ResMut<Stats>, ball_query: Query<&Velocity, With<Ball>>) {
+    for velocity in ball_query.iter() {
+        stats.fastest_ball = stats.fastest_ball.max(velocity.length());
+    }
+}
+
+fn record_match(
+    series: Res<Series>,
+    mut stats: ResMut<Stats>,
+    mut match_over_events: EventReader<MatchOverEvent>,
+) {
+    for match_over in match_over_events.iter() {
+        stats.matches_played += 1;
+        match series.player_on(match_over.winner) {
+            Player::P1 => stats.p1_wins += 1,
+            Player::P2 => stats.p2_wins += 1,
+        }
+
+        let path = data_path(STATS_PATH);
+        if let Err(err) = stats.save(&path) {
+            warn!("Could not save stats to {}: {}", path.display(), err);
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use directories::ProjectDirs;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    settings::SETTINGS_PATH,
+    stats::{Stats, STATS_PATH},
+    Achievements, DailyBests, LeaderboardCache, Profiles, Replay, Settings, ACHIEVEMENTS_PATH,
+    CAPTURES_DIR, DAILY_PATH, LEADERBOARD_PATH, PROFILES_PATH, REPLAYS_DIR, RESULTS_DIR,
+};
+
+/// Something an older version kept in the working directory, with how to
+/// tell it from somebody else's file of the same name.
+#[derive(Clone, Copy)]
+enum Save {
+    /// A file of settings, kept under [`config_path`] now.
+    Setting(fn(&Path) -> bool),
+    /// A file of data, kept under [`data_path`] now.
+    Data(fn(&Path) -> bool),
+    /// A folder under [`data_path`] now, moved a file at a time.
+    Folder(fn(&Path) -> bool),
+}
+
+const SAVES: &[(&str, Save)] = &[
+    (SETTINGS_PATH, Save::Setting(is_ron::<Settings>)),
+    (STATS_PATH, Save::Data(is_ron::<Stats>)),
+    (ACHIEVEMENTS_PATH, Save::Data(is_ron::<Achievements>)),
+    (PROFILES_PATH, Save::Data(is_ron::<Profiles>)),
+    (DAILY_PATH, Save::Data(is_ron::<DailyBests>)),
+    (LEADERBOARD_PATH, Save::Data(is_ron::<LeaderboardCache>)),
+    (RESULTS_DIR, Save::Folder(is_result)),
+    (REPLAYS_DIR, Save::Folder(is_replay)),
+    (CAPTURES_DIR, Save::Folder(is_capture)),
+];
+
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "", "fjong")
+}
+
+/// Where the setting file `name` lives: `~/.config/fjong` on Linux,
+/// `AppData\Roaming` on Windows and `Library/Application Support` on macOS.
+/// The working directory if the platform has nowhere for it.
+pub fn config_path(name: &str) -> PathBuf {
+    match project_dirs() {
+        Some(dirs) => dirs.config_dir().join(name),
+        None => PathBuf::from(name),
+    }
+}
+
+/// Where the save file or folder `name` lives: `~/.local/share/fjong` on
+/// Linux, `AppData\Roaming` on Windows and `Library/Application Support` on
+/// macOS. The working directory if the platform has nowhere for it.
+pub fn data_path(name: &str) -> PathBuf {
+    match project_dirs() {
+        Some(dirs) => dirs.data_dir().join(name),
+        None => PathBuf::from(name),
+    }
+}
+
+/// Moves what an older version left in the working directory to where it
+/// is kept now. Only for the game itself to call, before anything is
+/// loaded: a program embedding fjong has its own files there.
+pub fn migrate_legacy_files() {
+    if let Some(dirs) = project_dirs() {
+        migrate_saves(Path::new("."), dirs.config_dir(), dirs.data_dir());
+    }
+}
+
+/// Moves the settings and saves an older version left in `from` into
+/// `config_dir` and `data_dir`. Only files fjong could have written are
+/// moved, and nothing already there is overwritten.
+pub fn migrate_saves(from: &Path, config_dir: &Path, data_dir: &Path) {
+    for &(name, save) in SAVES {
+        let legacy = from.join(name);
+        match save {
+            Save::Setting(is_ours) => move_if(&legacy, &config_dir.join(name), is_ours),
+            Save::Data(is_ours) => move_if(&legacy, &data_dir.join(name), is_ours),
+            Save::Folder(is_ours) => {
+                let entries = match fs::read_dir(&legacy) {
+                    Ok(entries) => entries,
+                    Err(_) => continue,
+                };
+                for entry in entries.flatten() {
+                    let path = data_dir.join(name).join(entry.file_name());
+                    move_if(&entry.path(), &path, is_ours);
+                }
+                // Only goes if nothing of anybody else's is left in it.
+                let _ = fs::remove_dir(&legacy);
+            }
+        }
+    }
+}
+
+fn move_if(legacy: &Path, path: &Path, is_ours: fn(&Path) -> bool) {
+    if !legacy.is_file() || !is_ours(legacy) {
+        return;
+    }
+    if path.exists() {
+        warn!(
+            "Leaving {} be, {} is used instead",
+            legacy.display(),
+            path.display()
+        );
+        return;
+    }
+    match move_to(legacy, path) {
+        Ok(()) => info!("Moved {} to {}", legacy.display(), path.display()),
+        Err(err) => warn!(
+            "Could not move {} to {}: {}",
+            legacy.display(),
+            path.display(),
+            err
+        ),
+    }
+}
+
+/// Whether `path` reads as a `T` with no field a `T` doesn't have. Every
+/// field has a default, so reading at all says little by itself.
+fn is_ron<T: Serialize + DeserializeOwned>(path: &Path) -> bool {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(_) => return false,
+    };
+    let fields = |value: &ron::Value| match value {
+        ron::Value::Map(map) => map.keys().cloned().collect(),
+        _ => Vec::new(),
+    };
+    let (value, parsed) = match (
+        ron::from_str::<ron::Value>(&contents),
+        ron::from_str::<T>(&contents),
+    ) {
+        (Ok(value), Ok(parsed)) => (value, parsed),
+        _ => return false,
+    };
+    let known = ron::to_string(&parsed)
+        .ok()
+        .and_then(|known| ron::from_str::<ron::Value>(&known).ok())
+        .map_or_else(Vec::new, |known| fields(&known));
+    fields(&value).iter().all(|field| known.contains(field))
+}
+
+/// A match result as `ResultsPlugin` names and writes them.
+fn is_result(path: &Path) -> bool {
+    is_named(path, "match-", "json")
+        && fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok())
+            .is_some_and(|result| result.get("finished_at").is_some())
+}
+
+fn is_replay(path: &Path) -> bool {
+    is_named(path, "match-", "json") && Replay::load(path).is_ok()
+}
+
+fn is_capture(path: &Path) -> bool {
+    is_named(path, "screenshot-", "png") || is_named(path, "clip-", "gif")
+}
+
+fn is_named(path: &Path, prefix: &str, extension: &str) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with(prefix))
+        && path.extension().is_some_and(|ext| ext == extension)
+}
+
+/// Renames `from` to `to`, or copies it over and removes it where it can't
+/// be renamed, as between drives.
+fn move_to(from: &Path, to: &Path) -> io::Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)?;
+    fs::remove_file(from)
+}
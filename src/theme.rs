@@ -0,0 +1,488 @@
+use bevy::{
+    ecs::system::{lifetimeless::SRes, SystemParamItem},
+    prelude::*,
+    reflect::TypeUuid,
+    render::{
+        mesh::MeshVertexBufferLayout,
+        render_asset::{PrepareAssetError, RenderAsset},
+        render_resource::{
+            std140::{AsStd140, Std140},
+            *,
+        },
+        renderer::RenderDevice,
+    },
+    sprite::{Material2d, Material2dPipeline, Material2dPlugin, MaterialMesh2dBundle},
+};
+
+use crate::{
+    profiles::PaddleColors,
+    settings::{AccessibilitySettings, Settings, Theme},
+    skins::Skinned,
+    theme_packs::ActiveThemePack,
+    ArenaLayout, Ball, Frenzy, GameConfig, P1Goal, P1Paddle, P2Goal, P2Paddle, Scoreboard, Wall,
+    BACKGROUND_COLOR, FOREGROUND_COLOR,
+};
+
+/// How far the glow reaches past the edge of what is glowing.
+const GLOW_MARGIN: f32 = 16.0;
+const GLOW_INTENSITY: f32 = 0.6;
+/// Glows are added on top of their sprite, which is what makes the sprite
+/// look lit from within.
+const GLOW_Z_OFFSET: f32 = 0.5;
+/// Width of the ring the ball is drawn as in the colour-blind and
+/// high-contrast themes, so it never looks like a paddle.
+const BALL_RING_WIDTH: f32 = 6.0;
+/// Just in front of the ball.
+const BALL_CORE_Z_OFFSET: f32 = 0.1;
+/// The pull of a gravity well is shown as a faint haze, brightest in the
+/// middle, behind everything else on the court.
+const WELL_INTENSITY: f32 = 0.15;
+const WELL_Z: f32 = -1.0;
+
+/// Colours the court to match the chosen [`Theme`], and lights up everything
+/// in it with a soft glow for [`Theme::Neon`], no brighter than
+/// [`AccessibilitySettings::max_brightness`] allows. The ball heats up as a
+/// rally speeds it up, see [`BallHeat`](crate::settings::BallHeat). A
+/// [`ThemePack`](crate::ThemePack) with colours of its own paints over the
+/// theme's, keeping its hollow ball.
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(Material2dPlugin::<GlowMaterial>::default())
+            .init_resource::<AccessibilitySettings>()
+            .init_resource::<ActiveThemePack>()
+            .init_resource::<Frenzy>()
+            .init_resource::<GameConfig>()
+            .init_resource::<PaddleColors>()
+            .init_resource::<Scoreboard>()
+            .add_startup_system(create_glow_mesh)
+            .add_system(apply_theme)
+            .add_system(heat_ball.after(apply_theme))
+            .add_system_to_stage(CoreStage::PostUpdate, fit_glows)
+            .add_system_to_stage(CoreStage::PostUpdate, fit_ball_cores);
+    }
+}
+
+struct Palette {
+    background: Color,
+    p1: Color,
+    p2: Color,
+    ball: Color,
+    wall: Color,
+    /// Draw the ball as a ring rather than a solid block, so it stands out
+    /// by shape as well as by colour.
+    hollow_ball: bool,
+}
+
+// Colour-blind palettes are built from Okabe and Ito's colour-universal
+// set, picking pairs that stay apart under each kind of colour blindness.
+const SKY_BLUE: Color = Color::rgb(0.34, 0.71, 0.91);
+const ORANGE: Color = Color::rgb(0.9, 0.62, 0.0);
+const YELLOW: Color = Color::rgb(0.94, 0.89, 0.26);
+const VERMILLION: Color = Color::rgb(0.84, 0.37, 0.0);
+const BLUISH_GREEN: Color = Color::rgb(0.0, 0.62, 0.45);
+const NEUTRAL_GREY: Color = Color::rgb(0.6, 0.6, 0.6);
+
+fn palette(theme: Theme) -> Palette {
+    match theme {
+        Theme::Classic => Palette {
+            background: BACKGROUND_COLOR,
+            p1: FOREGROUND_COLOR,
+            p2: FOREGROUND_COLOR,
+            ball: FOREGROUND_COLOR,
+            wall: FOREGROUND_COLOR,
+            hollow_ball: false,
+        },
+        Theme::Neon => Palette {
+            background: Color::rgb(0.02, 0.0, 0.06),
+            p1: Color::rgb(0.0, 1.0, 0.95),
+            p2: Color::rgb(1.0, 0.1, 0.8),
+            ball: Color::rgb(1.0, 1.0, 0.6),
+            wall: Color::rgb(0.55, 0.2, 1.0),
+            hollow_ball: false,
+        },
+        Theme::Deuteranopia => Palette {
+            background: BACKGROUND_COLOR,
+            p1: SKY_BLUE,
+            p2: ORANGE,
+            ball: FOREGROUND_COLOR,
+            wall: NEUTRAL_GREY,
+            hollow_ball: true,
+        },
+        Theme::Protanopia => Palette {
+            background: BACKGROUND_COLOR,
+            p1: SKY_BLUE,
+            p2: YELLOW,
+            ball: FOREGROUND_COLOR,
+            wall: NEUTRAL_GREY,
+            hollow_ball: true,
+        },
+        Theme::Tritanopia => Palette {
+            background: BACKGROUND_COLOR,
+            p1: BLUISH_GREEN,
+            p2: VERMILLION,
+            ball: FOREGROUND_COLOR,
+            wall: NEUTRAL_GREY,
+            hollow_ball: true,
+        },
+        Theme::HighContrast => Palette {
+            background: Color::BLACK,
+            p1: Color::WHITE,
+            p2: Color::YELLOW,
+            ball: Color::WHITE,
+            wall: Color::WHITE,
+            hollow_ball: true,
+        },
+    }
+}
+
+/// Draws a soft halo around a rectangle of `core` half-extents, measured as
+/// a fraction of the quad it is drawn on.
+#[derive(Clone, TypeUuid)]
+#[uuid = "234d1474-1e3e-4ddb-98c6-d877872b66a0"]
+pub struct GlowMaterial {
+    pub color: Color,
+    pub core: Vec2,
+}
+
+#[derive(Clone, Default, AsStd140)]
+struct GlowMaterialUniformData {
+    color: Vec4,
+    core: Vec2,
+}
+
+pub struct GpuGlowMaterial {
+    bind_group: BindGroup,
+}
+
+impl RenderAsset for GlowMaterial {
+    type ExtractedAsset = GlowMaterial;
+    type PreparedAsset = GpuGlowMaterial;
+    type Param = (SRes<RenderDevice>, SRes<Material2dPipeline<GlowMaterial>>);
+
+    fn extract_asset(&self) -> Self::ExtractedAsset {
+        self.clone()
+    }
+
+    fn prepare_asset(
+        material: Self::ExtractedAsset,
+        (render_device, pipeline): &mut SystemParamItem<Self::Param>,
+    ) -> Result<Self::PreparedAsset, PrepareAssetError<Self::ExtractedAsset>> {
+        let value = GlowMaterialUniformData {
+            color: material.color.as_linear_rgba_f32().into(),
+            core: material.core,
+        };
+        let buffer = render_device.create_buffer_with_data(&BufferInitDescriptor {
+            label: Some("glow_material_uniform_buffer"),
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            contents: value.as_std140().as_bytes(),
+        });
+        let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+            label: Some("glow_material_bind_group"),
+            layout: &pipeline.material2d_layout,
+        });
+
+        Ok(GpuGlowMaterial { bind_group })
+    }
+}
+
+impl Material2d for GlowMaterial {
+    fn fragment_shader(asset_server: &AssetServer) -> Option<Handle<Shader>> {
+        Some(asset_server.load("shaders/glow.wgsl"))
+    }
+
+    fn bind_group(render_asset: &GpuGlowMaterial) -> &BindGroup {
+        &render_asset.bind_group
+    }
+
+    fn bind_group_layout(render_device: &RenderDevice) -> BindGroupLayout {
+        render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: BufferSize::new(
+                        GlowMaterialUniformData::std140_size_static() as u64,
+                    ),
+                },
+                count: None,
+            }],
+            label: Some("glow_material_layout"),
+        })
+    }
+
+    fn specialize(
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayout,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        // Light adds up rather than covering what's underneath.
+        let additive = BlendState {
+            color: BlendComponent {
+                src_factor: BlendFactor::SrcAlpha,
+                dst_factor: BlendFactor::One,
+                operation: BlendOperation::Add,
+            },
+            alpha: BlendComponent::OVER,
+        };
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            for target in fragment.targets.iter_mut() {
+                target.blend = Some(additive);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A halo that follows `target` around.
+#[derive(Component)]
+struct Glow {
+    target: Entity,
+}
+
+/// The middle of a hollow ball, painted over `target` in the background
+/// colour.
+#[derive(Component)]
+struct BallCore {
+    target: Entity,
+}
+
+/// The haze over the arena's gravity well.
+#[derive(Component)]
+struct WellGlow;
+
+struct GlowMesh(Handle<Mesh>);
+
+fn create_glow_mesh(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    let mesh = meshes.add(Mesh::from(shape::Quad::new(Vec2::ONE)));
+    commands.insert_resource(GlowMesh(mesh));
+}
+
+type CourtSprite<'a> = (
+    Entity,
+    &'a mut Sprite,
+    Option<&'a P1Paddle>,
+    Option<&'a P2Paddle>,
+    Option<&'a Ball>,
+    Option<&'a Skinned>,
+);
+type IsCourt = Or<(With<P1Paddle>, With<P2Paddle>, With<Ball>, With<Wall>)>;
+type IsGoal = Or<(With<P1Goal>, With<P2Goal>)>;
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn apply_theme(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    accessibility: Res<AccessibilitySettings>,
+    layout: Res<ArenaLayout>,
+    paddle_colors: Res<PaddleColors>,
+    theme_pack: Res<ActiveThemePack>,
+    frenzy: Res<Frenzy>,
+    mut frenzy_level: Local<usize>,
+    glow_mesh: Res<GlowMesh>,
+    mut materials: ResMut<Assets<GlowMaterial>>,
+    mut clear_color: ResMut<ClearColor>,
+    mut sprites: ParamSet<(Query<CourtSprite, IsCourt>, Query<&mut Sprite, IsGoal>)>,
+    glow_query: Query<Entity, Or<(With<Glow>, With<BallCore>, With<WellGlow>)>>,
+    new_walls: Query<(), Added<Wall>>,
+    new_skins: Query<(), Changed<Skinned>>,
+) {
+    // New arenas, and practice, put up walls of their own which need painting
+    // too. A frenzy recolours the ball as it goes up a level, but not on every
+    // hit of the rally it is counting. Players' profiles may paint their
+    // paddles colours of their own, and skins leave them unpainted.
+    let changed = settings.is_changed()
+        || accessibility.is_changed()
+        || layout.is_changed()
+        || paddle_colors.is_changed()
+        || theme_pack.is_changed()
+        || frenzy.level != *frenzy_level;
+    if !changed && new_walls.is_empty() && new_skins.is_empty() {
+        return;
+    }
+    *frenzy_level = frenzy.level;
+
+    let mut palette = palette(settings.video.theme);
+    if let Some(colors) = theme_pack.colors {
+        palette.background = colors.background;
+        palette.p1 = colors.p1;
+        palette.p2 = colors.p2;
+        palette.ball = colors.ball;
+        palette.wall = colors.wall;
+    }
+    let background = accessibility.cap(layout.background.unwrap_or(palette.background));
+    clear_color.0 = background;
+    for mut sprite in sprites.p1().iter_mut() {
+        sprite.color = background;
+    }
+
+    for glow in glow_query.iter() {
+        commands.entity(glow).despawn();
+    }
+
+    if let Some(well) = &layout.gravity_well {
+        let mut color = accessibility.cap(palette.wall);
+        color.set_a(WELL_INTENSITY * accessibility.max_brightness);
+        commands
+            .spawn_bundle(MaterialMesh2dBundle {
+                mesh: glow_mesh.0.clone().into(),
+                material: materials.add(GlowMaterial {
+                    color,
+                    core: Vec2::ZERO,
+                }),
+                transform: Transform {
+                    translation: well.position.extend(WELL_Z),
+                    scale: Vec3::new(2.0 * well.radius, 2.0 * well.radius, 1.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(WellGlow);
+    }
+
+    for (entity, mut sprite, p1, p2, ball, skinned) in sprites.p0().iter_mut() {
+        let skinned = skinned == Some(&Skinned(true));
+        let color = if skinned {
+            Color::WHITE
+        } else if p1.is_some() {
+            paddle_colors.0[0].unwrap_or(palette.p1)
+        } else if p2.is_some() {
+            paddle_colors.0[1].unwrap_or(palette.p2)
+        } else if ball.is_some() {
+            frenzy.tint(palette.ball)
+        } else {
+            palette.wall
+        };
+        let color = accessibility.cap(color);
+        // Keep the alpha, the ball fades in while it is being served.
+        let alpha = sprite.color.a();
+        sprite.color = color;
+        sprite.color.set_a(alpha);
+
+        if ball.is_some() && palette.hollow_ball && !skinned {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: background,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(BallCore { target: entity });
+        }
+
+        if settings.video.theme == Theme::Neon {
+            commands
+                .spawn_bundle(MaterialMesh2dBundle {
+                    mesh: glow_mesh.0.clone().into(),
+                    material: materials.add(GlowMaterial {
+                        color,
+                        core: Vec2::splat(0.5),
+                    }),
+                    ..default()
+                })
+                .insert(Glow { target: entity });
+        }
+    }
+}
+
+/// Moves the ball along the heat gradient as the rally speeds it up, in
+/// place of its theme's colour.
+fn heat_ball(
+    settings: Res<Settings>,
+    accessibility: Res<AccessibilitySettings>,
+    config: Res<GameConfig>,
+    scoreboard: Res<Scoreboard>,
+    frenzy: Res<Frenzy>,
+    mut ball_query: Query<(&mut Sprite, Option<&Skinned>), With<Ball>>,
+) {
+    let heat = &settings.video.ball_heat;
+    if !heat.enabled {
+        return;
+    }
+    let color = match heat.color(config.rally_speed_multiplier(scoreboard.fjongs)) {
+        Some(color) => accessibility.cap(frenzy.tint(color)),
+        None => return,
+    };
+    for (mut sprite, skinned) in ball_query.iter_mut() {
+        if skinned == Some(&Skinned(true)) {
+            continue;
+        }
+        let mut heated = color;
+        heated.set_a(sprite.color.a());
+        // Only touch the sprite when it changes, to keep change detection
+        // quiet.
+        if sprite.color != heated {
+            sprite.color = heated;
+        }
+    }
+}
+
+fn fit_glows(
+    mut commands: Commands,
+    accessibility: Res<AccessibilitySettings>,
+    mut materials: ResMut<Assets<GlowMaterial>>,
+    target_query: Query<(&Transform, &Sprite), Without<Glow>>,
+    mut glow_query: Query<(Entity, &Glow, &mut Transform, &Handle<GlowMaterial>)>,
+) {
+    for (entity, glow, mut transform, material) in glow_query.iter_mut() {
+        let (target_transform, target_sprite) = match target_query.get(glow.target) {
+            Ok(target) => target,
+            Err(_) => {
+                commands.entity(entity).despawn();
+                continue;
+            }
+        };
+
+        let size = target_transform.scale.truncate();
+        let glow_size = size + Vec2::splat(2.0 * GLOW_MARGIN);
+        transform.translation = target_transform.translation + Vec3::Z * GLOW_Z_OFFSET;
+        transform.rotation = target_transform.rotation;
+        transform.scale = glow_size.extend(1.0);
+
+        let mut color = target_sprite.color;
+        color.set_a(color.a() * GLOW_INTENSITY * accessibility.max_brightness);
+        let core = size / glow_size * 0.5;
+        // Only touch the material when it changes, as that re-uploads it.
+        if let Some(current) = materials.get(material) {
+            if current.color == color && current.core == core {
+                continue;
+            }
+        }
+        if let Some(current) = materials.get_mut(material) {
+            current.color = color;
+            current.core = core;
+        }
+    }
+}
+
+fn fit_ball_cores(
+    mut commands: Commands,
+    ball_query: Query<(&Transform, &Sprite), Without<BallCore>>,
+    mut core_query: Query<(Entity, &BallCore, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, core, mut transform, mut sprite) in core_query.iter_mut() {
+        let (ball_transform, ball_sprite) = match ball_query.get(core.target) {
+            Ok(ball) => ball,
+            Err(_) => {
+                commands.entity(entity).despawn();
+                continue;
+            }
+        };
+
+        let size = ball_transform.scale.truncate();
+        let ring = Vec2::splat(2.0 * BALL_RING_WIDTH) / size;
+        let shape = ball_sprite.custom_size.unwrap_or(Vec2::ONE);
+        *transform = *ball_transform;
+        transform.translation.z += BALL_CORE_Z_OFFSET;
+        sprite.custom_size = Some((shape - ring).max(Vec2::ZERO));
+        // The ring fades in with the ball on a serve, the middle stays hidden.
+        sprite.color.set_a(ball_sprite.color.a());
+    }
+}
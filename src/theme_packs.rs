@@ -0,0 +1,244 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{fonts::UiFont, settings::Settings, sound::Sounds, Skin};
+
+/// Folder, relative to the working directory, that theme packs are loaded
+/// from. It is shared with scripted mods, which are `.rhai` files instead.
+pub const THEME_PACKS_PATH: &str = "mods";
+
+/// A set of colours, a font, sounds and a skin to dress the game in, each
+/// one optional, in place of the game's own.
+///
+/// Packs are `.theme.ron` files in [`THEME_PACKS_PATH`], or in a folder of
+/// their own inside it, naming files next to them:
+///
+/// ```ron
+/// (
+///     name: "Sunset",
+///     colors: Some((
+///         background: Rgba(red: 0.12, green: 0.03, blue: 0.1, alpha: 1.0),
+///         p1: Rgba(red: 1.0, green: 0.6, blue: 0.2, alpha: 1.0),
+///         p2: Rgba(red: 0.9, green: 0.2, blue: 0.4, alpha: 1.0),
+///         ball: Rgba(red: 1.0, green: 0.9, blue: 0.6, alpha: 1.0),
+///         wall: Rgba(red: 0.5, green: 0.2, blue: 0.4, alpha: 1.0),
+///     )),
+///     font: Some("font.ttf"),
+///     sounds: (hit: Some("hit.wav"), goal: Some("goal.wav")),
+///     skin: Some((paddle: Some("paddle.png"), ball: None)),
+/// )
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ThemePack {
+    pub name: String,
+    pub colors: Option<PackColors>,
+    pub font: Option<PathBuf>,
+    pub sounds: PackSounds,
+    pub skin: Option<PackSkin>,
+    /// Where the pack's file is, which the files it names are relative to.
+    #[serde(skip)]
+    pub folder: PathBuf,
+}
+
+/// Colours for the court, taking over from the chosen theme's.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct PackColors {
+    pub background: Color,
+    pub p1: Color,
+    pub p2: Color,
+    pub ball: Color,
+    pub wall: Color,
+}
+
+/// Sounds to play in place of the game's own, by the cue they are for.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PackSounds {
+    pub hit: Option<PathBuf>,
+    pub smash: Option<PathBuf>,
+    pub wall: Option<PathBuf>,
+    pub goal: Option<PathBuf>,
+    pub game_point: Option<PathBuf>,
+    pub match_point: Option<PathBuf>,
+    pub match_win: Option<PathBuf>,
+}
+
+/// Images for the paddles and the ball, like a [`Skin`] of the pack's own.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PackSkin {
+    pub paddle: Option<PathBuf>,
+    pub ball: Option<PathBuf>,
+}
+
+/// Lists every [`ThemePack`] in `dir` into [`ThemePacks`] at startup, and
+/// dresses the game in the one picked in the settings, by pointing the
+/// game's font, sounds and skin at the pack's files instead.
+pub struct ThemePacksPlugin {
+    pub dir: PathBuf,
+}
+
+impl Plugin for ThemePacksPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ThemePacks::scan(&self.dir))
+            .init_resource::<ActiveThemePack>()
+            .init_resource::<Settings>()
+            .add_system(apply_theme_pack);
+    }
+}
+
+/// Every theme pack found, sorted by name.
+#[derive(Default)]
+pub struct ThemePacks(pub Vec<ThemePack>);
+
+impl ThemePacks {
+    /// Reads every pack in `dir` and the folders directly inside it, leaving
+    /// out any that can't be read.
+    pub fn scan(dir: impl AsRef<Path>) -> ThemePacks {
+        // Made absolute, as the asset server loads anything else from the
+        // assets folder.
+        let dir = env::current_dir().unwrap_or_default().join(dir);
+        let mut paths = pack_paths(&dir);
+        if let Ok(entries) = fs::read_dir(&dir) {
+            for entry in entries.filter_map(Result::ok) {
+                if entry.path().is_dir() {
+                    paths.extend(pack_paths(&entry.path()));
+                }
+            }
+        }
+
+        let mut packs: Vec<ThemePack> = paths
+            .into_iter()
+            .filter_map(|path| match ThemePack::load(&path) {
+                Ok(pack) => Some(pack),
+                Err(err) => {
+                    warn!("Ignoring theme pack {}: {}", path.display(), err);
+                    None
+                }
+            })
+            .collect();
+        packs.sort_by(|a, b| a.name.cmp(&b.name));
+        ThemePacks(packs)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ThemePack> {
+        self.0.iter().find(|pack| pack.name == name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.0.iter().map(|pack| pack.name.clone()).collect()
+    }
+}
+
+impl ThemePack {
+    pub fn load(path: &Path) -> io::Result<ThemePack> {
+        let contents = fs::read_to_string(path)?;
+        let mut pack: ThemePack = ron::from_str(&contents)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        pack.folder = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(pack)
+    }
+
+    /// Where a file the pack names is.
+    pub fn path(&self, file: &Path) -> PathBuf {
+        self.folder.join(file)
+    }
+}
+
+fn pack_paths(dir: &Path) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        // No mods folder, no packs.
+        Err(_) => return Vec::new(),
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.to_string_lossy().ends_with(".theme.ron"))
+        .collect()
+}
+
+/// What of the picked theme pack isn't applied by swapping handles, for the
+/// theme and skins to pick up.
+#[derive(Default)]
+pub(crate) struct ActiveThemePack {
+    pub(crate) colors: Option<PackColors>,
+    pub(crate) skin: Option<Skin>,
+}
+
+/// The game's own font and sounds, to go back to.
+struct Defaults {
+    font: Option<Handle<Font>>,
+    sounds: Option<Sounds>,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_theme_pack(
+    settings: Res<Settings>,
+    packs: Res<ThemePacks>,
+    asset_server: Res<AssetServer>,
+    mut active: ResMut<ActiveThemePack>,
+    font: Option<ResMut<UiFont>>,
+    sounds: Option<ResMut<Sounds>>,
+    mut defaults: Local<Option<Defaults>>,
+    mut applied: Local<Option<String>>,
+) {
+    // Sounds are loaded by a startup system, so are there by now.
+    let defaults = defaults.get_or_insert_with(|| Defaults {
+        font: font.as_ref().map(|font| font.0.clone()),
+        sounds: sounds.as_deref().cloned(),
+    });
+    let picked = &settings.video.theme_pack;
+    if *applied == *picked {
+        return;
+    }
+    *applied = picked.clone();
+
+    let pack = picked.as_deref().and_then(|name| packs.get(name));
+    if picked.is_some() && pack.is_none() {
+        warn!("No theme pack called {:?}", picked);
+    }
+    let load = |file: fn(&ThemePack) -> Option<&PathBuf>| {
+        let pack = pack?;
+        Some(pack.path(file(pack)?))
+    };
+
+    if let (Some(mut font), Some(default)) = (font, &defaults.font) {
+        font.0 = match load(|pack| pack.font.as_ref()) {
+            Some(path) => asset_server.load(path),
+            None => default.clone(),
+        };
+    }
+    if let (Some(mut sounds), Some(default)) = (sounds, &defaults.sounds) {
+        let sound = |file, default: &Handle<AudioSource>| match load(file) {
+            Some(path) => asset_server.load(path),
+            None => default.clone(),
+        };
+        *sounds = Sounds {
+            hit: sound(|pack| pack.sounds.hit.as_ref(), &default.hit),
+            smash: sound(|pack| pack.sounds.smash.as_ref(), &default.smash),
+            wall: sound(|pack| pack.sounds.wall.as_ref(), &default.wall),
+            goal: sound(|pack| pack.sounds.goal.as_ref(), &default.goal),
+            game_point: sound(|pack| pack.sounds.game_point.as_ref(), &default.game_point),
+            match_point: sound(
+                |pack| pack.sounds.match_point.as_ref(),
+                &default.match_point,
+            ),
+            match_win: sound(|pack| pack.sounds.match_win.as_ref(), &default.match_win),
+        };
+    }
+    let image = |file| load(file).map(|path| asset_server.load(path));
+    *active = ActiveThemePack {
+        colors: pack.and_then(|pack| pack.colors),
+        skin: pack.filter(|pack| pack.skin.is_some()).map(|pack| Skin {
+            name: pack.name.clone(),
+            paddle: image(|pack| pack.skin.as_ref()?.paddle.as_ref()),
+            ball: image(|pack| pack.skin.as_ref()?.ball.as_ref()),
+        }),
+    };
+}
@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+
+use crate::{GameMode, GoalEvent, MatchOverEvent, MatchRules, Player, Scoreboard, TickRate};
+
+/// How long a time-attack match lasts before overtime.
+pub const TIME_ATTACK_SECONDS: f32 = 120.0;
+
+/// What is left of a time-attack match, counted in simulation ticks so it
+/// runs down the same at any frame rate.
+pub struct MatchClock {
+    pub ticks_left: u32,
+    /// Time ran out on a tie, and the next goal wins.
+    pub overtime: bool,
+}
+
+impl Default for MatchClock {
+    fn default() -> Self {
+        MatchClock::new(TickRate::default())
+    }
+}
+
+impl MatchClock {
+    /// A full clock, in ticks of `tick_rate`.
+    pub fn new(tick_rate: TickRate) -> Self {
+        MatchClock {
+            ticks_left: tick_rate.ticks(TIME_ATTACK_SECONDS),
+            overtime: false,
+        }
+    }
+
+    pub fn seconds_left(&self, tick_rate: TickRate) -> f32 {
+        self.ticks_left as f32 * tick_rate.step()
+    }
+}
+
+/// A full clock whenever a match starts.
+pub fn reset_clock(tick_rate: Res<TickRate>, mut clock: ResMut<MatchClock>) {
+    *clock = MatchClock::new(*tick_rate);
+}
+
+/// Runs the clock down and ends the match when it runs out, in favour of
+/// whoever is ahead. On a tie the match goes to sudden death instead.
+pub fn run_clock(
+    rules: Res<MatchRules>,
+    scoreboard: Res<Scoreboard>,
+    mut clock: ResMut<MatchClock>,
+    mut goal_events: EventReader<GoalEvent>,
+    mut match_over_events: EventWriter<MatchOverEvent>,
+) {
+    if rules.mode != GameMode::TimeAttack {
+        return;
+    }
+
+    if clock.overtime {
+        if let Some(goal) = goal_events.iter().next() {
+            match_over_events.send(MatchOverEvent {
+                winner: goal.scorer,
+            });
+        }
+        return;
+    }
+
+    if clock.ticks_left == 0 {
+        return;
+    }
+    clock.ticks_left -= 1;
+    if clock.ticks_left > 0 {
+        return;
+    }
+    let winner = match scoreboard.p1_score.cmp(&scoreboard.p2_score) {
+        std::cmp::Ordering::Greater => Player::P1,
+        std::cmp::Ordering::Less => Player::P2,
+        std::cmp::Ordering::Equal => {
+            clock.overtime = true;
+            return;
+        }
+    };
+    match_over_events.send(MatchOverEvent { winner });
+}
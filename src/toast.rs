@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+use crate::{fonts::UiFont, FOREGROUND_COLOR};
+
+const TOAST_FONT_SIZE: f32 = 16.0;
+const TOAST_SECONDS: f32 = 2.5;
+/// How long a toast takes to fade out at the end of its time on screen.
+const FADE_SECONDS: f32 = 0.5;
+const TOAST_PADDING: f32 = 15.0;
+const TOAST_SPACING: f32 = 24.0;
+
+/// A short message shown at the bottom of the screen for a moment, such as a
+/// gamepad being plugged in or settings being saved.
+///
+/// Send one as an event from anywhere; newer toasts stack above older ones.
+pub struct Toast(pub String);
+
+/// Shows [`Toast`]s as they come in and clears them away once they have been
+/// up for a little while.
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Toast>()
+            .add_system(show_toasts)
+            .add_system(expire_toasts.after(show_toasts));
+    }
+}
+
+#[derive(Component)]
+struct ToastText {
+    remaining: f32,
+}
+
+fn show_toasts(mut commands: Commands, font: Res<UiFont>, mut toasts: EventReader<Toast>) {
+    for Toast(message) in toasts.iter() {
+        commands
+            .spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    message.clone(),
+                    TextStyle {
+                        font: font.0.clone(),
+                        font_size: TOAST_FONT_SIZE,
+                        color: FOREGROUND_COLOR,
+                    },
+                    default(),
+                ),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: Rect {
+                        bottom: Val::Px(TOAST_PADDING),
+                        right: Val::Px(TOAST_PADDING),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(ToastText {
+                remaining: TOAST_SECONDS,
+            });
+    }
+}
+
+/// Counts toasts down, fades them out and stacks the ones still up from the
+/// bottom, newest first.
+fn expire_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut ToastText, &mut Text, &mut Style)>,
+) {
+    let mut toasts: Vec<_> = query.iter_mut().collect();
+    toasts.sort_by(|(_, a, ..), (_, b, ..)| b.remaining.total_cmp(&a.remaining));
+
+    let mut slot = 0;
+    for (entity, mut toast, mut text, mut style) in toasts {
+        toast.remaining -= time.delta_seconds();
+        if toast.remaining <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let alpha = (toast.remaining / FADE_SECONDS).min(1.0);
+        text.sections[0].style.color.set_a(alpha);
+        style.position.bottom = Val::Px(TOAST_PADDING + slot as f32 * TOAST_SPACING);
+        slot += 1;
+    }
+}
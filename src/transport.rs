@@ -0,0 +1,93 @@
+use std::{
+    io::{self, Read, Write},
+    net::TcpStream,
+};
+
+/// Most unsent state the game holds on to for a bot or spectator that isn't
+/// reading, before giving up on them, and most unread lines it holds on to
+/// from one sending faster than the game keeps up with.
+const MAX_BACKLOG: usize = 64 * 1024;
+
+/// A line to whoever is on the other end of a match: a bot, a spectator, a
+/// player on another machine or the game hosting the match. All of them
+/// speak lines of JSON, read and written every frame without waiting, so a
+/// read or write that can't go ahead yet fails with
+/// [`io::ErrorKind::WouldBlock`], and a read of nothing means the other end
+/// hung up.
+///
+/// Games on the desktop talk over TCP, and over WebRTC data channels to
+/// games in the browser, see [`WebRtcPlugin`](crate::WebRtcPlugin).
+pub trait Transport: Read + Write + Send + Sync {
+    /// Gets the line ready to be read and written without waiting.
+    fn set_up(&self) -> io::Result<()>;
+
+    /// Another handle on the same line, so one part of the game can send
+    /// down it while another reads.
+    fn try_clone_line(&self) -> io::Result<Box<dyn Transport>>;
+}
+
+impl Transport for TcpStream {
+    fn set_up(&self) -> io::Result<()> {
+        self.set_nonblocking(true)?;
+        self.set_nodelay(true)
+    }
+
+    fn try_clone_line(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(Box::new(self.try_clone()?))
+    }
+}
+
+impl From<TcpStream> for Box<dyn Transport> {
+    fn from(stream: TcpStream) -> Box<dyn Transport> {
+        Box::new(stream)
+    }
+}
+
+/// Sends whatever of `outgoing` the line will take right now, and gives up
+/// on whoever is on the other end once too much piles up unread.
+pub(crate) fn flush(outgoing: &mut Vec<u8>, line: &mut dyn Transport) -> io::Result<()> {
+    while !outgoing.is_empty() {
+        match line.write(outgoing) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(sent) => {
+                outgoing.drain(..sent);
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+            Err(err) => return Err(err),
+        }
+    }
+    if outgoing.len() > MAX_BACKLOG {
+        return Err(io::Error::new(io::ErrorKind::TimedOut, "stopped reading"));
+    }
+    Ok(())
+}
+
+/// Reads what has arrived on `line` into `incoming`, up to [`MAX_BACKLOG`]
+/// at a time. Once more than that is waiting, the oldest whole lines are
+/// dropped, and whoever is on the other end is given up on if they send a
+/// single line that long.
+pub(crate) fn fill(incoming: &mut Vec<u8>, line: &mut dyn Transport) -> io::Result<()> {
+    let mut buffer = [0; 4096];
+    let mut read_now = 0;
+    while read_now < MAX_BACKLOG {
+        match line.read(&mut buffer) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(read) => {
+                incoming.extend_from_slice(&buffer[..read]);
+                read_now += read;
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+            Err(err) => return Err(err),
+        }
+    }
+    if incoming.len() > MAX_BACKLOG {
+        let excess = incoming.len() - MAX_BACKLOG;
+        match incoming[excess..].iter().position(|byte| *byte == b'\n') {
+            Some(end) => {
+                incoming.drain(..=excess + end);
+            }
+            None => return Err(io::Error::new(io::ErrorKind::InvalidData, "line too long")),
+        }
+    }
+    Ok(())
+}
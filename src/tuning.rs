@@ -0,0 +1,106 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{
+    ai::{BaseSkill, Brain, Skill},
+    GameConfig,
+};
+
+/// Tuning file under `assets/`, watched while the game runs.
+pub const TUNING_PATH: &str = "config.ron";
+
+/// Reloads [`GameConfig`] and the AI's [`Skill`] from `assets/config.ron`
+/// every time the file is saved, so the game can be tuned without a restart.
+///
+/// Only built with the `dev` feature, which turns on Bevy's file watcher.
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<TuningFile>()
+            .init_asset_loader::<TuningLoader>()
+            .add_startup_system(watch_tuning_file)
+            .add_system(apply_tuning);
+    }
+}
+
+/// Contents of the tuning file. Anything left out keeps its default.
+#[derive(Default, Deserialize, TypeUuid)]
+#[serde(default)]
+#[uuid = "5b1e7c0a-3d2f-4a8e-9c61-2f4b8d7e1a93"]
+struct TuningFile {
+    game: GameConfig,
+    /// Skill handed to every brain, if set.
+    ai: Option<Skill>,
+}
+
+#[derive(Default)]
+struct TuningLoader;
+
+impl AssetLoader for TuningLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let file: TuningFile = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(file));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+/// Keeps the tuning file loaded for as long as the game runs.
+struct TuningHandle(Handle<TuningFile>);
+
+fn watch_tuning_file(mut commands: Commands, asset_server: Res<AssetServer>) {
+    if let Err(err) = asset_server.watch_for_changes() {
+        warn!("Not watching {} for changes: {:?}", TUNING_PATH, err);
+    }
+    commands.insert_resource(TuningHandle(asset_server.load(TUNING_PATH)));
+}
+
+fn apply_tuning(
+    handle: Res<TuningHandle>,
+    files: Res<Assets<TuningFile>>,
+    mut asset_events: EventReader<AssetEvent<TuningFile>>,
+    mut config: ResMut<GameConfig>,
+    mut brain_query: Query<(&mut Brain, Option<&mut BaseSkill>)>,
+) {
+    for event in asset_events.iter() {
+        let changed = match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+        if *changed != handle.0 {
+            continue;
+        }
+        let file = match files.get(changed) {
+            Some(file) => file,
+            None => continue,
+        };
+
+        info!("Applying {}", TUNING_PATH);
+        *config = file.game.clone();
+        if let Some(skill) = file.ai {
+            for (mut brain, base) in brain_query.iter_mut() {
+                if let Some(brain_skill) = brain.skill_mut() {
+                    *brain_skill = skill;
+                }
+                if let Some(mut base) = base {
+                    base.0 = skill;
+                }
+            }
+        }
+    }
+}
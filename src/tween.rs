@@ -0,0 +1,191 @@
+use std::f32::consts::PI;
+
+use bevy::{math::const_vec2, prelude::*, transform::TransformSystem};
+
+use crate::{
+    settings::AccessibilitySettings, Ball, HitEvent, P1Paddle, P2Paddle, Player, Spin, Velocity,
+    WallBounceEvent,
+};
+
+const SQUASH_SECONDS: f32 = 0.15;
+/// How much a fresh impact flattens the ball along its path.
+const SQUASH_AMOUNT: f32 = 0.4;
+/// Ball speed at which it is stretched the most.
+const STRETCH_SPEED: f32 = 1000.0;
+const MAX_STRETCH: f32 = 0.5;
+/// Below this spin the ball stops tumbling and settles back in line with its
+/// path.
+const SETTLE_SPIN: f32 = 1.0;
+const SETTLE_RATE: f32 = 8.0;
+
+const POP_SECONDS: f32 = 0.2;
+/// How much wider and taller a paddle gets right after a hit.
+const POP_AMOUNT: Vec2 = const_vec2!([0.5, 0.08]);
+
+/// Squashes, stretches and spins the ball, and pops the paddles when they hit
+/// it.
+///
+/// This only changes how things are drawn; the simulation still sees the
+/// sizes in their transforms. All of it is left out with
+/// [`AccessibilitySettings::reduce_motion`].
+pub struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AccessibilitySettings>()
+            .add_system(attach_tweens)
+            .add_system_set_to_stage(
+                CoreStage::PostUpdate,
+                SystemSet::new()
+                    .before(TransformSystem::TransformPropagate)
+                    .with_system(start_tweens)
+                    .with_system(tick_tweens.after(start_tweens))
+                    .with_system(shape_ball.after(tick_tweens))
+                    .with_system(shape_paddles.after(tick_tweens)),
+            );
+    }
+}
+
+/// Runs from 1 down to 0 over `duration` seconds once restarted.
+pub struct Tween {
+    remaining: f32,
+    duration: f32,
+}
+
+impl Tween {
+    pub fn new(duration: f32) -> Tween {
+        Tween {
+            remaining: 0.0,
+            duration,
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.remaining = self.duration;
+    }
+
+    pub fn tick(&mut self, delta: f32) {
+        self.remaining = (self.remaining - delta).max(0.0);
+    }
+
+    /// Eased so it falls off quickly, then settles.
+    pub fn value(&self) -> f32 {
+        let t = self.remaining / self.duration;
+        t * t
+    }
+}
+
+#[derive(Component)]
+struct Squash(Tween);
+
+#[derive(Component)]
+struct Pop(Tween);
+
+/// How far the ball has turned over from its spin.
+#[derive(Component, Default)]
+struct Tumble(f32);
+
+type NewPaddle = Or<(Added<P1Paddle>, Added<P2Paddle>)>;
+
+fn attach_tweens(
+    mut commands: Commands,
+    ball_query: Query<Entity, Added<Ball>>,
+    paddle_query: Query<Entity, NewPaddle>,
+) {
+    for ball in ball_query.iter() {
+        commands
+            .entity(ball)
+            .insert(Squash(Tween::new(SQUASH_SECONDS)))
+            .insert(Tumble::default());
+    }
+    for paddle in paddle_query.iter() {
+        commands.entity(paddle).insert(Pop(Tween::new(POP_SECONDS)));
+    }
+}
+
+fn start_tweens(
+    accessibility: Res<AccessibilitySettings>,
+    mut wall_bounce_events: EventReader<WallBounceEvent>,
+    mut hit_events: EventReader<HitEvent>,
+    mut ball_query: Query<&mut Squash>,
+    mut p1_query: Query<&mut Pop, (With<P1Paddle>, Without<P2Paddle>)>,
+    mut p2_query: Query<&mut Pop, (With<P2Paddle>, Without<P1Paddle>)>,
+) {
+    let mut squashed = wall_bounce_events.iter().count() > 0;
+    if accessibility.reduce_motion {
+        // Still read, so nothing old turns up once it is switched back off.
+        hit_events.iter().for_each(drop);
+        return;
+    }
+    for hit in hit_events.iter() {
+        squashed = true;
+        let pop = match hit.player {
+            Player::P1 => p1_query.get_single_mut(),
+            Player::P2 => p2_query.get_single_mut(),
+        };
+        if let Ok(mut pop) = pop {
+            pop.0.restart();
+        }
+    }
+
+    if squashed {
+        for mut squash in ball_query.iter_mut() {
+            squash.0.restart();
+        }
+    }
+}
+
+fn tick_tweens(
+    time: Res<Time>,
+    mut squash_query: Query<&mut Squash>,
+    mut pop_query: Query<&mut Pop>,
+    mut tumble_query: Query<(&mut Tumble, &Spin)>,
+) {
+    let delta = time.delta_seconds();
+    for (mut tumble, spin) in tumble_query.iter_mut() {
+        tumble.0 += spin.0 * delta;
+        if spin.0.abs() < SETTLE_SPIN {
+            // A half turn looks the same as none at all.
+            let upright = (tumble.0 / PI).round() * PI;
+            tumble.0 += (upright - tumble.0) * (SETTLE_RATE * delta).min(1.0);
+        }
+    }
+    for mut squash in squash_query.iter_mut() {
+        squash.0.tick(delta);
+    }
+    for mut pop in pop_query.iter_mut() {
+        pop.0.tick(delta);
+    }
+}
+
+/// Points the ball along its path, stretched by its speed and flattened for
+/// a moment after each impact, and turns it over as it spins.
+fn shape_ball(
+    accessibility: Res<AccessibilitySettings>,
+    mut query: Query<(&Squash, &Tumble, &Velocity, &mut Transform, &mut Sprite)>,
+) {
+    for (squash, tumble, velocity, mut transform, mut sprite) in query.iter_mut() {
+        if accessibility.reduce_motion {
+            transform.rotation = Quat::IDENTITY;
+            sprite.custom_size = Some(Vec2::ONE);
+            continue;
+        }
+        if velocity.0 != Vec2::ZERO {
+            let heading = velocity.y.atan2(velocity.x);
+            transform.rotation = Quat::from_rotation_z(heading + tumble.0);
+        }
+
+        let stretch = (velocity.length() / STRETCH_SPEED).min(1.0) * MAX_STRETCH;
+        let squash = squash.0.value() * SQUASH_AMOUNT;
+        sprite.custom_size = Some(Vec2::new(
+            (1.0 + stretch) * (1.0 - squash),
+            (1.0 - stretch / 2.0) * (1.0 + squash),
+        ));
+    }
+}
+
+fn shape_paddles(mut query: Query<(&Pop, &mut Sprite)>) {
+    for (pop, mut sprite) in query.iter_mut() {
+        sprite.custom_size = Some(Vec2::ONE + POP_AMOUNT * pop.0.value());
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, Read, Write},
+    sync::{Arc, Mutex},
+};
+
+use bevy::prelude::*;
+use matchbox_socket::{MessageLoopFuture, PeerId, PeerState, WebRtcSocket};
+
+use crate::transport::Transport;
+
+/// Biggest message sent down a data channel at once, which every browser
+/// takes.
+const MAX_MESSAGE: usize = 16 * 1024;
+/// Most unread data kept from a peer before giving up on them.
+const MAX_INBOX: usize = 1024 * 1024;
+
+/// Lets games in the browser and on the desktop play each other, over WebRTC
+/// data channels that find each other through the signalling server at
+/// `url`, such as a `matchbox_server` at `ws://example.com:3536`.
+///
+/// Games hosted from here open a room there named by a room code, which
+/// guests join by typing it in, in place of the lobby server's. Once they
+/// are in, the match goes over the data channel just as it would over TCP
+/// on the LAN, greeting, reconnecting and all.
+pub struct WebRtcPlugin {
+    pub url: String,
+}
+
+impl Plugin for WebRtcPlugin {
+    fn build(&self, app: &mut App) {
+        let url = self.url.trim_end_matches('/').to_string();
+        app.insert_resource(Signalling(url));
+    }
+}
+
+/// The signalling server rooms are opened on.
+pub(crate) struct Signalling(pub(crate) String);
+
+/// A room on the signalling server, where the host of a match and its guest
+/// find each other by room code. Everyone who turns up gets a data channel
+/// to everyone else there, which is a [`Transport`] like any TCP stream.
+///
+/// The room stays open for as long as it or a line to anyone in it is kept.
+#[derive(Clone)]
+pub struct WebRtcRoom {
+    peers: Arc<Mutex<Peers>>,
+    code: String,
+}
+
+/// Who is in a room, and what they have sent that hasn't been read yet.
+struct Peers {
+    socket: WebRtcSocket,
+    inboxes: HashMap<PeerId, Inbox>,
+    /// Peers that turned up since the room was last asked.
+    arrived: Vec<PeerId>,
+}
+
+#[derive(Default)]
+struct Inbox {
+    bytes: VecDeque<u8>,
+    /// Handles on the line to the peer, which keep the inbox.
+    lines: usize,
+    gone: bool,
+}
+
+impl WebRtcRoom {
+    /// Opens room `code` on the signalling server at `url`. Games only meet
+    /// others of the same version.
+    pub fn open(url: &str, code: &str) -> WebRtcRoom {
+        let code = code.to_ascii_uppercase();
+        let room = format!("{}/fjong-{}-{}", url, env!("CARGO_PKG_VERSION"), code);
+        let (socket, message_loop) = WebRtcSocket::new_reliable(room);
+        drive(message_loop);
+        let peers = Peers {
+            socket,
+            inboxes: HashMap::new(),
+            arrived: Vec::new(),
+        };
+        WebRtcRoom {
+            peers: Arc::new(Mutex::new(peers)),
+            code,
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Lines to everyone who turned up in the room since it was last asked,
+    /// and who they are.
+    pub fn arrivals(&self) -> io::Result<Vec<(Box<dyn Transport>, String)>> {
+        let mut peers = self.peers.lock().unwrap();
+        peers.update()?;
+        let arrived = std::mem::take(&mut peers.arrived);
+        let lines = arrived
+            .into_iter()
+            .filter_map(|peer| {
+                peers.inboxes.get_mut(&peer)?.lines += 1;
+                let line = WebRtcLine {
+                    peers: self.peers.clone(),
+                    peer,
+                };
+                Some((Box::new(line) as Box<dyn Transport>, peer.to_string()))
+            })
+            .collect();
+        Ok(lines)
+    }
+}
+
+impl Peers {
+    /// Catches up on who came and went, and on what they sent.
+    fn update(&mut self) -> io::Result<()> {
+        let changes = self.socket.try_update_peers().map_err(|_| {
+            io::Error::new(io::ErrorKind::NotConnected, "lost the signalling server")
+        })?;
+        for (peer, state) in changes {
+            match state {
+                PeerState::Connected => {
+                    self.inboxes.insert(peer, Inbox::default());
+                    self.arrived.push(peer);
+                }
+                PeerState::Disconnected => {
+                    if let Some(inbox) = self.inboxes.get_mut(&peer) {
+                        inbox.gone = true;
+                    }
+                }
+            }
+        }
+
+        for (peer, packet) in self.socket.receive() {
+            let inbox = match self.inboxes.get_mut(&peer) {
+                Some(inbox) if !inbox.gone => inbox,
+                _ => continue,
+            };
+            inbox.bytes.extend(packet.iter());
+            if inbox.bytes.len() > MAX_INBOX {
+                inbox.bytes.clear();
+                inbox.gone = true;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Keeps the room's connections going until the room is dropped.
+#[cfg(not(target_arch = "wasm32"))]
+fn drive(message_loop: MessageLoopFuture) {
+    std::thread::spawn(move || {
+        if let Err(err) = futures_lite::future::block_on(message_loop) {
+            warn!("Left the WebRTC room: {}", err);
+        }
+    });
+}
+
+/// Keeps the room's connections going until the room is dropped.
+#[cfg(target_arch = "wasm32")]
+fn drive(message_loop: MessageLoopFuture) {
+    wasm_bindgen_futures::spawn_local(async move {
+        if let Err(err) = message_loop.await {
+            warn!("Left the WebRTC room: {}", err);
+        }
+    });
+}
+
+/// A data channel to someone in a [`WebRtcRoom`].
+struct WebRtcLine {
+    peers: Arc<Mutex<Peers>>,
+    peer: PeerId,
+}
+
+impl Read for WebRtcLine {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let mut peers = self.peers.lock().unwrap();
+        peers.update()?;
+        // Every line has an inbox for as long as it lives.
+        let inbox = peers.inboxes.get_mut(&self.peer).unwrap();
+        match inbox.bytes.read(buffer)? {
+            0 if inbox.gone => Ok(0),
+            0 => Err(io::ErrorKind::WouldBlock.into()),
+            read => Ok(read),
+        }
+    }
+}
+
+impl Write for WebRtcLine {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let mut peers = self.peers.lock().unwrap();
+        peers.update()?;
+        if peers.inboxes[&self.peer].gone {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        let sent = bytes.len().min(MAX_MESSAGE);
+        peers
+            .socket
+            .try_send(bytes[..sent].into(), self.peer)
+            .map_err(|err| io::Error::new(io::ErrorKind::BrokenPipe, err))?;
+        Ok(sent)
+    }
+
+    /// Everything written is on its way already.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for WebRtcLine {
+    fn set_up(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn try_clone_line(&self) -> io::Result<Box<dyn Transport>> {
+        let mut peers = self.peers.lock().unwrap();
+        peers.inboxes.get_mut(&self.peer).unwrap().lines += 1;
+        Ok(Box::new(WebRtcLine {
+            peers: self.peers.clone(),
+            peer: self.peer,
+        }))
+    }
+}
+
+impl Drop for WebRtcLine {
+    fn drop(&mut self) {
+        let mut peers = self.peers.lock().unwrap();
+        let inbox = peers.inboxes.get_mut(&self.peer).unwrap();
+        inbox.lines -= 1;
+        if inbox.lines == 0 {
+            peers.inboxes.remove(&self.peer);
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use bevy::prelude::*;
+
+use crate::{Locale, FOREGROUND_COLOR};
+
+pub const UNSELECTED_COLOR: Color = Color::GRAY;
+
+/// Focus handling for menus, driven by the arrow keys, WASD or a gamepad's
+/// D-pad or left stick.
+///
+/// Spawn a [`Menu`] with [`MenuButton`] children and listen for
+/// [`MenuEvent`]s to find out what the player picked. Screens that take
+/// text type it with the [`OnScreenKeyboard`] too, for players with only a
+/// gamepad.
+pub struct WidgetPlugin;
+
+impl Plugin for WidgetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MenuEvent>()
+            .init_resource::<OnScreenKeyboard>()
+            .add_system(navigate_menus)
+            .add_system(highlight_buttons.after(navigate_menus));
+    }
+}
+
+/// A column of buttons that focus moves up and down through.
+#[derive(Component, Default)]
+pub struct Menu {
+    /// Index of the focused [`MenuButton`].
+    pub focus: usize,
+}
+
+/// One focusable entry in a [`Menu`], numbered from the top. Must be a child
+/// of the menu, and is highlighted while it has focus.
+#[derive(Component)]
+pub struct MenuButton(pub usize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuAction {
+    /// Pressed the focused button.
+    Confirm,
+    /// Backed out of the menu.
+    Cancel,
+    /// Nudged the focused button's value, for buttons that hold one.
+    Decrease,
+    Increase,
+}
+
+/// A player acted on `menu` while `button` had focus.
+pub struct MenuEvent {
+    pub menu: Entity,
+    pub button: usize,
+    pub action: MenuAction,
+}
+
+const UP_KEYS: [KeyCode; 2] = [KeyCode::Up, KeyCode::W];
+const DOWN_KEYS: [KeyCode; 2] = [KeyCode::Down, KeyCode::S];
+const LEFT_KEYS: [KeyCode; 2] = [KeyCode::Left, KeyCode::A];
+const RIGHT_KEYS: [KeyCode; 2] = [KeyCode::Right, KeyCode::D];
+const CONFIRM_KEYS: [KeyCode; 2] = [KeyCode::Return, KeyCode::Space];
+const CANCEL_KEYS: [KeyCode; 2] = [KeyCode::Escape, KeyCode::Back];
+
+/// How far over a stick has to be pushed to move through a menu.
+const STICK_THRESHOLD: f32 = 0.6;
+
+/// Keys on each row of the [`OnScreenKeyboard`].
+const KEYBOARD_ROW: usize = 10;
+
+/// Which way the player pushed this frame, from any keyboard or gamepad.
+#[derive(Default)]
+struct Nudges {
+    up: bool,
+    down: bool,
+    action: Option<MenuAction>,
+}
+
+fn read_nudges(
+    keyboard_input: &mut Input<KeyCode>,
+    gamepads: &Gamepads,
+    buttons: &mut Input<GamepadButton>,
+) -> Nudges {
+    let mut pressed = |keys: [KeyCode; 2], button_type| {
+        // Consumed, so whatever a menu action leads to doesn't see the press
+        // as well.
+        let mut pressed = false;
+        for key in keys {
+            pressed |= keyboard_input.clear_just_pressed(key);
+        }
+        for gamepad in gamepads.iter() {
+            pressed |= buttons.clear_just_pressed(GamepadButton(*gamepad, button_type));
+        }
+        pressed
+    };
+
+    let up = pressed(UP_KEYS, GamepadButtonType::DPadUp);
+    let down = pressed(DOWN_KEYS, GamepadButtonType::DPadDown);
+    let action = if pressed(CONFIRM_KEYS, GamepadButtonType::South) {
+        Some(MenuAction::Confirm)
+    } else if pressed(CANCEL_KEYS, GamepadButtonType::East) {
+        Some(MenuAction::Cancel)
+    } else if pressed(LEFT_KEYS, GamepadButtonType::DPadLeft) {
+        Some(MenuAction::Decrease)
+    } else if pressed(RIGHT_KEYS, GamepadButtonType::DPadRight) {
+        Some(MenuAction::Increase)
+    } else {
+        None
+    };
+
+    Nudges { up, down, action }
+}
+
+/// A way a stick can be pushed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Push {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Which way any gamepad's left stick is pushed, if far enough over.
+fn read_stick(gamepads: &Gamepads, axes: &Axis<GamepadAxis>) -> Option<Push> {
+    gamepads.iter().find_map(|gamepad| {
+        let axis = |axis_type| {
+            axes.get(GamepadAxis(*gamepad, axis_type))
+                .unwrap_or_default()
+        };
+        let (x, y) = (
+            axis(GamepadAxisType::LeftStickX),
+            axis(GamepadAxisType::LeftStickY),
+        );
+        if y.abs() >= x.abs() && y.abs() > STICK_THRESHOLD {
+            Some(if y > 0.0 { Push::Up } else { Push::Down })
+        } else if x.abs() > STICK_THRESHOLD {
+            Some(if x > 0.0 { Push::Right } else { Push::Left })
+        } else {
+            None
+        }
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn navigate_menus(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    mut buttons: ResMut<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut menu_query: Query<(Entity, &mut Menu, &Children)>,
+    button_query: Query<(), With<MenuButton>>,
+    mut menu_events: EventWriter<MenuEvent>,
+    mut stick_held: Local<Option<Push>>,
+) {
+    // A push of the stick moves once, however long it is held over.
+    let stick = read_stick(&gamepads, &axes);
+    let pushed = stick.filter(|_| *stick_held != stick);
+    *stick_held = stick;
+    if menu_query.is_empty() {
+        return;
+    }
+
+    let mut nudges = read_nudges(&mut keyboard_input, &gamepads, &mut buttons);
+    match pushed {
+        Some(Push::Up) => nudges.up = true,
+        Some(Push::Down) => nudges.down = true,
+        Some(Push::Left) => nudges.action = nudges.action.or(Some(MenuAction::Decrease)),
+        Some(Push::Right) => nudges.action = nudges.action.or(Some(MenuAction::Increase)),
+        None => {}
+    }
+    for (entity, mut menu, children) in menu_query.iter_mut() {
+        let len = children
+            .iter()
+            .filter(|child| button_query.get(**child).is_ok())
+            .count();
+        if len == 0 {
+            continue;
+        }
+
+        if nudges.up {
+            menu.focus = (menu.focus + len - 1) % len;
+        }
+        if nudges.down {
+            menu.focus = (menu.focus + 1) % len;
+        }
+        if let Some(action) = nudges.action {
+            menu_events.send(MenuEvent {
+                menu: entity,
+                button: menu.focus,
+                action,
+            });
+        }
+    }
+}
+
+fn highlight_buttons(
+    menu_query: Query<&Menu>,
+    mut button_query: Query<(&MenuButton, &Parent, &mut Text)>,
+) {
+    for (button, parent, mut text) in button_query.iter_mut() {
+        let focused = match menu_query.get(parent.0) {
+            Ok(menu) => menu.focus == button.0,
+            Err(_) => continue,
+        };
+        text.sections[0].style.color = if focused {
+            FOREGROUND_COLOR
+        } else {
+            UNSELECTED_COLOR
+        };
+    }
+}
+
+/// A key on the [`OnScreenKeyboard`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OnScreenKey {
+    Char(char),
+    Space,
+    Delete,
+    /// Finishes typing.
+    Done,
+}
+
+/// Keys laid out in rows for typing with a gamepad. Left and right on the
+/// button being typed into move along the keys, from one row into the next,
+/// and confirming presses the focused one.
+///
+/// Focus starts on [`OnScreenKey::Done`], so confirming straight after
+/// typing on a real keyboard finishes as it always did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OnScreenKeyboard {
+    keys: Vec<OnScreenKey>,
+    focus: usize,
+}
+
+impl Default for OnScreenKeyboard {
+    fn default() -> Self {
+        OnScreenKeyboard::letters()
+    }
+}
+
+impl OnScreenKeyboard {
+    /// A to Z.
+    pub fn letters() -> OnScreenKeyboard {
+        OnScreenKeyboard::with_keys(('A'..='Z').map(OnScreenKey::Char).collect())
+    }
+
+    /// A to Z, 0 to 9 and a space bar.
+    pub fn alphanumeric() -> OnScreenKeyboard {
+        let mut keys: Vec<OnScreenKey> = ('A'..='Z')
+            .chain('0'..='9')
+            .map(OnScreenKey::Char)
+            .collect();
+        keys.push(OnScreenKey::Space);
+        OnScreenKeyboard::with_keys(keys)
+    }
+
+    fn with_keys(mut keys: Vec<OnScreenKey>) -> OnScreenKeyboard {
+        keys.extend([OnScreenKey::Delete, OnScreenKey::Done]);
+        OnScreenKeyboard {
+            focus: keys.len() - 1,
+            keys,
+        }
+    }
+
+    pub fn focused(&self) -> OnScreenKey {
+        self.keys[self.focus]
+    }
+
+    /// Moves focus `step` keys along, round from the last key to the first.
+    pub fn nudge(&mut self, step: isize) {
+        let len = self.keys.len() as isize;
+        self.focus = (self.focus as isize + step).rem_euclid(len) as usize;
+    }
+
+    /// Types the focused key into `text`, so long as it is shorter than
+    /// `max_chars`. Returns whether the key was [`OnScreenKey::Done`], for
+    /// the caller to finish.
+    pub fn press(&self, text: &mut String, max_chars: usize) -> bool {
+        let typed = match self.focused() {
+            OnScreenKey::Char(char) => char,
+            OnScreenKey::Space => ' ',
+            OnScreenKey::Delete => {
+                text.pop();
+                return false;
+            }
+            OnScreenKey::Done => return true,
+        };
+        if text.chars().count() < max_chars {
+            text.push(typed);
+        }
+        false
+    }
+
+    /// The keys, a row to a line, with the focused one in brackets.
+    pub fn render(&self, locale: &Locale) -> String {
+        let label = |key: OnScreenKey| match key {
+            OnScreenKey::Char(char) => char.to_string(),
+            OnScreenKey::Space => locale.text("keyboard-space"),
+            OnScreenKey::Delete => locale.text("keyboard-delete"),
+            OnScreenKey::Done => locale.text("keyboard-done"),
+        };
+        self.keys
+            .chunks(KEYBOARD_ROW)
+            .enumerate()
+            .map(|(row, keys)| {
+                keys.iter()
+                    .enumerate()
+                    .map(|(column, key)| {
+                        if row * KEYBOARD_ROW + column == self.focus {
+                            format!("[{}]", label(*key))
+                        } else {
+                            label(*key)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+use fjong::collision::{self, Contact, SpatialHash, MAX_BOUNCE_ANGLE};
+use proptest::prelude::*;
+
+/// Room for rounding in the trigonometry.
+const EPSILON: f32 = 1e-3;
+
+fn towards() -> impl Strategy<Value = f32> {
+    prop_oneof![Just(-1.0f32), Just(1.0f32)]
+}
+
+fn axis() -> impl Strategy<Value = f32> {
+    prop_oneof![Just(-1.0f32), Just(0.0f32), Just(1.0f32)]
+}
+
+/// Anywhere on the court, or a little past it.
+fn point() -> impl Strategy<Value = Vec2> {
+    (-500.0f32..500.0, -300.0f32..300.0).prop_map(|(x, y)| Vec2::new(x, y))
+}
+
+proptest! {
+    #[test]
+    fn paddle_returns_keep_their_speed(
+        speed in 100.0f32..5000.0,
+        offset in -2.0f32..2.0,
+        towards in towards(),
+    ) {
+        let velocity = collision::paddle_bounce(speed, offset, towards);
+        prop_assert!((velocity.length() - speed).abs() <= speed * EPSILON);
+    }
+
+    #[test]
+    fn paddle_returns_always_head_for_the_other_end(
+        speed in 100.0f32..5000.0,
+        offset in -2.0f32..2.0,
+        towards in towards(),
+    ) {
+        let velocity = collision::paddle_bounce(speed, offset, towards);
+        prop_assert_eq!(velocity.x.signum(), towards);
+    }
+
+    #[test]
+    fn paddle_returns_never_leave_the_cone(
+        speed in 100.0f32..5000.0,
+        offset in -2.0f32..2.0,
+        towards in towards(),
+    ) {
+        let velocity = collision::paddle_bounce(speed, offset, towards);
+        let angle = (velocity.y / velocity.x.abs()).atan();
+        prop_assert!(angle.abs() <= MAX_BOUNCE_ANGLE + EPSILON);
+    }
+
+    #[test]
+    fn both_paddles_return_the_ball_mirrored(
+        speed in 100.0f32..5000.0,
+        offset in -1.0f32..1.0,
+    ) {
+        let p1 = collision::paddle_bounce(speed, offset, 1.0);
+        let p2 = collision::paddle_bounce(speed, offset, -1.0);
+        prop_assert_eq!(p1, Vec2::new(-p2.x, p2.y));
+    }
+
+    #[test]
+    fn walls_send_the_ball_away_at_the_same_speed(
+        x in -2000.0f32..2000.0,
+        y in -2000.0f32..2000.0,
+        normal_x in axis(),
+        normal_y in axis(),
+    ) {
+        let contact = Contact {
+            normal: Vec2::new(normal_x, normal_y),
+            push_out: Vec2::ZERO,
+        };
+        let velocity = Vec2::new(x, y);
+        let reflected = contact.reflect(velocity);
+        prop_assert_eq!(reflected.abs(), velocity.abs());
+        prop_assert!(reflected.x * normal_x >= 0.0);
+        prop_assert!(reflected.y * normal_y >= 0.0);
+    }
+
+    #[test]
+    fn the_broad_phase_finds_every_box_the_ball_touches(
+        boxes in prop::collection::vec((point(), 1.0f32..900.0, 1.0f32..200.0), 1..50),
+        ball in point(),
+    ) {
+        let ball_size = Vec2::splat(30.0);
+        let mut hash = SpatialHash::default();
+        for (i, (centre, width, height)) in boxes.iter().enumerate() {
+            hash.insert(Entity::from_raw(i as u32), *centre, Vec2::new(*width, *height));
+        }
+
+        let near = hash.near(ball, ball_size);
+        for (i, (centre, width, height)) in boxes.into_iter().enumerate() {
+            let size = Vec2::new(width, height);
+            if collision::contact(ball, ball_size, centre, size).is_some() {
+                prop_assert!(near.contains(&Entity::from_raw(i as u32)));
+            }
+        }
+    }
+}
@@ -0,0 +1,2826 @@
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    thread,
+    time::Duration,
+};
+
+use bevy::{
+    asset::AssetPlugin,
+    ecs::event::Events,
+    input::{
+        gamepad::{GamepadEvent, GamepadEventType},
+        keyboard::KeyboardInput,
+        ElementState, InputPlugin,
+    },
+    prelude::*,
+    window::{ReceivedCharacter, WindowId},
+};
+use fjong::{
+    ai::{
+        AiLevel, BallView, Brain, Difficulty, PaddleBrain, PaddleView, PendingAi, Predictor, Skill,
+    },
+    lobby::{self, Rooms, CODE_LENGTH},
+    score_call,
+    selfplay::{self, Record},
+    settings::{BallHeat, HudLayout, StickMode},
+    tear_down_match, Achievement, Achievements, AchievementsPlugin, AppState, Arena, ArenaLayout,
+    Attract, Ball, Brick, Catch, Clip, ClipFrame, ConsoleCommandsExt, ConsolePlugin,
+    ControlsPlugin, CourtSize, DailyBests, DailyChallenge, DailyPlugin, DailyRun, Dash, Echo,
+    Emote, Endpoint, FjongEnv, FontPlugin, Frenzy, GameConfig, GameEntity, GameMode, GameRng,
+    GhostRecording, GravityWell, Grip, Handicap, HudPlugin, IndicatorsPlugin, InputState,
+    InstantReplayPlugin, InterpolationPlugin, KeyBindings, Kiosk, KioskPlugin, LastMoments, Layer,
+    LeaderboardEntry, Locale, MatchClock, MatchOverEvent, MatchRules, Observation, Obstacle,
+    OnScreenKey, OnScreenKeyboard, P1Paddle, P2Goal, P2Paddle, PaddleInput, PaddleKeys, Player,
+    Policy, PolicyPlugin, Possession, PracticeScore, Profile, ProfileStats, Profiles,
+    ProfilesPlugin, RemoteBrain, Replay, ReplayPlugin, ReplayRenderer, ResultsPlugin, Scoreboard,
+    Series, Serve, ServerPlugin, Settings, SimTick, SimulationPlugin, Skins, SkinsPlugin, Snapshot,
+    SpectatePlugin, SpectatorCount, SpectatorServerPlugin, Spin, ThemePacks, ThemePacksPlugin,
+    TickProgress, TickRate, UiFont, Velocity, Wall, WebRtcRoom, BALL_SIZE, BALL_SPEED, BOTTOM_WALL,
+    LEFT_WALL, PADDLE_SIZE, PADDLE_SPEED, RIGHT_WALL, TOP_COUNT, TOP_WALL,
+};
+use matchbox_signaling::SignalingServer;
+
+fn headless_app() -> App {
+    seeded_app(0)
+}
+
+fn seeded_app(seed: u64) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(SimulationPlugin::stepped(seed));
+    app
+}
+
+/// Runs ticks until the opening serve has been played.
+fn finish_serve(app: &mut App) {
+    app.update();
+    while app.world.resource::<Serve>().in_progress() {
+        app.update();
+    }
+}
+
+fn ball(app: &mut App) -> (Vec3, Vec2) {
+    let (transform, velocity) = app
+        .world
+        .query_filtered::<(&Transform, &Velocity), With<Ball>>()
+        .iter(&app.world)
+        .next()
+        .unwrap();
+    (transform.translation, velocity.0)
+}
+
+fn place_ball(app: &mut App, position: Vec2, velocity: Vec2) {
+    let (mut transform, mut ball_velocity) = app
+        .world
+        .query_filtered::<(&mut Transform, &mut Velocity), With<Ball>>()
+        .iter_mut(&mut app.world)
+        .next()
+        .unwrap();
+    transform.translation = position.extend(transform.translation.z);
+    ball_velocity.0 = velocity;
+}
+
+/// Parks a paddle at `y` and takes away any brain so it stays put.
+fn park_paddle<T: Component>(app: &mut App, y: f32) {
+    let (entity, mut transform) = app
+        .world
+        .query_filtered::<(Entity, &mut Transform), With<T>>()
+        .iter_mut(&mut app.world)
+        .next()
+        .unwrap();
+    transform.translation.y = y;
+    app.world.entity_mut(entity).remove::<Brain>();
+}
+
+fn paddle_y<T: Component>(app: &mut App) -> f32 {
+    app.world
+        .query_filtered::<&Transform, With<T>>()
+        .iter(&app.world)
+        .next()
+        .unwrap()
+        .translation
+        .y
+}
+
+#[test]
+fn ball_reflects_off_walls() {
+    let mut app = headless_app();
+    finish_serve(&mut app);
+
+    place_ball(
+        &mut app,
+        Vec2::new(0.0, TOP_WALL - 60.0),
+        Vec2::new(0.0, 400.0),
+    );
+    for _ in 0..30 {
+        app.update();
+        assert!(ball(&mut app).0.y < TOP_WALL);
+    }
+    assert!(ball(&mut app).1.y < 0.0);
+
+    place_ball(
+        &mut app,
+        Vec2::new(0.0, BOTTOM_WALL + 60.0),
+        Vec2::new(0.0, -400.0),
+    );
+    for _ in 0..30 {
+        app.update();
+        assert!(ball(&mut app).0.y > BOTTOM_WALL);
+    }
+    assert!(ball(&mut app).1.y > 0.0);
+}
+
+#[test]
+fn right_goal_scores_for_p1() {
+    let mut app = headless_app();
+    finish_serve(&mut app);
+    park_paddle::<P2Paddle>(&mut app, BOTTOM_WALL + 60.0);
+
+    place_ball(
+        &mut app,
+        Vec2::new(RIGHT_WALL - 100.0, 100.0),
+        Vec2::new(600.0, 0.0),
+    );
+    for _ in 0..60 {
+        app.update();
+    }
+
+    let scoreboard = app.world.resource::<Scoreboard>();
+    assert_eq!(scoreboard.p1_score, 1);
+    assert_eq!(scoreboard.p2_score, 0);
+}
+
+#[test]
+fn goals_worth_several_points_end_the_match_once() {
+    let mut app = headless_app();
+    app.add_event::<MatchOverEvent>()
+        .insert_resource(MatchRules {
+            points_to_win: 3,
+            points_per_goal: 2,
+            ..default()
+        });
+    finish_serve(&mut app);
+    park_paddle::<P2Paddle>(&mut app, BOTTOM_WALL + 60.0);
+
+    let mut match_over = 0;
+    for _ in 0..2 {
+        place_ball(
+            &mut app,
+            Vec2::new(RIGHT_WALL - 100.0, 100.0),
+            Vec2::new(600.0, 0.0),
+        );
+        for _ in 0..60 {
+            app.update();
+            match_over += app
+                .world
+                .resource::<Events<MatchOverEvent>>()
+                .iter_current_update_events()
+                .count();
+        }
+        finish_serve(&mut app);
+    }
+
+    assert_eq!(app.world.resource::<Scoreboard>().p1_score, 4);
+    assert_eq!(match_over, 1);
+}
+
+/// Sends the ball into P2's goal and plays out the serve after it, returning
+/// who any match over events named as the winner.
+fn score_for_p1(app: &mut App) -> Vec<Player> {
+    place_ball(
+        app,
+        Vec2::new(RIGHT_WALL - 100.0, 100.0),
+        Vec2::new(600.0, 0.0),
+    );
+    let mut winners = Vec::new();
+    for _ in 0..60 {
+        app.update();
+        winners.extend(
+            app.world
+                .resource::<Events<MatchOverEvent>>()
+                .iter_current_update_events()
+                .map(|event| event.winner),
+        );
+    }
+    winners
+}
+
+#[test]
+fn finished_matches_are_written_out_as_json() {
+    let dir = std::env::temp_dir().join(format!("fjong-results-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut app = seeded_app(7);
+    app.add_state(AppState::Playing)
+        .add_plugin(ResultsPlugin { dir: dir.clone() })
+        .insert_resource(MatchRules {
+            points_to_win: 2,
+            ..default()
+        });
+    finish_serve(&mut app);
+    park_paddle::<P2Paddle>(&mut app, BOTTOM_WALL + 60.0);
+
+    assert!(score_for_p1(&mut app).is_empty());
+    assert!(!dir.exists());
+    finish_serve(&mut app);
+    assert_eq!(score_for_p1(&mut app), vec![Player::P1]);
+
+    let files: Vec<_> = std::fs::read_dir(&dir).unwrap().flatten().collect();
+    assert_eq!(files.len(), 1);
+    let result: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(files[0].path()).unwrap()).unwrap();
+    assert_eq!(result["winner"], "P1");
+    assert_eq!(result["p1_score"], 2);
+    assert_eq!(result["p2_score"], 0);
+    assert_eq!(result["seed"], 7);
+    assert_eq!(result["rules"]["points_to_win"], 2);
+    let points = result["points"].as_array().unwrap();
+    assert_eq!(points.len(), 2);
+    assert!(points.iter().all(|point| point["scorer"] == "P1"));
+    assert!(points[0]["tick"].as_u64() < points[1]["tick"].as_u64());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn saves_left_in_the_working_directory_move_to_the_data_directories() {
+    let cwd = std::env::temp_dir().join(format!("fjong-legacy-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&cwd);
+    let (config_dir, data_dir) = (cwd.join("config"), cwd.join("data"));
+    std::fs::create_dir_all(cwd.join("results")).unwrap();
+    std::fs::create_dir_all(cwd.join("replays")).unwrap();
+    let settings = Settings {
+        seed: Some(42),
+        ..default()
+    };
+    settings.save(cwd.join("settings.ron")).unwrap();
+    // Somebody else's files, under fjong's names.
+    std::fs::write(cwd.join("stats.ron"), "(level: 3, lives: 2)").unwrap();
+    std::fs::write(cwd.join("results/notes.txt"), "not ours").unwrap();
+    std::fs::write(cwd.join("results/match-1.json"), r#"{"finished_at": 1}"#).unwrap();
+    Replay::default()
+        .save(cwd.join("replays/match-1.json"))
+        .unwrap();
+    // Already moved over by an earlier run, so not overwritten.
+    std::fs::create_dir_all(data_dir.join("replays")).unwrap();
+    std::fs::write(data_dir.join("replays/match-1.json"), "newer").unwrap();
+
+    fjong::migrate_saves(&cwd, &config_dir, &data_dir);
+
+    assert!(!cwd.join("settings.ron").exists());
+    assert_eq!(
+        Settings::load(config_dir.join("settings.ron")).seed,
+        Some(42)
+    );
+    assert!(cwd.join("stats.ron").exists());
+    assert!(!data_dir.join("stats.ron").exists());
+    assert!(data_dir.join("results/match-1.json").exists());
+    assert!(cwd.join("results/notes.txt").exists());
+    assert!(!data_dir.join("results/notes.txt").exists());
+    assert!(cwd.join("replays/match-1.json").exists());
+    assert_eq!(
+        std::fs::read_to_string(data_dir.join("replays/match-1.json")).unwrap(),
+        "newer"
+    );
+    assert!(fjong::data_path("stats.ron").ends_with("stats.ron"));
+    assert!(fjong::config_path("settings.ron").ends_with("settings.ron"));
+    let _ = std::fs::remove_dir_all(&cwd);
+}
+
+#[test]
+fn clips_keep_the_last_few_seconds_shrunk_into_a_gif() {
+    let frame = |shade: u8| {
+        let rgba = [shade, shade, shade, 255].repeat(1280 * 720);
+        ClipFrame::new(1280, 720, &rgba)
+    };
+    let mut clip = Clip::default();
+    assert!(clip.gif().is_none());
+    for i in 0..Clip::FRAMES * 2 {
+        clip.push(frame(i as u8));
+    }
+    assert_eq!(clip.len(), Clip::FRAMES);
+    let shrunk = frame(0);
+    assert_eq!((shrunk.width, shrunk.height), (640, 360));
+
+    let gif = clip.gif().unwrap().unwrap();
+    assert!(gif.starts_with(b"GIF89a"));
+    assert_eq!(gif[6..10], [128, 2, 104, 1]);
+    assert_eq!(gif.last(), Some(&0x3b));
+    let mut decoder = gif::DecodeOptions::new().read_info(&gif[..]).unwrap();
+    let mut frames = 0;
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        assert_eq!((frame.width, frame.height), (640, 360));
+        frames += 1;
+    }
+    assert_eq!(frames, Clip::FRAMES);
+
+    // Every frame of a GIF is the same size.
+    clip.push(ClipFrame::new(320, 240, &[0; 320 * 240 * 4]));
+    assert_eq!(clip.len(), 1);
+}
+
+#[test]
+fn replays_are_recorded_a_tick_at_a_time_and_render_the_same_every_time() {
+    let dir = std::env::temp_dir().join(format!("fjong-replays-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut app = seeded_app(3);
+    app.add_state(AppState::Playing)
+        .add_plugin(ReplayPlugin { dir: dir.clone() })
+        .insert_resource(MatchRules {
+            points_to_win: 1,
+            ..default()
+        });
+    finish_serve(&mut app);
+    park_paddle::<P2Paddle>(&mut app, BOTTOM_WALL + 60.0);
+    assert_eq!(score_for_p1(&mut app), vec![Player::P1]);
+
+    let files: Vec<_> = std::fs::read_dir(&dir).unwrap().flatten().collect();
+    assert_eq!(files.len(), 1);
+    let mut replay = Replay::load(files[0].path()).unwrap();
+    let ticks: Vec<_> = replay
+        .snapshots
+        .iter()
+        .map(|snapshot| snapshot.tick)
+        .collect();
+    assert!(ticks.windows(2).all(|pair| pair[1] == pair[0] + 1));
+    let last = replay.snapshots.last().unwrap();
+    assert_eq!((last.p1_score, last.p2_score), (1, 0));
+
+    // The ball is drawn where it was, at any size.
+    let mut snapshot = *last;
+    snapshot.ball = Vec2::ZERO;
+    for (width, height) in [(320, 180), (1920, 1080)] {
+        let renderer = ReplayRenderer::new(&replay, width, height);
+        let frame = renderer.frame(&snapshot);
+        assert_eq!(frame.len(), (width * height * 4) as usize);
+        let pixel = |x: u32, y: u32| &frame[((y * width + x) * 4) as usize..][..4];
+        assert_eq!(pixel(width / 2, height / 2), [255; 4]);
+        assert_eq!(pixel(width / 2, height * 3 / 4), [0, 0, 0, 255]);
+        assert_eq!(frame, renderer.frame(&snapshot));
+    }
+
+    let frames = dir.join("frames");
+    replay.snapshots.truncate(3);
+    assert_eq!(replay.export_frames(64, 36, &frames).unwrap(), 3);
+    let png = std::fs::read(frames.join("frame-000003.png")).unwrap();
+    assert!(png.starts_with(b"\x89PNG"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn the_last_three_seconds_are_kept_until_the_winning_goal() {
+    let mut app = seeded_app(5);
+    app.add_state(AppState::Playing)
+        .add_plugin(InstantReplayPlugin)
+        .insert_resource(MatchRules {
+            points_to_win: 1,
+            ..default()
+        });
+    finish_serve(&mut app);
+    park_paddle::<P2Paddle>(&mut app, BOTTOM_WALL + 60.0);
+    place_ball(&mut app, Vec2::ZERO, Vec2::ZERO);
+    for _ in 0..240 {
+        app.update();
+    }
+    assert_eq!(score_for_p1(&mut app), vec![Player::P1]);
+
+    let moments = app.world.resource::<LastMoments>();
+    assert!(moments.is_over());
+    assert_eq!(moments.snapshots().len(), 180);
+    let ticks: Vec<_> = moments
+        .snapshots()
+        .iter()
+        .map(|moment| moment.tick)
+        .collect();
+    assert!(ticks.windows(2).all(|pair| pair[1] == pair[0] + 1));
+    // Nothing after the goal, for all that the ticks went on.
+    let last = moments.snapshots().back().unwrap();
+    assert_eq!((last.p1_score, last.p2_score), (1, 0));
+    assert!(last.ball.x > RIGHT_WALL - 100.0);
+    assert!(last.tick < app.world.resource::<SimTick>().0);
+}
+
+#[test]
+fn the_ball_heats_up_from_white_to_red_as_the_rally_speeds_up() {
+    let config = GameConfig::default();
+    let heat = BallHeat::default();
+    let color_after = |fjongs| heat.color(config.rally_speed_multiplier(fjongs)).unwrap();
+    assert_eq!(color_after(0), Color::WHITE.as_rgba());
+    assert_eq!(color_after(100), *heat.gradient.last().unwrap());
+    // Warming up on the way, never cooling down.
+    let greens: Vec<_> = (0..=30).map(|fjongs| color_after(fjongs).g()).collect();
+    assert!(greens.windows(2).all(|pair| pair[1] <= pair[0]));
+    assert!(greens[10] < 1.0 && greens[10] > greens[30]);
+
+    let off = BallHeat {
+        gradient: Vec::new(),
+        ..default()
+    };
+    assert_eq!(off.color(2.0), None);
+}
+
+/// A bot that stays put.
+struct Idle;
+
+impl PaddleBrain for Idle {
+    fn decide(
+        &mut self,
+        _: &BallView,
+        _: &PaddleView,
+        _: &Arena,
+        _: TickRate,
+        _: &mut GameRng,
+    ) -> f32 {
+        0.0
+    }
+}
+
+#[test]
+fn beating_the_insane_ai_to_nil_unlocks_achievements_for_good() {
+    let path = std::env::temp_dir().join(format!("fjong-achievements-{}.ron", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut app = headless_app();
+    app.add_plugin(AchievementsPlugin { path: path.clone() })
+        .insert_resource(MatchRules {
+            points_to_win: 11,
+            difficulty: Difficulty::Insane,
+            ..default()
+        });
+    finish_serve(&mut app);
+    park_paddle::<P2Paddle>(&mut app, BOTTOM_WALL + 60.0);
+    // Still the AI's paddle, just one that never gets to the ball.
+    let p2 = app
+        .world
+        .query_filtered::<Entity, With<P2Paddle>>()
+        .iter(&app.world)
+        .next()
+        .unwrap();
+    app.world.entity_mut(p2).insert(Brain::new(Idle));
+    app.world.resource_mut::<Scoreboard>().p1_score = 10;
+
+    assert_eq!(score_for_p1(&mut app), vec![Player::P1]);
+    let unlocked = vec![
+        Achievement::FirstWin,
+        Achievement::Shutout,
+        Achievement::BeatInsane,
+    ];
+    let achievements = app.world.resource::<Achievements>();
+    assert_eq!(
+        achievements.unlocked.iter().copied().collect::<Vec<_>>(),
+        unlocked
+    );
+    let saved = Achievements::load(&path);
+    assert_eq!(saved.unlocked.into_iter().collect::<Vec<_>>(), unlocked);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn profiles_bring_their_keys_and_add_up_their_own_stats() {
+    let path = std::env::temp_dir().join(format!("fjong-profiles-{}.ron", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let arrows = PaddleKeys {
+        up: KeyCode::Up,
+        down: KeyCode::Down,
+        ..KeyBindings::default().p1
+    };
+    let mut app = headless_app();
+    app.add_state(AppState::Playing)
+        .add_plugin(ProfilesPlugin { path: path.clone() })
+        .insert_resource(MatchRules {
+            points_to_win: 1,
+            ..default()
+        })
+        .insert_resource(Profiles {
+            profiles: vec![
+                Profile {
+                    keys: Some(arrows.clone()),
+                    ..Profile::new("ann")
+                },
+                Profile::new("bo"),
+                Profile::new("cy"),
+            ],
+            p1: Some("ann".to_string()),
+            p2: Some("bo".to_string()),
+        });
+    finish_serve(&mut app);
+    assert_eq!(app.world.resource::<KeyBindings>().p1, arrows);
+    park_paddle::<P2Paddle>(&mut app, BOTTOM_WALL + 60.0);
+
+    assert_eq!(score_for_p1(&mut app), vec![Player::P1]);
+    let stats = |profiles: &Profiles, name: &str| profiles.get(name).unwrap().stats.clone();
+    let profiles = app.world.resource::<Profiles>();
+    let ann = stats(profiles, "ann");
+    assert_eq!((ann.matches_played, ann.wins), (1, 1));
+    let bo = stats(profiles, "bo");
+    assert_eq!((bo.matches_played, bo.wins), (1, 0));
+    assert_eq!(stats(profiles, "cy"), ProfileStats::default());
+
+    let saved = Profiles::load(&path);
+    assert_eq!(stats(&saved, "ann"), ann);
+    assert_eq!(saved.get("ann").unwrap().keys, Some(arrows));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn the_daily_challenge_is_the_same_all_day_and_keeps_the_best_score() {
+    let day = 20742;
+    let challenge = DailyChallenge::for_day(day);
+    assert_eq!(challenge.date_string(), "2026-10-16");
+    assert_eq!(DailyChallenge::for_day(11016).date_string(), "2000-02-29");
+    let again = DailyChallenge::for_day(day);
+    assert_eq!(again.seed, challenge.seed);
+    assert_eq!(again.rules.difficulty, challenge.rules.difficulty);
+    assert_eq!(again.rules.fog_of_war, challenge.rules.fog_of_war);
+    assert_ne!(DailyChallenge::for_day(day + 1).seed, challenge.seed);
+
+    let path = std::env::temp_dir().join(format!("fjong-daily-{}.ron", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut app = headless_app();
+    app.add_state(AppState::Playing)
+        .add_plugin(DailyPlugin { path: path.clone() })
+        .insert_resource(DailyRun {
+            challenge,
+            previous_seed: 0,
+            previous_rules: MatchRules::default(),
+        })
+        // Short and without modifiers, to score on.
+        .insert_resource(MatchRules {
+            points_to_win: 2,
+            ..default()
+        });
+    finish_serve(&mut app);
+    park_paddle::<P2Paddle>(&mut app, BOTTOM_WALL + 60.0);
+
+    assert!(score_for_p1(&mut app).is_empty());
+    assert_eq!(score_for_p1(&mut app), vec![Player::P1]);
+    assert_eq!(app.world.resource::<DailyBests>().best.get(&day), Some(&2));
+    assert_eq!(DailyBests::load(&path).best.get(&day), Some(&2));
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn time_attack_ends_on_the_clock_with_sudden_death_on_a_tie() {
+    let mut app = headless_app();
+    app.add_event::<MatchOverEvent>()
+        .insert_resource(MatchRules {
+            mode: GameMode::TimeAttack,
+            points_to_win: 1,
+            ..default()
+        });
+    finish_serve(&mut app);
+    park_paddle::<P2Paddle>(&mut app, BOTTOM_WALL + 60.0);
+
+    // Reaching points to win doesn't end a time-attack match.
+    assert!(score_for_p1(&mut app).is_empty());
+    app.world.resource_mut::<Scoreboard>().p2_score = 1;
+    app.world.resource_mut::<MatchClock>().ticks_left = 1;
+    app.update();
+    assert!(app.world.resource::<MatchClock>().overtime);
+
+    finish_serve(&mut app);
+    assert_eq!(score_for_p1(&mut app), vec![Player::P1]);
+}
+
+#[test]
+fn win_by_two_plays_on_from_deuce_until_someone_is_two_clear() {
+    let mut app = headless_app();
+    let rules = MatchRules {
+        points_to_win: 3,
+        win_by_two: true,
+        ..default()
+    };
+    app.add_event::<MatchOverEvent>()
+        .insert_resource(rules.clone());
+    finish_serve(&mut app);
+    park_paddle::<P2Paddle>(&mut app, BOTTOM_WALL + 60.0);
+    {
+        let mut scoreboard = app.world.resource_mut::<Scoreboard>();
+        scoreboard.p1_score = 2;
+        scoreboard.p2_score = 2;
+    }
+    assert!(rules.deuce(app.world.resource::<Scoreboard>()));
+
+    assert!(score_for_p1(&mut app).is_empty());
+    assert!(rules.game_point(app.world.resource::<Scoreboard>()));
+    finish_serve(&mut app);
+    assert_eq!(score_for_p1(&mut app), vec![Player::P1]);
+    assert_eq!(app.world.resource::<Scoreboard>().p1_score, 4);
+}
+
+#[test]
+fn handicaps_shrink_paddles_and_slow_returns() {
+    let return_off_p2 = |handicap: Handicap| {
+        let mut app = headless_app();
+        app.insert_resource(MatchRules {
+            p2_handicap: handicap,
+            ..default()
+        });
+        finish_serve(&mut app);
+        park_paddle::<P2Paddle>(&mut app, 0.0);
+        let paddle = *app
+            .world
+            .query_filtered::<&Transform, With<P2Paddle>>()
+            .iter(&app.world)
+            .next()
+            .unwrap();
+
+        place_ball(
+            &mut app,
+            Vec2::new(paddle.translation.x - 60.0, 0.0),
+            Vec2::new(600.0, 0.0),
+        );
+        for _ in 0..20 {
+            app.update();
+        }
+        let (_, velocity) = ball(&mut app);
+        assert!(velocity.x < 0.0, "ball wasn't returned");
+        (paddle.scale.y, velocity.length())
+    };
+
+    let (height, speed) = return_off_p2(Handicap::default());
+    let (handicapped_height, handicapped_speed) = return_off_p2(Handicap {
+        paddle_scale: 0.5,
+        return_speed: 0.5,
+        ..default()
+    });
+    assert_eq!(height, PADDLE_SIZE.y);
+    assert_eq!(handicapped_height, PADDLE_SIZE.y * 0.5);
+    assert!(
+        (handicapped_speed - speed * 0.5).abs() < 1.0,
+        "{} vs {}",
+        handicapped_speed,
+        speed
+    );
+}
+
+#[test]
+fn breakout_bricks_break_for_a_point_to_whoever_hit_the_ball() {
+    let mut app = headless_app();
+    app.add_event::<MatchOverEvent>()
+        .insert_resource(MatchRules {
+            mode: GameMode::Breakout,
+            ..default()
+        });
+    let bricks = |app: &mut App| {
+        app.world
+            .query_filtered::<(), With<Brick>>()
+            .iter(&app.world)
+            .count()
+    };
+    finish_serve(&mut app);
+    let laid = bricks(&mut app);
+    assert!(laid > 0);
+
+    // Straight back off the middle of P1's paddle into a row of bricks.
+    park_paddle::<P1Paddle>(&mut app, -30.0);
+    let paddle_x = LEFT_WALL + 60.0;
+    place_ball(
+        &mut app,
+        Vec2::new(paddle_x + 60.0, -30.0),
+        Vec2::new(-600.0, 0.0),
+    );
+    for _ in 0..40 {
+        app.update();
+    }
+
+    assert_eq!(bricks(&mut app), laid - 1);
+    assert_eq!(app.world.resource::<Scoreboard>().p1_score, 1);
+    let (_, velocity) = ball(&mut app);
+    assert!(velocity.x < 0.0, "ball didn't bounce off the brick");
+}
+
+#[test]
+fn shrinking_paddles_shrink_on_returns_until_a_goal() {
+    let mut app = headless_app();
+    app.insert_resource(MatchRules {
+        shrinking_paddles: true,
+        ..default()
+    });
+    finish_serve(&mut app);
+    park_paddle::<P1Paddle>(&mut app, 0.0);
+    park_paddle::<P2Paddle>(&mut app, BOTTOM_WALL + 60.0);
+    let p1_height = |app: &mut App| {
+        app.world
+            .query_filtered::<&Transform, With<P1Paddle>>()
+            .iter(&app.world)
+            .next()
+            .unwrap()
+            .scale
+            .y
+    };
+
+    place_ball(
+        &mut app,
+        Vec2::new(LEFT_WALL + 120.0, 0.0),
+        Vec2::new(-600.0, 0.0),
+    );
+    for _ in 0..20 {
+        app.update();
+    }
+    assert!(p1_height(&mut app) < PADDLE_SIZE.y);
+
+    while app.world.resource::<Scoreboard>().p1_score == 0 {
+        app.update();
+    }
+    app.update();
+    assert_eq!(p1_height(&mut app), PADDLE_SIZE.y);
+}
+
+#[test]
+fn frenzy_speeds_up_the_ball_and_doubles_the_next_goal() {
+    let mut app = headless_app();
+    app.insert_resource(GameConfig {
+        frenzy_rallies: vec![1, 1, 1],
+        ..default()
+    });
+    finish_serve(&mut app);
+    park_paddle::<P1Paddle>(&mut app, 0.0);
+    park_paddle::<P2Paddle>(&mut app, BOTTOM_WALL + 60.0);
+
+    place_ball(
+        &mut app,
+        Vec2::new(LEFT_WALL + 120.0, 0.0),
+        Vec2::new(-600.0, 0.0),
+    );
+    while ball(&mut app).1.x < 0.0 {
+        app.update();
+    }
+    assert_eq!(app.world.resource::<Frenzy>().level, 3);
+    assert!(ball(&mut app).1.x > 1.2 * BALL_SPEED);
+
+    while app.world.resource::<Scoreboard>().p1_score == 0 {
+        app.update();
+    }
+    assert_eq!(app.world.resource::<Scoreboard>().p1_score, 2);
+    assert_eq!(*app.world.resource::<Frenzy>(), Frenzy::default());
+}
+
+#[test]
+fn ghost_replays_the_previous_practice_streak() {
+    let mut app = headless_app();
+    app.insert_resource(MatchRules {
+        mode: GameMode::Practice,
+        ..default()
+    });
+    let miss = |app: &mut App| {
+        finish_serve(app);
+        place_ball(
+            app,
+            Vec2::new(LEFT_WALL + 120.0, -200.0),
+            Vec2::new(-600.0, 0.0),
+        );
+        while !app.world.resource::<GhostRecording>().current.is_empty() {
+            app.update();
+        }
+    };
+
+    finish_serve(&mut app);
+    park_paddle::<P1Paddle>(&mut app, 200.0);
+    miss(&mut app);
+    // A whole streak, serve and all, with the paddle held still.
+    park_paddle::<P1Paddle>(&mut app, 100.0);
+    miss(&mut app);
+
+    park_paddle::<P1Paddle>(&mut app, -100.0);
+    for _ in 0..5 {
+        app.update();
+        let recording = app.world.resource::<GhostRecording>();
+        assert_eq!(recording.ghost_y(), Some(100.0));
+    }
+}
+
+#[test]
+fn a_wide_court_is_built_longer_with_thicker_walls_and_the_paddles_further_in() {
+    let classic = ArenaLayout::default();
+    let wide = CourtSize::Wide.apply(&classic);
+    assert!(wide.size.x > classic.size.x);
+    assert!(wide.wall_thickness > classic.wall_thickness);
+    assert!(wide.paddle_gap > classic.paddle_gap);
+    assert_eq!(CourtSize::Classic.apply(&classic).size, classic.size);
+
+    let mut app = headless_app();
+    app.insert_resource(wide.clone());
+    app.update();
+
+    let arena = app.world.resource::<Arena>().clone();
+    assert_eq!(arena.width(), wide.size.x);
+    assert_eq!(arena.wall_thickness, wide.wall_thickness);
+    let p2_x = app
+        .world
+        .query_filtered::<&Transform, With<P2Paddle>>()
+        .iter(&app.world)
+        .next()
+        .unwrap()
+        .translation
+        .x;
+    assert_eq!(p2_x, arena.right - wide.paddle_gap);
+    let walls: Vec<Vec3> = app
+        .world
+        .query_filtered::<&Transform, With<Wall>>()
+        .iter(&app.world)
+        .map(|transform| transform.scale)
+        .collect();
+    assert!(walls.contains(&Vec3::new(
+        wide.size.x + wide.wall_thickness,
+        wide.wall_thickness,
+        1.0
+    )));
+}
+
+#[test]
+fn arena_layout_puts_up_obstacles_and_goal_posts() {
+    let mut app = headless_app();
+    app.insert_resource(ArenaLayout {
+        goal_height: Some(200.0),
+        obstacles: vec![Obstacle {
+            position: Vec2::new(0.0, 150.0),
+            size: Vec2::new(40.0, 40.0),
+        }],
+        ..default()
+    });
+    finish_serve(&mut app);
+    park_paddle::<P2Paddle>(&mut app, BOTTOM_WALL + 60.0);
+
+    // Above the goal, the end of the court is wall.
+    place_ball(
+        &mut app,
+        Vec2::new(RIGHT_WALL - 100.0, 200.0),
+        Vec2::new(600.0, 0.0),
+    );
+    for _ in 0..30 {
+        app.update();
+    }
+    assert!(ball(&mut app).1.x < 0.0);
+    assert_eq!(app.world.resource::<Scoreboard>().p1_score, 0);
+
+    place_ball(&mut app, Vec2::new(-100.0, 150.0), Vec2::new(600.0, 0.0));
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(ball(&mut app).1.x < 0.0);
+}
+
+#[test]
+fn moving_goals_sweep_along_the_end_wall() {
+    let mut app = headless_app();
+    app.insert_resource(MatchRules {
+        moving_goals: true,
+        ..default()
+    });
+    finish_serve(&mut app);
+    park_paddle::<P2Paddle>(&mut app, BOTTOM_WALL + 60.0);
+
+    // A quarter of the way through its sweep, P2's goal is down at the
+    // bottom of its end, so the middle of the end is wall.
+    app.world.resource_mut::<SimTick>().0 = 89;
+    app.update();
+    assert!(paddle_y::<P2Goal>(&mut app) < BOTTOM_WALL / 2.0);
+
+    place_ball(
+        &mut app,
+        Vec2::new(RIGHT_WALL - 100.0, 0.0),
+        Vec2::new(600.0, 0.0),
+    );
+    for _ in 0..20 {
+        app.update();
+    }
+    assert!(ball(&mut app).1.x < 0.0);
+    assert_eq!(app.world.resource::<Scoreboard>().p1_score, 0);
+
+    // Halfway through, it is back in the middle.
+    app.world.resource_mut::<SimTick>().0 = 179;
+    app.update();
+    assert!(paddle_y::<P2Goal>(&mut app).abs() < 1.0);
+
+    place_ball(
+        &mut app,
+        Vec2::new(RIGHT_WALL - 100.0, 0.0),
+        Vec2::new(600.0, 0.0),
+    );
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_eq!(app.world.resource::<Scoreboard>().p1_score, 1);
+}
+
+#[test]
+fn gravity_well_bends_the_ball_towards_it() {
+    let mut app = headless_app();
+    app.insert_resource(ArenaLayout {
+        gravity_well: Some(GravityWell {
+            position: Vec2::ZERO,
+            strength: 900.0,
+            radius: 260.0,
+        }),
+        ..default()
+    });
+    finish_serve(&mut app);
+
+    place_ball(&mut app, Vec2::new(-150.0, 100.0), Vec2::new(400.0, 0.0));
+    for _ in 0..20 {
+        app.update();
+    }
+    let (position, velocity) = ball(&mut app);
+    assert!(velocity.y < 0.0);
+    assert!(position.y < 100.0);
+
+    // Out of reach of the well, the ball goes straight.
+    place_ball(&mut app, Vec2::new(-150.0, 270.0), Vec2::new(400.0, 0.0));
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(ball(&mut app).1.y, 0.0);
+}
+
+#[test]
+fn left_goal_scores_for_p2() {
+    let mut app = headless_app();
+    finish_serve(&mut app);
+    park_paddle::<P1Paddle>(&mut app, BOTTOM_WALL + 60.0);
+
+    place_ball(
+        &mut app,
+        Vec2::new(LEFT_WALL + 100.0, 100.0),
+        Vec2::new(-600.0, 0.0),
+    );
+    for _ in 0..60 {
+        app.update();
+    }
+
+    let scoreboard = app.world.resource::<Scoreboard>();
+    assert_eq!(scoreboard.p1_score, 0);
+    assert_eq!(scoreboard.p2_score, 1);
+}
+
+#[test]
+fn practice_wall_returns_the_ball_until_p1_misses() {
+    let mut app = headless_app();
+    app.world.resource_mut::<MatchRules>().mode = GameMode::Practice;
+    finish_serve(&mut app);
+    park_paddle::<P1Paddle>(&mut app, BOTTOM_WALL + 60.0);
+    app.world.resource_mut::<PracticeScore>().returns = 4;
+
+    place_ball(
+        &mut app,
+        Vec2::new(RIGHT_WALL - 150.0, 100.0),
+        Vec2::new(600.0, 0.0),
+    );
+    for _ in 0..30 {
+        app.update();
+    }
+    assert!(ball(&mut app).1.x < 0.0);
+
+    for _ in 0..120 {
+        app.update();
+    }
+    let scoreboard = app.world.resource::<Scoreboard>();
+    assert_eq!((scoreboard.p1_score, scoreboard.p2_score), (0, 0));
+    let practice_score = app.world.resource::<PracticeScore>();
+    assert_eq!((practice_score.returns, practice_score.best), (0, 4));
+}
+
+#[test]
+fn sticky_paddle_holds_the_ball_until_the_shot_clock_runs_out() {
+    let mut app = headless_app();
+    app.world.resource_mut::<MatchRules>().sticky_paddles = true;
+    finish_serve(&mut app);
+    park_paddle::<P1Paddle>(&mut app, 0.0);
+    let (mut grip, transform) = app
+        .world
+        .query_filtered::<(&mut Grip, &Transform), With<P1Paddle>>()
+        .iter_mut(&mut app.world)
+        .next()
+        .unwrap();
+    grip.0 = true;
+    let paddle_x = transform.translation.x;
+
+    place_ball(
+        &mut app,
+        Vec2::new(paddle_x + 60.0, 10.0),
+        Vec2::new(-600.0, 0.0),
+    );
+    for _ in 0..20 {
+        app.update();
+    }
+    assert!(app.world.resource::<Catch>().holding());
+    let (held_at, velocity) = ball(&mut app);
+    assert_eq!(velocity, Vec2::ZERO);
+    assert!(held_at.x > paddle_x);
+
+    for _ in 0..120 {
+        app.update();
+    }
+    assert!(!app.world.resource::<Catch>().holding());
+    assert!(ball(&mut app).1.x > 0.0);
+}
+
+#[test]
+fn caught_balls_go_back_as_fast_as_handicapped_returns() {
+    let mut app = headless_app();
+    app.insert_resource(MatchRules {
+        sticky_paddles: true,
+        p1_handicap: Handicap {
+            return_speed: 0.5,
+            ..default()
+        },
+        ..default()
+    });
+    finish_serve(&mut app);
+    park_paddle::<P1Paddle>(&mut app, 0.0);
+    let set_grip = |app: &mut App, held: bool| {
+        let (mut grip, transform) = app
+            .world
+            .query_filtered::<(&mut Grip, &Transform), With<P1Paddle>>()
+            .iter_mut(&mut app.world)
+            .next()
+            .unwrap();
+        grip.0 = held;
+        transform.translation.x
+    };
+    let paddle_x = set_grip(&mut app, true);
+
+    place_ball(
+        &mut app,
+        Vec2::new(paddle_x + 60.0, 0.0),
+        Vec2::new(-600.0, 0.0),
+    );
+    for _ in 0..20 {
+        app.update();
+    }
+    assert!(app.world.resource::<Catch>().holding());
+
+    set_grip(&mut app, false);
+    app.update();
+    assert!(!app.world.resource::<Catch>().holding());
+    let config = app.world.resource::<GameConfig>();
+    let fjongs = app.world.resource::<Scoreboard>().fjongs;
+    let expected = config.ball_speed * config.rally_speed_multiplier(fjongs) * 0.5;
+    let (_, velocity) = ball(&mut app);
+    assert!(velocity.x > 0.0);
+    assert!(
+        (velocity.length() - expected).abs() < 1.0,
+        "{} vs {}",
+        velocity.length(),
+        expected
+    );
+}
+
+#[test]
+fn player_paddle_stays_within_bounds() {
+    let mut app = headless_app();
+    app.add_plugin(InputPlugin).add_plugin(ControlsPlugin);
+    app.update();
+
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::W);
+    for _ in 0..120 {
+        app.update();
+    }
+    assert!(paddle_y::<P1Paddle>(&mut app) + PADDLE_SIZE.y / 2.0 <= TOP_WALL);
+
+    let mut input = app.world.resource_mut::<Input<KeyCode>>();
+    input.release(KeyCode::W);
+    input.press(KeyCode::S);
+    for _ in 0..240 {
+        app.update();
+    }
+    assert!(paddle_y::<P1Paddle>(&mut app) - PADDLE_SIZE.y / 2.0 >= BOTTOM_WALL);
+}
+
+#[test]
+fn mirrored_controls_turn_up_into_down_for_both_players() {
+    let mut app = headless_app();
+    app.insert_resource(MatchRules {
+        mirror_controls: true,
+        ..default()
+    });
+    app.add_plugin(InputPlugin).add_plugin(ControlsPlugin);
+    finish_serve(&mut app);
+    let p2 = app
+        .world
+        .query_filtered::<Entity, With<P2Paddle>>()
+        .iter(&app.world)
+        .next()
+        .unwrap();
+    app.world.entity_mut(p2).remove::<Brain>();
+
+    let mut input = app.world.resource_mut::<Input<KeyCode>>();
+    input.press(KeyCode::W);
+    input.press(KeyCode::L);
+    for _ in 0..20 {
+        app.update();
+    }
+    assert!(paddle_y::<P1Paddle>(&mut app) < 0.0);
+    assert!(paddle_y::<P2Paddle>(&mut app) > 0.0);
+}
+
+#[test]
+fn series_follows_players_to_the_other_end() {
+    let rules = MatchRules {
+        best_of: 3,
+        ..default()
+    };
+    let mut series = Series::default();
+    series.record(Player::P1);
+    assert_eq!(series.winner(&rules), None);
+
+    // P1 has moved to P2's end of the court, and wins from there.
+    series.switched = true;
+    series.record(Player::P2);
+    assert_eq!((series.p1_games, series.p2_games), (2, 0));
+    assert_eq!(series.winner(&rules), Some(Player::P1));
+
+    // P1's keys drive the paddle at P2's end.
+    let mut app = headless_app();
+    app.insert_resource(series);
+    app.add_plugin(InputPlugin).add_plugin(ControlsPlugin);
+    app.update();
+    let p2 = app
+        .world
+        .query_filtered::<Entity, With<P2Paddle>>()
+        .iter(&app.world)
+        .next()
+        .unwrap();
+    app.world.entity_mut(p2).remove::<Brain>();
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::W);
+    for _ in 0..20 {
+        app.update();
+    }
+    assert!(paddle_y::<P2Paddle>(&mut app) > 0.0);
+    assert_eq!(paddle_y::<P1Paddle>(&mut app), 0.0);
+}
+
+fn paddle_speed<T: Component>(app: &mut App) -> f32 {
+    app.world
+        .query_filtered::<&Velocity, With<T>>()
+        .iter(&app.world)
+        .next()
+        .unwrap()
+        .y
+        .abs()
+}
+
+#[test]
+fn dash_doubles_paddle_speed_briefly_then_needs_to_charge() {
+    let mut app = headless_app();
+    app.add_plugin(InputPlugin).add_plugin(ControlsPlugin);
+    app.update();
+    park_paddle::<P1Paddle>(&mut app, BOTTOM_WALL + 60.0);
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::W);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert!(paddle_speed::<P1Paddle>(&mut app) <= PADDLE_SPEED);
+
+    press_key(&mut app, KeyCode::LShift);
+    for _ in 0..5 {
+        app.update();
+    }
+    assert!(paddle_speed::<P1Paddle>(&mut app) > PADDLE_SPEED * 1.5);
+
+    // Worn off, and still charging, so another tap does nothing.
+    park_paddle::<P1Paddle>(&mut app, BOTTOM_WALL + 60.0);
+    for _ in 0..30 {
+        app.update();
+    }
+    assert!(paddle_speed::<P1Paddle>(&mut app) <= PADDLE_SPEED);
+    press_key(&mut app, KeyCode::LShift);
+    for _ in 0..5 {
+        app.update();
+    }
+    assert!(paddle_speed::<P1Paddle>(&mut app) <= PADDLE_SPEED);
+}
+
+fn press_key(app: &mut App, key: KeyCode) {
+    for state in [ElementState::Pressed, ElementState::Released] {
+        app.world
+            .resource_mut::<Events<KeyboardInput>>()
+            .send(KeyboardInput {
+                scan_code: 0,
+                key_code: Some(key),
+                state,
+            });
+        app.update();
+    }
+}
+
+fn enter_console_line(app: &mut App, line: &str) {
+    let mut characters = app.world.resource_mut::<Events<ReceivedCharacter>>();
+    for char in line.chars() {
+        characters.send(ReceivedCharacter {
+            id: WindowId::primary(),
+            char,
+        });
+    }
+    press_key(app, KeyCode::Return);
+}
+
+#[test]
+fn a_kiosk_plays_a_demo_while_left_alone_and_takes_a_coin_a_match() {
+    let mut app = headless_app();
+    app.add_plugin(InputPlugin)
+        .add_state(AppState::MainMenu)
+        .add_plugin(KioskPlugin {
+            attract_after: 0.0,
+            ..default()
+        });
+    let p1_has_brain = |app: &mut App| {
+        app.world
+            .query_filtered::<(), (With<Brain>, With<P1Paddle>)>()
+            .iter(&app.world)
+            .count()
+            > 0
+    };
+    let state = |app: &App| *app.world.resource::<State<AppState>>().current();
+
+    for _ in 0..5 {
+        app.update();
+    }
+    assert_eq!(state(&app), AppState::Playing);
+    assert!(app.world.contains_resource::<Attract>());
+    assert!(p1_has_brain(&mut app));
+
+    // A coin stops the demo, and start spends it on a match against the AI.
+    app.world.resource_mut::<Kiosk>().attract_after = f32::INFINITY;
+    press_key(&mut app, KeyCode::Key5);
+    assert_eq!(state(&app), AppState::MainMenu);
+    assert!(!app.world.contains_resource::<Attract>());
+    assert!(!p1_has_brain(&mut app));
+    assert_eq!(app.world.resource::<Kiosk>().credits, 1);
+
+    press_key(&mut app, KeyCode::Key1);
+    assert_eq!(state(&app), AppState::Playing);
+    assert_eq!(app.world.resource::<Kiosk>().credits, 0);
+
+    // Without a credit, a match is turned down.
+    app.world
+        .resource_mut::<State<AppState>>()
+        .set(AppState::MainMenu)
+        .unwrap();
+    app.update();
+    press_key(&mut app, KeyCode::Key1);
+    assert_eq!(state(&app), AppState::MainMenu);
+}
+
+#[test]
+fn the_on_screen_keyboard_types_names_without_a_real_one() {
+    let locale = Locale::default();
+    let mut keyboard = OnScreenKeyboard::alphanumeric();
+    let mut name = String::new();
+    assert_eq!(keyboard.focused(), OnScreenKey::Done);
+
+    // Round from the end to the first letter, and back to the space bar.
+    keyboard.nudge(1);
+    assert!(!keyboard.press(&mut name, 3));
+    assert!(keyboard.render(&locale).starts_with("[A] B C"));
+    keyboard.nudge(-3);
+    assert_eq!(keyboard.focused(), OnScreenKey::Space);
+    keyboard.press(&mut name, 3);
+    keyboard.press(&mut name, 3);
+    keyboard.press(&mut name, 3);
+    assert_eq!(name, "A  ");
+
+    keyboard.nudge(1);
+    keyboard.press(&mut name, 3);
+    assert_eq!(name, "A ");
+    keyboard.nudge(1);
+    assert!(keyboard.press(&mut name, 3));
+    assert!(keyboard.render(&locale).ends_with("[OK]"));
+}
+
+#[test]
+fn console_runs_built_in_and_plugin_commands() {
+    let mut app = headless_app();
+    app.add_plugin(AssetPlugin)
+        .add_plugin(InputPlugin)
+        .add_plugin(ControlsPlugin)
+        .add_asset::<Font>()
+        .add_event::<ReceivedCharacter>()
+        .add_plugin(FontPlugin)
+        .add_plugin(ConsolePlugin)
+        .add_console_command("serve", "serve", |world, _args| {
+            world.resource_mut::<Scoreboard>().p2_score = 7;
+            Ok("Served".to_string())
+        });
+    app.update();
+
+    press_key(&mut app, KeyCode::Grave);
+    enter_console_line(&mut app, "set ball_speed 600");
+    enter_console_line(&mut app, "score p1 10");
+    enter_console_line(&mut app, "ai difficulty hard");
+    enter_console_line(&mut app, "serve");
+    assert_eq!(app.world.resource::<GameConfig>().ball_speed, 600.0);
+    assert_eq!(app.world.resource::<Scoreboard>().p1_score, 10);
+    assert_eq!(app.world.resource::<Scoreboard>().p2_score, 7);
+    assert_eq!(
+        app.world.resource::<MatchRules>().difficulty,
+        Difficulty::Hard
+    );
+
+    // Holding W while the console is open doesn't move the paddle.
+    let y = paddle_y::<P1Paddle>(&mut app);
+    app.world
+        .resource_mut::<Events<KeyboardInput>>()
+        .send(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(KeyCode::W),
+            state: ElementState::Pressed,
+        });
+    for _ in 0..30 {
+        app.update();
+    }
+    assert_eq!(paddle_y::<P1Paddle>(&mut app), y);
+}
+
+/// Always heading up, faster than any paddle is allowed to go.
+struct Runaway;
+
+impl PaddleBrain for Runaway {
+    fn decide(
+        &mut self,
+        _: &BallView,
+        _: &PaddleView,
+        _: &Arena,
+        _: TickRate,
+        _: &mut GameRng,
+    ) -> f32 {
+        10_000.0
+    }
+}
+
+#[test]
+fn a_bot_at_full_tilt_is_stopped_at_the_wall() {
+    let mut app = headless_app();
+    app.update();
+    let p2 = app
+        .world
+        .query_filtered::<Entity, With<P2Paddle>>()
+        .iter(&app.world)
+        .next()
+        .unwrap();
+    app.world.entity_mut(p2).insert(Brain::new(Runaway));
+
+    for _ in 0..120 {
+        app.update();
+    }
+    let (_, top_bound) = app.world.resource::<Arena>().paddle_bounds(PADDLE_SIZE.y);
+    assert_eq!(paddle_y::<P2Paddle>(&mut app), top_bound);
+    assert_eq!(paddle_speed::<P2Paddle>(&mut app), 0.0);
+}
+
+#[test]
+fn ai_never_leaves_the_arena() {
+    let mut app = headless_app();
+    app.update();
+
+    let p2 = app
+        .world
+        .query_filtered::<Entity, With<P2Paddle>>()
+        .iter(&app.world)
+        .next()
+        .unwrap();
+    app.world
+        .entity_mut(p2)
+        .insert(Brain::new(Predictor::new(Difficulty::Insane)));
+
+    for _ in 0..3000 {
+        app.update();
+        let y = paddle_y::<P2Paddle>(&mut app);
+        assert!(
+            (BOTTOM_WALL..=TOP_WALL).contains(&y),
+            "AI paddle left the arena at y = {}",
+            y
+        );
+    }
+}
+
+#[test]
+fn same_seed_plays_out_the_same_match() {
+    let play = |seed| {
+        let mut app = seeded_app(seed);
+        app.update();
+
+        // Let two fallible bots play each other so the rng gets exercised.
+        let p1 = app
+            .world
+            .query_filtered::<Entity, With<P1Paddle>>()
+            .iter(&app.world)
+            .next()
+            .unwrap();
+        app.world
+            .entity_mut(p1)
+            .insert(Brain::new(Predictor::new(Difficulty::Easy)));
+
+        let mut trajectory = Vec::new();
+        for _ in 0..1500 {
+            app.update();
+            trajectory.push(ball(&mut app).0);
+        }
+        assert_eq!(app.world.resource::<SimTick>().0, 1501);
+
+        let scoreboard = app.world.resource::<Scoreboard>();
+        (trajectory, scoreboard.p1_score, scoreboard.p2_score)
+    };
+
+    assert_eq!(play(7), play(7));
+}
+
+#[test]
+fn serve_angle_comes_from_the_seed() {
+    let serve = |seed| {
+        let mut app = seeded_app(seed);
+        finish_serve(&mut app);
+        ball(&mut app).1
+    };
+
+    assert_eq!(serve(3), serve(3));
+    let serves: Vec<_> = (0..8).map(serve).collect();
+    assert!(serves.iter().any(|velocity| *velocity != serves[0]));
+}
+
+#[test]
+fn ball_clipping_a_paddle_corner_is_pushed_out() {
+    let mut app = headless_app();
+    finish_serve(&mut app);
+    park_paddle::<P2Paddle>(&mut app, 0.0);
+
+    let paddle = app
+        .world
+        .query_filtered::<&Transform, With<P2Paddle>>()
+        .iter(&app.world)
+        .next()
+        .unwrap()
+        .translation
+        .truncate();
+    let corner = paddle + Vec2::new(-PADDLE_SIZE.x, PADDLE_SIZE.y) / 2.0;
+
+    // Sunk equally deep into the face and the top of the paddle.
+    place_ball(
+        &mut app,
+        corner + Vec2::new(-10.0, 10.0),
+        Vec2::new(300.0, -300.0),
+    );
+    app.update();
+
+    let (position, velocity) = ball(&mut app);
+    let gap = (position.truncate() - paddle).abs() - (BALL_SIZE + PADDLE_SIZE).truncate() / 2.0;
+    assert!(gap.x >= 0.0 || gap.y >= 0.0, "ball still inside the paddle");
+    assert!(velocity.x < 0.0, "ball kept going into the paddle");
+}
+
+fn ai_max_speed(app: &mut App) -> f32 {
+    app.world
+        .query::<&mut Brain>()
+        .iter_mut(&mut app.world)
+        .next()
+        .unwrap()
+        .skill_mut()
+        .unwrap()
+        .max_speed
+}
+
+#[test]
+fn adaptive_ai_eases_off_when_ahead_and_tries_harder_when_behind() {
+    let mut app = headless_app();
+    app.world.resource_mut::<MatchRules>().adaptive_ai = true;
+    app.update();
+    let base_speed = ai_max_speed(&mut app);
+
+    app.world.resource_mut::<Scoreboard>().p2_score = 5;
+    app.update();
+    assert!(ai_max_speed(&mut app) < base_speed);
+
+    *app.world.resource_mut::<Scoreboard>() = Scoreboard {
+        p1_score: 5,
+        ..default()
+    };
+    app.update();
+    assert!(ai_max_speed(&mut app) > base_speed);
+
+    app.world.resource_mut::<Scoreboard>().p2_score = 5;
+    app.update();
+    assert_eq!(ai_max_speed(&mut app), base_speed);
+}
+
+#[test]
+fn edge_of_the_paddle_smashes_the_ball() {
+    let return_speed = |offset: f32| {
+        let mut app = headless_app();
+        finish_serve(&mut app);
+        park_paddle::<P2Paddle>(&mut app, 0.0);
+        let paddle_x = app
+            .world
+            .query_filtered::<&Transform, With<P2Paddle>>()
+            .iter(&app.world)
+            .next()
+            .unwrap()
+            .translation
+            .x;
+
+        place_ball(
+            &mut app,
+            Vec2::new(paddle_x - 60.0, offset),
+            Vec2::new(600.0, 0.0),
+        );
+        for _ in 0..20 {
+            app.update();
+        }
+        let (_, velocity) = ball(&mut app);
+        assert!(velocity.x < 0.0, "ball wasn't returned");
+        velocity.length()
+    };
+
+    let inside_edge = return_speed(PADDLE_SIZE.y * 0.35);
+    let edge = return_speed(PADDLE_SIZE.y * 0.45);
+    assert!(edge > inside_edge * 1.3, "{} vs {}", edge, inside_edge);
+}
+
+#[test]
+fn spin_curves_the_ball_and_wears_off() {
+    let mut app = headless_app();
+    finish_serve(&mut app);
+
+    place_ball(&mut app, Vec2::new(-200.0, 0.0), Vec2::new(400.0, 0.0));
+    let mut spin = app
+        .world
+        .query_filtered::<&mut Spin, With<Ball>>()
+        .iter_mut(&mut app.world)
+        .next()
+        .unwrap();
+    spin.0 = 10.0;
+
+    for _ in 0..30 {
+        app.update();
+    }
+    let (position, velocity) = ball(&mut app);
+    // Counter-clockwise spin bends a ball heading right upwards, without
+    // changing its speed.
+    assert!(position.y > 0.0 && velocity.y > 0.0);
+    assert!((velocity.length() - 400.0).abs() < 0.1);
+
+    let spin = app
+        .world
+        .query_filtered::<&Spin, With<Ball>>()
+        .iter(&app.world)
+        .next()
+        .unwrap();
+    assert!(spin.0 > 0.0 && spin.0 < 10.0);
+}
+
+#[test]
+fn spectators_are_counted_and_sent_snapshots() {
+    // Somewhere free to listen.
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let mut app = headless_app();
+    app.add_state(AppState::Playing)
+        .add_plugin(SpectatePlugin)
+        .add_plugin(SpectatorServerPlugin { addr });
+    app.update();
+
+    let spectator = TcpStream::connect(addr).unwrap();
+    for _ in 0..100 {
+        app.update();
+        if app.world.resource::<SpectatorCount>().0 > 0 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(app.world.resource::<SpectatorCount>().0, 1);
+
+    app.update();
+    let mut line = String::new();
+    BufReader::new(&spectator).read_line(&mut line).unwrap();
+    let snapshot: Snapshot = serde_json::from_str(&line).unwrap();
+    assert_eq!(snapshot.spectators, 1);
+    assert_eq!(snapshot.p1.height, PADDLE_SIZE.y);
+
+    drop(spectator);
+    for _ in 0..100 {
+        app.update();
+        if app.world.resource::<SpectatorCount>().0 == 0 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(app.world.resource::<SpectatorCount>().0, 0);
+}
+
+#[test]
+fn emotes_go_out_to_spectators_over_the_emoting_paddle() {
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let mut app = headless_app();
+    app.add_state(AppState::Playing)
+        .add_plugin(InputPlugin)
+        .add_plugin(ControlsPlugin)
+        .add_plugin(SpectatePlugin)
+        .add_plugin(SpectatorServerPlugin { addr });
+    app.update();
+
+    let spectator = TcpStream::connect(addr).unwrap();
+    for _ in 0..100 {
+        app.update();
+        if app.world.resource::<SpectatorCount>().0 > 0 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    // P1's second emote key.
+    press_key(&mut app, KeyCode::Key2);
+
+    spectator
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let mut reader = BufReader::new(&spectator);
+    let emoted = (0..100)
+        .map(|_| {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            serde_json::from_str::<Snapshot>(&line).unwrap()
+        })
+        .find(|snapshot| snapshot.p1.emote.is_some() || snapshot.p2.emote.is_some())
+        .unwrap();
+    assert_eq!(emoted.p1.emote, Some(Emote::Nice));
+    assert_eq!(emoted.p2.emote, None);
+}
+
+#[test]
+fn listening_remote_brain_follows_commands_without_sending_state() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut guest = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    let echo = Echo::default();
+    let mut brain = RemoteBrain::listening(stream, echo.clone()).unwrap();
+
+    let ball = BallView {
+        position: Vec2::ZERO,
+        velocity: Vec2::new(400.0, 50.0),
+    };
+    let paddle = PaddleView {
+        position: Vec2::new(390.0, 0.0),
+        velocity: Vec2::ZERO,
+        size: Vec2::new(20.0, 120.0),
+    };
+    let arena = Arena::default();
+    let tick_rate = TickRate::default();
+    let mut rng = GameRng::new(0);
+    guest
+        .write_all(b"{\"velocity\":300.0,\"ping\":1234}\n")
+        .unwrap();
+    let mut velocity = 0.0;
+    for _ in 0..100 {
+        velocity = brain.decide(&ball, &paddle, &arena, tick_rate, &mut rng);
+        if velocity != 0.0 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(velocity, 300.0);
+    // Pings are left for the snapshots to echo back.
+    assert_eq!(echo.get(), Some(1234));
+
+    // The guest sees the match through snapshots instead.
+    guest
+        .set_read_timeout(Some(Duration::from_millis(50)))
+        .unwrap();
+    let mut state = String::new();
+    assert!(BufReader::new(&guest).read_line(&mut state).is_err());
+}
+
+#[test]
+fn remote_brain_streams_state_and_follows_commands() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut bot = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    let mut brain = RemoteBrain::new(stream).unwrap();
+
+    let ball = BallView {
+        position: Vec2::new(10.0, 20.0),
+        velocity: Vec2::new(400.0, 50.0),
+    };
+    let paddle = PaddleView {
+        position: Vec2::new(390.0, 0.0),
+        velocity: Vec2::ZERO,
+        size: Vec2::new(20.0, 120.0),
+    };
+    let arena = Arena::default();
+    let tick_rate = TickRate::default();
+    let mut rng = GameRng::new(0);
+    assert_eq!(
+        brain.decide(&ball, &paddle, &arena, tick_rate, &mut rng),
+        0.0
+    );
+
+    let mut state = String::new();
+    BufReader::new(&bot).read_line(&mut state).unwrap();
+    assert!(state.starts_with(r#"{"ball":{"position":[10.0,20.0]"#));
+
+    // Commands take effect on a later tick, and are held to the AI's top speed.
+    bot.write_all(b"{\"velocity\":-250.0}\n").unwrap();
+    let mut velocity = 0.0;
+    for _ in 0..100 {
+        velocity = brain.decide(&ball, &paddle, &arena, tick_rate, &mut rng);
+        if velocity != 0.0 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(velocity, -250.0);
+
+    bot.write_all(b"{\"velocity\":1e9}\n").unwrap();
+    thread::sleep(Duration::from_millis(50));
+    assert!(brain.decide(&ball, &paddle, &arena, tick_rate, &mut rng) < 1e9);
+}
+
+#[test]
+fn remote_brain_keeps_up_with_a_flooding_bot_and_drops_one_that_never_ends_a_line() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut bot = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (stream, _) = listener.accept().unwrap();
+    let mut brain = RemoteBrain::listening(stream, Echo::default()).unwrap();
+
+    let ball = BallView {
+        position: Vec2::ZERO,
+        velocity: Vec2::new(400.0, 0.0),
+    };
+    let paddle = PaddleView {
+        position: Vec2::new(390.0, 0.0),
+        velocity: Vec2::ZERO,
+        size: Vec2::new(20.0, 120.0),
+    };
+    let arena = Arena::default();
+    let tick_rate = TickRate::default();
+    let mut rng = GameRng::new(0);
+
+    // Far more than is ever held on to, sent faster than the game reads it.
+    let flood = thread::spawn(move || {
+        for _ in 0..20_000 {
+            bot.write_all(b"{\"velocity\":-100.0}\n").unwrap();
+        }
+        bot.write_all(b"{\"velocity\":250.0}\n").unwrap();
+        bot
+    });
+    let mut velocity = 0.0;
+    for _ in 0..200 {
+        velocity = brain.decide(&ball, &paddle, &arena, tick_rate, &mut rng);
+        if velocity == 250.0 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(velocity, 250.0);
+
+    let mut bot = flood.join().unwrap();
+    bot.write_all(&[b' '; 100 * 1024]).unwrap();
+    for _ in 0..100 {
+        velocity = brain.decide(&ball, &paddle, &arena, tick_rate, &mut rng);
+        if velocity == 0.0 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(velocity, 0.0);
+}
+
+/// Heads for the ball's height: the weights pick out the ball's and the
+/// paddle's y.
+fn follow_the_ball() -> Policy {
+    Policy {
+        layers: vec![Layer {
+            weights: vec![vec![0.0, 5.0, 0.0, 0.0, -5.0, 0.0]],
+            biases: vec![0.0],
+        }],
+    }
+}
+
+#[test]
+fn env_plays_whole_matches_and_rewards_returns() {
+    let policy = follow_the_ball();
+    let mut env = FjongEnv::with_rules(
+        1,
+        MatchRules {
+            points_to_win: 3,
+            ..default()
+        },
+    );
+    let mut observation = env.reset();
+
+    let mut total = 0.0;
+    let mut done = false;
+    for _ in 0..100_000 {
+        let step = env.step(policy.act(&observation));
+        observation = step.observation;
+        total += step.reward;
+        if step.done {
+            done = true;
+            break;
+        }
+    }
+    assert!(done, "match never finished");
+    assert!(total > -3.0, "never returned the ball");
+
+    let fresh = env.reset();
+    assert_eq!(fresh.ball_position, Vec2::ZERO);
+}
+
+#[test]
+fn policy_is_checked_on_load_and_sees_both_sides_alike() {
+    let path = std::env::temp_dir().join("fjong-policy-test.ron");
+    let save = |policy: &Policy| {
+        let ron = ron::ser::to_string(policy).unwrap();
+        std::fs::write(&path, ron).unwrap();
+    };
+
+    save(&follow_the_ball());
+    let policy = Policy::load(&path).unwrap();
+
+    let mut lopsided = follow_the_ball();
+    lopsided.layers[0].weights[0].pop();
+    save(&lopsided);
+    assert!(Policy::load(&path).is_err());
+    std::fs::remove_file(&path).unwrap();
+
+    // Mirrored courts look the same from either paddle.
+    let arena = Arena::default();
+    let ball = |x: f32| BallView {
+        position: Vec2::new(x, 100.0),
+        velocity: Vec2::new(-x, 50.0),
+    };
+    let paddle = |x: f32| PaddleView {
+        position: Vec2::new(x, 0.0),
+        velocity: Vec2::ZERO,
+        size: Vec2::new(20.0, 120.0),
+    };
+    let left = Observation::new(&ball(100.0), &paddle(-390.0), &arena);
+    let right = Observation::new(&ball(-100.0), &paddle(390.0), &arena);
+    assert_eq!(left, right);
+    assert!(policy.act(&left) > 0.5);
+}
+
+#[test]
+fn a_loaded_policy_plays_p2_as_the_learned_boss_until_another_difficulty_is_picked() {
+    let path = std::env::temp_dir().join("fjong-boss-test.ron");
+    std::fs::write(&path, ron::ser::to_string(&follow_the_ball()).unwrap()).unwrap();
+    let mut app = headless_app();
+    app.add_plugin(PolicyPlugin {
+        path: path.to_string_lossy().into_owned(),
+    });
+    std::fs::remove_file(&path).unwrap();
+
+    let p2_skill = |app: &mut App| {
+        app.world
+            .query_filtered::<&mut Brain, With<P2Paddle>>()
+            .iter_mut(&mut app.world)
+            .next()
+            .unwrap()
+            .skill_mut()
+            .map(|skill| skill.max_speed)
+    };
+    app.update();
+    app.update();
+    assert_eq!(
+        app.world.resource::<MatchRules>().difficulty,
+        Difficulty::Learned
+    );
+    assert_eq!(p2_skill(&mut app), None);
+
+    app.world.resource_mut::<MatchRules>().difficulty = Difficulty::Easy;
+    app.update();
+    app.update();
+    assert_eq!(p2_skill(&mut app), Some(Difficulty::Easy.skill().max_speed));
+
+    app.world.resource_mut::<MatchRules>().difficulty = Difficulty::Learned;
+    app.update();
+    app.update();
+    assert_eq!(p2_skill(&mut app), None);
+}
+
+#[test]
+fn the_ai_takes_a_new_difficulty_at_whichever_end_it_plays() {
+    let mut app = headless_app();
+    app.update();
+
+    // The series has switched ends, so the AI is playing P1.
+    let p1 = app
+        .world
+        .query_filtered::<Entity, With<P1Paddle>>()
+        .iter(&app.world)
+        .next()
+        .unwrap();
+    let p2 = app
+        .world
+        .query_filtered::<Entity, With<P2Paddle>>()
+        .iter(&app.world)
+        .next()
+        .unwrap();
+    let brain = app.world.entity_mut(p2).remove::<Brain>().unwrap();
+    app.world.entity_mut(p1).insert(brain);
+    app.world.resource_mut::<Series>().switched = true;
+
+    app.world.resource_mut::<MatchRules>().difficulty = Difficulty::Easy;
+    app.update();
+    let mut brain_query = app.world.query::<(&mut Brain, Option<&P1Paddle>)>();
+    let mut brains: Vec<_> = brain_query.iter_mut(&mut app.world).collect();
+    assert_eq!(brains.len(), 1);
+    assert!(brains[0].1.is_some());
+    let skill = brains[0].0.skill_mut().unwrap();
+    assert_eq!(skill.max_speed, Difficulty::Easy.skill().max_speed);
+}
+
+#[test]
+fn an_ai_picked_mid_match_takes_over_at_the_next_serve() {
+    let mut app = headless_app();
+    finish_serve(&mut app);
+    park_paddle::<P2Paddle>(&mut app, BOTTOM_WALL + 60.0);
+
+    app.world.resource_mut::<PendingAi>().0 = Some(AiLevel::Hard);
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(
+        app.world.resource::<MatchRules>().difficulty,
+        Difficulty::Medium
+    );
+
+    score_for_p1(&mut app);
+    let rules = app.world.resource::<MatchRules>();
+    assert_eq!(rules.difficulty, Difficulty::Hard);
+    assert!(!rules.adaptive_ai);
+    assert_eq!(app.world.resource::<PendingAi>().0, None);
+
+    // Adaptive keeps the difficulty and eases it off or sharpens it up.
+    let mut rules = app.world.resource::<MatchRules>().clone();
+    AiLevel::Adaptive.apply(&mut rules);
+    assert_eq!(rules.difficulty, Difficulty::Hard);
+    assert_eq!(AiLevel::of(&rules), Some(AiLevel::Adaptive));
+}
+
+#[test]
+fn lobby_rooms_are_joined_by_code_and_close_with_their_host() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()
+        .unwrap();
+    let listener = runtime
+        .block_on(tokio::net::TcpListener::bind("127.0.0.1:0"))
+        .unwrap();
+    let lobby = listener.local_addr().unwrap();
+    let rooms = Rooms::default();
+    thread::spawn(move || {
+        runtime.block_on(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let rooms = rooms.clone();
+                tokio::spawn(async move { rooms.serve(stream).await });
+            }
+        })
+    });
+
+    let (host, code) = lobby::create_room(lobby, "kitchen", 5000).unwrap();
+    assert_eq!(code.len(), CODE_LENGTH);
+    let rooms = lobby::list_rooms(lobby).unwrap();
+    assert_eq!(rooms.len(), 1);
+    assert_eq!(rooms[0].name, "kitchen");
+    // Codes can be typed in either case.
+    let addr = lobby::find_room(lobby, &code.to_lowercase()).unwrap();
+    assert_eq!(addr, "127.0.0.1:5000".parse().unwrap());
+    assert!(lobby::find_room(lobby, "????").is_err());
+
+    drop(host);
+    for _ in 0..100 {
+        if lobby::list_rooms(lobby).unwrap().is_empty() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert!(lobby::list_rooms(lobby).unwrap().is_empty());
+}
+
+#[test]
+fn webrtc_rooms_carry_a_remote_players_moves_and_snapshots() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let mut server = SignalingServer::full_mesh_builder(([127, 0, 0, 1], 0)).build();
+    let addr = {
+        let _entered = runtime.enter();
+        server.bind().unwrap()
+    };
+    thread::spawn(move || runtime.block_on(server.serve()));
+
+    let url = format!("ws://{}", addr);
+    let host = WebRtcRoom::open(&url, "abcd");
+    let guest = WebRtcRoom::open(&url, "abcd");
+    assert_eq!(host.code(), "ABCD");
+    let (mut to_guest, mut to_host) = (Vec::new(), Vec::new());
+    for _ in 0..1000 {
+        to_guest.extend(host.arrivals().unwrap());
+        to_host.extend(guest.arrivals().unwrap());
+        if !to_guest.is_empty() && !to_host.is_empty() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    let (mut to_guest, _) = to_guest.pop().unwrap();
+    let (mut to_host, _) = to_host.pop().unwrap();
+
+    // The host plays the guest's paddle from the moves they send...
+    let echo = Echo::default();
+    let mut brain =
+        RemoteBrain::listening(to_guest.try_clone_line().unwrap(), echo.clone()).unwrap();
+    let ball = BallView {
+        position: Vec2::ZERO,
+        velocity: Vec2::new(400.0, 50.0),
+    };
+    let paddle = PaddleView {
+        position: Vec2::new(390.0, 0.0),
+        velocity: Vec2::ZERO,
+        size: Vec2::new(20.0, 120.0),
+    };
+    let arena = Arena::default();
+    let tick_rate = TickRate::default();
+    let mut rng = GameRng::new(0);
+    to_host
+        .write_all(b"{\"velocity\":300.0,\"ping\":1234}\n")
+        .unwrap();
+    let mut velocity = 0.0;
+    for _ in 0..100 {
+        velocity = brain.decide(&ball, &paddle, &arena, tick_rate, &mut rng);
+        if velocity != 0.0 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(velocity, 300.0);
+    assert_eq!(echo.get(), Some(1234));
+
+    // ...and sends them the match down the same line.
+    to_guest.write_all(b"{\"p1_score\":1}\n").unwrap();
+    let mut snapshot = Vec::new();
+    let mut buffer = [0; 64];
+    for _ in 0..100 {
+        match to_host.read(&mut buffer) {
+            Ok(read) => snapshot.extend_from_slice(&buffer[..read]),
+            Err(err) => assert_eq!(err.kind(), std::io::ErrorKind::WouldBlock),
+        }
+        if snapshot.ends_with(b"\n") {
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(snapshot, b"{\"p1_score\":1}\n");
+}
+
+/// Answers one HTTP request on `listener` with `response`, giving back the
+/// request line and body it was sent.
+fn answer_http(listener: &TcpListener, response: &str) -> (String, Vec<u8>) {
+    let (stream, _) = listener.accept().unwrap();
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        if header == "\r\n" {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().unwrap();
+            }
+        }
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).unwrap();
+    (&stream).write_all(response.as_bytes()).unwrap();
+    (request_line.trim_end().to_string(), body)
+}
+
+#[test]
+fn leaderboard_takes_entries_and_gives_back_the_top_scores() {
+    assert!("https://example.com/scores".parse::<Endpoint>().is_ok());
+    assert!("ftp://example.com/scores".parse::<Endpoint>().is_err());
+    assert!("http://:80/".parse::<Endpoint>().is_err());
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/scores", listener.local_addr().unwrap());
+    let endpoint: Endpoint = url.parse().unwrap();
+    let entry = |score| LeaderboardEntry {
+        name: "kitchen".to_string(),
+        score,
+        seed: 7,
+        fjong: "0.1.0".to_string(),
+        daily: None,
+    };
+
+    let server = thread::spawn(move || {
+        let posted = answer_http(&listener, "HTTP/1.0 201 Created\r\n\r\n");
+        let top: Vec<_> = (0..150).rev().map(entry).collect();
+        let body = serde_json::to_string(&top).unwrap();
+        let got = answer_http(&listener, &format!("HTTP/1.0 200 OK\r\n\r\n{}", body));
+        answer_http(&listener, "HTTP/1.0 503 Service Unavailable\r\n\r\n");
+        (posted, got)
+    });
+
+    endpoint.submit(&entry(42)).unwrap();
+    let top = endpoint.top().unwrap();
+    assert_eq!(top.len(), TOP_COUNT);
+    assert_eq!(top[0].score, 149);
+    assert!(endpoint.top().is_err());
+
+    let ((post, body), (get, _)) = server.join().unwrap();
+    assert_eq!(post, "POST /scores HTTP/1.1");
+    assert_eq!(
+        serde_json::from_slice::<LeaderboardEntry>(&body).unwrap(),
+        entry(42)
+    );
+    assert_eq!(get, "GET /scores HTTP/1.1");
+}
+
+#[test]
+fn server_starts_once_both_seats_are_taken_and_pauses_when_a_player_drops() {
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let mut app = headless_app();
+    app.add_plugin(ServerPlugin { addr });
+
+    let join = || {
+        let mut player = TcpStream::connect(addr).unwrap();
+        player.write_all(b"{\"session\":null}\n").unwrap();
+        player
+    };
+    let p1 = join();
+    let p2 = join();
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(
+        *app.world.resource::<State<AppState>>().current(),
+        AppState::Playing
+    );
+
+    // Welcomed with a session to come back to, then sent the match.
+    let mut lines = BufReader::new(p1).lines();
+    let welcome = lines.next().unwrap().unwrap();
+    assert!(welcome.starts_with("{\"session\":"));
+    let snapshot: Snapshot = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+    assert_eq!(snapshot.p1_score, 0);
+
+    drop(p2);
+    for _ in 0..100 {
+        app.update();
+        if *app.world.resource::<State<AppState>>().current() == AppState::Reconnecting {
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(
+        *app.world.resource::<State<AppState>>().current(),
+        AppState::Reconnecting
+    );
+}
+
+#[test]
+fn skins_draw_the_paddles_and_ball_and_go_back_to_flat_colours() {
+    let mut app = headless_app();
+    app.add_plugin(AssetPlugin)
+        .add_asset::<Image>()
+        .add_plugin(SkinsPlugin);
+    app.world.resource_mut::<Settings>().video.skin = Some("Wood".to_string());
+    for _ in 0..100 {
+        app.update();
+        if app.world.resource::<Skins>().get("Wood").is_some() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    app.update();
+
+    let wood = app.world.resource::<Skins>().get("Wood").unwrap().clone();
+    let drawn = |app: &mut App| {
+        let mut ball_query = app
+            .world
+            .query_filtered::<(&Sprite, &Handle<Image>), With<Ball>>();
+        let (sprite, ball) = ball_query.iter(&app.world).next().unwrap();
+        let ball = (sprite.custom_size, ball.clone());
+        let mut paddle_query = app
+            .world
+            .query_filtered::<(&Sprite, &Handle<Image>), With<P1Paddle>>();
+        let (sprite, paddle) = paddle_query.iter(&app.world).next().unwrap();
+        (ball, (sprite.custom_size, paddle.clone()))
+    };
+    let (ball, paddle) = drawn(&mut app);
+    assert_eq!(ball, (Some(Vec2::ONE), wood.ball.clone().unwrap()));
+    assert_eq!(paddle, (Some(Vec2::ONE), wood.paddle.clone().unwrap()));
+
+    app.world.resource_mut::<Settings>().video.skin = None;
+    app.update();
+    let (ball, paddle) = drawn(&mut app);
+    assert_eq!(ball.0, None);
+    assert_eq!(paddle.0, None);
+    assert_ne!(ball.1, wood.ball.unwrap());
+    assert_ne!(paddle.1, wood.paddle.unwrap());
+}
+
+#[test]
+fn theme_packs_in_the_mods_folder_swap_in_their_font_and_skin() {
+    let dir = std::env::temp_dir().join(format!("fjong-mods-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("sunset")).unwrap();
+    std::fs::write(
+        dir.join("sunset").join("sunset.theme.ron"),
+        r#"(name: "Sunset", font: Some("sunset.ttf"), skin: Some((ball: Some("ball.png"))))"#,
+    )
+    .unwrap();
+    std::fs::write(dir.join("broken.theme.ron"), "(name: ").unwrap();
+    assert_eq!(ThemePacks::scan(&dir).names(), vec!["Sunset".to_string()]);
+
+    let mut app = headless_app();
+    app.add_plugin(AssetPlugin)
+        .add_asset::<Font>()
+        .add_asset::<Image>()
+        .add_plugin(FontPlugin)
+        .add_plugin(SkinsPlugin)
+        .add_plugin(ThemePacksPlugin { dir: dir.clone() });
+    app.update();
+    let own_font = app.world.resource::<UiFont>().0.clone();
+
+    app.world.resource_mut::<Settings>().video.theme_pack = Some("Sunset".to_string());
+    app.update();
+    app.update();
+    let asset_server = app.world.resource::<AssetServer>().clone();
+    let pack_font: Handle<Font> = asset_server.load(dir.join("sunset").join("sunset.ttf"));
+    let pack_ball: Handle<Image> = asset_server.load(dir.join("sunset").join("ball.png"));
+    assert_eq!(app.world.resource::<UiFont>().0, pack_font);
+    let mut ball_query = app.world.query_filtered::<&Handle<Image>, With<Ball>>();
+    assert_eq!(*ball_query.iter(&app.world).next().unwrap(), pack_ball);
+
+    app.world.resource_mut::<Settings>().video.theme_pack = None;
+    app.update();
+    app.update();
+    assert_eq!(app.world.resource::<UiFont>().0, own_font);
+    assert_ne!(*ball_query.iter(&app.world).next().unwrap(), pack_ball);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn the_hud_moves_the_scores_to_the_middle_and_grows_with_the_ui_scale() {
+    let mut app = headless_app();
+    app.add_plugin(AssetPlugin)
+        .add_asset::<Font>()
+        .add_plugin(FontPlugin)
+        .add_plugin(HudPlugin);
+    app.update();
+
+    let padding = Val::Px(15.0);
+    let mut text_query = app.world.query::<(Entity, &Style, &Text)>();
+    let p1_score = text_query
+        .iter(&app.world)
+        .find(|(_, style, _)| style.position.top == padding && style.position.left == padding)
+        .map(|(entity, _, _)| entity)
+        .unwrap();
+    let font_size = |app: &App| {
+        let text = app.world.get::<Text>(p1_score).unwrap();
+        text.sections[0].style.font_size
+    };
+    assert_eq!(font_size(&app), 32.0);
+
+    let mut settings = app.world.resource_mut::<Settings>();
+    settings.video.hud_layout = HudLayout::Centred;
+    settings.video.ui_scale = 2.0;
+    app.update();
+    let style = app.world.get::<Style>(p1_score).unwrap();
+    assert_eq!(style.position.right, Val::Percent(50.0));
+    assert_eq!(style.position.top, Val::Px(30.0));
+    assert_eq!(font_size(&app), 64.0);
+}
+
+#[test]
+fn possession_follows_the_last_hit_until_a_goal_serves_at_whoever_conceded() {
+    let mut app = headless_app();
+    app.add_state(AppState::Playing)
+        .add_plugin(AssetPlugin)
+        .add_asset::<Font>()
+        .add_plugin(FontPlugin)
+        .add_plugin(IndicatorsPlugin);
+    assert_eq!(app.world.resource::<Serve>().towards(), Player::P1);
+    finish_serve(&mut app);
+    park_paddle::<P1Paddle>(&mut app, 0.0);
+    park_paddle::<P2Paddle>(&mut app, BOTTOM_WALL + 60.0);
+
+    place_ball(
+        &mut app,
+        Vec2::new(LEFT_WALL + 150.0, 0.0),
+        Vec2::new(-600.0, 0.0),
+    );
+    let mut hit_back = false;
+    for _ in 0..30 {
+        app.update();
+        hit_back |= *app.world.resource::<Possession>() == Possession(Some(Player::P1));
+    }
+    assert!(hit_back);
+
+    for _ in 0..120 {
+        app.update();
+    }
+    assert_eq!(app.world.resource::<Scoreboard>().p1_score, 1);
+    assert_eq!(*app.world.resource::<Possession>(), Possession(None));
+    assert_eq!(app.world.resource::<Serve>().towards(), Player::P2);
+}
+
+#[test]
+fn the_announcer_only_calls_game_point_when_the_next_goal_would_win() {
+    let rules = MatchRules {
+        points_to_win: 5,
+        win_by_two: true,
+        ..default()
+    };
+    let scoreboard = |p1_score, p2_score| Scoreboard {
+        p1_score,
+        p2_score,
+        ..default()
+    };
+    let calm = Frenzy::default();
+
+    assert_eq!(
+        score_call(&rules, &scoreboard(4, 2), &calm, None),
+        ["4", "2", "game_point"]
+    );
+    // Five would only be one clear.
+    assert_eq!(
+        score_call(&rules, &scoreboard(4, 4), &calm, None),
+        ["4", "4", "deuce"]
+    );
+    assert_eq!(
+        score_call(&rules, &scoreboard(5, 4), &calm, None),
+        ["5", "4", "game_point"]
+    );
+    assert_eq!(
+        score_call(&rules, &scoreboard(6, 4), &calm, Some(Player::P1)),
+        ["6", "4", "p1_wins"]
+    );
+
+    // A goal that counts double is a goal closer.
+    let frenzy = Frenzy {
+        level: Frenzy::DOUBLE_POINTS,
+        ..default()
+    };
+    assert_eq!(
+        score_call(&rules, &scoreboard(1, 3), &calm, None),
+        ["1", "3"]
+    );
+    assert_eq!(
+        score_call(&rules, &scoreboard(1, 3), &frenzy, None),
+        ["1", "3", "game_point"]
+    );
+}
+
+#[test]
+fn game_point_turns_into_match_point_a_game_from_winning_the_series() {
+    let rules = MatchRules {
+        best_of: 3,
+        ..default()
+    };
+    let scoreboard = Scoreboard {
+        p1_score: 10,
+        p2_score: 5,
+        ..default()
+    };
+    let mut series = Series::default();
+    assert!(rules.game_point_for(&scoreboard, Player::P1));
+    assert!(!rules.game_point_for(&scoreboard, Player::P2));
+    assert!(!series.match_point(&rules, &scoreboard, Player::P1));
+
+    series.p1_games = 1;
+    assert!(series.match_point(&rules, &scoreboard, Player::P1));
+    assert!(!series.match_point(&rules, &scoreboard, Player::P2));
+
+    // Once ends are switched, P2 is the one at the end a goal from winning.
+    series.switched = true;
+    assert!(!series.match_point(&rules, &scoreboard, Player::P1));
+    series.p2_games = 1;
+    assert!(series.match_point(&rules, &scoreboard, Player::P1));
+}
+
+#[test]
+fn control_hints_show_the_bound_keys_while_serving_and_the_stick_with_a_gamepad() {
+    let mut app = headless_app();
+    app.add_state(AppState::Playing)
+        .add_plugin(InputPlugin)
+        .add_plugin(AssetPlugin)
+        .add_asset::<Font>()
+        .add_plugin(FontPlugin)
+        .add_plugin(ControlsPlugin)
+        .add_plugin(HudPlugin);
+    app.world.resource_mut::<KeyBindings>().p1 = PaddleKeys {
+        up: KeyCode::I,
+        down: KeyCode::K,
+        ..KeyBindings::default().p1
+    };
+    let hints = |app: &mut App| {
+        app.update();
+        app.world
+            .query::<&Text>()
+            .iter(&app.world)
+            .map(|text| text.sections[0].value.clone())
+            .find(|text| text.contains("to move"))
+    };
+    assert_eq!(hints(&mut app).as_deref(), Some("P1: I/K to move"));
+
+    app.world
+        .resource_mut::<Events<GamepadEvent>>()
+        .send(GamepadEvent(Gamepad(0), GamepadEventType::Connected));
+    app.update();
+    assert_eq!(hints(&mut app).as_deref(), Some("P1: left stick to move"));
+
+    finish_serve(&mut app);
+    assert_eq!(hints(&mut app), None);
+}
+
+#[test]
+fn a_ball_fast_enough_to_sink_past_the_middle_of_a_paddle_still_bounces_back() {
+    let mut app = headless_app();
+    finish_serve(&mut app);
+    park_paddle::<P1Paddle>(&mut app, 0.0);
+    let paddle_x = app
+        .world
+        .query_filtered::<&Transform, With<P1Paddle>>()
+        .iter(&app.world)
+        .next()
+        .unwrap()
+        .translation
+        .x;
+
+    // Clear of the paddle, and 5 past its middle after the next tick.
+    let reach = (PADDLE_SIZE.x + BALL_SIZE.x) / 2.0;
+    place_ball(
+        &mut app,
+        Vec2::new(paddle_x + reach + 10.0, 0.0),
+        Vec2::new(-2400.0, 0.0),
+    );
+    app.update();
+    let (position, velocity) = ball(&mut app);
+    assert!(velocity.x > 0.0);
+    assert!(position.x >= paddle_x + reach);
+
+    for _ in 0..10 {
+        app.update();
+    }
+    assert_eq!(app.world.resource::<Scoreboard>().p2_score, 0);
+}
+
+#[test]
+fn the_scoreboard_text_is_only_rewritten_when_the_score_changes() {
+    let mut app = headless_app();
+    app.add_plugin(AssetPlugin)
+        .add_asset::<Font>()
+        .add_plugin(FontPlugin)
+        .add_plugin(HudPlugin);
+    app.update();
+
+    let mut text_query = app.world.query::<(Entity, &Text)>();
+    let p1_score = text_query
+        .iter(&app.world)
+        .find(|(_, text)| text.sections[0].value == "P1: 0")
+        .map(|(entity, _)| entity)
+        .unwrap();
+    app.world.get_mut::<Text>(p1_score).unwrap().sections[0].value = "untouched".to_string();
+    app.update();
+    let value = |app: &App| {
+        app.world.get::<Text>(p1_score).unwrap().sections[0]
+            .value
+            .clone()
+    };
+    assert_eq!(value(&app), "untouched");
+
+    app.world.resource_mut::<Scoreboard>().p1_score = 1;
+    app.update();
+    assert_eq!(value(&app), "P1: 1");
+}
+
+#[test]
+fn the_ball_is_drawn_between_its_last_two_ticks() {
+    let mut app = headless_app();
+    app.add_plugin(TransformPlugin)
+        .add_plugin(InterpolationPlugin);
+    finish_serve(&mut app);
+    let drawn = |app: &mut App| {
+        app.world
+            .query_filtered::<&GlobalTransform, With<Ball>>()
+            .iter(&app.world)
+            .next()
+            .unwrap()
+            .translation
+    };
+
+    place_ball(&mut app, Vec2::ZERO, Vec2::new(BALL_SPEED, 0.0));
+    app.world.resource_mut::<TickProgress>().0 = 0.25;
+    app.update();
+    let (position, _) = ball(&mut app);
+    assert!(position.x > 0.0);
+    assert!((drawn(&mut app).x - position.x * 0.25).abs() < 1e-3);
+
+    // Put straight back in the middle, as after a goal.
+    place_ball(&mut app, Vec2::new(200.0, 0.0), Vec2::ZERO);
+    app.update();
+    place_ball(&mut app, Vec2::ZERO, Vec2::ZERO);
+    app.update();
+    assert_eq!(drawn(&mut app).x, 0.0);
+}
+
+#[test]
+fn the_ball_covers_the_same_ground_a_second_at_any_tick_rate() {
+    let distance = |hz: u32| {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TickRate(hz))
+            .add_plugin(SimulationPlugin::stepped(0));
+        finish_serve(&mut app);
+        place_ball(&mut app, Vec2::new(-100.0, 0.0), Vec2::new(BALL_SPEED, 0.0));
+        // Half a second.
+        for _ in 0..hz / 2 {
+            app.update();
+        }
+        ball(&mut app).0.x + 100.0
+    };
+
+    assert!((distance(60) - BALL_SPEED / 2.0).abs() < 1e-2);
+    assert!((distance(120) - BALL_SPEED / 2.0).abs() < 1e-2);
+}
+
+#[test]
+fn the_serve_and_dash_cooldown_last_as_long_at_any_tick_rate() {
+    let seconds = |hz: u32| {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TickRate(hz))
+            .add_plugin(SimulationPlugin::stepped(0))
+            .add_plugin(InputPlugin)
+            .add_plugin(ControlsPlugin);
+        let mut serve_ticks = 0;
+        app.update();
+        while app.world.resource::<Serve>().in_progress() {
+            app.update();
+            serve_ticks += 1;
+        }
+
+        press_key(&mut app, KeyCode::LShift);
+        let mut charge_ticks = 0;
+        while app
+            .world
+            .query::<&Dash>()
+            .iter(&app.world)
+            .any(|dash| dash.charge() < 1.0)
+        {
+            app.update();
+            charge_ticks += 1;
+        }
+        let step = TickRate(hz).step();
+        (serve_ticks as f32 * step, charge_ticks as f32 * step)
+    };
+
+    let (serve, charge) = seconds(60);
+    assert!((serve - 0.7).abs() < 0.02, "{}", serve);
+    assert!((charge - 2.0).abs() < 0.05, "{}", charge);
+    let (serve_at_120, charge_at_120) = seconds(120);
+    assert!((serve_at_120 - serve).abs() < 0.02, "{}", serve_at_120);
+    assert!((charge_at_120 - charge).abs() < 0.02, "{}", charge_at_120);
+}
+
+#[test]
+fn every_device_is_sampled_into_one_input_state_a_tick() {
+    let mut app = headless_app();
+    app.add_plugin(InputPlugin).add_plugin(ControlsPlugin);
+    app.update();
+
+    let mut keyboard_input = app.world.resource_mut::<Input<KeyCode>>();
+    keyboard_input.press(KeyCode::W);
+    keyboard_input.press(KeyCode::K);
+    app.update();
+    assert_eq!(
+        *app.world.resource::<InputState>(),
+        InputState {
+            p1: PaddleInput {
+                direction: 1.0,
+                ..default()
+            },
+            p2: PaddleInput {
+                grip: true,
+                ..default()
+            },
+        }
+    );
+    assert!(paddle_y::<P1Paddle>(&mut app) > 0.0);
+
+    // P1's gamepad takes over from their keys.
+    app.world
+        .resource_mut::<Events<GamepadEvent>>()
+        .send(GamepadEvent(Gamepad(0), GamepadEventType::Connected));
+    app.update();
+    app.world
+        .resource_mut::<Axis<GamepadAxis>>()
+        .set(GamepadAxis(Gamepad(0), GamepadAxisType::LeftStickY), -1.0);
+    app.update();
+    assert_eq!(app.world.resource::<InputState>().p1.direction, -1.0);
+}
+
+#[test]
+fn predictors_angle_returns_towards_the_corner_away_from_the_opponent() {
+    let arena = Arena::default();
+    let ball = BallView {
+        position: Vec2::ZERO,
+        velocity: Vec2::new(400.0, 0.0),
+    };
+    let paddle = PaddleView {
+        position: Vec2::new(390.0, 0.0),
+        velocity: Vec2::ZERO,
+        size: Vec2::new(20.0, 120.0),
+    };
+    let opponent = |y: f32| PaddleView {
+        position: Vec2::new(-390.0, y),
+        velocity: Vec2::ZERO,
+        size: Vec2::new(20.0, 120.0),
+    };
+    let tick_rate = TickRate::default();
+    let mut rng = GameRng::new(0);
+
+    // Not knowing where the opponent is, it meets the ball square on.
+    let mut brain = Predictor::new(Difficulty::Insane);
+    assert_eq!(
+        brain.decide(&ball, &paddle, &arena, tick_rate, &mut rng),
+        0.0
+    );
+
+    // It drops the paddle to take the ball on its top half, sending it up,
+    // away from an opponent down low, and the other way round.
+    let mut brain = Predictor::new(Difficulty::Insane);
+    brain.see_opponent(&opponent(-200.0));
+    assert!(brain.decide(&ball, &paddle, &arena, tick_rate, &mut rng) < 0.0);
+    let mut brain = Predictor::new(Difficulty::Insane);
+    brain.see_opponent(&opponent(200.0));
+    assert!(brain.decide(&ball, &paddle, &arena, tick_rate, &mut rng) > 0.0);
+
+    let mut brain = Predictor::new(Difficulty::Easy);
+    brain.skill.noise = 0.0;
+    brain.skill.misjudge_chance = 0.0;
+    brain.skill.reaction_time = 0.0;
+    brain.see_opponent(&opponent(200.0));
+    assert_eq!(
+        brain.decide(&ball, &paddle, &arena, tick_rate, &mut rng),
+        0.0
+    );
+}
+
+#[test]
+fn predictors_take_as_long_to_react_at_any_tick_rate() {
+    let arena = Arena::default();
+    let ball = BallView {
+        position: Vec2::new(0.0, 100.0),
+        velocity: Vec2::new(400.0, 0.0),
+    };
+    let paddle = PaddleView {
+        position: Vec2::new(390.0, 0.0),
+        velocity: Vec2::ZERO,
+        size: Vec2::new(20.0, 120.0),
+    };
+    let mut rng = GameRng::new(0);
+
+    for (tick_rate, still_ticks) in [(TickRate(60), 6), (TickRate(120), 12)] {
+        let mut brain = Predictor::new(Difficulty::Hard);
+        brain.skill.noise = 0.0;
+        brain.skill.misjudge_chance = 0.0;
+        let ticks = (0..)
+            .take_while(|_| brain.decide(&ball, &paddle, &arena, tick_rate, &mut rng) == 0.0)
+            .count();
+        assert_eq!(ticks, still_ticks);
+    }
+}
+
+#[test]
+fn the_predicted_path_bounces_off_the_walls_to_the_intercept() {
+    let arena = Arena::default();
+    let ball = BallView {
+        position: Vec2::new(-300.0, 0.0),
+        velocity: Vec2::new(400.0, 600.0),
+    };
+    let paddle = PaddleView {
+        position: Vec2::new(390.0, 0.0),
+        velocity: Vec2::ZERO,
+        size: Vec2::new(20.0, 120.0),
+    };
+
+    let path = Predictor::predicted_path(&ball, &paddle, &arena);
+    assert_eq!(path[0], ball.position);
+    let bounces = &path[1..path.len() - 1];
+    assert!(bounces.len() >= 2);
+    for bounce in bounces {
+        assert!(bounce.y == arena.top || bounce.y == arena.bottom);
+    }
+    let end = *path.last().unwrap();
+    assert_eq!(end.x, paddle.position.x);
+    assert!((end.y - Predictor::intercept_y(&ball, &paddle, &arena)).abs() < 1e-2);
+
+    let going_away = BallView {
+        velocity: -ball.velocity,
+        ..ball
+    };
+    assert_eq!(
+        Predictor::predicted_path(&going_away, &paddle, &arena),
+        vec![ball.position]
+    );
+}
+
+#[test]
+fn self_play_counts_the_wins_of_each_skill_from_either_end() {
+    let rules = MatchRules {
+        points_to_win: 2,
+        ..Default::default()
+    };
+    let frozen = Skill {
+        max_speed: 0.0,
+        ..Difficulty::Easy.skill()
+    };
+
+    let record = selfplay::play_matches(Difficulty::Insane.skill(), frozen, &rules, 2, 0);
+    assert_eq!(
+        record,
+        Record {
+            wins: 2,
+            losses: 0,
+            unfinished: 0
+        }
+    );
+    assert_eq!(record.win_rate(), Some(1.0));
+    assert_eq!(
+        selfplay::play_matches(frozen, Difficulty::Insane.skill(), &rules, 2, 0).win_rate(),
+        Some(0.0)
+    );
+}
+
+#[test]
+fn tearing_down_a_match_puts_up_a_fresh_court_ball_and_paddles() {
+    let mut app = headless_app();
+    app.world.resource_mut::<MatchRules>().difficulty = Difficulty::Hard;
+    finish_serve(&mut app);
+    for _ in 0..20 {
+        app.update();
+    }
+    assert_ne!(ball(&mut app).1, Vec2::ZERO);
+
+    let mut match_query = app.world.query_filtered::<Entity, With<GameEntity>>();
+    let before: Vec<Entity> = match_query.iter(&app.world).collect();
+    tear_down_match(&mut app.world);
+    app.update();
+
+    let after: Vec<Entity> = match_query.iter(&app.world).collect();
+    assert_eq!(before.len(), after.len());
+    assert!(before
+        .iter()
+        .all(|entity| app.world.get_entity(*entity).is_none()));
+    assert_eq!(ball(&mut app).1, Vec2::ZERO);
+    // The AI is still the one picked, and still only playing P2.
+    let mut brain_query = app.world.query::<(&mut Brain, Option<&P2Paddle>)>();
+    let mut brains: Vec<_> = brain_query.iter_mut(&mut app.world).collect();
+    assert_eq!(brains.len(), 1);
+    assert!(brains[0].1.is_some());
+    let skill = brains[0].0.skill_mut().unwrap();
+    assert_eq!(skill.max_speed, Difficulty::Hard.skill().max_speed);
+}
+
+#[test]
+fn both_kinds_of_stick_move_the_paddle_by_velocity_and_the_wall_stops_it() {
+    let mut app = headless_app();
+    app.add_plugin(InputPlugin).add_plugin(ControlsPlugin);
+    app.update();
+
+    // Held against the top wall, the paddle stays there, at a standstill.
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::W);
+    for _ in 0..120 {
+        app.update();
+    }
+    let arena = app.world.resource::<Arena>().clone();
+    let (_, top_bound) = arena.paddle_bounds(PADDLE_SIZE.y);
+    assert_eq!(paddle_y::<P1Paddle>(&mut app), top_bound);
+    assert_eq!(paddle_speed::<P1Paddle>(&mut app), 0.0);
+
+    // An absolute stick gets the paddle where it points by moving it there,
+    // not by putting it there.
+    app.world.resource_mut::<Settings>().controls.stick_mode = StickMode::Absolute;
+    app.world
+        .resource_mut::<Events<GamepadEvent>>()
+        .send(GamepadEvent(Gamepad(0), GamepadEventType::Connected));
+    app.update();
+    app.world
+        .resource_mut::<Axis<GamepadAxis>>()
+        .set(GamepadAxis(Gamepad(0), GamepadAxisType::LeftStickY), 0.0);
+    app.update();
+    assert!(paddle_y::<P1Paddle>(&mut app).abs() < 1e-3);
+    assert!(paddle_speed::<P1Paddle>(&mut app) > 0.0);
+}